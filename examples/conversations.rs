@@ -21,9 +21,9 @@ async fn main() {
         println!("-----");
         println!("Conversation with @{}:", user.screen_name);
         for msg in convo {
-            if !users.contains_key(&msg.sender_id) {
+            if let std::collections::hash_map::Entry::Vacant(e) = users.entry(msg.sender_id) {
                 let sender = egg_mode::user::show(msg.sender_id, &c.token).await.unwrap();
-                users.insert(msg.sender_id, sender);
+                e.insert(sender);
             }
             let sender = &users[&msg.sender_id];
             println!(
@@ -33,6 +33,6 @@ async fn main() {
             );
             println!("    {}", msg.text);
         }
-        println!("");
+        println!();
     }
 }
@@ -25,7 +25,7 @@ async fn main() {
     for place in &result.results {
         println!("{}", place.full_name);
     }
-    println!("");
+    println!();
 
     let result = egg_mode::place::reverse_geocode(51.507222, -0.1275)
         .granularity(PlaceType::City)
@@ -16,13 +16,13 @@ use std::collections::HashSet;
 async fn main() -> Result<()> {
     let config = common::Config::load().await;
 
-    println!("");
-    let friends: HashSet<u64> = user::friends_ids(config.user_id, &config.token)
+    println!();
+    let friends: HashSet<user::UserId> = user::friends_ids(config.user_id, &config.token)
         .map_ok(|r| r.response)
         .try_collect()
         .await?;
 
-    let followers: HashSet<u64> = user::followers_ids(config.user_id, &config.token)
+    let followers: HashSet<user::UserId> = user::followers_ids(config.user_id, &config.token)
         .map_ok(|r| r.response)
         .try_collect()
         .await?;
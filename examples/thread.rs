@@ -34,8 +34,8 @@ async fn main() -> Result<()> {
 
     //The example post used in this demo is the fourth post in a seven-post thread I
     //(@QuietMisdreavus) posted shortly before writing this. You can easily extrapolate this into a
-    //function that takes u64 as needed.
-    let start_id: u64 = 773236818921873409;
+    //function that takes a TweetId as needed.
+    let start_id = tweet::TweetId::from(773236818921873409);
 
     println!("Let's reconstruct a tweet thread!");
 
@@ -79,11 +79,11 @@ async fn main() -> Result<()> {
     }
 
     for tweet in &thread {
-        println!("");
+        println!();
         if tweet.id == start_id {
             println!("-- this is our starting tweet");
         }
-        common::print_tweet(&tweet);
+        common::print_tweet(tweet);
     }
     Ok(())
 }
@@ -1,13 +1,19 @@
-//! Functionality to alter a user's public profile.
+//! Functionality to alter a user's public profile and account settings.
 //!
 //! Specifically, this module contains functions which update the information
-//! that is publically visible on a user's timeline (e.g. name, location). This module does *not*
-//! modify a user's private account settings (e.g. email, password).
+//! that is publically visible on a user's timeline (e.g. name, location), and functions which
+//! read and update the privacy settings of the account (e.g. who can send it Direct Messages).
+//! This module does *not* modify a user's login credentials (e.g. email, password).
+
+use serde::Deserialize;
 
 use crate::{
     auth,
-    common::{post, request_with_empty_response, request_with_json_response, ParamList},
+    common::{
+        get, post, request_with_empty_response, request_with_json_response, MapString, ParamList,
+    },
     error, links,
+    trend::TrendLocation,
     user::TwitterUser,
     Response,
 };
@@ -106,3 +112,200 @@ pub async fn update_profile(
 
     request_with_json_response(req).await
 }
+
+/// Represents who is allowed to send Direct Messages to an account.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum AllowDmsFrom {
+    /// Anyone can send the account a Direct Message.
+    #[display(fmt = "all")]
+    All,
+    /// Only accounts that the user follows can send it a Direct Message.
+    #[display(fmt = "following")]
+    Following,
+    /// A setting that egg-mode doesn't know about yet. This can't be sent with
+    /// `update_settings`.
+    #[serde(other)]
+    #[display(fmt = "unknown")]
+    Unknown,
+}
+
+/// Represents who is allowed to tag an account in photos.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum AllowMediaTagging {
+    /// Anyone can tag the account in photos.
+    #[display(fmt = "all")]
+    All,
+    /// Only accounts that the user follows can tag it in photos.
+    #[display(fmt = "following")]
+    Following,
+    /// Nobody can tag the account in photos.
+    #[display(fmt = "none")]
+    None,
+    /// A setting that egg-mode doesn't know about yet. This can't be sent with
+    /// `update_settings`.
+    #[serde(other)]
+    #[display(fmt = "unknown")]
+    Unknown,
+}
+
+/// The time zone setting of an account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeZone {
+    /// The display name of the time zone, e.g. `"Pacific Time (US & Canada)"`.
+    pub name: String,
+    /// The offset of the time zone from UTC, in seconds.
+    pub utc_offset: i32,
+    /// The name of the time zone in the tz database, e.g. `"America/Los_Angeles"`.
+    pub tzinfo_name: String,
+}
+
+/// The "sleep time" setting of an account, during which Twitter won't send it notifications.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SleepTime {
+    /// Whether sleep time is enabled.
+    pub enabled: bool,
+    /// The hour (in the account's time zone, from 0 to 23) when sleep time begins, if enabled.
+    pub start_time: Option<u32>,
+    /// The hour (in the account's time zone, from 0 to 23) when sleep time ends, if enabled.
+    pub end_time: Option<u32>,
+}
+
+/// The settings of the authenticated user's account, as returned by `settings`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountSettings {
+    /// The screen name of the account.
+    pub screen_name: String,
+    /// Whether the account is protected, so that only approved followers can see its tweets.
+    pub protected: bool,
+    /// Whether the account has enabled attaching location data to its tweets.
+    pub geo_enabled: bool,
+    /// The interface language of the account.
+    pub language: String,
+    /// The time zone of the account, if set.
+    pub time_zone: Option<TimeZone>,
+    /// The sleep time setting of the account.
+    pub sleep_time: SleepTime,
+    /// Whether other users can find the account by its email address.
+    pub discoverable_by_email: bool,
+    /// Whether other users can find the account by its phone number, if present.
+    pub discoverable_by_mobile_phone: Option<bool>,
+    /// Whether media that may be sensitive is shown without a warning.
+    pub display_sensitive_media: bool,
+    /// Who can send the account Direct Messages.
+    pub allow_dms_from: AllowDmsFrom,
+    /// Who can add the account to group Direct Messages, if present.
+    pub allow_dm_groups_from: Option<AllowDmsFrom>,
+    /// Who can tag the account in photos, if present.
+    pub allow_media_tagging: Option<AllowMediaTagging>,
+    /// The locations the account has chosen to see trending topics for.
+    #[serde(default)]
+    pub trend_location: Vec<TrendLocation>,
+}
+
+/// Options for updating the account settings
+///
+/// Only the fields that are set will be updated.
+#[derive(Debug, Default)]
+pub struct SettingsUpdate {
+    /// Whether sleep time should be enabled.
+    pub sleep_time_enabled: Option<bool>,
+    /// The hour (from 0 to 23, in the account's time zone) when sleep time should begin.
+    pub start_sleep_time: Option<u32>,
+    /// The hour (from 0 to 23, in the account's time zone) when sleep time should end.
+    pub end_sleep_time: Option<u32>,
+    /// The time zone of the account, as a name from the tz database (e.g. `"Europe/Copenhagen"`)
+    /// or in the form returned in `TimeZone::name`.
+    pub time_zone: Option<String>,
+    /// The "where on earth identifier" of the location to show trending topics for.
+    pub trend_location_woeid: Option<u32>,
+    /// The interface language of the account, as a language code supported by Twitter.
+    pub lang: Option<String>,
+    /// Who can send the account Direct Messages.
+    pub allow_dms_from: Option<AllowDmsFrom>,
+    /// Who can tag the account in photos.
+    pub allow_media_tagging: Option<AllowMediaTagging>,
+}
+
+/// Returns the settings of the authenticated user's account, including its privacy settings.
+pub async fn settings(token: &auth::Token) -> error::Result<Response<AccountSettings>> {
+    let req = get(links::account::SETTINGS, token, None);
+    request_with_json_response(req).await
+}
+
+/// Updates the settings of the authenticated user's account. Only the parameters specified will be
+/// updated.
+///
+/// On success, the returned future yields the full set of settings after the update. Returns
+/// `Error::InvalidParam` without contacting Twitter if `allow_dms_from` or `allow_media_tagging`
+/// is `Unknown`.
+pub async fn update_settings(
+    update: SettingsUpdate,
+    token: &auth::Token,
+) -> error::Result<Response<AccountSettings>> {
+    if update.allow_dms_from == Some(AllowDmsFrom::Unknown)
+        || update.allow_media_tagging == Some(AllowMediaTagging::Unknown)
+    {
+        return Err(error::Error::InvalidParam(
+            "unknown privacy settings can't be sent to Twitter",
+        ));
+    }
+
+    let params = ParamList::new()
+        .add_opt_param("sleep_time_enabled", update.sleep_time_enabled.map_string())
+        .add_opt_param("start_sleep_time", update.start_sleep_time.map_string())
+        .add_opt_param("end_sleep_time", update.end_sleep_time.map_string())
+        .add_opt_param("time_zone", update.time_zone)
+        .add_opt_param(
+            "trend_location_woeid",
+            update.trend_location_woeid.map_string(),
+        )
+        .add_opt_param("lang", update.lang)
+        .add_opt_param("allow_dms_from", update.allow_dms_from.map_string())
+        .add_opt_param(
+            "allow_media_tagging",
+            update.allow_media_tagging.map_string(),
+        );
+
+    let req = post(links::account::SETTINGS, token, Some(&params));
+
+    request_with_json_response(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_settings() {
+        let content = load_file("sample_payloads/account_settings.json");
+        let settings = ::serde_json::from_str::<AccountSettings>(&content).unwrap();
+
+        assert_eq!(settings.screen_name, "theSeanCook");
+        assert_eq!(settings.allow_dms_from, AllowDmsFrom::Following);
+        assert_eq!(settings.allow_media_tagging, Some(AllowMediaTagging::None));
+        assert!(!settings.sleep_time.enabled);
+        assert_eq!(settings.trend_location[0].woeid, 2357024);
+        assert_eq!(settings.allow_dms_from.to_string(), "following");
+
+        let unknown = serde_json::from_str::<AllowDmsFrom>(r#""verified""#).unwrap();
+        assert_eq!(unknown, AllowDmsFrom::Unknown);
+        let unknown = serde_json::from_str::<AllowMediaTagging>(r#""verified""#).unwrap();
+        assert_eq!(unknown, AllowMediaTagging::Unknown);
+    }
+
+    #[tokio::test]
+    async fn update_unknown_settings() {
+        let token = auth::Token::Bearer("token".to_string());
+        let update = SettingsUpdate {
+            allow_dms_from: Some(AllowDmsFrom::Unknown),
+            ..SettingsUpdate::default()
+        };
+        match update_settings(update, &token).await {
+            Err(error::Error::InvalidParam(_)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+/// The name of the header Twitter signs each webhook event with.
+pub const SIGNATURE_HEADER: &str = "X-Twitter-Webhooks-Signature";
+
+fn mac(consumer_secret: &str, message: &[u8]) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(consumer_secret.as_bytes()).unwrap();
+    mac.update(message);
+    mac
+}
+
+/// Returns the body to respond to a Challenge-Response Check with.
+///
+/// Twitter checks webhooks by sending them a GET request with a `crc_token` query parameter. To
+/// pass the check, the server needs to respond within 3 seconds with a 200 status, a content type
+/// of `application/json`, and the body returned here: a JSON object whose `response_token` is
+/// `sha256=` followed by the base64-encoded HMAC-SHA256 of the `crc_token`, keyed with your app's
+/// consumer secret.
+///
+/// # Example
+///
+/// ```rust
+/// use egg_mode::activity::crc_response;
+///
+/// let body = crc_response("consumer secret", "crc token from the query string");
+/// assert!(body.starts_with(r#"{"response_token":"sha256="#));
+/// ```
+pub fn crc_response(consumer_secret: &str, crc_token: &str) -> String {
+    let hash = mac(consumer_secret, crc_token.as_bytes()).finalize();
+    serde_json::json!({
+        "response_token": format!("sha256={}", base64::encode(hash.into_bytes())),
+    })
+    .to_string()
+}
+
+/// Checks the `X-Twitter-Webhooks-Signature` header of an event sent to a webhook, to make sure
+/// the event came from Twitter.
+///
+/// Anyone who knows your webhook's URL can send requests to it, so Twitter signs each event it
+/// sends: the header holds `sha256=` followed by the base64-encoded HMAC-SHA256 of the request
+/// body, keyed with your app's consumer secret. Pass the header's value as `signature`, and the
+/// raw request body, before it's parsed, as `body`. The comparison takes the same time whether or
+/// not the signature matches, so it doesn't give away how close a forged signature was.
+pub fn validate_signature(consumer_secret: &str, body: &[u8], signature: &str) -> bool {
+    let signature = match signature.strip_prefix("sha256=") {
+        Some(signature) => signature,
+        None => return false,
+    };
+    match base64::decode(signature) {
+        Ok(signature) => mac(consumer_secret, body).verify(&signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_signatures() {
+        // HMAC-SHA256 test vector from RFC 4231, test case 2
+        let expected = base64::encode(
+            [
+                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+                0x64, 0xec, 0x38, 0x43,
+            ]
+            .as_ref(),
+        );
+        let body = crc_response("Jefe", "what do ya want for nothing?");
+        assert_eq!(
+            body,
+            format!("{{\"response_token\":\"sha256={}\"}}", expected)
+        );
+
+        let event = b"what do ya want for nothing?";
+        let header = format!("sha256={}", expected);
+        assert!(validate_signature("Jefe", event, &header));
+        assert!(!validate_signature("Jefe", b"something else", &header));
+        assert!(!validate_signature("Jefe", event, &expected));
+        assert!(!validate_signature("Jefe", event, "sha256=not base64!"));
+    }
+}
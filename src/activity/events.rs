@@ -0,0 +1,546 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::common::*;
+use crate::direct::{raw as dm_raw, DirectMessage};
+use crate::tweet::{Tweet, TweetId, TweetSource};
+use crate::user::{TwitterUser, UserId};
+
+use super::ReplayJobStatus;
+
+/// An event sent to a webhook by the Account Activity API.
+///
+/// Each request Twitter sends to a webhook carries one of these: a set of events of a single
+/// kind, all for the same subscribed user. Parse the body of the request with
+/// `serde_json::from_slice`, after checking its signature with [`validate_signature`].
+///
+/// [`validate_signature`]: fn.validate_signature.html
+#[derive(Debug, Clone)]
+pub struct AccountActivityEvent {
+    /// The subscribed user the events are for.
+    pub for_user_id: UserId,
+    /// For `TweetCreate` events about a mention of the subscribed user, whether the subscribed
+    /// user has blocked the author of the tweet.
+    pub user_has_blocked: Option<bool>,
+    /// The events themselves.
+    pub events: ActivityEvents,
+}
+
+/// The kinds of events the Account Activity API can send, as part of an `AccountActivityEvent`.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum ActivityEvents {
+    /// Tweets posted by the subscribed user, or retweets, replies, mentions, or quotes of their
+    /// tweets.
+    TweetCreate(Vec<Tweet>),
+    /// Tweets deleted by the subscribed user.
+    TweetDelete(Vec<TweetDeleteEvent>),
+    /// Likes by the subscribed user, or of their tweets.
+    Favorite(Vec<FavoriteEvent>),
+    /// Follows or unfollows by the subscribed user, or follows of them.
+    Follow(Vec<UserActionEvent>),
+    /// Blocks or unblocks by the subscribed user.
+    Block(Vec<UserActionEvent>),
+    /// Mutes or unmutes by the subscribed user.
+    Mute(Vec<UserActionEvent>),
+    /// The subscribed user revoked your app's access to their account. No more events will be sent
+    /// for them.
+    UserRevoke(RevokeEvent),
+    /// Direct messages sent or received by the subscribed user.
+    DirectMessage {
+        /// The messages themselves.
+        messages: Vec<DirectMessage>,
+        /// The users who sent or received the messages, by ID.
+        users: HashMap<UserId, ActivityUser>,
+    },
+    /// Notices that someone is typing a direct message to the subscribed user.
+    DirectMessageTyping {
+        /// The notices themselves.
+        events: Vec<DirectMessageTypingEvent>,
+        /// The users who are typing, by ID.
+        users: HashMap<UserId, ActivityUser>,
+    },
+    /// Notices that the subscribed user's direct messages were read.
+    DirectMessageRead {
+        /// The notices themselves.
+        events: Vec<DirectMessageReadEvent>,
+        /// The users who read the messages, by ID.
+        users: HashMap<UserId, ActivityUser>,
+    },
+    /// A notice that a replay job started with `replay` has finished.
+    ReplayJobStatus(ReplayJobStatus),
+    /// An event that egg-mode doesn't know how to parse, with the whole JSON payload.
+    ///
+    /// Twitter can add new kinds of events, and egg-mode includes them here so that they can be
+    /// used before egg-mode has a chance to handle them.
+    Unknown(serde_json::Value),
+}
+
+/// A notice that a tweet was deleted, as part of `ActivityEvents::TweetDelete`.
+#[derive(Debug, Clone)]
+pub struct TweetDeleteEvent {
+    /// The tweet that was deleted.
+    pub tweet_id: TweetId,
+    /// The user who posted the tweet.
+    pub user_id: UserId,
+    /// UTC timestamp from when the tweet was deleted.
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// A like, as part of `ActivityEvents::Favorite`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FavoriteEvent {
+    /// The ID of this event.
+    pub id: String,
+    /// UTC timestamp from when the tweet was liked.
+    #[serde(
+        rename = "timestamp_ms",
+        deserialize_with = "serde_millis::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+    /// The tweet that was liked.
+    pub favorited_status: Tweet,
+    /// The user who liked the tweet.
+    pub user: TwitterUser,
+}
+
+/// The kinds of actions one user can take on another, as given in a `UserActionEvent`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserAction {
+    /// The source user followed the target user.
+    Follow,
+    /// The source user unfollowed the target user.
+    Unfollow,
+    /// The source user blocked the target user.
+    Block,
+    /// The source user unblocked the target user.
+    Unblock,
+    /// The source user muted the target user.
+    Mute,
+    /// The source user unmuted the target user.
+    Unmute,
+}
+
+/// A follow, block, or mute (or their reversal), as part of `ActivityEvents::Follow`,
+/// `ActivityEvents::Block`, or `ActivityEvents::Mute`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserActionEvent {
+    /// What the source user did.
+    #[serde(rename = "type")]
+    pub action: UserAction,
+    /// UTC timestamp from when the action was taken.
+    #[serde(
+        rename = "created_timestamp",
+        deserialize_with = "serde_millis::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+    /// The user who took the action.
+    pub source: ActivityUser,
+    /// The user the action was taken on.
+    pub target: ActivityUser,
+}
+
+/// A notice that a user revoked an app's access to their account, as part of
+/// `ActivityEvents::UserRevoke`.
+#[derive(Debug, Clone)]
+pub struct RevokeEvent {
+    /// UTC timestamp from when access was revoked.
+    pub revoked_at: DateTime<Utc>,
+    /// The ID of the app whose access was revoked.
+    pub app_id: String,
+    /// The user who revoked access.
+    pub user_id: UserId,
+}
+
+/// A notice that someone is typing a direct message, as part of
+/// `ActivityEvents::DirectMessageTyping`.
+#[derive(Debug, Clone)]
+pub struct DirectMessageTypingEvent {
+    /// UTC timestamp from when the user started typing.
+    pub created_at: DateTime<Utc>,
+    /// The user who is typing.
+    pub sender_id: UserId,
+    /// The user who is being written to.
+    pub recipient_id: UserId,
+}
+
+/// A notice that direct messages were read, as part of `ActivityEvents::DirectMessageRead`.
+#[derive(Debug, Clone)]
+pub struct DirectMessageReadEvent {
+    /// UTC timestamp from when the messages were read.
+    pub created_at: DateTime<Utc>,
+    /// The user who read the messages.
+    pub sender_id: UserId,
+    /// The user who sent the messages.
+    pub recipient_id: UserId,
+    /// The ID of the last message that was read.
+    pub last_read_event_id: u64,
+}
+
+/// A summary of a user, as included with some Account Activity events.
+///
+/// These are smaller than a full `TwitterUser`, and some of their fields are left out depending
+/// on the event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityUser {
+    /// The ID of this user.
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    pub id: UserId,
+    /// The display name of this user.
+    pub name: String,
+    /// The screen name of this user.
+    pub screen_name: String,
+    /// The location this user has given in their profile.
+    pub location: Option<String>,
+    /// The biography of this user.
+    pub description: Option<String>,
+    /// Whether this user has protected their tweets.
+    #[serde(default)]
+    pub protected: bool,
+    /// Whether this user is verified.
+    #[serde(default)]
+    pub verified: bool,
+    /// The number of accounts following this user.
+    pub followers_count: Option<i32>,
+    /// The number of accounts this user follows.
+    pub friends_count: Option<i32>,
+    /// The number of tweets this user has posted, including retweets.
+    pub statuses_count: Option<i32>,
+    /// The URL of this user's profile image.
+    pub profile_image_url_https: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawTweetDelete {
+    status: RawDeletedStatus,
+    #[serde(deserialize_with = "serde_millis::deserialize")]
+    timestamp_ms: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct RawDeletedStatus {
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    id: TweetId,
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    user_id: UserId,
+}
+
+#[derive(Deserialize)]
+struct RawRevoke {
+    #[serde(deserialize_with = "deserialize_revoke_time")]
+    date_time: DateTime<Utc>,
+    target: RawRevokeTarget,
+    source: RawRevokeSource,
+}
+
+#[derive(Deserialize)]
+struct RawRevokeTarget {
+    app_id: String,
+}
+
+#[derive(Deserialize)]
+struct RawRevokeSource {
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    user_id: UserId,
+}
+
+fn deserialize_revoke_time<'de, D>(deser: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deser)?;
+    DateTime::parse_from_rfc3339(&s)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(D::Error::custom)
+}
+
+#[derive(Deserialize)]
+struct RawDmNotice {
+    #[serde(
+        rename = "created_timestamp",
+        deserialize_with = "serde_millis::deserialize"
+    )]
+    created_at: DateTime<Utc>,
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    sender_id: UserId,
+    target: RawDmTarget,
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    last_read_event_id: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RawDmTarget {
+    #[serde(deserialize_with = "serde_number_or_string::deserialize")]
+    recipient_id: UserId,
+}
+
+impl<'de> Deserialize<'de> for AccountActivityEvent {
+    fn deserialize<D>(deser: D) -> Result<AccountActivityEvent, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn parse<T: serde::de::DeserializeOwned, E: Error>(
+            value: &serde_json::Value,
+        ) -> Result<T, E> {
+            T::deserialize(value).map_err(E::custom)
+        }
+
+        let input = serde_json::Value::deserialize(deser)?;
+        let for_user_id = match input.get("for_user_id") {
+            Some(id) => serde_number_or_string::deserialize(id).map_err(D::Error::custom)?,
+            None => return Err(D::Error::missing_field("for_user_id")),
+        };
+        let user_has_blocked = input.get("user_has_blocked").and_then(|b| b.as_bool());
+        let users = || -> Result<HashMap<UserId, ActivityUser>, D::Error> {
+            let users: HashMap<String, ActivityUser> = match input.get("users") {
+                Some(users) => parse(users)?,
+                None => HashMap::new(),
+            };
+            Ok(users.values().map(|user| (user.id, user.clone())).collect())
+        };
+
+        let events = if let Some(tweets) = input.get("tweet_create_events") {
+            ActivityEvents::TweetCreate(parse(tweets)?)
+        } else if let Some(deletes) = input.get("tweet_delete_events") {
+            let deletes: Vec<RawTweetDelete> = parse(deletes)?;
+            ActivityEvents::TweetDelete(
+                deletes
+                    .into_iter()
+                    .map(|del| TweetDeleteEvent {
+                        tweet_id: del.status.id,
+                        user_id: del.status.user_id,
+                        deleted_at: del.timestamp_ms,
+                    })
+                    .collect(),
+            )
+        } else if let Some(favs) = input.get("favorite_events") {
+            ActivityEvents::Favorite(parse(favs)?)
+        } else if let Some(follows) = input.get("follow_events") {
+            ActivityEvents::Follow(parse(follows)?)
+        } else if let Some(blocks) = input.get("block_events") {
+            ActivityEvents::Block(parse(blocks)?)
+        } else if let Some(mutes) = input.get("mute_events") {
+            ActivityEvents::Mute(parse(mutes)?)
+        } else if let Some(revoke) = input.get("user_event").and_then(|e| e.get("revoke")) {
+            let revoke: RawRevoke = parse(revoke)?;
+            ActivityEvents::UserRevoke(RevokeEvent {
+                revoked_at: revoke.date_time,
+                app_id: revoke.target.app_id,
+                user_id: revoke.source.user_id,
+            })
+        } else if let Some(dms) = input.get("direct_message_events") {
+            let dms: Vec<dm_raw::EventType> = parse(dms)?;
+            let apps: HashMap<String, TweetSource> = match input.get("apps") {
+                Some(apps) => parse(apps)?,
+                None => HashMap::new(),
+            };
+            ActivityEvents::DirectMessage {
+                messages: dms
+                    .into_iter()
+                    .map(|dm| dm.as_raw_dm().into_dm(&apps))
+                    .collect(),
+                users: users()?,
+            }
+        } else if let Some(typing) = input.get("direct_message_indicate_typing_events") {
+            let typing: Vec<RawDmNotice> = parse(typing)?;
+            ActivityEvents::DirectMessageTyping {
+                events: typing
+                    .into_iter()
+                    .map(|ev| DirectMessageTypingEvent {
+                        created_at: ev.created_at,
+                        sender_id: ev.sender_id,
+                        recipient_id: ev.target.recipient_id,
+                    })
+                    .collect(),
+                users: users()?,
+            }
+        } else if let Some(reads) = input.get("direct_message_mark_read_events") {
+            let reads: Vec<RawDmNotice> = parse(reads)?;
+            ActivityEvents::DirectMessageRead {
+                events: reads
+                    .into_iter()
+                    .map(|ev| DirectMessageReadEvent {
+                        created_at: ev.created_at,
+                        sender_id: ev.sender_id,
+                        recipient_id: ev.target.recipient_id,
+                        last_read_event_id: ev.last_read_event_id.unwrap_or(0),
+                    })
+                    .collect(),
+                users: users()?,
+            }
+        } else if let Some(status) = input.get("replay_job_status") {
+            ActivityEvents::ReplayJobStatus(parse(status)?)
+        } else {
+            ActivityEvents::Unknown(input.clone())
+        };
+
+        Ok(AccountActivityEvent {
+            for_user_id,
+            user_has_blocked,
+            events,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    fn parse_event(value: serde_json::Value) -> AccountActivityEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn parse_activity_events() {
+        let tweet: serde_json::Value =
+            serde_json::from_str(&load_file("sample_payloads/sample-reply.json")).unwrap();
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "user_has_blocked": false,
+            "tweet_create_events": [tweet.clone()],
+        }));
+        assert_eq!(event.for_user_id, 2244994945);
+        assert_eq!(event.user_has_blocked, Some(false));
+        match event.events {
+            ActivityEvents::TweetCreate(tweets) => assert_eq!(tweets.len(), 1),
+            other => panic!("not a tweet_create event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "favorite_events": [{
+                "id": "a7ba59eab0bfcba386f7acedac279542",
+                "created_at": "Mon Mar 26 16:33:26 +0000 2018",
+                "timestamp_ms": 1522082006140u64,
+                "favorited_status": tweet.clone(),
+                "user": tweet["user"].clone(),
+            }],
+        }));
+        match event.events {
+            ActivityEvents::Favorite(favs) => {
+                assert_eq!(favs[0].created_at.timestamp_millis(), 1522082006140);
+            }
+            other => panic!("not a favorite event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "follow_events": [{
+                "type": "follow",
+                "created_timestamp": "1517588749178",
+                "target": { "id": "2244994945", "name": "Twitter Dev", "screen_name": "TwitterDev" },
+                "source": { "id": "3198576760", "name": "Someone", "screen_name": "someone",
+                    "protected": true, "followers_count": 10 },
+            }],
+        }));
+        match event.events {
+            ActivityEvents::Follow(follows) => {
+                assert_eq!(follows[0].action, UserAction::Follow);
+                assert_eq!(follows[0].source.id, 3198576760);
+                assert!(follows[0].source.protected);
+                assert_eq!(follows[0].target.screen_name, "TwitterDev");
+            }
+            other => panic!("not a follow event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "tweet_delete_events": [{
+                "status": { "id": "601430178305220608", "user_id": "3198576760" },
+                "timestamp_ms": "1432228155593",
+            }],
+        }));
+        match event.events {
+            ActivityEvents::TweetDelete(deletes) => {
+                assert_eq!(deletes[0].tweet_id, 601430178305220608);
+                assert_eq!(deletes[0].user_id, 3198576760);
+            }
+            other => panic!("not a tweet_delete event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "user_event": { "revoke": {
+                "date_time": "2018-05-24T09:48:12+00:00",
+                "target": { "app_id": "13090192" },
+                "source": { "user_id": "63046977" },
+            }},
+        }));
+        match event.events {
+            ActivityEvents::UserRevoke(revoke) => {
+                assert_eq!(revoke.app_id, "13090192");
+                assert_eq!(revoke.user_id, 63046977);
+            }
+            other => panic!("not a revoke event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "direct_message_mark_read_events": [{
+                "created_timestamp": "1518452444662",
+                "sender_id": "2244994945",
+                "target": { "recipient_id": "3198576760" },
+                "last_read_event_id": "963085315333238788",
+            }],
+            "users": {
+                "2244994945": { "id": "2244994945", "name": "Twitter Dev",
+                    "screen_name": "TwitterDev" },
+            },
+        }));
+        match event.events {
+            ActivityEvents::DirectMessageRead { events, users } => {
+                assert_eq!(events[0].last_read_event_id, 963085315333238788);
+                assert_eq!(users[&events[0].sender_id].screen_name, "TwitterDev");
+            }
+            other => panic!("not a mark_read event: {:?}", other),
+        }
+
+        let dm: serde_json::Value =
+            serde_json::from_str(&load_file("sample_payloads/dm-ctas.json")).unwrap();
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2977334326",
+            "direct_message_events": [dm["event"].clone()],
+            "apps": dm["apps"].clone(),
+        }));
+        match event.events {
+            ActivityEvents::DirectMessage { messages, users } => {
+                assert_eq!(messages[0].id, 1255290722656870404);
+                assert!(messages[0].source_app.is_some());
+                assert!(users.is_empty());
+            }
+            other => panic!("not a direct message event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "replay_job_status": {
+                "job_id": "1095098195724558337",
+                "job_state": "Complete",
+                "job_state_description": "All data has been delivered",
+            },
+        }));
+        match event.events {
+            ActivityEvents::ReplayJobStatus(status) => {
+                assert_eq!(status.job_id, "1095098195724558337");
+                assert_eq!(status.state, crate::activity::ReplayJobState::Complete);
+            }
+            other => panic!("not a replay status event: {:?}", other),
+        }
+
+        let event = parse_event(serde_json::json!({
+            "for_user_id": "2244994945",
+            "some_new_events": [],
+        }));
+        assert!(matches!(event.events, ActivityEvents::Unknown(_)));
+    }
+}
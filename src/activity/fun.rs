@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use hyper::Method;
+
+use crate::auth::raw::RequestBuilder;
+use crate::common::*;
+use crate::error::Result;
+use crate::{auth, links};
+
+use super::Webhook;
+
+/// Register the given URL as a webhook for the given dev environment.
+///
+/// Twitter sends the URL a Challenge-Response Check right away, and only registers it if the
+/// server responds in time, so the server needs to be running first. Each environment can only
+/// have one webhook. The `token` needs to be an access token for the user that owns the app.
+pub async fn register_webhook(
+    env_name: &str,
+    url: &str,
+    token: &auth::Token,
+) -> Result<Response<Webhook>> {
+    let endpoint = format!("{}/{}/webhooks.json", links::activity::STEM, env_name);
+    let params = ParamList::new().add_param("url", url.to_string());
+    let req = post(&endpoint, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+/// Load the webhooks registered with the given dev environment.
+///
+/// This can be called with a Bearer token.
+pub async fn list_webhooks(env_name: &str, token: &auth::Token) -> Result<Response<Vec<Webhook>>> {
+    let endpoint = format!("{}/{}/webhooks.json", links::activity::STEM, env_name);
+    let req = get(&endpoint, token, None);
+    request_with_json_response(req).await
+}
+
+/// Remove the webhook with the given ID from the given dev environment. Twitter stops sending
+/// events to it, and its subscriptions are removed along with it.
+pub async fn delete_webhook(
+    env_name: &str,
+    webhook_id: &str,
+    token: &auth::Token,
+) -> Result<Response<()>> {
+    let endpoint = format!(
+        "{}/{}/webhooks/{}.json",
+        links::activity::STEM,
+        env_name,
+        webhook_id
+    );
+    let req = auth::raw::delete(&endpoint, token, None);
+    request_with_empty_response(req).await
+}
+
+/// Ask Twitter to send a Challenge-Response Check to the webhook with the given ID.
+///
+/// A webhook that fails a check is marked as invalid, and stops receiving events. Once the server
+/// is responding again, call this to have Twitter check it again; if it passes, it's marked as
+/// valid, and events start arriving again.
+pub async fn trigger_crc(
+    env_name: &str,
+    webhook_id: &str,
+    token: &auth::Token,
+) -> Result<Response<()>> {
+    let endpoint = format!(
+        "{}/{}/webhooks/{}.json",
+        links::activity::STEM,
+        env_name,
+        webhook_id
+    );
+    let req = RequestBuilder::new(Method::PUT, &endpoint).request_token(token);
+    request_with_empty_response(req).await
+}
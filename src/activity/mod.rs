@@ -0,0 +1,122 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Methods to receive account events through the Account Activity API's webhooks.
+//!
+//! Rather than polling timelines and DMs for changes, the [Account Activity API][] sends events
+//! (like new tweets, mentions, likes, follows, and Direct Messages) for the accounts your app has
+//! subscribed to, as HTTPS POST requests to a URL your app serves. That URL is called a webhook,
+//! and it's registered with one of your app's dev environments, named in your developer portal.
+//!
+//! [Account Activity API]: https://developer.twitter.com/en/docs/twitter-api/enterprise/account-activity-api/overview
+//!
+//! The functions in this module manage those webhooks:
+//!
+//! - `register_webhook` registers a new webhook URL with an environment.
+//! - `list_webhooks` loads the webhooks registered with an environment.
+//! - `delete_webhook` removes a webhook, so that no more events are sent to it.
+//! - `trigger_crc` asks Twitter to check a webhook again, and mark it as valid if it responds.
+//! - `replay` asks Twitter to send the events from the last five days again, to catch up after
+//!   the webhook's server was down.
+//!
+//! When a webhook is registered, and from time to time afterward, Twitter checks that your app
+//! controls it by sending it a Challenge-Response Check (or CRC): a GET request with a
+//! `crc_token`, which your server needs to answer before Twitter will send it events. Answer it
+//! with the body from `crc_response`. Twitter also signs every event it sends to the webhook;
+//! check the signature with `validate_signature` to make sure an event came from Twitter.
+//!
+//! The events themselves can be parsed from the body of each request into an
+//! `AccountActivityEvent`, whose `events` hold the tweets, likes, follows, Direct Messages, or
+//! other events that happened.
+//!
+//! With the `webhook-server` feature on, `serve_webhook` does all of this for you: it runs a small
+//! HTTP server that answers CRCs, rejects requests without a valid signature, and hands the
+//! parsed events to your app as a `Stream`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let token: egg_mode::Token = unimplemented!();
+//! use egg_mode::activity;
+//!
+//! let webhook = activity::register_webhook("dev", "https://example.com/webhooks/twitter", &token)
+//!     .await
+//!     .unwrap();
+//! println!("registered webhook {}: valid? {}", webhook.id, webhook.valid);
+//! # }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+mod crc;
+mod events;
+mod fun;
+mod replay;
+#[cfg(feature = "webhook-server")]
+mod server;
+
+pub use self::crc::{crc_response, validate_signature, SIGNATURE_HEADER};
+pub use self::events::*;
+pub use self::fun::*;
+pub use self::replay::{replay, ReplayJob, ReplayJobState, ReplayJobStatus};
+#[cfg(feature = "webhook-server")]
+pub use self::server::{serve_webhook, WebhookEvents};
+
+/// A webhook URL registered with the Account Activity API, as returned by `register_webhook` and
+/// `list_webhooks`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Webhook {
+    /// The ID of this webhook, used to delete it or trigger a CRC for it.
+    pub id: String,
+    /// The URL events are sent to.
+    pub url: String,
+    /// Whether the webhook passed its most recent Challenge-Response Check. Twitter only sends
+    /// events to valid webhooks.
+    pub valid: bool,
+    /// UTC timestamp from when this webhook was registered.
+    #[serde(
+        rename = "created_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Parses the `created_timestamp` of a webhook, which has its own format, like
+/// `2016-06-02 23:54:02 +0000`.
+fn deserialize_timestamp<'de, D>(deser: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deser)?;
+    DateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S %z")
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_webhooks() {
+        let json = r#"[{
+            "id": "1234567890",
+            "url": "https://example.com/webhooks/twitter",
+            "valid": true,
+            "created_timestamp": "2016-06-02 23:54:02 +0000"
+        }]"#;
+        let webhooks = serde_json::from_str::<Vec<Webhook>>(json).unwrap();
+        assert_eq!(webhooks.len(), 1);
+        assert_eq!(webhooks[0].id, "1234567890");
+        assert!(webhooks[0].valid);
+        assert_eq!(
+            webhooks[0].created_at,
+            Utc.with_ymd_and_hms(2016, 6, 2, 23, 54, 2).unwrap()
+        );
+    }
+}
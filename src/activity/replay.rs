@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::common::*;
+use crate::error::{Error, Result};
+use crate::{auth, links};
+
+use super::deserialize_timestamp;
+
+/// How far back the Replay API can reach.
+const REPLAY_WINDOW_DAYS: i64 = 5;
+
+/// A replay job, as returned by [`replay`].
+///
+/// [`replay`]: fn.replay.html
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayJob {
+    /// The ID of this job. The `ReplayJobStatus` event sent when the job finishes carries the same
+    /// ID.
+    pub job_id: String,
+    /// UTC timestamp from when this job was created.
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// The ways a replay job can finish, as given in a `ReplayJobStatus`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum ReplayJobState {
+    /// Every event in the job's time range was sent again.
+    Complete,
+    /// The job stopped before every event was sent. Its `description` says why.
+    Failed,
+}
+
+/// A notice that a replay job has finished, sent to the webhook as
+/// `ActivityEvents::ReplayJobStatus`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayJobStatus {
+    /// The ID of the job, as returned by `replay`.
+    pub job_id: String,
+    /// How the job finished.
+    #[serde(rename = "job_state")]
+    pub state: ReplayJobState,
+    /// A description of how the job finished.
+    #[serde(rename = "job_state_description")]
+    pub description: String,
+}
+
+/// Ask Twitter to send the events for every subscription of the given webhook again, from the
+/// given time range.
+///
+/// If the app serving a webhook goes down, Twitter retries events for a while, but eventually
+/// gives up on them. Once the app is back, a replay job sends the events it missed, up to five
+/// days old. Replayed events arrive at the webhook like any other, and once they've all been
+/// sent, the webhook receives an `ActivityEvents::ReplayJobStatus` with the job's ID, saying
+/// whether it completed. That event is how a job's status is reported: the Replay API has no
+/// endpoint to check on a job while it runs. Only one replay job can run at a time for each
+/// webhook.
+///
+/// Times are rounded down to the minute. Returns `Error::InvalidParam` if `from` isn't before
+/// `to`, if `to` is in the future, or if `from` is more than five days ago.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use chrono::{Duration, Utc};
+///
+/// let outage_start = Utc::now() - Duration::hours(6);
+/// let outage_end = Utc::now() - Duration::hours(2);
+/// let job = egg_mode::activity::replay("1234567890", outage_start, outage_end, &token)
+///     .await
+///     .unwrap();
+/// println!("replay job {} started", job.job_id);
+/// # }
+/// ```
+pub async fn replay(
+    webhook_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    token: &auth::Token,
+) -> Result<Response<ReplayJob>> {
+    check_replay_window(from, to, Utc::now())?;
+    let url = format!(
+        "{}/{}/subscriptions/all.json",
+        links::activity::REPLAY_STEM,
+        webhook_id
+    );
+    let params = ParamList::new()
+        .add_param("from_date", replay_date(from))
+        .add_param("to_date", replay_date(to));
+    let req = post(&url, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+fn check_replay_window(from: DateTime<Utc>, to: DateTime<Utc>, now: DateTime<Utc>) -> Result<()> {
+    if from >= to {
+        return Err(Error::InvalidParam(
+            "replay's from time must be before its to time",
+        ));
+    }
+    if to > now {
+        return Err(Error::InvalidParam(
+            "replay's to time can't be in the future",
+        ));
+    }
+    if from < now - Duration::days(REPLAY_WINDOW_DAYS) {
+        return Err(Error::InvalidParam(
+            "replay can't reach more than five days back",
+        ));
+    }
+    Ok(())
+}
+
+/// Formats a time the way the Replay API expects, as `yyyymmddhhmm` in UTC.
+fn replay_date(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%d%H%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn replay_window() {
+        let now = Utc.with_ymd_and_hms(2021, 7, 10, 12, 30, 45).unwrap();
+        assert_eq!(replay_date(now), "202107101230");
+
+        let hour = Duration::hours(1);
+        assert!(check_replay_window(now - hour * 4, now - hour, now).is_ok());
+        assert!(check_replay_window(now - hour, now - hour * 4, now).is_err());
+        assert!(check_replay_window(now - hour, now + hour, now).is_err());
+        assert!(check_replay_window(now - Duration::days(6), now - hour, now).is_err());
+
+        let job = serde_json::from_str::<ReplayJob>(
+            r#"{"job_id":"1095098195724558337","created_at":"2019-02-11 22:48:57 +0000"}"#,
+        )
+        .unwrap();
+        assert_eq!(job.job_id, "1095098195724558337");
+        assert_eq!(
+            job.created_at,
+            Utc.with_ymd_and_hms(2019, 2, 11, 22, 48, 57).unwrap()
+        );
+    }
+}
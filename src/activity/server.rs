@@ -0,0 +1,270 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc;
+use futures::future::{AbortHandle, Abortable};
+use futures::{SinkExt, Stream};
+use hyper::body::HttpBody;
+use hyper::server::conn::AddrIncoming;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+
+use crate::error::Result;
+
+use super::{crc_response, validate_signature, AccountActivityEvent, SIGNATURE_HEADER};
+
+/// The largest request body the webhook accepts, in bytes. Larger requests get a `413 Payload Too
+/// Large` response without being read.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// How many events can be waiting in `WebhookEvents` before the server stops answering Twitter's
+/// requests until some are read.
+const EVENT_BUFFER: usize = 100;
+
+/// A `Stream` of the events Twitter sends to a webhook served by [`serve_webhook`].
+///
+/// The server keeps running until this is dropped.
+///
+/// [`serve_webhook`]: fn.serve_webhook.html
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct WebhookEvents {
+    events: mpsc::Receiver<AccountActivityEvent>,
+    local_addr: SocketAddr,
+    server: AbortHandle,
+}
+
+impl WebhookEvents {
+    /// Returns the address the server is listening on. This is useful when `serve_webhook` was
+    /// given port 0, to find out which port was picked.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Stream for WebhookEvents {
+    type Item = AccountActivityEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.events).poll_next(cx)
+    }
+}
+
+impl Drop for WebhookEvents {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Serve a webhook at the given address, returning a `Stream` of the events Twitter sends to it.
+///
+/// This runs a small HTTP server that does everything the Account Activity API asks of a
+/// webhook: it answers Challenge-Response Checks with `crc_response`, checks the signature of
+/// each event with `validate_signature` (rejecting any that don't match), and parses the events
+/// into `AccountActivityEvent`s. Requests to any path are handled the same way, and request bodies
+/// over 1 MB are rejected.
+///
+/// Events are buffered until they're read from the returned stream. If too many are waiting, the
+/// server holds off on answering Twitter until some are read, so a slow reader slows down
+/// delivery rather than using more and more memory.
+///
+/// Twitter only sends events to HTTPS URLs, so the server needs to sit behind a proxy or load
+/// balancer that handles TLS. Once it's reachable, register its public URL with
+/// [`register_webhook`]. This spawns the server on the current Tokio runtime, so it needs to be
+/// called from inside one. Returns `Error::NetError` if the address can't be bound.
+///
+/// This function is only available with the `webhook-server` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use egg_mode::activity::{self, ActivityEvents};
+/// use futures::StreamExt;
+///
+/// let addr = ([0, 0, 0, 0], 8080).into();
+/// let mut events = activity::serve_webhook(addr, "consumer secret").unwrap();
+/// while let Some(event) = events.next().await {
+///     if let ActivityEvents::TweetCreate(tweets) = event.events {
+///         for tweet in tweets {
+///             println!("new tweet for {}: {}", event.for_user_id, tweet.text);
+///         }
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`register_webhook`]: fn.register_webhook.html
+pub fn serve_webhook(
+    addr: SocketAddr,
+    consumer_secret: impl Into<String>,
+) -> Result<WebhookEvents> {
+    let incoming = AddrIncoming::bind(&addr)?;
+    let local_addr = incoming.local_addr();
+    let secret: Arc<str> = consumer_secret.into().into();
+    let (sender, events) = mpsc::channel(EVENT_BUFFER);
+
+    let make_service = make_service_fn(move |_conn| {
+        let secret = secret.clone();
+        let sender = sender.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                handle(req, secret.clone(), sender.clone())
+            }))
+        }
+    });
+    let server = Server::builder(incoming).serve(make_service);
+
+    let (handle, registration) = AbortHandle::new_pair();
+    tokio::spawn(Abortable::new(server, registration));
+
+    Ok(WebhookEvents {
+        events,
+        local_addr,
+        server: handle,
+    })
+}
+
+/// Handles one request to the webhook.
+async fn handle(
+    req: Request<Body>,
+    secret: Arc<str>,
+    mut sender: mpsc::Sender<AccountActivityEvent>,
+) -> std::result::Result<Response<Body>, Infallible> {
+    let resp = match *req.method() {
+        Method::GET => {
+            let crc_token = req.uri().query().and_then(|query| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .find(|(key, _)| key == "crc_token")
+                    .map(|(_, value)| value.into_owned())
+            });
+            match crc_token {
+                Some(crc_token) => Response::builder()
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(crc_response(&secret, &crc_token))),
+                None => status(StatusCode::BAD_REQUEST),
+            }
+        }
+        Method::POST => {
+            let signature = req
+                .headers()
+                .get(SIGNATURE_HEADER)
+                .and_then(|sig| sig.to_str().ok())
+                .map(str::to_owned);
+            let too_large = req
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok())
+                .and_then(|len| len.parse::<usize>().ok())
+                .map_or(false, |len| len > MAX_BODY_SIZE);
+            if too_large {
+                return Ok(status(StatusCode::PAYLOAD_TOO_LARGE).unwrap());
+            }
+            let body = match read_body(req.into_body()).await {
+                Ok(Some(body)) => body,
+                Ok(None) => return Ok(status(StatusCode::PAYLOAD_TOO_LARGE).unwrap()),
+                Err(_) => return Ok(status(StatusCode::BAD_REQUEST).unwrap()),
+            };
+            match signature {
+                Some(sig) if validate_signature(&secret, &body, &sig) => {
+                    match serde_json::from_slice::<AccountActivityEvent>(&body) {
+                        Ok(event) => {
+                            // if the stream was dropped, the server is about to stop anyway
+                            let _ = sender.send(event).await;
+                            status(StatusCode::OK)
+                        }
+                        Err(_) => status(StatusCode::BAD_REQUEST),
+                    }
+                }
+                _ => status(StatusCode::UNAUTHORIZED),
+            }
+        }
+        _ => status(StatusCode::METHOD_NOT_ALLOWED),
+    };
+    Ok(resp.unwrap())
+}
+
+/// Reads the given request body, or returns `None` if it's larger than `MAX_BODY_SIZE`.
+async fn read_body(mut body: Body) -> hyper::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > MAX_BODY_SIZE {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(bytes))
+}
+
+fn status(status: StatusCode) -> hyper::http::Result<Response<Body>> {
+    Response::builder().status(status).body(Body::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn webhook_requests() {
+        let secret: Arc<str> = "consumer secret".into();
+        let (sender, mut events) = mpsc::channel(1);
+
+        let crc = Request::get("/webhook?crc_token=abc%3D")
+            .body(Body::empty())
+            .unwrap();
+        let resp = handle(crc, secret.clone(), sender.clone()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, crc_response("consumer secret", "abc="));
+
+        let event = r#"{"for_user_id":"2244994945","some_new_events":[]}"#;
+        let signed = |signature: &str| {
+            Request::post("/webhook")
+                .header(SIGNATURE_HEADER, signature)
+                .body(Body::from(event))
+                .unwrap()
+        };
+        let response_token = crc_response("consumer secret", event);
+        let response_token: serde_json::Value = serde_json::from_str(&response_token).unwrap();
+        let signature = response_token["response_token"].as_str().unwrap();
+
+        let resp = handle(signed("sha256=forged"), secret.clone(), sender.clone())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = handle(signed(signature), secret.clone(), sender.clone())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let huge = Request::post("/webhook")
+            .header(SIGNATURE_HEADER, signature)
+            .header(header::CONTENT_LENGTH, MAX_BODY_SIZE + 1)
+            .body(Body::empty())
+            .unwrap();
+        let resp = handle(huge, secret.clone(), sender.clone()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let huge = Request::post("/webhook")
+            .header(SIGNATURE_HEADER, signature)
+            .body(Body::from(vec![b' '; MAX_BODY_SIZE + 1]))
+            .unwrap();
+        let resp = handle(huge, secret.clone(), sender.clone()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        drop(sender);
+        let event = events.next().await.unwrap();
+        assert_eq!(event.for_user_id, 2244994945);
+        assert!(events.next().await.is_none());
+    }
+}
@@ -193,7 +193,7 @@ use serde_json;
 use crate::common::*;
 use crate::{
     error::{self, Result},
-    links,
+    links, state,
 };
 
 pub(crate) mod raw;
@@ -287,6 +287,27 @@ pub enum Token {
     Bearer(String),
 }
 
+impl Token {
+    /// Saves this token in the given [`StateStore`] under the given name, so it can be loaded
+    /// again later with `load_from`.
+    ///
+    /// [`StateStore`]: ../state/trait.StateStore.html
+    ///
+    /// Remember that the keys in a token work like a password! Make sure the store you're saving
+    /// it into is kept somewhere safe.
+    pub fn save_to<S: state::StateStore + ?Sized>(&self, store: &S, name: &str) -> Result<()> {
+        Ok(state::save_json(store, &format!("token:{}", name), self)?)
+    }
+
+    /// Loads a token that was previously saved with `save_to` under the given name.
+    pub fn load_from<S: state::StateStore + ?Sized>(
+        store: &S,
+        name: &str,
+    ) -> Result<Option<Token>> {
+        Ok(state::load_json(store, &format!("token:{}", name))?)
+    }
+}
+
 /// With the given consumer KeyPair, ask Twitter for a request KeyPair that can be used to request
 /// access to the user's account.
 ///
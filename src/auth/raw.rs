@@ -30,7 +30,7 @@ pub struct RequestBuilder<'a> {
     method: Method,
     params: Option<ParamList>,
     query: Option<String>,
-    body: Option<(Body, &'static str)>,
+    body: Option<(Body, Cow<'static, str>)>,
     addon: OAuthAddOn,
 }
 
@@ -91,7 +91,7 @@ impl<'a> RequestBuilder<'a> {
         RequestBuilder {
             body: Some((
                 Body::from(params.to_urlencoded()),
-                "application/x-www-form-urlencoded",
+                "application/x-www-form-urlencoded".into(),
             )),
             params: Some(total_params),
             ..self
@@ -117,9 +117,9 @@ impl<'a> RequestBuilder<'a> {
     /// Note that the functions that specify a request body each overwrite the body. For example,
     /// if you specify `with_body_params` and also `with_body`, only the one you call last will be
     /// sent with the request.
-    pub fn with_body(self, body: impl Into<Body>, content: &'static str) -> Self {
+    pub fn with_body(self, body: impl Into<Body>, content: impl Into<Cow<'static, str>>) -> Self {
         RequestBuilder {
-            body: Some((body.into(), content)),
+            body: Some((body.into(), content.into())),
             ..self
         }
     }
@@ -202,7 +202,10 @@ impl<'a> RequestBuilder<'a> {
             .header(AUTHORIZATION, authorization);
 
         if let Some((body, content)) = self.body {
-            request.header(CONTENT_TYPE, content).body(body).unwrap()
+            request
+                .header(CONTENT_TYPE, content.as_ref())
+                .body(body)
+                .unwrap()
         } else {
             request.body(Body::empty()).unwrap()
         }
@@ -341,7 +344,7 @@ impl OAuthParams {
 
         params.insert(
             "oauth_signature",
-            base64::encode(&digest.finalize().into_bytes()).into(),
+            base64::encode(digest.finalize().into_bytes()).into(),
         );
 
         SignedHeader { params }
@@ -474,6 +477,18 @@ pub fn post_json<B: serde::Serialize>(uri: &str, token: &Token, body: B) -> Requ
         .request_token(token)
 }
 
+// n.b. this function is re-exported in the `raw` module - these docs are public!
+/// Assemble a signed PUT request to the given URL with the given JSON body.
+///
+/// This works like `post_json`, but for endpoints that expect a PUT request, like the v2
+/// `PUT /2/tweets/:id/hidden`. As with `post_json`, the body is not encoded into the OAuth
+/// signature.
+pub fn put_json<B: serde::Serialize>(uri: &str, token: &Token, body: B) -> Request<Body> {
+    RequestBuilder::new(Method::PUT, uri)
+        .with_body_json(body)
+        .request_token(token)
+}
+
 #[cfg(test)]
 mod tests {
     use super::bearer_request;
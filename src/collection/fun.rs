@@ -0,0 +1,223 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use hyper::{Body, Request};
+use serde::Serialize;
+
+use super::*;
+
+///The most changes `curate` can make in one call.
+const MAX_CHANGES: usize = 100;
+
+///Look up the metadata for a single collection.
+pub async fn show(id: impl Into<String>, token: &auth::Token) -> Result<Response<Collection>> {
+    let params = ParamList::new().add_param("id", id.into());
+
+    let req = get(links::collections::SHOW, token, Some(&params));
+    let resp = request_with_json_response::<RawCollectionResponse>(req).await?;
+    Response::try_map(resp, RawCollectionResponse::into_collection)
+}
+
+///Creates a collection, with the given name, description, and order.
+///
+///The new collection is owned by the authenticated user, and starts out empty. Names can be up to
+///25 characters long, and descriptions up to 160.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::collection::{self, TimelineOrder};
+///
+///let collection = collection::create(
+///    "Release notes",
+///    Some("Every release announcement".into()),
+///    TimelineOrder::TweetReverseChron,
+///    &token,
+///)
+///.await
+///.unwrap();
+///println!("created {} at {}", collection.id, collection.collection_url);
+///# }
+///```
+pub async fn create(
+    name: impl Into<String>,
+    desc: Option<String>,
+    timeline_order: TimelineOrder,
+    token: &auth::Token,
+) -> Result<Response<Collection>> {
+    let params = ParamList::new()
+        .add_param("name", name.into())
+        .add_opt_param("description", desc)
+        .add_param("timeline_order", timeline_order.to_string());
+
+    let req = post(links::collections::CREATE, token, Some(&params));
+    let resp = request_with_json_response::<RawCollectionResponse>(req).await?;
+    Response::try_map(resp, RawCollectionResponse::into_collection)
+}
+
+///Deletes the given collection.
+///
+///The authenticated user must have created the collection.
+pub async fn delete(id: impl Into<String>, token: &auth::Token) -> Result<Response<()>> {
+    let params = ParamList::new().add_param("id", id.into());
+
+    let req = post(links::collections::DESTROY, token, Some(&params));
+    request_with_empty_response(req).await
+}
+
+///Begins updating a collection's metadata.
+///
+///This method is exposed using a builder struct. See the [`CollectionUpdate`] docs for details.
+///
+///[`CollectionUpdate`]: struct.CollectionUpdate.html
+pub fn update(id: impl Into<String>) -> CollectionUpdate {
+    CollectionUpdate {
+        id: id.into(),
+        name: None,
+        desc: None,
+        url: None,
+    }
+}
+
+///Make an `EntryTimeline` for the tweets in the given collection.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///let timeline = egg_mode::collection::entries("custom-539487832448843776", &token)
+///    .with_page_size(50);
+///let (timeline, first_page) = timeline.start().await.unwrap();
+///let (_timeline, second_page) = timeline.older().await.unwrap();
+///for tweet in first_page.iter().chain(second_page.iter()) {
+///    println!("{}", tweet.text);
+///}
+///# }
+///```
+pub fn entries(id: impl Into<String>, token: &auth::Token) -> EntryTimeline {
+    EntryTimeline::new(id.into(), token)
+}
+
+///Adds the given tweet to a collection.
+///
+///If `placement` is given, the tweet is placed above or below another tweet in the collection.
+///Otherwise, it's placed where the collection's `timeline_order` puts it. If the tweet couldn't be
+///added (for example, because it's already in the collection), this returns the reason as a
+///`ChangeError`.
+pub async fn add_entry(
+    id: impl Into<String>,
+    tweet_id: impl Into<TweetId>,
+    placement: Option<Placement>,
+    token: &auth::Token,
+) -> Result<Response<Vec<ChangeError>>> {
+    let mut params = ParamList::new()
+        .add_param("id", id.into())
+        .add_param("tweet_id", tweet_id.into().to_string());
+    if let Some(placement) = placement {
+        params = placement.add_params(params);
+    }
+
+    let req = post(links::collections::ADD, token, Some(&params));
+    changes_response(req).await
+}
+
+///Removes the given tweet from a collection.
+///
+///If the tweet couldn't be removed (for example, because it wasn't in the collection), this
+///returns the reason as a `ChangeError`.
+pub async fn remove_entry(
+    id: impl Into<String>,
+    tweet_id: impl Into<TweetId>,
+    token: &auth::Token,
+) -> Result<Response<Vec<ChangeError>>> {
+    let params = ParamList::new()
+        .add_param("id", id.into())
+        .add_param("tweet_id", tweet_id.into().to_string());
+
+    let req = post(links::collections::REMOVE, token, Some(&params));
+    changes_response(req).await
+}
+
+///Moves a tweet in a collection to above or below another tweet in it.
+///
+///Tweets can only be moved in collections ordered by `TimelineOrder::CurationReverseChron`. If
+///the tweet couldn't be moved, this returns the reason as a `ChangeError`.
+pub async fn move_entry(
+    id: impl Into<String>,
+    tweet_id: impl Into<TweetId>,
+    placement: Placement,
+    token: &auth::Token,
+) -> Result<Response<Vec<ChangeError>>> {
+    let params = ParamList::new()
+        .add_param("id", id.into())
+        .add_param("tweet_id", tweet_id.into().to_string());
+    let params = placement.add_params(params);
+
+    let req = post(links::collections::MOVE, token, Some(&params));
+    changes_response(req).await
+}
+
+///Adds and removes tweets from a collection in one call.
+///
+///The changes are made in the order given, and any that couldn't be made are returned as
+///`ChangeError`s; the others are still made. A single call can make up to 100 changes; if more
+///than that are given, this returns `Error::InvalidParam` without contacting Twitter.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::collection::{self, CollectionChange};
+///
+///let changes = vec![
+///    CollectionChange::Add { tweet_id: 782644334671691776.into() },
+///    CollectionChange::Remove { tweet_id: 390897780949925889.into() },
+///];
+///let errors = collection::curate("custom-539487832448843776", changes, &token)
+///    .await
+///    .unwrap();
+///for error in errors.iter() {
+///    println!("couldn't make change {:?}: {}", error.change, error.reason);
+///}
+///# }
+///```
+pub async fn curate(
+    id: impl Into<String>,
+    changes: impl IntoIterator<Item = CollectionChange>,
+    token: &auth::Token,
+) -> Result<Response<Vec<ChangeError>>> {
+    #[derive(Serialize)]
+    struct Curate {
+        id: String,
+        changes: Vec<CollectionChange>,
+    }
+
+    let body = Curate {
+        id: id.into(),
+        changes: changes.into_iter().collect(),
+    };
+    if body.changes.len() > MAX_CHANGES {
+        return Err(Error::InvalidParam(
+            "curate can make at most 100 changes at once",
+        ));
+    }
+
+    let req = post_json(links::collections::CURATE, token, body);
+    changes_response(req).await
+}
+
+async fn changes_response(req: Request<Body>) -> Result<Response<Vec<ChangeError>>> {
+    let resp = request_with_json_response::<RawChanges>(req).await?;
+    Ok(Response::map(resp, |changes| changes.response.errors))
+}
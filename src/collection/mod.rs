@@ -0,0 +1,534 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and functions for working with collections.
+//!
+//! A collection is a timeline of tweets that a user has picked out by hand, rather than one made
+//! from the tweets of the accounts they follow. Any tweet can be added to a collection, and the
+//! tweets in it can be kept in the order they were added, or in the order they were posted. A
+//! collection is referred to by its ID, a string like `"custom-539487832448843776"`.
+//!
+//! ## Types
+//!
+//! - `Collection`: The metadata for a collection, returned when loading it or making some change
+//!   to it.
+//! - `TimelineOrder`: How the tweets in a collection are ordered.
+//! - `CollectionUpdate`: When updating a collection's metadata, all the fields that can be updated
+//!   are optional, so the `update` function returns this builder struct so you don't have to
+//!   provide all the parameters if you don't need to.
+//! - `EntryTimeline`: The tweets in a collection, loaded a page at a time by `entries`.
+//! - `Placement`: Where to put a tweet when adding or moving it in a collection.
+//! - `CollectionChange`/`ChangeError`: The changes for `curate` to make in bulk, and the ones
+//!   Twitter couldn't make.
+//!
+//! ## Functions
+//!
+//! ### Basic actions
+//!
+//! These functions make changes to collections owned by the authenticated user. These all require
+//! write access to the authenticated user's account.
+//!
+//! - `create`/`delete`
+//! - `update` (see `CollectionUpdate` for full details)
+//! - `add_entry`/`remove_entry`/`move_entry`
+//! - `curate`: This adds and removes up to 100 tweets in one call
+//!
+//! ### Basic queries
+//!
+//! - `show`
+//! - `entries`
+
+use std::collections::HashMap;
+use std::fmt;
+
+use hyper::{Body, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::error::{Error, Result};
+use crate::tweet::{Tweet, TweetId};
+use crate::user::UserId;
+use crate::{auth, links};
+
+mod fun;
+
+pub use self::fun::*;
+
+/// Represents the metadata for a collection.
+#[derive(Debug, Clone)]
+pub struct Collection {
+    /// The ID of the collection, like `"custom-539487832448843776"`.
+    pub id: String,
+    /// The name of the collection.
+    pub name: String,
+    /// The description of the collection, as entered by its creator.
+    pub description: Option<String>,
+    /// The URL the creator attached to the collection, if any.
+    pub url: Option<String>,
+    /// The link to view the collection on twitter.com.
+    pub collection_url: String,
+    /// How the tweets in the collection are ordered.
+    pub timeline_order: TimelineOrder,
+    /// The ID of the user who created the collection.
+    pub user_id: UserId,
+}
+
+/// Represents the ways the tweets in a collection can be ordered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineOrder {
+    /// Tweets are ordered by when they were added to the collection, newest first, and can be moved
+    /// with `move_entry`. This is the default.
+    CurationReverseChron,
+    /// Tweets are ordered by when they were posted, oldest first.
+    TweetChron,
+    /// Tweets are ordered by when they were posted, newest first.
+    TweetReverseChron,
+}
+
+/// Display impl to make `to_string()` format the enum for sending to Twitter.
+impl fmt::Display for TimelineOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quoted = serde_json::to_string(self).unwrap();
+        let inner = &quoted[1..quoted.len() - 1]; // ignore the quote marks
+        write!(f, "{}", inner)
+    }
+}
+
+/// Represents where to put a tweet in a collection, relative to another tweet in it.
+///
+/// Tweets can only be placed this way in collections ordered by `CurationReverseChron`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// Place the tweet just above the given tweet.
+    Above(TweetId),
+    /// Place the tweet just below the given tweet.
+    Below(TweetId),
+}
+
+impl Placement {
+    fn add_params(self, params: ParamList) -> ParamList {
+        let (relative_to, above) = match self {
+            Placement::Above(id) => (id, true),
+            Placement::Below(id) => (id, false),
+        };
+        params
+            .add_param("relative_to", relative_to.to_string())
+            .add_param("above", above.to_string())
+    }
+}
+
+/// Represents a change to make to a collection with `curate`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum CollectionChange {
+    /// Add the given tweet to the collection.
+    Add {
+        /// The tweet to add.
+        #[serde(with = "serde_via_string")]
+        tweet_id: TweetId,
+    },
+    /// Remove the given tweet from the collection.
+    Remove {
+        /// The tweet to remove.
+        #[serde(with = "serde_via_string")]
+        tweet_id: TweetId,
+    },
+}
+
+/// Represents a change to a collection that Twitter couldn't make.
+///
+/// Changes to a collection don't fail as a whole: Twitter makes the changes it can, and returns
+/// one of these for each of the others.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangeError {
+    /// The change that couldn't be made.
+    pub change: CollectionChange,
+    /// Why the change couldn't be made, like `"duplicate"` for a tweet that was already in the
+    /// collection, or `"not_found"` for a tweet that wasn't.
+    pub reason: String,
+}
+
+/// Represents an in-progress update to a collection's metadata.
+///
+/// To begin an update, call `update` with the collection's ID. Set each field you want to change,
+/// then call `send` to make the change.
+pub struct CollectionUpdate {
+    id: String,
+    name: Option<String>,
+    desc: Option<String>,
+    url: Option<String>,
+}
+
+impl CollectionUpdate {
+    /// Updates the name of the collection. Names can be up to 25 characters long.
+    pub fn name(self, name: impl Into<String>) -> CollectionUpdate {
+        CollectionUpdate {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Updates the description of the collection. Descriptions can be up to 160 characters long.
+    pub fn desc(self, desc: impl Into<String>) -> CollectionUpdate {
+        CollectionUpdate {
+            desc: Some(desc.into()),
+            ..self
+        }
+    }
+
+    /// Updates the URL attached to the collection.
+    pub fn url(self, url: impl Into<String>) -> CollectionUpdate {
+        CollectionUpdate {
+            url: Some(url.into()),
+            ..self
+        }
+    }
+
+    /// Sends the update request to Twitter.
+    pub async fn send(self, token: &auth::Token) -> Result<Response<Collection>> {
+        let params = ParamList::new()
+            .add_param("id", self.id)
+            .add_opt_param("name", self.name)
+            .add_opt_param("description", self.desc)
+            .add_opt_param("url", self.url);
+
+        let req = post(links::collections::UPDATE, token, Some(&params));
+        let resp = request_with_json_response::<RawCollectionResponse>(req).await?;
+        Response::try_map(resp, RawCollectionResponse::into_collection)
+    }
+}
+
+/// Helper struct to navigate the tweets in a collection.
+///
+/// `EntryTimeline` works like the [`Timeline`] used for other lists of tweets, but collections
+/// page through their tweets by "position" instead of by tweet ID. Call `start` to load the first
+/// page, then `older` and `newer` to load the pages around it. Each call returns the timeline
+/// back alongside the tweets, with its positions updated to match.
+///
+/// Whichever order the collection is in, `older` loads the tweets further down the collection,
+/// and `newer` loads the tweets further up, including any that have been added since `start`.
+///
+/// [`Timeline`]: ../tweet/struct.Timeline.html
+pub struct EntryTimeline {
+    /// The ID of the collection to load.
+    id: String,
+    /// The token to authorize requests with.
+    token: auth::Token,
+    /// The maximum number of tweets to return in a single call. This defaults to 20, and can be up
+    /// to 200.
+    pub count: u32,
+    /// The position of the first tweet returned in the last call to `start`, `older`, or `newer`.
+    pub max_position: Option<String>,
+    /// The position of the last tweet returned in the last call to `start`, `older`, or `newer`.
+    pub min_position: Option<String>,
+    /// Whether the last call to `start`, `older`, or `newer` left out tweets between its positions
+    /// because of `count`.
+    pub was_truncated: bool,
+}
+
+impl EntryTimeline {
+    fn new(id: String, token: &auth::Token) -> EntryTimeline {
+        EntryTimeline {
+            id,
+            token: token.clone(),
+            count: 20,
+            max_position: None,
+            min_position: None,
+            was_truncated: false,
+        }
+    }
+
+    /// Builder function to set the page size. This defaults to 20, and can be up to 200.
+    pub fn with_page_size(self, count: u32) -> EntryTimeline {
+        EntryTimeline { count, ..self }
+    }
+
+    /// Clear the saved positions on this timeline.
+    pub fn reset(&mut self) {
+        self.max_position = None;
+        self.min_position = None;
+        self.was_truncated = false;
+    }
+
+    /// Clear the saved positions on this timeline, and return the first page of tweets.
+    pub async fn start(mut self) -> Result<(EntryTimeline, Response<Vec<Tweet>>)> {
+        self.reset();
+        self.load(None, None).await
+    }
+
+    /// Return the tweets after the last page loaded.
+    pub async fn older(self) -> Result<(EntryTimeline, Response<Vec<Tweet>>)> {
+        let max_position = self.min_position.clone();
+        self.load(max_position, None).await
+    }
+
+    /// Return the tweets before the last page loaded.
+    pub async fn newer(self) -> Result<(EntryTimeline, Response<Vec<Tweet>>)> {
+        let min_position = self.max_position.clone();
+        self.load(None, min_position).await
+    }
+
+    async fn load(
+        mut self,
+        max_position: Option<String>,
+        min_position: Option<String>,
+    ) -> Result<(EntryTimeline, Response<Vec<Tweet>>)> {
+        let req = self.request(max_position, min_position);
+        let resp = request_with_json_response::<RawEntries>(req).await?;
+        let position = resp.response.response.position.clone();
+        let tweets = Response::try_map(resp, RawEntries::into_tweets)?;
+
+        if tweets.response.is_empty() {
+            self.reset();
+        } else {
+            self.max_position = position.max_position;
+            self.min_position = position.min_position;
+            self.was_truncated = position.was_truncated;
+        }
+
+        Ok((self, tweets))
+    }
+
+    fn request(&self, max_position: Option<String>, min_position: Option<String>) -> Request<Body> {
+        let params = ParamList::new()
+            .extended_tweets()
+            .add_param("id", self.id.clone())
+            .add_param("count", self.count.to_string())
+            .add_param("include_ext_alt_text", "true")
+            .add_opt_param("max_position", max_position)
+            .add_opt_param("min_position", min_position);
+
+        get(links::collections::ENTRIES, &self.token, Some(&params))
+    }
+}
+
+/// The objects that come alongside the response of most collections calls, by ID.
+#[derive(Debug, Deserialize)]
+struct RawObjects {
+    #[serde(default)]
+    timelines: HashMap<String, RawCollection>,
+    #[serde(default)]
+    tweets: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    users: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCollection {
+    name: String,
+    description: Option<String>,
+    url: Option<String>,
+    collection_url: String,
+    timeline_order: TimelineOrder,
+    #[serde(with = "serde_via_string")]
+    user_id: UserId,
+}
+
+impl RawCollection {
+    fn into_collection(self, id: String) -> Collection {
+        Collection {
+            id,
+            name: self.name,
+            description: self.description.filter(|desc| !desc.is_empty()),
+            url: self.url.filter(|url| !url.is_empty()),
+            collection_url: self.collection_url,
+            timeline_order: self.timeline_order,
+            user_id: self.user_id,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCollectionResponse {
+    objects: RawObjects,
+    response: RawTimelineId,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTimelineId {
+    timeline_id: String,
+}
+
+impl RawCollectionResponse {
+    fn into_collection(mut self) -> Result<Collection> {
+        let id = self.response.timeline_id;
+        match self.objects.timelines.remove(&id) {
+            Some(raw) => Ok(raw.into_collection(id)),
+            None => Err(Error::InvalidResponse(
+                "collection missing from response",
+                Some(id),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEntries {
+    objects: RawObjects,
+    response: RawEntriesResponse,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEntriesResponse {
+    position: RawPosition,
+    #[serde(default)]
+    timeline: Vec<RawEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawPosition {
+    max_position: Option<String>,
+    min_position: Option<String>,
+    #[serde(default)]
+    was_truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEntry {
+    tweet: RawEntryTweet,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEntryTweet {
+    id: String,
+}
+
+impl RawEntries {
+    /// Assembles the tweets in the order given by the timeline, filling in their authors from the
+    /// users sent alongside them.
+    fn into_tweets(self) -> Result<Vec<Tweet>> {
+        let RawObjects {
+            mut tweets, users, ..
+        } = self.objects;
+        self.response
+            .timeline
+            .into_iter()
+            .filter_map(|entry| tweets.remove(&entry.tweet.id))
+            .map(|mut tweet| {
+                // the tweets are sent with a stub user object, holding just the author's ID
+                let author = tweet
+                    .get("user")
+                    .and_then(|user| user.get("id_str"))
+                    .and_then(|id| id.as_str())
+                    .and_then(|id| users.get(id));
+                if let Some(author) = author {
+                    tweet["user"] = author.clone();
+                }
+                Ok(serde_json::from_value(tweet)?)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawChanges {
+    response: RawChangeErrors,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawChangeErrors {
+    #[serde(default)]
+    errors: Vec<ChangeError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_collection() {
+        let content = load_file("sample_payloads/collection-show.json");
+        let raw = ::serde_json::from_str::<RawCollectionResponse>(&content).unwrap();
+        let collection = raw.into_collection().unwrap();
+
+        assert_eq!(collection.id, "custom-539487832448843776");
+        assert_eq!(collection.name, "Keepers");
+        assert_eq!(
+            collection.description.as_deref(),
+            Some("Tweets worth keeping around")
+        );
+        assert_eq!(collection.url, None);
+        assert_eq!(
+            collection.timeline_order,
+            TimelineOrder::CurationReverseChron
+        );
+        assert_eq!(collection.user_id, 2977334326);
+        assert_eq!(
+            TimelineOrder::TweetReverseChron.to_string(),
+            "tweet_reverse_chron"
+        );
+    }
+
+    #[test]
+    fn parse_entries() {
+        let content = load_file("sample_payloads/collection-entries.json");
+        let raw = ::serde_json::from_str::<RawEntries>(&content).unwrap();
+        let position = raw.response.position.clone();
+        let tweets = raw.into_tweets().unwrap();
+
+        assert_eq!(tweets.len(), 2);
+        assert_eq!(tweets[0].id, 782644334671691776);
+        assert_eq!(tweets[1].id, 782349500404862976);
+        assert_eq!(
+            tweets[0].user.as_ref().unwrap().screen_name,
+            "QuietMisdreavus"
+        );
+        assert_eq!(tweets[1].user.as_ref().unwrap().screen_name, "0xabad1dea");
+        assert_eq!(position.max_position.as_deref(), Some("371578415352947200"));
+        assert_eq!(position.min_position.as_deref(), Some("371578380871797248"));
+        assert!(!position.was_truncated);
+    }
+
+    #[test]
+    fn entries_request() {
+        let token = auth::Token::Bearer("token".to_string());
+        let timeline = entries("custom-539487832448843776", &token).with_page_size(50);
+
+        let uri = timeline
+            .request(Some("1001".to_string()), None)
+            .uri()
+            .to_string();
+        assert!(uri.starts_with(links::collections::ENTRIES));
+        assert!(uri.contains("id=custom-539487832448843776"));
+        assert!(uri.contains("count=50"));
+        assert!(uri.contains("tweet_mode=extended"));
+        assert!(uri.contains("max_position=1001"));
+        assert!(!uri.contains("min_position"));
+    }
+
+    #[test]
+    fn parse_change_errors() {
+        let content = load_file("sample_payloads/collection-curate.json");
+        let errors = ::serde_json::from_str::<RawChanges>(&content)
+            .unwrap()
+            .response
+            .errors;
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].change,
+            CollectionChange::Add {
+                tweet_id: TweetId(782644334671691776)
+            }
+        );
+        assert_eq!(errors[0].reason, "duplicate");
+        assert_eq!(
+            errors[1].change,
+            CollectionChange::Remove {
+                tweet_id: TweetId(390897780949925889)
+            }
+        );
+
+        let body = serde_json::to_value(CollectionChange::Remove {
+            tweet_id: TweetId(390897780949925889),
+        })
+        .unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"op": "remove", "tweet_id": "390897780949925889"})
+        );
+    }
+}
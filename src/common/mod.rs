@@ -55,7 +55,14 @@
 //! `serde_datetime` and `serde_via_string` are helper modules to use with derived
 //! `Serialize`/`Deserialize` implementations. `serde_datetime` loads and saves `DateTime`s with
 //! the format Twitter uses for timestamps, and `serde_via_string` uses `Display` and `FromStr` to
-//! save a string representation of the original type.
+//! save a string representation of the original type. `serde_opt_via_string` does the same for an
+//! `Option`, for the v2 API's optional ID fields. `serde_number_or_string` loads a numeric ID that
+//! Twitter might send either as a number or as a string, and its `RawId` type does the same for
+//! IDs inside other containers. `serde_millis` uses it to load timestamps given as milliseconds
+//! since the Unix epoch, like the `created_timestamp` of DM events.
+//!
+//! `id_newtype!` creates the numeric ID newtypes like `TweetId` and `UserId`, so they all get the
+//! same set of conversions and trait impls without copying them around between modules.
 //!
 //! `merge_by` and its companion type `MergeBy` is a copy of the iterator adapter of the same name
 //! from itertools, because i didn't want to add another dependency onto the great towering pile
@@ -68,8 +75,8 @@
 //!
 //! ## Authentication functions
 //!
-//! The functions `get`, `post`, and `post_json` are re-exported here to keep people from having to
-//! qualify them from `auth::raw`.
+//! The functions `get`, `post`, `post_json`, and `put_json` are re-exported here to keep people from
+//! having to qualify them from `auth::raw`.
 //!
 //! ## `Response`
 //!
@@ -99,7 +106,7 @@ use percent_encoding::{utf8_percent_encode, AsciiSet, PercentEncode};
 
 mod response;
 
-pub use crate::auth::raw::{get, post, post_json};
+pub use crate::auth::raw::{get, post, post_json, put_json};
 
 pub use crate::common::response::*;
 use crate::{error, list, user};
@@ -147,6 +154,11 @@ use crate::{error, list, user};
 /// the original struct. This way, either representation can be used to load the struct without the
 /// overhead of loading it all into a `serde_json::Value` first to manually decode into either
 /// type.
+///
+/// The exception is when the `raw-json` feature is enabled. In that case, the struct is loaded
+/// into a `serde_json::Value` first anyway, so that it can be kept in a hidden `raw` field and read
+/// back with the generated `raw()` accessor. Any code constructing the struct directly needs to set
+/// `raw: None` behind the same feature gate.
 macro_rules! round_trip {
     ( $raw_name:path,
       $(#[$outer_attr:meta])*
@@ -157,11 +169,17 @@ macro_rules! round_trip {
         $(#[$outer_attr])*
         #[derive(serde::Serialize)]
         #[derive(serde::Deserialize)]
-        #[serde(try_from = "SerEnum")]
+        #[cfg_attr(not(feature = "raw-json"), serde(try_from = "SerEnum"))]
+        #[cfg_attr(feature = "raw-json", serde(try_from = "serde_json::Value"))]
         pub struct $struct_name { $(
             $(#[$attr])*
-            $v $f: $t
-        ),+ }
+            $v $f: $t,
+            )+
+            #[serde(skip)]
+            raw: Option<std::sync::Arc<serde_json::Value>>,
+        }
+
+        raw_accessor!($struct_name);
 
         #[allow(unused_qualifications)]
         impl crate::common::RoundTrip for $struct_name {
@@ -187,19 +205,22 @@ macro_rules! round_trip {
         impl From<SerCopy> for $struct_name {
             fn from(src: SerCopy) -> $struct_name {
                 $struct_name { $(
-                    $f: src.$f
-                ),+ }
+                    $f: src.$f,
+                    )+
+                    raw: None,
+                }
             }
         }
 
         #[derive(serde::Deserialize)]
         #[serde(untagged)]
+        #[allow(clippy::large_enum_variant)]
         enum SerEnum {
             Raw($raw_name),
             Ser(SerCopy),
         }
 
-        #[allow(unused_qualifications)]
+        #[allow(unused_qualifications, clippy::infallible_try_from)]
         impl std::convert::TryFrom<SerEnum> for $struct_name
         where
             $struct_name: std::convert::TryFrom<$raw_name>,
@@ -215,6 +236,138 @@ macro_rules! round_trip {
                 }
             }
         }
+
+        #[cfg(feature = "raw-json")]
+        #[allow(unused_qualifications)]
+        impl std::convert::TryFrom<serde_json::Value> for $struct_name
+        where
+            $struct_name: std::convert::TryFrom<$raw_name>,
+            <$struct_name as std::convert::TryFrom<$raw_name>>::Error: std::fmt::Display,
+        {
+            type Error = String;
+
+            fn try_from(src: serde_json::Value) -> std::result::Result<$struct_name, String> {
+                use std::convert::TryInto;
+
+                let ser: SerEnum = serde_json::from_value(src.clone()).map_err(|e| e.to_string())?;
+                let mut parsed: $struct_name = ser.try_into().map_err(|e| format!("{}", e))?;
+                parsed.raw = Some(std::sync::Arc::new(src));
+                Ok(parsed)
+            }
+        }
+    };
+}
+
+/// Generates the `raw()` accessor for a struct with a hidden `raw` field.
+///
+/// The field and accessor exist whether or not the `raw-json` feature is enabled, so that turning
+/// the feature on doesn't change the layout of the struct; without it, the field is always `None`.
+macro_rules! raw_accessor {
+    ($struct_name:ident) => {
+        impl $struct_name {
+            /// Returns the full JSON this value was parsed from, including any fields that
+            /// egg-mode doesn't know about yet.
+            ///
+            /// This returns `None` unless the `raw-json` feature is enabled, or if the value was
+            /// created some other way than by loading it from JSON.
+            pub fn raw(&self) -> Option<&serde_json::Value> {
+                self.raw.as_deref()
+            }
+        }
+    };
+}
+
+/// Macro to derive `Deserialize` for a struct that's loaded straight from Twitter's JSON, keeping
+/// that JSON around when the `raw-json` feature is enabled.
+///
+/// This is the counterpart to `round_trip!` for structs that don't need a separate "raw" type.
+/// Without the `raw-json` feature, it's the same as adding `#[derive(Deserialize)]` to the struct.
+/// With it, the struct is loaded into a `serde_json::Value` first, then parsed through a
+/// generated `RawJsonCopy` struct, keeping the `Value` in a hidden `raw` field that the generated
+/// `raw()` accessor reads. Like `round_trip!`, this can only be used once per module.
+macro_rules! raw_json {
+    ( $(#[$outer_attr:meta])*
+      pub struct $struct_name:ident { $(
+          $(#[$attr:meta])*
+          $v:vis $f:ident : $t:ty
+      ),+ $(,)? } ) => {
+        $(#[$outer_attr])*
+        #[derive(serde::Deserialize)]
+        #[cfg_attr(feature = "raw-json", serde(try_from = "serde_json::Value"))]
+        pub struct $struct_name { $(
+            $(#[$attr])*
+            $v $f: $t,
+            )+
+            #[serde(skip)]
+            raw: Option<std::sync::Arc<serde_json::Value>>,
+        }
+
+        raw_accessor!($struct_name);
+
+        #[cfg(feature = "raw-json")]
+        #[derive(serde::Deserialize)]
+        struct RawJsonCopy { $(
+            $(#[$attr])*
+            $v $f: $t
+        ),+ }
+
+        #[cfg(feature = "raw-json")]
+        #[allow(unused_qualifications)]
+        impl std::convert::TryFrom<serde_json::Value> for $struct_name {
+            type Error = serde_json::Error;
+
+            fn try_from(src: serde_json::Value) -> std::result::Result<$struct_name, Self::Error> {
+                let copy: RawJsonCopy = serde_json::from_value(src.clone())?;
+                Ok($struct_name { $(
+                    $f: copy.$f,
+                    )+
+                    raw: Some(std::sync::Arc::new(src)),
+                })
+            }
+        }
+    };
+}
+
+/// Declares a newtype around a numeric ID, so that different kinds of IDs can't be mixed up with
+/// each other.
+///
+/// The generated type is `Copy`, can be compared, hashed, and sorted, serializes as the bare
+/// number, and converts to and from `u64`. It also prints and parses as the bare number, so it can
+/// be used directly as a request parameter.
+macro_rules! id_newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[derive(derive_more::From, derive_more::Into, derive_more::Display)]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl From<&u64> for $name {
+            fn from(id: &u64) -> $name {
+                $name(*id)
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(id: &$name) -> $name {
+                *id
+            }
+        }
+
+        impl PartialEq<u64> for $name {
+            fn eq(&self, other: &u64) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> std::result::Result<$name, Self::Err> {
+                Ok($name(s.parse()?))
+            }
+        }
     };
 }
 
@@ -309,6 +462,14 @@ impl ParamList {
         self.add_param("tweet_mode", "extended")
     }
 
+    /// Adds the `include_cards` and `cards_platform` parameters to this `ParamList`. Twitter only
+    /// includes the "card" attached to a tweet when these parameters are given, which is where
+    /// polls are delivered; without them, the `poll` field of each `Tweet` will be `None`.
+    pub fn include_cards(self) -> Self {
+        self.add_param("include_cards", "true")
+            .add_param("cards_platform", "Web-12")
+    }
+
     /// Adds the given key/value parameter to this `ParamList`.
     pub fn add_param(
         mut self,
@@ -420,10 +581,26 @@ where
     (ids.join(","), names.join(","))
 }
 
+///The maximum number of items Twitter accepts in a single call to its bulk lookup endpoints.
+pub(crate) const LOOKUP_CHUNK_SIZE: usize = 100;
+
 ///Convenient type alias for futures that resolve to responses from Twitter.
 pub(crate) type FutureResponse<T> =
     Pin<Box<dyn Future<Output = error::Result<Response<T>>> + Send>>;
 
+///Returns `Error::InvalidParam` if the given latitude/longitude pair is out of range.
+///
+///Twitter silently drops coordinates outside these ranges, rather than returning an error, so
+///drafts that take coordinates check them before sending.
+pub(crate) fn check_coordinates(lat: f64, long: f64) -> error::Result<()> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&long) {
+        return Err(error::Error::InvalidParam(
+            "latitude must be within -90 to 90, and longitude within -180 to 180",
+        ));
+    }
+    Ok(())
+}
+
 pub fn codepoints_to_bytes(&mut (ref mut start, ref mut end): &mut (usize, usize), text: &str) {
     let mut byte_start = *start;
     let mut byte_end = *end;
@@ -443,6 +620,7 @@ pub fn codepoints_to_bytes(&mut (ref mut start, ref mut end): &mut (usize, usize
 }
 
 ///A clone of MergeBy from Itertools.
+#[allow(dead_code)]
 pub struct MergeBy<Iter, Fun>
 where
     Iter: Iterator,
@@ -486,7 +664,6 @@ where
 }
 
 pub mod serde_datetime {
-    use chrono::TimeZone;
     use serde::de::Error;
     use serde::{Deserialize, Deserializer, Serializer};
 
@@ -497,10 +674,8 @@ pub mod serde_datetime {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(ser)?;
-        let date = (chrono::Utc)
-            .datetime_from_str(&s, DATE_FORMAT)
-            .map_err(D::Error::custom)?;
-        Ok(date)
+        let date = chrono::DateTime::parse_from_str(&s, DATE_FORMAT).map_err(D::Error::custom)?;
+        Ok(date.with_timezone(&chrono::Utc))
     }
 
     pub fn serialize<S>(src: &chrono::DateTime<chrono::Utc>, ser: S) -> Result<S::Ok, S::Error>
@@ -521,7 +696,7 @@ pub mod serde_via_string {
     where
         D: Deserializer<'de>,
         T: std::str::FromStr,
-        <T as std::str::FromStr>::Err: std::fmt::Display,
+        <T as std::str::FromStr>::Err: fmt::Display,
     {
         let str = String::deserialize(ser)?;
         str.parse().map_err(D::Error::custom)
@@ -536,6 +711,74 @@ pub mod serde_via_string {
     }
 }
 
+pub mod serde_opt_via_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    use std::fmt;
+
+    pub fn deserialize<'de, D, T>(ser: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: std::str::FromStr,
+        <T as std::str::FromStr>::Err: fmt::Display,
+    {
+        match Option::<String>::deserialize(ser)? {
+            Some(str) => str.parse().map(Some).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+pub mod serde_number_or_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    /// A numeric ID given either as a number or as a string.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub enum RawId {
+        Number(u64),
+        String(String),
+    }
+
+    impl RawId {
+        /// Converts this into the given ID type, parsing it if it was given as a string.
+        pub fn into_id<T: From<u64>, E: Error>(self) -> Result<T, E> {
+            match self {
+                RawId::Number(id) => Ok(id.into()),
+                RawId::String(id) => id.parse::<u64>().map(Into::into).map_err(E::custom),
+            }
+        }
+    }
+
+    pub fn deserialize<'de, D, T>(ser: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<u64>,
+    {
+        RawId::deserialize(ser)?.into_id()
+    }
+}
+
+pub mod serde_millis {
+    use chrono::TimeZone;
+    use serde::de::Error;
+    use serde::Deserializer;
+    use std::convert::TryFrom;
+
+    pub fn deserialize<'de, D>(ser: D) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let millis: u64 = super::serde_number_or_string::deserialize(ser)?;
+        i64::try_from(millis)
+            .ok()
+            .and_then(|millis| chrono::Utc.timestamp_millis_opt(millis).single())
+            .ok_or_else(|| D::Error::custom("timestamp out of range"))
+    }
+}
+
 /// Percent-encodes the given string based on the Twitter API specification.
 ///
 /// Twitter bases its encoding scheme on RFC 3986, Section 2.1. They describe the process in full
@@ -547,11 +790,11 @@ pub mod serde_via_string {
 ///
 /// When this function was originally implemented, the `percent_encoding` crate did not have an
 /// encoding set that matched this, so it was recreated here.
-pub fn percent_encode(src: &str) -> PercentEncode {
+pub fn percent_encode(src: &str) -> PercentEncode<'_> {
     lazy_static::lazy_static! {
         static ref ENCODER: AsciiSet = percent_encoding::NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
     }
-    utf8_percent_encode(src, &*ENCODER)
+    utf8_percent_encode(src, &ENCODER)
 }
 
 #[cfg(test)]
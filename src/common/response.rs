@@ -112,6 +112,16 @@ impl<T> Response<T> {
     }
 }
 
+impl<T> Response<Vec<T>> {
+    ///Appends the contents of a later response onto this one, for endpoints that need to be
+    ///called several times to cover the full set of requested items. The rate-limit information is
+    ///taken from the later response, since it reflects the most recent state of the rate window.
+    pub(crate) fn extend(&mut self, later: Response<Vec<T>>) {
+        self.rate_limit_status = later.rate_limit_status;
+        self.response.extend(later.response);
+    }
+}
+
 impl<T: IntoIterator> IntoIterator for Response<T> {
     type IntoIter = ResponseIter<T::IntoIter>;
     type Item = Response<T::Item>;
@@ -0,0 +1,573 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Methods to keep stored tweets and users in line with what's still public, via Twitter's v2
+//! compliance endpoints.
+//!
+//! Twitter's developer policy asks apps that store tweets or user information to remove or update
+//! them when they're deleted, made protected, withheld, or their author is suspended. There are
+//! two ways to find out about these changes:
+//!
+//! - A batch compliance job checks a list of tweet or user IDs you've stored. Create one with
+//!   [`create_job`], send the IDs to it with [`upload_ids`], wait for its `status` (loaded again
+//!   with [`show_job`]) to become `Complete`, and load the changes with [`download_results`]. The
+//!   results only list the IDs that need to be changed.
+//! - The compliance stream, opened with [`stream`], delivers these changes for every tweet or user
+//!   as they happen. It's only available to some enterprise accounts.
+//!
+//! These endpoints all require a Bearer token.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let token: egg_mode::Token = unimplemented!();
+//! use std::time::Duration;
+//! use egg_mode::compliance::{self, JobKind, JobStatus};
+//!
+//! let stored_ids = vec![1261253754969640960u64, 1261253754969640961];
+//! let mut job = compliance::create_job(JobKind::Tweets, None, &token).await?.response;
+//! compliance::upload_ids(&job, stored_ids).await?;
+//!
+//! while job.status == JobStatus::Created || job.status == JobStatus::InProgress {
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!     job = compliance::show_job(&job.id, &token).await?.response;
+//! }
+//!
+//! for result in compliance::download_results(&job).await? {
+//!     if let Some(id) = result.tweet_id {
+//!         println!("tweet {} needs to be handled: {}", id, result.reason);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`create_job`]: fn.create_job.html
+//! [`upload_ids`]: fn.upload_ids.html
+//! [`show_job`]: fn.show_job.html
+//! [`download_results`]: fn.download_results.html
+//! [`stream`]: fn.stream.html
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use hyper::{header, Body, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::error::{Error, Result};
+use crate::stream::{StreamMessage, TwitterStream};
+use crate::tweet::TweetId;
+use crate::user::UserId;
+use crate::{auth, links, v2};
+
+/// The kinds of IDs a compliance job or compliance stream can check.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Tweet IDs.
+    Tweets,
+    /// User IDs.
+    Users,
+}
+
+impl JobKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobKind::Tweets => "tweets",
+            JobKind::Users => "users",
+        }
+    }
+}
+
+/// The stages a compliance job goes through.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job was created, and is waiting for IDs to be uploaded.
+    Created,
+    /// The uploaded IDs are being checked.
+    InProgress,
+    /// The job finished, and its results can be downloaded.
+    Complete,
+    /// The job's results expired before they were downloaded.
+    Expired,
+    /// The job couldn't be completed. Its `error` describes why.
+    Failed,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Created => "created",
+            JobStatus::InProgress => "in_progress",
+            JobStatus::Complete => "complete",
+            JobStatus::Expired => "expired",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A batch compliance job, as returned by [`create_job`], [`show_job`], and [`list_jobs`].
+///
+/// [`create_job`]: fn.create_job.html
+/// [`show_job`]: fn.show_job.html
+/// [`list_jobs`]: fn.list_jobs.html
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComplianceJob {
+    /// The ID of this job.
+    pub id: String,
+    /// The kind of IDs this job checks.
+    #[serde(rename = "type")]
+    pub kind: JobKind,
+    /// The name given to this job when it was created, if any.
+    pub name: Option<String>,
+    /// The stage this job is in.
+    pub status: JobStatus,
+    /// Whether the IDs can be uploaded in more than one request.
+    #[serde(default)]
+    pub resumable: bool,
+    /// UTC timestamp from when this job was created.
+    pub created_at: DateTime<Utc>,
+    /// The URL to upload the IDs to check to, with [`upload_ids`].
+    ///
+    /// [`upload_ids`]: fn.upload_ids.html
+    pub upload_url: String,
+    /// UTC timestamp from when `upload_url` stops accepting uploads.
+    pub upload_expires_at: DateTime<Utc>,
+    /// The URL to download the results from once the job is complete, with
+    /// [`download_results`].
+    ///
+    /// [`download_results`]: fn.download_results.html
+    pub download_url: String,
+    /// UTC timestamp from when the results stop being available from `download_url`.
+    pub download_expires_at: DateTime<Utc>,
+    /// A description of what went wrong, if the job failed.
+    pub error: Option<String>,
+}
+
+/// A change to a tweet or user that a compliance job found, as returned by
+/// [`download_results`].
+///
+/// Which of `tweet_id` and `user_id` is filled in depends on the job's `kind`.
+///
+/// [`download_results`]: fn.download_results.html
+#[derive(Debug, Clone)]
+pub struct ComplianceResult {
+    /// The tweet that changed, for `Tweets` jobs.
+    pub tweet_id: Option<TweetId>,
+    /// The user that changed, for `Users` jobs.
+    pub user_id: Option<UserId>,
+    /// What needs to be done with the tweet or user. This is `"delete"` for every result so far.
+    pub action: String,
+    /// UTC timestamp from when the tweet or user was created, if Twitter returned it.
+    pub created_at: Option<DateTime<Utc>>,
+    /// UTC timestamp from when the tweet or user stopped being public.
+    pub redacted_at: Option<DateTime<Utc>>,
+    /// Why the tweet or user needs to be handled, like `"deleted"`, `"suspended"`, `"protected"`,
+    /// or `"deactivated"`.
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct RawResult {
+    #[serde(with = "serde_via_string")]
+    id: u64,
+    action: String,
+    created_at: Option<DateTime<Utc>>,
+    redacted_at: Option<DateTime<Utc>>,
+    reason: String,
+}
+
+impl ComplianceResult {
+    fn from_raw(raw: RawResult, kind: JobKind) -> ComplianceResult {
+        let (tweet_id, user_id) = match kind {
+            JobKind::Tweets => (Some(raw.id.into()), None),
+            JobKind::Users => (None, Some(raw.id.into())),
+        };
+        ComplianceResult {
+            tweet_id,
+            user_id,
+            action: raw.action,
+            created_at: raw.created_at,
+            redacted_at: raw.redacted_at,
+            reason: raw.reason,
+        }
+    }
+}
+
+/// An event from the compliance stream, opened with [`stream`].
+///
+/// [`stream`]: fn.stream.html
+#[derive(Debug, Clone)]
+pub struct ComplianceEvent {
+    /// The kind of event, like `"delete"`, `"withheld"`, or `"scrub_geo"` for tweets, or
+    /// `"user_delete"`, `"user_protect"`, or `"user_suspend"` for users.
+    pub kind: String,
+    /// The tweet the event is about, for tweet events.
+    pub tweet_id: Option<TweetId>,
+    /// The user the event is about, or the author of the tweet, for tweet events.
+    pub user_id: Option<UserId>,
+    /// The countries the tweet or user is withheld in, for `withheld` and `user_withheld` events.
+    pub withheld_in_countries: Vec<String>,
+    /// UTC timestamp from when the event happened.
+    pub event_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+    tweet: Option<RawObject>,
+    user: Option<RawObject>,
+    #[serde(default)]
+    withheld_in_countries: Vec<String>,
+    event_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    #[serde(with = "serde_via_string")]
+    id: u64,
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    author_id: Option<u64>,
+}
+
+impl ComplianceEvent {
+    /// Parses an event from a compliance stream message, or the errors Twitter sent in its place.
+    fn from_message(value: &serde_json::Value) -> Option<Result<ComplianceEvent>> {
+        if let Some(data) = value.get("data") {
+            return ComplianceEvent::from_data(data);
+        }
+        let errors = value.get("errors")?;
+        match Vec::<v2::PartialError>::deserialize(errors) {
+            Ok(errors) => Some(Err(Error::TwitterErrorV2(errors))),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+
+    /// Parses an event from the `data` of a compliance stream message, which holds a single key
+    /// named for the kind of event.
+    fn from_data(data: &serde_json::Value) -> Option<Result<ComplianceEvent>> {
+        let (kind, event) = data.as_object()?.iter().next()?;
+        let event = match RawEvent::deserialize(event) {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e.into())),
+        };
+        let (tweet_id, user_id) = match (event.tweet, event.user) {
+            (Some(tweet), _) => (Some(tweet.id.into()), tweet.author_id.map(Into::into)),
+            (None, Some(user)) => (None, Some(user.id.into())),
+            (None, None) => (None, None),
+        };
+        Some(Ok(ComplianceEvent {
+            kind: kind.clone(),
+            tweet_id,
+            user_id,
+            withheld_in_countries: event.withheld_in_countries,
+            event_at: event.event_at,
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+#[derive(Serialize)]
+struct CreateJob<'a> {
+    #[serde(rename = "type")]
+    kind: JobKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+}
+
+/// Create a batch compliance job to check the given kind of IDs.
+///
+/// Once it's created, upload the IDs to check to the job with [`upload_ids`], before its
+/// `upload_expires_at`. The `name` is only used to tell jobs apart in [`list_jobs`].
+///
+/// [`upload_ids`]: fn.upload_ids.html
+/// [`list_jobs`]: fn.list_jobs.html
+pub async fn create_job(
+    kind: JobKind,
+    name: Option<&str>,
+    token: &auth::Token,
+) -> Result<Response<ComplianceJob>> {
+    let req = post_json(links::compliance::JOBS, token, CreateJob { kind, name });
+    let resp = request_with_json_response::<Data<ComplianceJob>>(req).await?;
+    Ok(Response::map(resp, |resp| resp.data))
+}
+
+/// Look up the batch compliance job with the given ID, to see whether it's complete.
+pub async fn show_job(id: &str, token: &auth::Token) -> Result<Response<ComplianceJob>> {
+    let url = format!("{}/{}", links::compliance::JOBS, id);
+    let req = get(&url, token, None);
+    let resp = request_with_json_response::<Data<ComplianceJob>>(req).await?;
+    Ok(Response::map(resp, |resp| resp.data))
+}
+
+/// Load the batch compliance jobs of the given kind created by your app, optionally only the ones
+/// with the given status.
+pub async fn list_jobs(
+    kind: JobKind,
+    status: Option<JobStatus>,
+    token: &auth::Token,
+) -> Result<Response<Vec<ComplianceJob>>> {
+    let params = ParamList::new()
+        .add_param("type", kind.as_str())
+        .add_opt_param("status", status.map(JobStatus::as_str));
+    let req = get(links::compliance::JOBS, token, Some(&params));
+    let resp = request_with_json_response::<Data<Option<Vec<ComplianceJob>>>>(req).await?;
+    Ok(Response::map(resp, |resp| resp.data.unwrap_or_default()))
+}
+
+/// Upload the tweet or user IDs to check to the given batch compliance job. Once they're
+/// uploaded, the job starts checking them.
+///
+/// Since a job can check either tweets or users, this takes any IDs that convert into a `u64`,
+/// like `TweetId`s and `UserId`s. The IDs are sent to the job's `upload_url`, which doesn't need a
+/// token.
+pub async fn upload_ids<I, T>(job: &ComplianceJob, ids: I) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<u64>,
+{
+    let req = Request::put(&job.upload_url)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(Body::from(id_list(ids)))
+        .map_err(|_| {
+            Error::InvalidResponse(
+                "compliance job has an invalid upload_url",
+                Some(job.upload_url.clone()),
+            )
+        })?;
+    let resp = get_response(req).await?;
+    if !resp.status().is_success() {
+        return Err(Error::BadStatus(resp.status()));
+    }
+    Ok(())
+}
+
+/// Formats the given IDs as the job upload expects: one ID per line.
+fn id_list(ids: impl IntoIterator<Item = impl Into<u64>>) -> String {
+    ids.into_iter()
+        .map(|id| format!("{}\n", id.into()))
+        .collect()
+}
+
+/// Download the results of the given batch compliance job, once its status is `Complete`.
+///
+/// Only the IDs that need to be changed are listed; any uploaded ID that isn't in the results can
+/// be kept as it is. The results are loaded from the job's `download_url`, which doesn't need a
+/// token.
+pub async fn download_results(job: &ComplianceJob) -> Result<Vec<ComplianceResult>> {
+    let req = Request::get(&job.download_url)
+        .body(Body::empty())
+        .map_err(|_| {
+            Error::InvalidResponse(
+                "compliance job has an invalid download_url",
+                Some(job.download_url.clone()),
+            )
+        })?;
+    let resp = get_response(req).await?;
+    if !resp.status().is_success() {
+        return Err(Error::BadStatus(resp.status()));
+    }
+    let body = hyper::body::to_bytes(resp.into_body()).await?;
+    parse_results(&body, job.kind)
+}
+
+/// Parses the results of a compliance job, which come as one JSON object per line.
+fn parse_results(body: &[u8], kind: JobKind) -> Result<Vec<ComplianceResult>> {
+    body.split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| {
+            Ok(ComplianceResult::from_raw(
+                serde_json::from_slice(line)?,
+                kind,
+            ))
+        })
+        .collect()
+}
+
+/// A `Stream` of the events from the compliance stream. Created with [`stream`].
+///
+/// Keep-alive messages are skipped. Errors that Twitter sends in place of an event, like a notice
+/// that the stream is being disconnected, are returned as `Error::TwitterErrorV2`.
+///
+/// [`stream`]: fn.stream.html
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct ComplianceStream {
+    inner: TwitterStream,
+}
+
+impl ComplianceStream {
+    /// Sets the stream to reconnect automatically when its connection fails or is closed, rather
+    /// than ending. See [`TwitterStream::auto_reconnect`] for the rules this follows.
+    ///
+    /// [`TwitterStream::auto_reconnect`]: ../stream/struct.TwitterStream.html#method.auto_reconnect
+    pub fn auto_reconnect(self) -> ComplianceStream {
+        ComplianceStream {
+            inner: self.inner.auto_reconnect(),
+        }
+    }
+}
+
+impl Stream for ComplianceStream {
+    type Item = Result<ComplianceEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(StreamMessage::Unknown(value)))) => {
+                    if let Some(event) = ComplianceEvent::from_message(&value) {
+                        return Poll::Ready(Some(event));
+                    }
+                }
+                Poll::Ready(Some(Ok(_))) => (),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Open one partition of the compliance stream for the given kind of IDs.
+///
+/// The compliance stream delivers an event whenever any tweet or user changes in a way that
+/// matters for compliance. Its events are split across 4 partitions, numbered from 1, and every
+/// partition needs to be read to see all of them. Returns `Error::InvalidParam` if `partition`
+/// isn't between 1 and 4.
+pub fn stream(kind: JobKind, partition: u8, token: &auth::Token) -> Result<ComplianceStream> {
+    if !(1..=4).contains(&partition) {
+        return Err(Error::InvalidParam(
+            "compliance stream partitions are numbered from 1 to 4",
+        ));
+    }
+    let url = match kind {
+        JobKind::Tweets => links::compliance::TWEETS_STREAM,
+        JobKind::Users => links::compliance::USERS_STREAM,
+    };
+    let params = ParamList::new().add_param("partition", partition.to_string());
+    let token = token.clone();
+    let inner = TwitterStream::with_connector(move || get(url, &token, Some(&params)));
+    Ok(ComplianceStream { inner })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_job() {
+        let json = r#"{"data":{"id":"1382081613278814209","type":"tweets","name":"my_job",
+            "status":"created","resumable":false,"created_at":"2021-04-13T20:50:23.000Z",
+            "upload_url":"https://storage.googleapis.com/upload",
+            "upload_expires_at":"2021-04-13T21:05:23.000Z",
+            "download_url":"https://storage.googleapis.com/download",
+            "download_expires_at":"2021-04-20T20:50:23.000Z"}}"#;
+        let job = serde_json::from_str::<Data<ComplianceJob>>(json)
+            .unwrap()
+            .data;
+        assert_eq!(job.id, "1382081613278814209");
+        assert_eq!(job.kind, JobKind::Tweets);
+        assert_eq!(job.status, JobStatus::Created);
+        assert_eq!(job.name.as_deref(), Some("my_job"));
+
+        assert_eq!(id_list(vec![1u64, 2]), "1\n2\n");
+        assert_eq!(id_list(vec![TweetId(1), TweetId(2)]), "1\n2\n");
+    }
+
+    #[tokio::test]
+    async fn invalid_job_urls() {
+        let json = r#"{"data":{"id":"1382081613278814209","type":"tweets","status":"complete",
+            "created_at":"2021-04-13T20:50:23.000Z","upload_url":"not a url",
+            "upload_expires_at":"2021-04-13T21:05:23.000Z","download_url":"also not a url",
+            "download_expires_at":"2021-04-20T20:50:23.000Z"}}"#;
+        let job = serde_json::from_str::<Data<ComplianceJob>>(json)
+            .unwrap()
+            .data;
+
+        match upload_ids(&job, vec![1u64]).await {
+            Err(Error::InvalidResponse(_, Some(url))) => assert_eq!(url, "not a url"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match download_results(&job).await {
+            Err(Error::InvalidResponse(_, Some(url))) => assert_eq!(url, "also not a url"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_job_results() {
+        let body = concat!(
+            r#"{"id":"1","action":"delete","created_at":"2021-01-01T00:00:00.000Z","#,
+            r#""redacted_at":"2021-03-01T00:00:00.000Z","reason":"deleted"}"#,
+            "\n",
+            r#"{"id":"2","action":"delete","redacted_at":"2021-03-02T00:00:00.000Z","#,
+            r#""reason":"suspended"}"#,
+            "\n\n",
+        );
+        let results = parse_results(body.as_bytes(), JobKind::Tweets).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].tweet_id, Some(1.into()));
+        assert_eq!(results[0].user_id, None);
+        assert_eq!(results[1].reason, "suspended");
+        assert!(results[1].created_at.is_none());
+
+        let results = parse_results(body.as_bytes(), JobKind::Users).unwrap();
+        assert_eq!(results[0].tweet_id, None);
+        assert_eq!(results[1].user_id, Some(2.into()));
+    }
+
+    #[test]
+    fn parse_stream_events() {
+        let delete = serde_json::json!({
+            "delete": {
+                "tweet": { "id": "1170882402035019776", "author_id": "2244994945" },
+                "event_at": "2021-04-13T20:50:23.000Z"
+            }
+        });
+        let event = ComplianceEvent::from_data(&delete).unwrap().unwrap();
+        assert_eq!(event.kind, "delete");
+        assert_eq!(event.tweet_id, Some(1170882402035019776.into()));
+        assert_eq!(event.user_id, Some(2244994945.into()));
+
+        let withheld = serde_json::json!({
+            "user_withheld": {
+                "user": { "id": "2244994945" },
+                "withheld_in_countries": ["DE"],
+                "event_at": "2021-04-13T20:50:23.000Z"
+            }
+        });
+        let event = ComplianceEvent::from_data(&withheld).unwrap().unwrap();
+        assert_eq!(event.kind, "user_withheld");
+        assert_eq!(event.tweet_id, None);
+        assert_eq!(event.user_id, Some(2244994945.into()));
+        assert_eq!(event.withheld_in_countries, vec!["DE"]);
+
+        let disconnect = serde_json::json!({
+            "errors": [{
+                "title": "operational-disconnect",
+                "disconnect_type": "UpstreamOperationalDisconnect",
+                "detail": "This stream has been disconnected upstream for operational reasons.",
+                "type": "https://api.twitter.com/2/problems/operational-disconnect"
+            }]
+        });
+        match ComplianceEvent::from_message(&disconnect) {
+            Some(Err(Error::TwitterErrorV2(errors))) => {
+                assert_eq!(errors[0].title, "operational-disconnect")
+            }
+            other => panic!("Not an error: {:?}", other),
+        }
+        assert!(ComplianceEvent::from_message(&serde_json::json!({})).is_none());
+
+        let token = auth::Token::Bearer("token".to_string());
+        assert!(stream(JobKind::Tweets, 5, &token).is_err());
+    }
+}
@@ -84,11 +84,28 @@ pub struct IDCursor {
     ///Numeric reference to the next page of results.
     pub next_cursor: i64,
     ///The list of user IDs in this page of results.
-    pub ids: Vec<u64>,
+    ///
+    ///These are loaded the same way whether or not the IDs were requested as strings with
+    ///`CursorIter::with_stringified_ids`.
+    #[serde(deserialize_with = "deserialize_ids")]
+    pub ids: Vec<user::UserId>,
+}
+
+///Loads a list of IDs given either as numbers or, with `stringify_ids` set, as strings.
+fn deserialize_ids<'de, D>(deser: D) -> std::result::Result<Vec<user::UserId>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use crate::common::serde_number_or_string::RawId;
+
+    Vec::<RawId>::deserialize(deser)?
+        .into_iter()
+        .map(RawId::into_id)
+        .collect()
 }
 
 impl Cursor for IDCursor {
-    type Item = u64;
+    type Item = user::UserId;
 
     fn previous_cursor_id(&self) -> i64 {
         self.previous_cursor
@@ -324,6 +341,30 @@ where
     }
 }
 
+impl CursorIter<IDCursor> {
+    ///Asks Twitter to send the IDs in each page as strings instead of numbers.
+    ///
+    ///IDs are returned as `UserId`s either way, but some tools that handle Twitter's responses (for
+    ///example, JavaScript's `JSON.parse`) lose precision on IDs this large when they come as
+    ///numbers, so this can be useful when the raw responses are being passed along elsewhere.
+    ///
+    ///Calling this function will invalidate any current results, if any were previously loaded.
+    pub fn with_stringified_ids(self) -> CursorIter<IDCursor> {
+        let params = self
+            .params_base
+            .unwrap_or_default()
+            .add_param("stringify_ids", "true");
+        CursorIter {
+            params_base: Some(params),
+            previous_cursor: -1,
+            next_cursor: -1,
+            loader: None,
+            iter: None,
+            ..self
+        }
+    }
+}
+
 impl<T> Stream for CursorIter<T>
 where
     T: Cursor + DeserializeOwned + 'static,
@@ -373,3 +414,36 @@ where
         self.poll_next(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stringified_ids() {
+        let numbers = r#"{"ids":[2244994945,783214],"next_cursor":0,"previous_cursor":0}"#;
+        let strings = r#"{"ids":["2244994945","783214"],"next_cursor":0,"previous_cursor":0}"#;
+        let numbers = serde_json::from_str::<IDCursor>(numbers).unwrap();
+        let strings = serde_json::from_str::<IDCursor>(strings).unwrap();
+        assert_eq!(numbers.ids, vec![2244994945, 783214]);
+        assert_eq!(strings.ids, numbers.ids);
+
+        let bad = r#"{"ids":["not an id"],"next_cursor":0,"previous_cursor":0}"#;
+        assert!(serde_json::from_str::<IDCursor>(bad).is_err());
+
+        let token = auth::Token::Bearer("token".to_string());
+        let ids = user::followers_ids("rustlang", &token)
+            .with_page_size(5000)
+            .with_stringified_ids();
+        let params = ids.params_base.as_ref().unwrap();
+        assert_eq!(
+            params.get("stringify_ids").map(|v| v.as_ref()),
+            Some("true")
+        );
+        assert_eq!(
+            params.get("screen_name").map(|v| v.as_ref()),
+            Some("rustlang")
+        );
+        assert_eq!(ids.page_size, Some(5000));
+    }
+}
@@ -0,0 +1,175 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono;
+use serde::Deserialize;
+
+use crate::common::*;
+use crate::{auth, error, links, media};
+
+/// An alternate name and avatar that Direct Messages can be sent under.
+///
+/// Custom profiles let a single account send messages that appear to come from a specific person
+/// or bot, such as the support agent handling a conversation. Messages sent with a custom profile
+/// show its name and avatar in place of the authenticated user's. To send a message with a custom
+/// profile, pass its ID to `custom_profile` on `DraftMessage`.
+///
+/// Custom profiles are created with [`create_custom_profile`].
+///
+/// [`create_custom_profile`]: fn.create_custom_profile.html
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawCustomProfile")]
+pub struct CustomProfile {
+    /// Numeric ID for this custom profile.
+    pub id: u64,
+    /// UTC timestamp from when this custom profile was created.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// The name shown on messages sent with this custom profile.
+    pub name: String,
+    /// The URL of the avatar shown on messages sent with this custom profile.
+    pub avatar_url: String,
+}
+
+/// A page of custom profiles, as returned by [`custom_profiles`].
+///
+/// [`custom_profiles`]: fn.custom_profiles.html
+#[derive(Debug, Deserialize)]
+pub struct CustomProfileList {
+    /// The custom profiles on this page.
+    #[serde(rename = "custom_profiles", default)]
+    pub profiles: Vec<CustomProfile>,
+    /// The cursor to give to `custom_profiles` to load the next page, if more exist.
+    pub next_cursor: Option<String>,
+}
+
+/// Creates a new custom profile with the given name and avatar.
+///
+/// The `name` can be at most 50 characters long. The `avatar` needs to have been uploaded with
+/// [`media::upload_media`] beforehand.
+///
+/// [`media::upload_media`]: ../media/fn.upload_media.html
+pub async fn create_custom_profile(
+    name: impl Into<String>,
+    avatar: media::MediaId,
+    token: &auth::Token,
+) -> Result<Response<CustomProfile>, error::Error> {
+    let body = serde_json::json!({
+        "custom_profile": {
+            "name": name.into(),
+            "avatar": {
+                "media": {
+                    "id": avatar.0
+                }
+            }
+        }
+    });
+    let req = post_json(links::direct::CUSTOM_PROFILE_NEW, token, body);
+    let resp: Response<SingleCustomProfile> = request_with_json_response(req).await?;
+    Ok(Response::map(resp, |p| p.custom_profile))
+}
+
+/// Lookup a single custom profile by its numeric ID.
+pub async fn show_custom_profile(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<CustomProfile>, error::Error> {
+    let url = format!("{}/{}.json", links::direct::CUSTOM_PROFILE_STEM, id);
+    let req = get(&url, token, None);
+    let resp: Response<SingleCustomProfile> = request_with_json_response(req).await?;
+    Ok(Response::map(resp, |p| p.custom_profile))
+}
+
+/// Load a page of the custom profiles created by the authenticated user.
+///
+/// Pass `None` as the `cursor` to load the first page, then the `next_cursor` of the returned page
+/// to load the one after it. Pages are loaded with the maximum size of 50 profiles.
+pub async fn custom_profiles(
+    cursor: Option<String>,
+    token: &auth::Token,
+) -> Result<Response<CustomProfileList>, error::Error> {
+    let params = ParamList::new()
+        .add_param("count", "50")
+        .add_opt_param("cursor", cursor);
+    let req = get(links::direct::CUSTOM_PROFILE_LIST, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+/// Delete the custom profile with the given ID.
+///
+/// Messages that were already sent with the custom profile keep showing its name and avatar.
+///
+/// Twitter does not return anything upon a successful deletion, so this function will return an
+/// empty `Response` upon success.
+pub async fn delete_custom_profile(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<()>, error::Error> {
+    let params = ParamList::new().add_param("id", id.to_string());
+    let req = auth::raw::delete(links::direct::CUSTOM_PROFILE_DELETE, token, Some(&params));
+    request_with_empty_response(req).await
+}
+
+#[derive(Deserialize)]
+struct RawCustomProfile {
+    #[serde(with = "serde_via_string")]
+    id: u64,
+    #[serde(deserialize_with = "serde_millis::deserialize")]
+    created_timestamp: chrono::DateTime<chrono::Utc>,
+    name: String,
+    avatar: RawAvatar,
+}
+
+#[derive(Deserialize)]
+struct RawAvatar {
+    media: RawAvatarMedia,
+}
+
+#[derive(Deserialize)]
+struct RawAvatarMedia {
+    url: String,
+}
+
+impl From<RawCustomProfile> for CustomProfile {
+    fn from(raw: RawCustomProfile) -> CustomProfile {
+        CustomProfile {
+            id: raw.id,
+            created_at: raw.created_timestamp,
+            name: raw.name,
+            avatar_url: raw.avatar.media.url,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SingleCustomProfile {
+    custom_profile: CustomProfile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_custom_profiles() {
+        let content = load_file("sample_payloads/dm-custom-profiles.json");
+        let list = ::serde_json::from_str::<CustomProfileList>(&content).unwrap();
+
+        assert_eq!(list.profiles.len(), 2);
+        assert_eq!(list.profiles[0].id, 1260254221499961344);
+        assert_eq!(list.profiles[0].name, "Quinn, Support Team");
+        assert_eq!(
+            list.profiles[1].avatar_url,
+            "https://pbs.twimg.com/media/EYAdyPJXQAESBRs.png"
+        );
+        assert_eq!(
+            list.next_cursor.as_deref(),
+            Some("MTI2MDI1MDg2MjM1NTg1OTQ1Ng")
+        );
+
+        let profile = r#"{"id":"1","created_timestamp":"-9223372036854775808","name":"x",
+            "avatar":{"media":{"url":"https://pbs.twimg.com/media/x.png"}}}"#;
+        assert!(::serde_json::from_str::<CustomProfile>(profile).is_err());
+    }
+}
@@ -27,6 +27,36 @@ pub fn list(token: &auth::Token) -> Timeline {
     Timeline::new(links::direct::LIST, token.clone())
 }
 
+/// Send a plain-text direct message to the given user.
+///
+/// This is a shorthand for `DraftMessage::new(text, recipient).send(token)`. To add Quick Replies,
+/// buttons, or media to the message, build a [`DraftMessage`] instead.
+///
+/// [`DraftMessage`]: struct.DraftMessage.html
+///
+/// Note that while this function accepts any `UserID`, the underlying Twitter API call only
+/// accepts a numeric ID for the recipient. If you pass a string Screen Name to this function, a
+/// separate user lookup will occur prior to sending the message. To avoid this extra lookup,
+/// pass a numeric ID (or the `UserID::ID` variant of `UserID`) to this function.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// let sent = egg_mode::direct::send(782349500404862976, "thanks for the follow!", &token)
+///     .await
+///     .unwrap();
+/// println!("sent message {}", sent.id);
+/// # }
+/// ```
+pub async fn send(
+    recipient: impl Into<UserID>,
+    text: impl Into<Cow<'static, str>>,
+    token: &auth::Token,
+) -> Result<Response<DirectMessage>, error::Error> {
+    DraftMessage::new(text, recipient).send(token).await
+}
+
 /// Delete the direct message with the given ID.
 ///
 /// The authenticated user must be the sender of this DM for this call to be successful.
@@ -53,6 +83,17 @@ pub async fn delete(id: u64, token: &auth::Token) -> Result<Response<()>, error:
 /// accepts a numeric ID for the sender. If you pass a string Screen Name to this function, a
 /// separate user lookup will occur prior to sending the read receipt. To avoid this extra lookup,
 /// pass a numeric ID (or the `UserID::ID` variant of `UserID`) to this function.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// # let incoming: egg_mode::direct::DirectMessage = unimplemented!();
+/// egg_mode::direct::mark_read(incoming.id, incoming.sender_id, &token)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
 pub async fn mark_read(
     id: u64,
     sender: impl Into<UserID>,
@@ -83,9 +124,22 @@ pub async fn mark_read(
 /// the Twitter rate limit constraints.
 ///
 /// Note that while this function accepts any `UserID`, the underlying Twitter API call only
-/// accepts a numeric ID for the sender. If you pass a string Screen Name to this function, a
-/// separate user lookup will occur prior to sending the read receipt. To avoid this extra lookup,
-/// pass a numeric ID (or the `UserID::ID` variant of `UserID`) to this function.
+/// accepts a numeric ID for the recipient. If you pass a string Screen Name to this function, a
+/// separate user lookup will occur prior to sending the typing indicator. To avoid this extra
+/// lookup, pass a numeric ID (or the `UserID::ID` variant of `UserID`) to this function.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// # let incoming: egg_mode::direct::DirectMessage = unimplemented!();
+/// use egg_mode::direct;
+///
+/// direct::indicate_typing(incoming.sender_id, &token).await.unwrap();
+/// //... work out a response ...
+/// direct::send(incoming.sender_id, "all done!", &token).await.unwrap();
+/// # }
+/// ```
 pub async fn indicate_typing(
     recipient: impl Into<UserID>,
     token: &auth::Token,
@@ -0,0 +1,234 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use crate::user::UserId;
+use crate::{auth, error};
+
+use super::{list, mark_read, DMConversations, DirectMessage};
+
+/// Keeps track of the authenticated user's Direct Message conversations, and which messages in
+/// them have been seen.
+///
+/// `Inbox` takes care of the bookkeeping a chat client or bot needs on top of [`list`]: sorting
+/// messages into conversations with each user, only loading messages that arrived since the last
+/// check, and counting how many messages in each conversation haven't been seen yet.
+///
+/// Create an `Inbox` with the ID of the authenticated user, then call `poll_new` to load messages.
+/// The first call loads every message available (Twitter only keeps the last 30 days); later calls
+/// only load messages that are newer than the ones already loaded. Messages received from other
+/// users count as unread until they're marked as seen, either locally with `mark_seen` or by also
+/// sending a read receipt with `mark_read`.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// # let my_id: u64 = unimplemented!();
+/// use egg_mode::direct::Inbox;
+///
+/// let mut inbox = Inbox::new(my_id, &token);
+/// inbox.poll_new().await.unwrap();
+/// //don't treat the existing history as new messages
+/// inbox.mark_all_seen();
+///
+/// loop {
+///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+///     for dm in inbox.poll_new().await.unwrap() {
+///         println!("{}: {}", dm.sender_id, dm.text);
+///     }
+///     println!("{} unread messages", inbox.total_unread());
+/// }
+/// # }
+/// ```
+///
+/// [`list`]: fn.list.html
+pub struct Inbox {
+    token: auth::Token,
+    me: UserId,
+    conversations: DMConversations,
+    last_seen: HashMap<UserId, u64>,
+    newest_id: Option<u64>,
+}
+
+impl Inbox {
+    /// Creates a new, empty `Inbox` for the authenticated user with the given ID.
+    ///
+    /// The ID is used to tell which messages were sent by the authenticated user, so it needs to
+    /// match the user the `Token` belongs to.
+    pub fn new(me: impl Into<UserId>, token: &auth::Token) -> Inbox {
+        Inbox {
+            token: token.clone(),
+            me: me.into(),
+            conversations: HashMap::new(),
+            last_seen: HashMap::new(),
+            newest_id: None,
+        }
+    }
+
+    /// Loads the messages that arrived since the last call, and adds them to their conversations.
+    ///
+    /// The new messages are also returned, oldest first. This includes messages sent by the
+    /// authenticated user, such as ones sent from a different client.
+    ///
+    /// If loading a page of messages fails, the error is returned and the `Inbox` is left as it
+    /// was, so calling `poll_new` again will try to load the same messages.
+    pub async fn poll_new(&mut self) -> Result<Vec<DirectMessage>, error::Error> {
+        let mut timeline = list(&self.token).with_page_size(50);
+        let mut new_dms = vec![];
+
+        //messages are loaded newest first, so stop as soon as one comes up that was already loaded
+        'pages: loop {
+            let page = timeline.next_page().await?;
+            for dm in page.response {
+                if matches!(self.newest_id, Some(newest) if dm.id <= newest) {
+                    break 'pages;
+                }
+                new_dms.push(dm);
+            }
+            if timeline.next_cursor.is_none() {
+                break;
+            }
+        }
+
+        new_dms.reverse();
+        self.insert(new_dms.clone());
+        Ok(new_dms)
+    }
+
+    /// Adds the given messages, sorted oldest first, to their conversations.
+    fn insert(&mut self, dms: Vec<DirectMessage>) {
+        for dm in dms {
+            self.newest_id = Some(self.newest_id.map_or(dm.id, |id| id.max(dm.id)));
+            let partner = self.partner(&dm);
+            //conversations are kept newest first, like the ones from `Timeline::into_conversations`
+            self.conversations.entry(partner).or_default().insert(0, dm);
+        }
+    }
+
+    /// Returns the ID of the other user in the conversation the given message belongs to.
+    fn partner(&self, dm: &DirectMessage) -> UserId {
+        if dm.sender_id == self.me {
+            dm.recipient_id
+        } else {
+            dm.sender_id
+        }
+    }
+
+    /// Returns the messages loaded so far, sorted into conversations with each user.
+    ///
+    /// Each conversation is sorted newest first. Messages the authenticated user sent to themself
+    /// are under their own ID.
+    pub fn conversations(&self) -> &DMConversations {
+        &self.conversations
+    }
+
+    /// Returns the messages loaded so far in the conversation with the given user, newest first.
+    pub fn conversation(&self, partner: impl Into<UserId>) -> &[DirectMessage] {
+        self.conversations
+            .get(&partner.into())
+            .map_or(&[], |dms| dms.as_slice())
+    }
+
+    /// Returns the number of messages from the given user that haven't been marked as seen.
+    pub fn unread_count(&self, partner: impl Into<UserId>) -> usize {
+        let partner = partner.into();
+        let last_seen = self.last_seen.get(&partner).copied();
+        self.conversation(partner)
+            .iter()
+            .take_while(|dm| !matches!(last_seen, Some(seen) if dm.id <= seen))
+            .filter(|dm| dm.sender_id != self.me)
+            .count()
+    }
+
+    /// Returns the number of unread messages in each conversation that has any.
+    pub fn unread_counts(&self) -> HashMap<UserId, usize> {
+        self.conversations
+            .keys()
+            .map(|&partner| (partner, self.unread_count(partner)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Returns the total number of unread messages across all conversations.
+    pub fn total_unread(&self) -> usize {
+        self.unread_counts().values().sum()
+    }
+
+    /// Marks every message loaded so far in the conversation with the given user as seen.
+    ///
+    /// This only updates the `Inbox`; to also let the other user know their messages were read,
+    /// use `mark_read`.
+    pub fn mark_seen(&mut self, partner: impl Into<UserId>) {
+        let partner = partner.into();
+        if let Some(newest) = self.conversation(partner).first() {
+            self.last_seen.insert(partner, newest.id);
+        }
+    }
+
+    /// Marks every message loaded so far, in every conversation, as seen.
+    pub fn mark_all_seen(&mut self) {
+        for (&partner, dms) in &self.conversations {
+            if let Some(newest) = dms.first() {
+                self.last_seen.insert(partner, newest.id);
+            }
+        }
+    }
+
+    /// Marks every message loaded so far in the conversation with the given user as seen, and
+    /// sends them a read receipt for the newest one.
+    ///
+    /// If there are no unread messages from the user, this doesn't send anything.
+    pub async fn mark_read(&mut self, partner: impl Into<UserId>) -> Result<(), error::Error> {
+        let partner = partner.into();
+        let newest_received = self
+            .conversation(partner)
+            .iter()
+            .find(|dm| dm.sender_id != self.me)
+            .map(|dm| dm.id);
+
+        if let Some(id) = newest_received {
+            if self.unread_count(partner) > 0 {
+                mark_read(id, partner, &self.token).await?;
+            }
+        }
+        self.mark_seen(partner);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+    use crate::direct::raw;
+
+    #[test]
+    fn unread_counts() {
+        let content = load_file("sample_payloads/dm-quick-reply.json");
+        let page = ::serde_json::from_str::<raw::EventCursor>(&content).unwrap();
+        let mut dms: Vec<DirectMessage> = page.into();
+        dms.reverse();
+
+        let me = UserId(1191527248742010880);
+        let partner = UserId(2977334326);
+        let mut inbox = Inbox::new(me, &auth::Token::Bearer(String::new()));
+        inbox.insert(dms);
+
+        assert_eq!(inbox.conversation(partner).len(), 2);
+        assert_eq!(inbox.conversation(partner)[0].text, "yes");
+        assert_eq!(inbox.newest_id, Some(1255276898024914948));
+
+        //only the message the partner sent counts as unread
+        assert_eq!(inbox.unread_count(partner), 1);
+        assert_eq!(inbox.total_unread(), 1);
+
+        inbox.mark_seen(partner);
+        assert_eq!(inbox.unread_count(partner), 0);
+        assert!(inbox.unread_counts().is_empty());
+    }
+}
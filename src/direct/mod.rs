@@ -14,50 +14,83 @@
 //! customer-service, interactive storytelling, etc. The extra DM-specific facilities are
 //! documented in their respective builder functions on `DraftMessage`.
 //!
+//! The accounts that send and receive messages are identified by `UserId`, like everywhere else in
+//! egg-mode. The IDs of messages, welcome messages, welcome message rules, and custom profiles
+//! stay plain `u64`s: each of them is its own kind of ID, only used with the functions in this
+//! module, so the tweet, user, and list ID types don't cover them.
+//!
 //! ## Types
 //!
 //! * `DirectMessage`: The primary representation of a DM as retrieved from Twitter. Contains the
-//!   types `DMEntities`/`Cta`/`QuickReply` as fields.
+//!   types `DMEntities`/`Cta`/`QuickReply`/`SharedLocation` as fields.
 //! * `Timeline`: Returned by `list`, this is how you load a user's Direct Messages. Contains
-//!   adapters to consume the collection as a `Stream` or to load it into a `DMConversations`
-//!   collection.
+//!   adapters to consume the collection as a `Stream` (optionally limited to the conversation with
+//!   one user) or to load it into a `DMConversations` collection.
+//! * `Inbox`: A higher-level view of the authenticated user's conversations, which loads new
+//!   messages as they arrive and keeps count of the unread ones.
 //! * `DraftMessage`: As DMs have many optional parameters when creating them, this builder struct
 //!   allows you to build up a DM before sending it.
+//! * `WelcomeMessage`/`DraftWelcomeMessage`: A message shown to users when they open a new
+//!   conversation with the authenticated user, and the builder used to create one.
+//! * `WelcomeMessageRule`: The rule that picks which `WelcomeMessage` is shown by default.
+//! * `CustomProfile`: An alternate name and avatar that messages can be sent with.
 //!
 //! ## Functions
 //!
 //! * `list`: This creates a `Timeline` struct to load a user's Direct Messages.
 //! * `show`: This allows you to load a single DM from its ID.
+//! * `send`: This sends a plain-text DM to a given user. (To send a DM with Quick Replies,
+//!   buttons, or media, use `DraftMessage`.)
 //! * `delete`: This allows you to delete a DM from a user's own views. Note that it will not
 //!   delete it entirely from the system; the recipient will still have a copy of the message.
 //! * `mark_read`: This sends a read receipt for a given message to a given user. This also has the
 //!   effect of clearing the message's "unread" status for the authenticated user.
 //! * `indicate_typing`: This sends a typing indicator to a given user, to indicate that the
 //!   authenticated user is typing or thinking of a response.
+//!
+//! ### Welcome messages
+//!
+//! * `welcome_messages`/`show_welcome_message`/`delete_welcome_message`: These load and delete
+//!   the welcome messages created with `DraftWelcomeMessage`.
+//! * `set_default_welcome_message`: This creates a `WelcomeMessageRule` to show the given welcome
+//!   message to users.
+//! * `welcome_message_rules`/`show_welcome_message_rule`/`delete_welcome_message_rule`: These load
+//!   and delete the rule that picks the default welcome message.
+//!
+//! ### Custom profiles
+//!
+//! * `create_custom_profile`/`custom_profiles`/`show_custom_profile`/`delete_custom_profile`:
+//!   These manage the custom profiles that can be given to `custom_profile` on `DraftMessage`.
 
 use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 
 use chrono;
+use futures::future::{self, FutureExt};
 use futures::stream::{self, Stream, StreamExt, TryStreamExt};
-use futures::FutureExt;
 use hyper::{Body, Request};
 use serde::{Deserialize, Serialize};
 
 use crate::common::*;
 use crate::tweet::TweetSource;
-use crate::user::{self, UserID};
+use crate::user::{self, UserID, UserId};
 use crate::{auth, entities, error, links, media};
 
+mod custom_profile;
 mod fun;
+mod inbox;
 pub(crate) mod raw;
+mod welcome;
 
+pub use self::custom_profile::*;
 pub use self::fun::*;
+pub use self::inbox::Inbox;
+pub use self::welcome::*;
 
 // TODO is this enough? i'm not sure if i want a field-by-field breakdown like with Tweet
 /// Represents a single direct message.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DirectMessage {
     /// Numeric ID for this DM.
     pub id: u64,
@@ -69,6 +102,10 @@ pub struct DirectMessage {
     pub entities: DMEntities,
     /// An image, gif, or video attachment, if present.
     pub attachment: Option<entities::MediaEntity>,
+    /// A location shared in the DM, if present.
+    ///
+    /// A DM can only have one attachment, so this is only present if `attachment` isn't.
+    pub location: Option<SharedLocation>,
     /// A list of "call to action" buttons attached to the DM, if present.
     pub ctas: Option<Vec<Cta>>,
     /// A list of "Quick Replies" sent with this message to request structured input from the
@@ -86,7 +123,7 @@ pub struct DirectMessage {
     /// To load full user information for the sender or recipient, use `user::show`. Note that
     /// Twitter may show a message with a user that doesn't exist if that user has been suspended
     /// or has deleted their account.
-    pub sender_id: u64,
+    pub sender_id: UserId,
     /// The app that sent this direct message.
     ///
     /// Source app information is only available for messages sent by the authorized user. For
@@ -97,7 +134,12 @@ pub struct DirectMessage {
     /// To load full user information for the sender or recipient, use `user::show`. Note that
     /// Twitter may show a message with a user that doesn't exist if that user has been suspended
     /// or has deleted their account.
-    pub recipient_id: u64,
+    pub recipient_id: UserId,
+    /// The ID of the custom profile the DM was sent with, if any.
+    ///
+    /// Custom profiles replace the sender's name and avatar on a message; they can be loaded with
+    /// `show_custom_profile`.
+    pub custom_profile_id: Option<u64>,
 }
 
 impl From<raw::SingleEvent> for DirectMessage {
@@ -132,7 +174,7 @@ impl From<raw::EventCursor> for Vec<DirectMessage> {
 ///
 /// For all other fields, if the message contains no hashtags, financial symbols ("cashtags"),
 /// links, or mentions, those corresponding fields will be empty.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct DMEntities {
     /// Collection of hashtags parsed from the DM.
     pub hashtags: Vec<entities::HashtagEntity>,
@@ -150,7 +192,7 @@ pub struct DMEntities {
 /// message. For more information, see the `cta_button` function on [`DraftMessage`].
 ///
 /// [`DraftMessage`]: struct.DraftMessage.html
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Cta {
     /// The label shown to the user for the CTA.
     pub label: String,
@@ -160,6 +202,26 @@ pub struct Cta {
     pub url: String,
 }
 
+/// A location shared in a direct message.
+///
+/// To share a location in a message, use `share_coordinates` or `share_place` on
+/// [`DraftMessage`].
+///
+/// [`DraftMessage`]: struct.DraftMessage.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedLocation {
+    /// A specific point, as a (latitude, longitude) pair.
+    Coordinates(f64, f64),
+    /// A Twitter Place, given by its ID. To load more information about it, use `place::show`.
+    Place(String),
+}
+
+/// The attachment to send with a `DraftMessage`.
+enum DraftAttachment {
+    Media(media::MediaId),
+    Location(SharedLocation),
+}
+
 /// A version of `Cta` without `tco_url` to be used in `DraftMessage`.
 struct DraftCta {
     label: String,
@@ -172,7 +234,7 @@ struct DraftCta {
 /// [`DraftMessage`].
 ///
 /// [`DraftMessage`]: struct.DraftMessage.html
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuickReply {
     /// The label shown to the user. When the user selects this Quick Reply, the label will be sent
     /// as the `text` of the reply message.
@@ -321,6 +383,36 @@ impl Timeline {
         .try_flatten()
     }
 
+    /// Converts this `Timeline` into a `Stream` of the direct messages exchanged with the given
+    /// user, which automatically loads the next page as needed.
+    ///
+    /// Twitter doesn't offer a way to load only one conversation, so this still loads every page
+    /// of the authenticated user's messages, and skips the ones that weren't sent to or received
+    /// from `partner`. To load messages the authenticated user sent to themself, pass their own
+    /// ID.
+    ///
+    /// ```no_run
+    /// use futures::stream::TryStreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// # let token: egg_mode::Token = unimplemented!();
+    /// let timeline = egg_mode::direct::list(&token).with_page_size(50);
+    /// let history = timeline.into_conversation_stream(782349500404862976)
+    ///                       .try_collect::<Vec<_>>()
+    ///                       .await
+    ///                       .unwrap();
+    /// # }
+    /// ```
+    pub fn into_conversation_stream(
+        self,
+        partner: impl Into<UserId>,
+    ) -> impl Stream<Item = Result<Response<DirectMessage>, error::Error>> {
+        let partner = partner.into();
+        self.into_stream().try_filter(move |dm| {
+            future::ready(dm.sender_id == partner || dm.recipient_id == partner)
+        })
+    }
+
     /// Loads all the direct messages from this `Timeline` and sorts them into a `DMConversations`
     /// map.
     ///
@@ -387,7 +479,7 @@ impl Timeline {
 /// [`Timeline`].
 ///
 /// [`Timeline`]: struct.Timeline.html
-pub type DMConversations = HashMap<u64, Vec<DirectMessage>>;
+pub type DMConversations = HashMap<UserId, Vec<DirectMessage>>;
 
 /// Represents a direct message before it is sent.
 ///
@@ -422,7 +514,8 @@ pub struct DraftMessage {
     recipient: UserID,
     quick_reply_options: VecDeque<QuickReply>,
     cta_buttons: VecDeque<DraftCta>,
-    media_attachment: Option<media::MediaId>,
+    attachment: Option<DraftAttachment>,
+    custom_profile_id: Option<u64>,
 }
 
 impl DraftMessage {
@@ -438,7 +531,8 @@ impl DraftMessage {
             recipient: recipient.into(),
             quick_reply_options: VecDeque::new(),
             cta_buttons: VecDeque::new(),
-            media_attachment: None,
+            attachment: None,
+            custom_profile_id: None,
         }
     }
 
@@ -472,14 +566,12 @@ impl DraftMessage {
         metadata: impl Into<String>,
         description: Option<String>,
     ) -> Self {
-        if self.quick_reply_options.len() == 20 {
-            self.quick_reply_options.pop_front();
-        }
-        self.quick_reply_options.push_back(QuickReply {
-            label: label.into(),
-            metadata: metadata.into(),
+        push_quick_reply(
+            &mut self.quick_reply_options,
+            label.into(),
+            metadata.into(),
             description,
-        });
+        );
         self
     }
 
@@ -497,15 +589,7 @@ impl DraftMessage {
     /// There is a maximum of 3 CTA Buttons on a single Direct Message. If you try to add more, the
     /// oldest one will be removed.
     pub fn cta_button(mut self, label: impl Into<String>, url: impl Into<String>) -> Self {
-        if self.cta_buttons.is_empty() {
-            self.cta_buttons.reserve_exact(3);
-        } else if self.cta_buttons.len() == 3 {
-            self.cta_buttons.pop_front();
-        }
-        self.cta_buttons.push_back(DraftCta {
-            label: label.into(),
-            url: url.into(),
-        });
+        push_cta_button(&mut self.cta_buttons, label.into(), url.into());
         self
     }
 
@@ -516,10 +600,53 @@ impl DraftMessage {
     /// In addition, there's an extra setting available for media attached to Direct Messages. For
     /// more information, see the documentation for `upload_media_for_dm`.
     ///
+    /// A message can only have one attachment, so this replaces any location added with
+    /// `share_coordinates` or `share_place`.
+    ///
     /// [`media::upload_media_for_dm`]: ../media/fn.upload_media_for_dm.html
     pub fn attach_media(self, media_id: media::MediaId) -> Self {
         DraftMessage {
-            media_attachment: Some(media_id),
+            attachment: Some(DraftAttachment::Media(media_id)),
+            ..self
+        }
+    }
+
+    /// Shares the given latitude/longitude coordinates in this message.
+    ///
+    /// A message can only have one attachment, so this replaces any media added with
+    /// `attach_media` or place added with `share_place`.
+    pub fn share_coordinates(self, latitude: f64, longitude: f64) -> Self {
+        DraftMessage {
+            attachment: Some(DraftAttachment::Location(SharedLocation::Coordinates(
+                latitude, longitude,
+            ))),
+            ..self
+        }
+    }
+
+    /// Shares the Place with the given ID in this message.
+    ///
+    /// Place IDs can be found with the functions in the `place` module. A message can only have
+    /// one attachment, so this replaces any media added with `attach_media` or coordinates added
+    /// with `share_coordinates`.
+    pub fn share_place(self, place_id: impl Into<String>) -> Self {
+        DraftMessage {
+            attachment: Some(DraftAttachment::Location(SharedLocation::Place(
+                place_id.into(),
+            ))),
+            ..self
+        }
+    }
+
+    /// Sends this message with the custom profile with the given ID, so it shows that profile's
+    /// name and avatar instead of the authenticated user's.
+    ///
+    /// Custom profiles can be created with [`create_custom_profile`].
+    ///
+    /// [`create_custom_profile`]: fn.create_custom_profile.html
+    pub fn custom_profile(self, custom_profile_id: u64) -> Self {
+        DraftMessage {
+            custom_profile_id: Some(custom_profile_id),
             ..self
         }
     }
@@ -533,8 +660,14 @@ impl DraftMessage {
     /// requested DM beforehand.
     ///
     /// If the message was successfully sent, this function will return the `DirectMessage` that
-    /// was just sent.
+    /// was just sent. If the coordinates given to `share_coordinates` are out of range, this
+    /// returns `Error::InvalidParam` without contacting Twitter.
     pub async fn send(self, token: &auth::Token) -> Result<Response<DirectMessage>, error::Error> {
+        if let Some(DraftAttachment::Location(SharedLocation::Coordinates(lat, long))) =
+            self.attachment
+        {
+            check_coordinates(lat, long)?;
+        }
         let recipient_id = match self.recipient {
             UserID::ID(id) => id,
             UserID::ScreenName(name) => {
@@ -542,47 +675,14 @@ impl DraftMessage {
                 user.id
             }
         };
-        let mut message_data = serde_json::json!({
-            "text": self.text
-        });
-        if !self.quick_reply_options.is_empty() {
-            message_data.as_object_mut().unwrap().insert(
-                "quick_reply".into(),
-                serde_json::json!({
-                    "type": "options",
-                    "options": self.quick_reply_options
-                }),
-            );
-        }
-        if !self.cta_buttons.is_empty() {
-            message_data.as_object_mut().unwrap().insert(
-                "ctas".into(),
-                self.cta_buttons
-                    .into_iter()
-                    .map(|b| {
-                        serde_json::json!({
-                            "type": "web_url",
-                            "label": b.label,
-                            "url": b.url,
-                        })
-                    })
-                    .collect::<Vec<_>>()
-                    .into(),
-            );
-        }
-        if let Some(media_id) = self.media_attachment {
-            message_data.as_object_mut().unwrap().insert(
-                "attachment".into(),
-                serde_json::json!({
-                    "type": "media",
-                    "media": {
-                        "id": media_id.0
-                    }
-                }),
-            );
-        }
-
-        let message = serde_json::json!({
+        let message_data = draft_message_data(
+            self.text,
+            self.quick_reply_options,
+            self.cta_buttons,
+            self.attachment,
+        );
+
+        let mut message = serde_json::json!({
             "event": {
                 "type": "message_create",
                 "message_create": {
@@ -593,8 +693,268 @@ impl DraftMessage {
                 }
             }
         });
+        if let Some(id) = self.custom_profile_id {
+            message["event"]["message_create"]["custom_profile_id"] = id.to_string().into();
+        }
         let req = post_json(links::direct::SEND, token, message);
         let resp: Response<raw::SingleEvent> = request_with_json_response(req).await?;
         Ok(Response::into(resp))
     }
 }
+
+/// Adds an Option-type Quick Reply to the given draft options, removing the oldest one if there
+/// are already 20.
+fn push_quick_reply(
+    options: &mut VecDeque<QuickReply>,
+    label: String,
+    metadata: String,
+    description: Option<String>,
+) {
+    if options.len() == 20 {
+        options.pop_front();
+    }
+    options.push_back(QuickReply {
+        label,
+        metadata,
+        description,
+    });
+}
+
+/// Adds a "Call To Action" button to the given draft buttons, removing the oldest one if there are
+/// already 3.
+fn push_cta_button(buttons: &mut VecDeque<DraftCta>, label: String, url: String) {
+    if buttons.is_empty() {
+        buttons.reserve_exact(3);
+    } else if buttons.len() == 3 {
+        buttons.pop_front();
+    }
+    buttons.push_back(DraftCta { label, url });
+}
+
+/// Assembles the `message_data` object Twitter expects when creating a direct message or a welcome
+/// message.
+fn draft_message_data(
+    text: Cow<'static, str>,
+    quick_reply_options: VecDeque<QuickReply>,
+    cta_buttons: VecDeque<DraftCta>,
+    attachment: Option<DraftAttachment>,
+) -> serde_json::Value {
+    let mut message_data = serde_json::json!({
+        "text": text
+    });
+    if !quick_reply_options.is_empty() {
+        message_data.as_object_mut().unwrap().insert(
+            "quick_reply".into(),
+            serde_json::json!({
+                "type": "options",
+                "options": quick_reply_options
+            }),
+        );
+    }
+    if !cta_buttons.is_empty() {
+        message_data.as_object_mut().unwrap().insert(
+            "ctas".into(),
+            cta_buttons
+                .into_iter()
+                .map(|b| {
+                    serde_json::json!({
+                        "type": "web_url",
+                        "label": b.label,
+                        "url": b.url,
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into(),
+        );
+    }
+    if let Some(attachment) = attachment {
+        let attachment = match attachment {
+            DraftAttachment::Media(media_id) => serde_json::json!({
+                "type": "media",
+                "media": {
+                    "id": media_id.0
+                }
+            }),
+            DraftAttachment::Location(SharedLocation::Coordinates(lat, long)) => {
+                serde_json::json!({
+                    "type": "location",
+                    "location": {
+                        "type": "shared_coordinate",
+                        "shared_coordinate": {
+                            "coordinates": {
+                                "type": "Point",
+                                "coordinates": [long, lat]
+                            }
+                        }
+                    }
+                })
+            }
+            DraftAttachment::Location(SharedLocation::Place(id)) => serde_json::json!({
+                "type": "location",
+                "location": {
+                    "type": "shared_place",
+                    "shared_place": {
+                        "place": {
+                            "id": id
+                        }
+                    }
+                }
+            }),
+        };
+        message_data
+            .as_object_mut()
+            .unwrap()
+            .insert("attachment".into(), attachment);
+    }
+
+    message_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    fn load_page(path: &str) -> Vec<DirectMessage> {
+        let content = load_file(path);
+        let page = ::serde_json::from_str::<raw::EventCursor>(&content).unwrap();
+        page.into()
+    }
+
+    #[test]
+    fn parse_quick_reply() {
+        let dms = load_page("sample_payloads/dm-quick-reply.json");
+        assert_eq!(dms.len(), 2);
+
+        let (reply, question) = (&dms[0], &dms[1]);
+        assert_eq!(reply.quick_reply_response.as_deref(), Some("answer-yes"));
+        assert!(reply.quick_replies.is_none());
+        assert!(reply.source_app.is_none());
+        assert!(reply.custom_profile_id.is_none());
+
+        let options = question.quick_replies.as_ref().unwrap();
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[1].label, "no");
+        assert_eq!(options[1].description.as_deref(), Some("it's still broken"));
+        assert_eq!(
+            question.source_app.as_ref().unwrap().name,
+            "Twitter Web Client"
+        );
+        assert_eq!(question.recipient_id, reply.sender_id);
+    }
+
+    #[test]
+    fn parse_media_attachment() {
+        let content = load_file("sample_payloads/dm-media.json");
+        let event = ::serde_json::from_str::<raw::SingleEvent>(&content).unwrap();
+        let dm = DirectMessage::from(event);
+
+        let media = dm.attachment.as_ref().unwrap();
+        assert_eq!(media.id, 1255281501603225600);
+        assert_eq!(media.media_type, entities::MediaType::Photo);
+        assert_eq!(media.sizes.large.w, 1600);
+
+        //indices come in as codepoints, but should be translated to byte offsets
+        assert_eq!(&dm.text[media.range.0..media.range.1], media.url);
+        let url = &dm.entities.urls[0];
+        assert_eq!(&dm.text[url.range.0..url.range.1], url.url);
+    }
+
+    #[test]
+    fn bad_timestamp() {
+        let content = load_file("sample_payloads/dm-media.json")
+            .replace("\"1588181598211\"", "\"99999999999999999\"");
+        assert!(::serde_json::from_str::<raw::SingleEvent>(&content).is_err());
+    }
+
+    #[test]
+    fn bad_custom_profile_id() {
+        let content = load_file("sample_payloads/dm-ctas.json")
+            .replace("\"1260254221499961344\"", "\"not a number\"");
+        assert!(::serde_json::from_str::<raw::SingleEvent>(&content).is_err());
+    }
+
+    #[test]
+    fn unknown_attachment() {
+        let content = load_file("sample_payloads/dm-media.json")
+            .replace("\"type\": \"media\"", "\"type\": \"sticker\"");
+        let event = ::serde_json::from_str::<raw::SingleEvent>(&content).unwrap();
+        let dm = DirectMessage::from(event);
+        assert!(dm.attachment.is_none());
+        assert!(dm.location.is_none());
+    }
+
+    #[test]
+    fn parse_ctas() {
+        let content = load_file("sample_payloads/dm-ctas.json");
+        let event = ::serde_json::from_str::<raw::SingleEvent>(&content).unwrap();
+        let dm = DirectMessage::from(event);
+
+        let ctas = dm.ctas.as_ref().unwrap();
+        assert_eq!(ctas.len(), 2);
+        assert_eq!(ctas[0].label, "Track your package");
+        assert_eq!(ctas[0].url, "https://example.com/orders/1234/tracking");
+        assert_eq!(ctas[1].tco_url, "https://t.co/kU3d3xvHnb");
+        assert!(dm.quick_replies.is_none());
+        assert_eq!(dm.custom_profile_id, Some(1260254221499961344));
+    }
+
+    #[test]
+    fn cta_button_limit() {
+        let draft = DraftMessage::new("hello", 1234)
+            .cta_button("one", "https://example.com/1")
+            .cta_button("two", "https://example.com/2")
+            .cta_button("three", "https://example.com/3")
+            .cta_button("four", "https://example.com/4");
+
+        let labels = draft
+            .cta_buttons
+            .iter()
+            .map(|b| b.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["two", "three", "four"]);
+    }
+
+    #[test]
+    fn parse_location() {
+        let dms = load_page("sample_payloads/dm-location.json");
+        assert_eq!(dms.len(), 2);
+
+        assert_eq!(
+            dms[0].location,
+            Some(SharedLocation::Coordinates(37.78194, -122.39923))
+        );
+        assert_eq!(
+            dms[1].location,
+            Some(SharedLocation::Place("5a110d312052166f".to_string()))
+        );
+        assert!(dms.iter().all(|dm| dm.attachment.is_none()));
+
+        let draft = DraftMessage::new("meet here?", 1234).share_coordinates(37.78194, -122.39923);
+        let data = draft_message_data(
+            draft.text,
+            draft.quick_reply_options,
+            draft.cta_buttons,
+            draft.attachment,
+        );
+        let location = &data["attachment"]["location"];
+        assert_eq!(location["type"], "shared_coordinate");
+        assert_eq!(
+            location["shared_coordinate"]["coordinates"]["coordinates"],
+            serde_json::json!([-122.39923, 37.78194])
+        );
+    }
+
+    #[tokio::test]
+    async fn draft_coordinates_range() {
+        let token = auth::Token::Bearer("not a real token".to_string());
+        let draft = DraftMessage::new("lost", 1234).share_coordinates(-122.39923, 37.78194);
+        match draft.send(&token).await {
+            Err(error::Error::InvalidParam(_)) => (),
+            other => panic!(
+                "expected InvalidParam, got {:?}",
+                other.map(|r| r.response.id)
+            ),
+        }
+    }
+}
@@ -11,8 +11,9 @@ use serde::Deserialize;
 
 use crate::entities::MediaEntity;
 use crate::tweet::TweetSource;
+use crate::user::UserId;
 
-use super::{Cta, DMEntities, DirectMessage, QuickReply};
+use super::{Cta, DMEntities, DirectMessage, QuickReply, SharedLocation};
 
 // n.b. all of the types in this module are re-exported in `raw::types::direct` - these docs are
 // public!
@@ -46,6 +47,8 @@ pub struct RawDirectMessage {
     pub entities: DMEntities,
     /// Media attached to the DM, if present.
     pub attachment: Option<MediaEntity>,
+    /// A location shared in the DM, if present.
+    pub location: Option<SharedLocation>,
     /// A list of "call to action" buttons, if present.
     pub ctas: Option<Vec<Cta>>,
     /// A list of "quick reply" options, if present.
@@ -53,12 +56,14 @@ pub struct RawDirectMessage {
     /// The `metadata` associated with the Quick Reply chosen by the sender, if present.
     pub quick_reply_response: Option<String>,
     /// The ID of the user who sent the DM.
-    pub sender_id: u64,
+    pub sender_id: UserId,
     /// The string ID associated with the app used to send the DM, if sent by the authenticated
     /// user.
     pub source_app_id: Option<String>,
     /// The ID of the user who received the DM.
-    pub recipient_id: u64,
+    pub recipient_id: UserId,
+    /// The ID of the custom profile the DM was sent with, if any.
+    pub custom_profile_id: Option<u64>,
     translated: bool,
 }
 
@@ -74,22 +79,7 @@ impl RawDirectMessage {
     pub fn translate_indices(&mut self) {
         if !self.translated {
             self.translated = true;
-
-            for entity in &mut self.entities.hashtags {
-                codepoints_to_bytes(&mut entity.range, &self.text);
-            }
-            for entity in &mut self.entities.symbols {
-                codepoints_to_bytes(&mut entity.range, &self.text);
-            }
-            for entity in &mut self.entities.urls {
-                codepoints_to_bytes(&mut entity.range, &self.text);
-            }
-            for entity in &mut self.entities.user_mentions {
-                codepoints_to_bytes(&mut entity.range, &self.text);
-            }
-            if let Some(ref mut media) = self.attachment {
-                codepoints_to_bytes(&mut media.range, &self.text);
-            }
+            translate_entities(&mut self.entities, self.attachment.as_mut(), &self.text);
         }
     }
 
@@ -110,10 +100,12 @@ impl RawDirectMessage {
             text: self.text,
             entities: self.entities,
             attachment: self.attachment,
+            location: self.location,
             ctas: self.ctas,
             sender_id: self.sender_id,
             source_app,
             recipient_id: self.recipient_id,
+            custom_profile_id: self.custom_profile_id,
             quick_replies: self.quick_replies,
             quick_reply_response: self.quick_reply_response,
         }
@@ -122,23 +114,53 @@ impl RawDirectMessage {
     // TODO: provide a conversion that drops source-app information?
 }
 
+/// Translates the codepoint-based indices in the given entities and media attachment into
+/// byte-based ones, using the given message text.
+pub(super) fn translate_entities(
+    entities: &mut DMEntities,
+    attachment: Option<&mut MediaEntity>,
+    text: &str,
+) {
+    for entity in &mut entities.hashtags {
+        codepoints_to_bytes(&mut entity.range, text);
+    }
+    for entity in &mut entities.symbols {
+        codepoints_to_bytes(&mut entity.range, text);
+    }
+    for entity in &mut entities.urls {
+        codepoints_to_bytes(&mut entity.range, text);
+    }
+    for entity in &mut entities.user_mentions {
+        codepoints_to_bytes(&mut entity.range, text);
+    }
+    if let Some(media) = attachment {
+        codepoints_to_bytes(&mut media.range, text);
+    }
+}
+
 // DMs received from twitter are structured as events in their activity API, which means they have
 // a lot of deep nesting for how they are structured. The types and From impl below convert that
 // into a flat object ready for processing/export by egg-mode.
 
 impl From<DMEvent> for RawDirectMessage {
     fn from(ev: DMEvent) -> RawDirectMessage {
-        use chrono::TimeZone;
+        let (attachment, location) = match ev.message_create.message_data.attachment {
+            Some(MessageAttachment::Media { media }) => (Some(media), None),
+            Some(MessageAttachment::Location { location }) => (None, Some(location.into())),
+            Some(MessageAttachment::Unknown) | None => (None, None),
+        };
         RawDirectMessage {
             id: ev.id,
-            created_at: chrono::Utc.timestamp_millis(ev.created_timestamp),
+            created_at: ev.created_timestamp,
             text: ev.message_create.message_data.text,
             entities: ev.message_create.message_data.entities,
-            attachment: ev.message_create.message_data.attachment.map(|a| a.media),
+            attachment,
+            location,
             ctas: ev.message_create.message_data.ctas,
             sender_id: ev.message_create.sender_id,
             source_app_id: ev.message_create.source_app_id,
             recipient_id: ev.message_create.target.recipient_id,
+            custom_profile_id: ev.message_create.custom_profile_id,
             quick_replies: ev
                 .message_create
                 .message_data
@@ -211,8 +233,8 @@ struct DMEvent {
     id: u64,
     /// UTC Unix timestamp for when the message was sent, encoded as the number of milliseconds
     /// since the Unix epoch.
-    #[serde(with = "serde_via_string")]
-    created_timestamp: i64,
+    #[serde(deserialize_with = "serde_millis::deserialize")]
+    created_timestamp: chrono::DateTime<chrono::Utc>,
     /// Message data for this event.
     message_create: MessageCreateEvent,
 }
@@ -224,49 +246,126 @@ struct MessageCreateEvent {
     message_data: MessageData,
     #[serde(with = "serde_via_string")]
     /// The numeric User ID of the sender.
-    sender_id: u64,
+    sender_id: UserId,
     /// The string ID of the app used to send the message, if it was sent by the authenticated
     /// user.
     source_app_id: Option<String>,
+    /// The numeric ID of the custom profile the message was sent with, if any.
+    #[serde(default, with = "serde_opt_via_string")]
+    custom_profile_id: Option<u64>,
     /// Information about the recipient of the message.
     target: MessageTarget,
 }
 
 /// The `message_data` portion of a `DMEvent`, containing the bulk of information about a direct
 /// message.
+///
+/// This is also used for the content of welcome messages.
 #[derive(Deserialize)]
-struct MessageData {
+pub(super) struct MessageData {
     /// A list of "call to action" buttons, if present.
-    ctas: Option<Vec<Cta>>,
+    pub(super) ctas: Option<Vec<Cta>>,
     /// Information about attached media, if present.
-    attachment: Option<MessageAttachment>,
+    pub(super) attachment: Option<MessageAttachment>,
     /// Information about URL, hashtag, or user-mention entities used in the message.
-    entities: DMEntities,
+    pub(super) entities: DMEntities,
     /// Information about Quick Reply options, if present.
-    quick_reply: Option<RawQuickReply>,
+    pub(super) quick_reply: Option<RawQuickReply>,
     /// Information about a selected Quick Reply option, if the sender selected one.
     quick_reply_response: Option<QuickReplyResponse>,
     /// The message text.
-    text: String,
+    pub(super) text: String,
 }
 
-/// Represents attached media information from within a `DMEvent`.
+/// Represents an attachment from within a `DMEvent`, either media or a location.
 #[derive(Deserialize)]
-struct MessageAttachment {
-    /// Information about the attached media.
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub(super) enum MessageAttachment {
+    /// Information about attached media.
     ///
     /// Note that the indices used within the `MediaEntity` are received from Twitter using
     /// codepoint-based indexing. Using the indices from within this type directly without
     /// translating them may result in string-slicing errors or panics unless you translate the
     /// indices or use `char_indices` and `enumerate` yourself to ensure proper use of the indices.
-    media: MediaEntity,
+    Media { media: MediaEntity },
+    /// Information about a shared location.
+    Location { location: RawLocation },
+    /// An attachment type egg-mode doesn't know about, which is skipped.
+    #[serde(other)]
+    Unknown,
+}
+
+impl MessageAttachment {
+    /// Returns the attached media, if this attachment is media.
+    pub(super) fn into_media(self) -> Option<MediaEntity> {
+        match self {
+            MessageAttachment::Media { media } => Some(media),
+            MessageAttachment::Location { .. } | MessageAttachment::Unknown => None,
+        }
+    }
+}
+
+/// Represents a shared location from within a `MessageAttachment`.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(super) enum RawLocation {
+    /// A specific point.
+    SharedCoordinate { shared_coordinate: SharedCoordinate },
+    /// A Twitter Place.
+    SharedPlace { shared_place: SharedPlace },
+}
+
+impl From<RawLocation> for SharedLocation {
+    fn from(loc: RawLocation) -> SharedLocation {
+        match loc {
+            RawLocation::SharedCoordinate { shared_coordinate } => {
+                // GeoJSON points are given as (longitude, latitude)
+                let (long, lat) = shared_coordinate.coordinates.coordinates;
+                SharedLocation::Coordinates(lat, long)
+            }
+            RawLocation::SharedPlace { shared_place } => {
+                SharedLocation::Place(shared_place.place.id)
+            }
+        }
+    }
+}
+
+/// Represents the coordinates of a shared point from within a `RawLocation`.
+#[derive(Deserialize)]
+pub(super) struct SharedCoordinate {
+    /// The GeoJSON point for the shared location.
+    coordinates: GeoPoint,
+}
+
+/// Represents a GeoJSON point from within a `SharedCoordinate`.
+#[derive(Deserialize)]
+struct GeoPoint {
+    /// The (longitude, latitude) pair for this point.
+    coordinates: (f64, f64),
+}
+
+/// Represents a shared place from within a `RawLocation`.
+#[derive(Deserialize)]
+pub(super) struct SharedPlace {
+    /// The place that was shared.
+    place: PlaceRef,
+}
+
+/// Represents the ID of a shared place from within a `SharedPlace`.
+#[derive(Deserialize)]
+struct PlaceRef {
+    /// The ID of the place.
+    id: String,
 }
 
 /// Represents a list of Quick Reply options from within a `DMEvent`.
 #[derive(Deserialize)]
-struct RawQuickReply {
+pub(super) struct RawQuickReply {
     /// The list of Quick Reply options sent with this message.
-    options: Vec<QuickReply>,
+    pub(super) options: Vec<QuickReply>,
 }
 
 /// Represents the `metadata` from a selected Quick Reply from within a `DMEvent`.
@@ -281,5 +380,5 @@ struct QuickReplyResponse {
 struct MessageTarget {
     #[serde(with = "serde_via_string")]
     /// The numeric user ID of the recipient of the message.
-    recipient_id: u64,
+    recipient_id: UserId,
 }
@@ -0,0 +1,485 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+use chrono;
+use hyper::Method;
+use serde::Deserialize;
+
+use crate::auth::raw::RequestBuilder;
+use crate::common::*;
+use crate::tweet::TweetSource;
+use crate::{auth, entities, error, links, media};
+
+use super::{
+    draft_message_data, push_cta_button, push_quick_reply, raw, Cta, DMEntities, DraftAttachment,
+    DraftCta, QuickReply,
+};
+
+/// A message shown to users when they open a new Direct Message conversation with the
+/// authenticated user.
+///
+/// Welcome messages are created with [`DraftWelcomeMessage`], and only shown to users once they
+/// are set as the default with [`set_default_welcome_message`]. Like regular Direct Messages, they
+/// can contain Quick Replies, "call to action" buttons, and a media attachment, so a conversation
+/// with a bot can start with a menu of options without waiting for the user to say something
+/// first.
+///
+/// [`DraftWelcomeMessage`]: struct.DraftWelcomeMessage.html
+/// [`set_default_welcome_message`]: fn.set_default_welcome_message.html
+#[derive(Debug)]
+pub struct WelcomeMessage {
+    /// Numeric ID for this welcome message.
+    pub id: u64,
+    /// UTC timestamp from when this welcome message was created.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// The name given to this welcome message when it was created, if any. This is never shown to
+    /// users.
+    pub name: Option<String>,
+    /// The text of the welcome message.
+    pub text: String,
+    /// Link, hashtag, and user mention information parsed out of the welcome message.
+    pub entities: DMEntities,
+    /// Media attached to the welcome message, if present.
+    pub attachment: Option<entities::MediaEntity>,
+    /// A list of "call to action" buttons attached to the welcome message, if present.
+    pub ctas: Option<Vec<Cta>>,
+    /// A list of "quick reply" options attached to the welcome message, if present.
+    pub quick_replies: Option<Vec<QuickReply>>,
+    /// Information about the app that was used to create this welcome message, if available.
+    pub source_app: Option<TweetSource>,
+}
+
+/// A rule specifying which welcome message is shown to users when they open a new Direct Message
+/// conversation.
+///
+/// Twitter only allows one welcome message rule to be active at once, which picks the default
+/// welcome message. Rules are created with [`set_default_welcome_message`].
+///
+/// [`set_default_welcome_message`]: fn.set_default_welcome_message.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawWelcomeMessageRule")]
+pub struct WelcomeMessageRule {
+    /// Numeric ID for this rule.
+    pub id: u64,
+    /// UTC timestamp from when this rule was created.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// The ID of the welcome message this rule shows.
+    pub welcome_message_id: u64,
+}
+
+/// A page of welcome messages, as returned by [`welcome_messages`].
+///
+/// [`welcome_messages`]: fn.welcome_messages.html
+#[derive(Debug)]
+pub struct WelcomeMessageList {
+    /// The welcome messages on this page.
+    pub messages: Vec<WelcomeMessage>,
+    /// The cursor to give to `welcome_messages` to load the next page, if more exist.
+    pub next_cursor: Option<String>,
+}
+
+/// A page of welcome message rules, as returned by [`welcome_message_rules`].
+///
+/// [`welcome_message_rules`]: fn.welcome_message_rules.html
+#[derive(Debug, Deserialize)]
+pub struct WelcomeMessageRuleList {
+    /// The rules on this page.
+    #[serde(rename = "welcome_message_rules", default)]
+    pub rules: Vec<WelcomeMessageRule>,
+    /// The cursor to give to `welcome_message_rules` to load the next page, if more exist.
+    pub next_cursor: Option<String>,
+}
+
+/// Represents a welcome message before it is created.
+///
+/// This works much like [`DraftMessage`], except that a welcome message has no recipient. Instead,
+/// it can be given a `name` to keep track of it, which is never shown to users. Once the message
+/// is set up, call `create` to save it, then pass its ID to [`set_default_welcome_message`] to
+/// start showing it to users.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::direct::{self, DraftWelcomeMessage};
+///
+/// let welcome = DraftWelcomeMessage::new("Hi! What can we help you with?")
+///     .name("support-menu")
+///     .quick_reply_option("Track an order", "menu-orders", None)
+///     .quick_reply_option("Talk to a person", "menu-human", None)
+///     .create(&token)
+///     .await
+///     .unwrap();
+/// direct::set_default_welcome_message(welcome.id, &token)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+///
+/// [`DraftMessage`]: struct.DraftMessage.html
+/// [`set_default_welcome_message`]: fn.set_default_welcome_message.html
+pub struct DraftWelcomeMessage {
+    text: Cow<'static, str>,
+    name: Option<String>,
+    quick_reply_options: VecDeque<QuickReply>,
+    cta_buttons: VecDeque<DraftCta>,
+    media_attachment: Option<media::MediaId>,
+}
+
+impl DraftWelcomeMessage {
+    /// Creates a new `DraftWelcomeMessage` with the given text.
+    pub fn new(text: impl Into<Cow<'static, str>>) -> DraftWelcomeMessage {
+        DraftWelcomeMessage {
+            text: text.into(),
+            name: None,
+            quick_reply_options: VecDeque::new(),
+            cta_buttons: VecDeque::new(),
+            media_attachment: None,
+        }
+    }
+
+    /// Gives this welcome message a name, to help tell it apart from others when listing them.
+    ///
+    /// The name is never shown to users. It can be at most 100 characters long.
+    pub fn name(self, name: impl Into<String>) -> Self {
+        DraftWelcomeMessage {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Adds an Option-type Quick Reply to this welcome message.
+    ///
+    /// This has the same restrictions as `quick_reply_option` on `DraftMessage`: there is a
+    /// maximum of 20 options, and adding more will remove the oldest one.
+    pub fn quick_reply_option(
+        mut self,
+        label: impl Into<String>,
+        metadata: impl Into<String>,
+        description: Option<String>,
+    ) -> Self {
+        push_quick_reply(
+            &mut self.quick_reply_options,
+            label.into(),
+            metadata.into(),
+            description,
+        );
+        self
+    }
+
+    /// Adds a "Call To Action" button to this welcome message.
+    ///
+    /// This has the same restrictions as `cta_button` on `DraftMessage`: there is a maximum of 3
+    /// buttons, and adding more will remove the oldest one.
+    pub fn cta_button(mut self, label: impl Into<String>, url: impl Into<String>) -> Self {
+        push_cta_button(&mut self.cta_buttons, label.into(), url.into());
+        self
+    }
+
+    /// Adds the given media to this welcome message.
+    ///
+    /// As with `DraftMessage`, the media needs to have been uploaded via
+    /// [`media::upload_media_for_dm`].
+    ///
+    /// [`media::upload_media_for_dm`]: ../media/fn.upload_media_for_dm.html
+    pub fn attach_media(self, media_id: media::MediaId) -> Self {
+        DraftWelcomeMessage {
+            media_attachment: Some(media_id),
+            ..self
+        }
+    }
+
+    fn message_data(self) -> serde_json::Value {
+        draft_message_data(
+            self.text,
+            self.quick_reply_options,
+            self.cta_buttons,
+            self.media_attachment.map(DraftAttachment::Media),
+        )
+    }
+
+    /// Saves this welcome message using the given `Token`, returning the newly-created
+    /// `WelcomeMessage`.
+    ///
+    /// Note that creating a welcome message doesn't start showing it to users. To do that, pass its
+    /// ID to [`set_default_welcome_message`].
+    ///
+    /// [`set_default_welcome_message`]: fn.set_default_welcome_message.html
+    pub async fn create(
+        self,
+        token: &auth::Token,
+    ) -> Result<Response<WelcomeMessage>, error::Error> {
+        let mut welcome_message = serde_json::json!({});
+        if let Some(ref name) = self.name {
+            welcome_message["name"] = name.as_str().into();
+        }
+        welcome_message["message_data"] = self.message_data();
+
+        let body = serde_json::json!({ "welcome_message": welcome_message });
+        let req = post_json(links::direct::WELCOME_NEW, token, body);
+        let resp: Response<SingleWelcomeMessage> = request_with_json_response(req).await?;
+        Ok(Response::into(resp))
+    }
+
+    /// Replaces the content of the welcome message with the given ID with the content of this
+    /// draft, returning the updated `WelcomeMessage`.
+    ///
+    /// Twitter only allows the text, Quick Replies, buttons, and media of a welcome message to be
+    /// changed, so the `name` of this draft is not used.
+    pub async fn update(
+        self,
+        id: u64,
+        token: &auth::Token,
+    ) -> Result<Response<WelcomeMessage>, error::Error> {
+        let params = ParamList::new().add_param("id", id.to_string());
+        let body = serde_json::json!({ "message_data": self.message_data() });
+        let req = RequestBuilder::new(Method::PUT, links::direct::WELCOME_UPDATE)
+            .with_query_params(&params)
+            .with_body_json(body)
+            .request_token(token);
+        let resp: Response<SingleWelcomeMessage> = request_with_json_response(req).await?;
+        Ok(Response::into(resp))
+    }
+}
+
+/// Lookup a single welcome message by its numeric ID.
+pub async fn show_welcome_message(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<WelcomeMessage>, error::Error> {
+    let params = ParamList::new().add_param("id", id.to_string());
+    let req = get(links::direct::WELCOME_SHOW, token, Some(&params));
+    let resp: Response<SingleWelcomeMessage> = request_with_json_response(req).await?;
+    Ok(Response::into(resp))
+}
+
+/// Load a page of the welcome messages created by the authenticated user.
+///
+/// Pass `None` as the `cursor` to load the first page, then the `next_cursor` of the returned page
+/// to load the one after it. Pages are loaded with the maximum size of 50 messages.
+pub async fn welcome_messages(
+    cursor: Option<String>,
+    token: &auth::Token,
+) -> Result<Response<WelcomeMessageList>, error::Error> {
+    let params = ParamList::new()
+        .add_param("count", "50")
+        .add_opt_param("cursor", cursor);
+    let req = get(links::direct::WELCOME_LIST, token, Some(&params));
+    let resp: Response<WelcomeMessageCursor> = request_with_json_response(req).await?;
+    Ok(Response::into(resp))
+}
+
+/// Delete the welcome message with the given ID.
+///
+/// If the message is currently set as the default, the rule pointing to it is deleted as well, and
+/// no welcome message will be shown to users until a new default is set.
+///
+/// Twitter does not return anything upon a successful deletion, so this function will return an
+/// empty `Response` upon success.
+pub async fn delete_welcome_message(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<()>, error::Error> {
+    let params = ParamList::new().add_param("id", id.to_string());
+    let req = auth::raw::delete(links::direct::WELCOME_DELETE, token, Some(&params));
+    request_with_empty_response(req).await
+}
+
+/// Sets the welcome message with the given ID as the default, to be shown to users when they open
+/// a new conversation with the authenticated user.
+///
+/// This creates a `WelcomeMessageRule` pointing at the message. Twitter only allows one rule at a
+/// time, so if a different message is already the default, delete its rule with
+/// `delete_welcome_message_rule` before calling this.
+pub async fn set_default_welcome_message(
+    welcome_message_id: u64,
+    token: &auth::Token,
+) -> Result<Response<WelcomeMessageRule>, error::Error> {
+    let body = serde_json::json!({
+        "welcome_message_rule": {
+            "welcome_message_id": welcome_message_id.to_string(),
+        }
+    });
+    let req = post_json(links::direct::WELCOME_RULE_NEW, token, body);
+    let resp: Response<SingleWelcomeMessageRule> = request_with_json_response(req).await?;
+    Ok(Response::map(resp, |r| r.welcome_message_rule))
+}
+
+/// Lookup a single welcome message rule by its numeric ID.
+pub async fn show_welcome_message_rule(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<WelcomeMessageRule>, error::Error> {
+    let params = ParamList::new().add_param("id", id.to_string());
+    let req = get(links::direct::WELCOME_RULE_SHOW, token, Some(&params));
+    let resp: Response<SingleWelcomeMessageRule> = request_with_json_response(req).await?;
+    Ok(Response::map(resp, |r| r.welcome_message_rule))
+}
+
+/// Load a page of the welcome message rules set up by the authenticated user.
+///
+/// Since Twitter only allows one rule at a time, the first page will contain the current default
+/// welcome message's rule, if one has been set. Paging works the same as with `welcome_messages`.
+pub async fn welcome_message_rules(
+    cursor: Option<String>,
+    token: &auth::Token,
+) -> Result<Response<WelcomeMessageRuleList>, error::Error> {
+    let params = ParamList::new()
+        .add_param("count", "50")
+        .add_opt_param("cursor", cursor);
+    let req = get(links::direct::WELCOME_RULE_LIST, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+/// Delete the welcome message rule with the given ID.
+///
+/// This stops showing its welcome message to users, but doesn't delete the message itself.
+///
+/// Twitter does not return anything upon a successful deletion, so this function will return an
+/// empty `Response` upon success.
+pub async fn delete_welcome_message_rule(
+    id: u64,
+    token: &auth::Token,
+) -> Result<Response<()>, error::Error> {
+    let params = ParamList::new().add_param("id", id.to_string());
+    let req = auth::raw::delete(links::direct::WELCOME_RULE_DELETE, token, Some(&params));
+    request_with_empty_response(req).await
+}
+
+// welcome messages come back in a similar structure to DM events, except the message data sits
+// directly on the welcome message instead of inside a `message_create` object
+
+#[derive(Deserialize)]
+struct RawWelcomeMessage {
+    #[serde(with = "serde_via_string")]
+    id: u64,
+    #[serde(deserialize_with = "serde_millis::deserialize")]
+    created_timestamp: chrono::DateTime<chrono::Utc>,
+    name: Option<String>,
+    source_app_id: Option<String>,
+    message_data: raw::MessageData,
+}
+
+impl RawWelcomeMessage {
+    fn into_welcome_message(self, apps: &HashMap<String, TweetSource>) -> WelcomeMessage {
+        let raw::MessageData {
+            text,
+            mut entities,
+            attachment,
+            ctas,
+            quick_reply,
+            ..
+        } = self.message_data;
+        let mut attachment = attachment.and_then(|a| a.into_media());
+        raw::translate_entities(&mut entities, attachment.as_mut(), &text);
+
+        WelcomeMessage {
+            id: self.id,
+            created_at: self.created_timestamp,
+            name: self.name,
+            text,
+            entities,
+            attachment,
+            ctas,
+            quick_replies: quick_reply.map(|q| q.options),
+            source_app: self.source_app_id.and_then(|id| apps.get(&id).cloned()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SingleWelcomeMessage {
+    welcome_message: RawWelcomeMessage,
+    #[serde(default)]
+    apps: HashMap<String, TweetSource>,
+}
+
+impl From<SingleWelcomeMessage> for WelcomeMessage {
+    fn from(msg: SingleWelcomeMessage) -> WelcomeMessage {
+        msg.welcome_message.into_welcome_message(&msg.apps)
+    }
+}
+
+#[derive(Deserialize)]
+struct WelcomeMessageCursor {
+    #[serde(default)]
+    welcome_messages: Vec<RawWelcomeMessage>,
+    #[serde(default)]
+    apps: HashMap<String, TweetSource>,
+    next_cursor: Option<String>,
+}
+
+impl From<WelcomeMessageCursor> for WelcomeMessageList {
+    fn from(page: WelcomeMessageCursor) -> WelcomeMessageList {
+        let apps = page.apps;
+        WelcomeMessageList {
+            messages: page
+                .welcome_messages
+                .into_iter()
+                .map(|msg| msg.into_welcome_message(&apps))
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawWelcomeMessageRule {
+    #[serde(with = "serde_via_string")]
+    id: u64,
+    #[serde(deserialize_with = "serde_millis::deserialize")]
+    created_timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(with = "serde_via_string")]
+    welcome_message_id: u64,
+}
+
+impl From<RawWelcomeMessageRule> for WelcomeMessageRule {
+    fn from(raw: RawWelcomeMessageRule) -> WelcomeMessageRule {
+        WelcomeMessageRule {
+            id: raw.id,
+            created_at: raw.created_timestamp,
+            welcome_message_id: raw.welcome_message_id,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SingleWelcomeMessageRule {
+    welcome_message_rule: WelcomeMessageRule,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_welcome_message() {
+        let content = load_file("sample_payloads/dm-welcome-message.json");
+        let msg = ::serde_json::from_str::<SingleWelcomeMessage>(&content).unwrap();
+        let msg = WelcomeMessage::from(msg);
+
+        assert_eq!(msg.id, 1256283445345188868);
+        assert_eq!(msg.name.as_deref(), Some("support-menu"));
+        assert_eq!(msg.quick_replies.as_ref().unwrap().len(), 2);
+        assert_eq!(msg.source_app.as_ref().unwrap().name, "egg-mode sample app");
+
+        let mention = &msg.entities.user_mentions[0];
+        assert_eq!(&msg.text[mention.range.0..mention.range.1], "@rustlang");
+
+        let content = load_file("sample_payloads/dm-welcome-rules.json");
+        let rules = ::serde_json::from_str::<WelcomeMessageRuleList>(&content).unwrap();
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.rules[0].welcome_message_id, msg.id);
+        assert!(rules.next_cursor.is_none());
+        assert_eq!(rules.rules[0].created_at.timestamp_millis(), 1588420735822);
+
+        let rule =
+            r#"{"id":"1","created_timestamp":"9223372036854775807","welcome_message_id":"2"}"#;
+        assert!(::serde_json::from_str::<WelcomeMessageRule>(rule).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Methods to load impressions and engagement metrics for tweets, via the Engagement API.
+//!
+//! The [Engagement API][] is a premium product: it's only available to apps that have been granted
+//! access to it, and most metrics are only available for tweets posted by the authenticated user.
+//! For tweets owned by someone else, only the public metrics (`Favorites`, `Retweets`,
+//! `QuoteTweets`, `Replies`, and `VideoViews`) are available, and only through `totals`.
+//!
+//! [Engagement API]: https://developer.twitter.com/en/docs/twitter-api/enterprise/engagement-api/overview
+//!
+//! There are three functions here, one for each of the API's endpoints:
+//!
+//! - `totals` loads the all-time total of each metric, for up to 250 tweets at once.
+//! - `last_28_hours` loads hourly counts of each metric from the last 28 hours, for up to 25 tweets
+//!   at once.
+//! - `historical` loads daily counts of each metric over a span of up to four weeks, for up to 25
+//!   tweets at once.
+//!
+//! If more tweets than that are given, egg-mode splits them into several calls and combines the
+//! results. In all cases, tweets that can't be measured (for example because they were deleted,
+//! or aren't owned by the authenticated user) are listed in the response's
+//! `unavailable_tweet_ids` instead of causing the whole request to fail.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let token: egg_mode::Token = unimplemented!();
+//! use egg_mode::engagement::{self, EngagementType};
+//!
+//! let ids = [1184191501121523712u64, 1184191501121523713];
+//! let types = [EngagementType::Impressions, EngagementType::Engagements];
+//! let totals = engagement::totals(ids.iter().copied(), &types, &token).await.unwrap();
+//!
+//! for (id, metrics) in &totals.tweets {
+//!     println!("{}: {:?} impressions", id, metrics.get(&EngagementType::Impressions));
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::error::{Error::InvalidResponse, Result};
+use crate::tweet::TweetId;
+use crate::{auth, links};
+
+/// The name egg-mode gives to the grouping it requests. The Engagement API lets the caller name
+/// each grouping, and uses that name as the key for its results. (The `Raw*` structs below rename
+/// their `tweets` field to match.)
+const GROUPING: &str = "by_tweet";
+
+/// The most tweets `totals` can load in one call.
+const TOTALS_CHUNK_SIZE: usize = 250;
+
+/// The most tweets `last_28_hours` and `historical` can load in one call.
+const SERIES_CHUNK_SIZE: usize = 25;
+
+/// A metric that the Engagement API can report for a tweet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngagementType {
+    /// The number of times the tweet was seen.
+    Impressions,
+    /// The total number of times a user interacted with the tweet, including clicks, likes,
+    /// retweets, and replies.
+    Engagements,
+    /// The number of times the tweet was liked.
+    Favorites,
+    /// The number of times the tweet was retweeted.
+    Retweets,
+    /// The number of times the tweet was quoted.
+    QuoteTweets,
+    /// The number of replies to the tweet.
+    Replies,
+    /// The number of times a video in the tweet was viewed.
+    VideoViews,
+    /// The number of times media in the tweet was viewed.
+    MediaViews,
+    /// The number of times media in the tweet was clicked.
+    MediaEngagements,
+    /// The number of times a link in the tweet was clicked.
+    UrlClicks,
+    /// The number of times a hashtag in the tweet was clicked.
+    HashtagClicks,
+    /// The number of times the tweet was clicked to view more details.
+    DetailExpands,
+    /// The number of times the tweet's permalink was clicked.
+    PermalinkClicks,
+    /// The number of times the tweet was shared by email.
+    EmailTweet,
+    /// The number of times the author was followed from the tweet.
+    UserFollows,
+    /// The number of times the author's profile was viewed from the tweet.
+    UserProfileClicks,
+    /// A metric that egg-mode doesn't know about yet.
+    #[serde(other)]
+    Other,
+}
+
+/// The all-time engagement totals for a set of tweets, as returned by `totals`.
+#[derive(Debug, Clone, Default)]
+pub struct EngagementTotals {
+    /// The requested metrics for each tweet that could be measured.
+    pub tweets: HashMap<TweetId, HashMap<EngagementType, u64>>,
+    /// The IDs of the requested tweets that couldn't be measured.
+    pub unavailable_tweet_ids: Vec<TweetId>,
+}
+
+/// A list of `(time, count)` pairs for one metric, sorted by time.
+///
+/// `time` is the start of the hour (for `last_28_hours`) or day (for `historical`) that the count
+/// covers.
+pub type EngagementCounts = Vec<(DateTime<Utc>, u64)>;
+
+/// Engagement counts over time for a set of tweets, as returned by `last_28_hours` and
+/// `historical`.
+#[derive(Debug, Clone, Default)]
+pub struct EngagementSeries {
+    /// The requested metrics for each tweet that could be measured.
+    pub tweets: HashMap<TweetId, HashMap<EngagementType, EngagementCounts>>,
+    /// The IDs of the requested tweets that couldn't be measured.
+    pub unavailable_tweet_ids: Vec<TweetId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTotals {
+    #[serde(rename = "by_tweet", default)]
+    tweets: HashMap<String, HashMap<EngagementType, String>>,
+    #[serde(default)]
+    unavailable_tweet_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSeries {
+    #[serde(rename = "by_tweet", default)]
+    tweets: HashMap<String, HashMap<EngagementType, HashMap<String, String>>>,
+    #[serde(default)]
+    unavailable_tweet_ids: Vec<String>,
+}
+
+fn parse_id(id: &str) -> Result<TweetId> {
+    id.parse()
+        .map_err(|_| InvalidResponse("invalid tweet ID", Some(id.to_string())))
+}
+
+fn parse_count(count: &str) -> Result<u64> {
+    count
+        .parse()
+        .map_err(|_| InvalidResponse("invalid engagement count", Some(count.to_string())))
+}
+
+/// The Engagement API labels hourly counts like "2019-01-01 13:00" and daily counts like
+/// "2019-01-01", both in UTC.
+fn parse_bucket(bucket: &str) -> Result<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(bucket, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(bucket, "%Y-%m-%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+        })
+        .map(|time| Utc.from_utc_datetime(&time))
+        .ok_or_else(|| InvalidResponse("invalid engagement time", Some(bucket.to_string())))
+}
+
+fn parse_unavailable(ids: Vec<String>) -> Result<Vec<TweetId>> {
+    ids.iter().map(|id| parse_id(id)).collect()
+}
+
+impl EngagementTotals {
+    fn from_raw(raw: RawTotals) -> Result<EngagementTotals> {
+        let mut tweets = HashMap::new();
+        for (id, metrics) in raw.tweets {
+            let metrics = metrics
+                .into_iter()
+                .map(|(kind, count)| Ok((kind, parse_count(&count)?)))
+                .collect::<Result<_>>()?;
+            tweets.insert(parse_id(&id)?, metrics);
+        }
+
+        Ok(EngagementTotals {
+            tweets,
+            unavailable_tweet_ids: parse_unavailable(raw.unavailable_tweet_ids)?,
+        })
+    }
+
+    /// Adds the tweets from a later call onto this one.
+    fn extend(&mut self, later: EngagementTotals) {
+        self.tweets.extend(later.tweets);
+        self.unavailable_tweet_ids
+            .extend(later.unavailable_tweet_ids);
+    }
+}
+
+impl EngagementSeries {
+    fn from_raw(raw: RawSeries) -> Result<EngagementSeries> {
+        let mut tweets = HashMap::new();
+        for (id, metrics) in raw.tweets {
+            let mut series = HashMap::new();
+            for (kind, buckets) in metrics {
+                let mut counts = buckets
+                    .into_iter()
+                    .map(|(time, count)| Ok((parse_bucket(&time)?, parse_count(&count)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                counts.sort();
+                series.insert(kind, counts);
+            }
+            tweets.insert(parse_id(&id)?, series);
+        }
+
+        Ok(EngagementSeries {
+            tweets,
+            unavailable_tweet_ids: parse_unavailable(raw.unavailable_tweet_ids)?,
+        })
+    }
+
+    /// Adds the tweets from a later call onto this one.
+    fn extend(&mut self, later: EngagementSeries) {
+        self.tweets.extend(later.tweets);
+        self.unavailable_tweet_ids
+            .extend(later.unavailable_tweet_ids);
+    }
+}
+
+fn request_body(
+    ids: &[TweetId],
+    types: &[EngagementType],
+    time_bucket: Option<&str>,
+) -> serde_json::Value {
+    let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+    let mut group_by = vec!["tweet.id", "engagement.type"];
+    group_by.extend(time_bucket);
+
+    serde_json::json!({
+        "tweet_ids": ids,
+        "engagement_types": types,
+        "groupings": {
+            GROUPING: { "group_by": group_by },
+        },
+    })
+}
+
+/// Loads the all-time totals of the given metrics for the given tweets.
+///
+/// The Engagement API accepts up to 250 tweet IDs in one call to this endpoint. If more than that
+/// are given, egg-mode splits them into batches of 250 and makes one call for each, combining the
+/// results into one `Response`. The rate-limit information in that response is from the last
+/// call made. If any of the calls fail, the whole request returns that error.
+pub async fn totals(
+    ids: impl IntoIterator<Item = impl Into<TweetId>>,
+    types: &[EngagementType],
+    token: &auth::Token,
+) -> Result<Response<EngagementTotals>> {
+    let ids = ids.into_iter().map(Into::into).collect::<Vec<TweetId>>();
+    let mut chunks = ids.chunks(TOTALS_CHUNK_SIZE);
+
+    let mut resp = totals_chunk(chunks.next().unwrap_or(&[]), types, token).await?;
+    for chunk in chunks {
+        let later = totals_chunk(chunk, types, token).await?;
+        resp.rate_limit_status = later.rate_limit_status;
+        resp.response.extend(later.response);
+    }
+
+    Ok(resp)
+}
+
+async fn totals_chunk(
+    ids: &[TweetId],
+    types: &[EngagementType],
+    token: &auth::Token,
+) -> Result<Response<EngagementTotals>> {
+    let body = request_body(ids, types, None);
+    let req = post_json(links::engagement::TOTALS, token, body);
+    let resp = request_with_json_response::<RawTotals>(req).await?;
+    Response::try_map(resp, EngagementTotals::from_raw)
+}
+
+/// Loads hourly counts of the given metrics for the given tweets, over the last 28 hours.
+///
+/// The Engagement API accepts up to 25 tweet IDs in one call to this endpoint. Like `totals`, if
+/// more than that are given, egg-mode splits them into batches of 25 and combines the results,
+/// with the rate-limit information coming from the last call. Only tweets posted by the
+/// authenticated user can be measured.
+pub async fn last_28_hours(
+    ids: impl IntoIterator<Item = impl Into<TweetId>>,
+    types: &[EngagementType],
+    token: &auth::Token,
+) -> Result<Response<EngagementSeries>> {
+    let ids = ids.into_iter().map(Into::into).collect::<Vec<TweetId>>();
+    series(links::engagement::HOURS_28, &ids, types, None, token).await
+}
+
+/// Loads daily counts of the given metrics for the given tweets, between the given days.
+///
+/// `start` and `end` are both inclusive, and can be at most four weeks apart. The Engagement API
+/// accepts up to 25 tweet IDs in one call to this endpoint; more than that are split into batches
+/// like `last_28_hours` does. Only tweets posted by the authenticated user can be measured.
+pub async fn historical(
+    ids: impl IntoIterator<Item = impl Into<TweetId>>,
+    types: &[EngagementType],
+    start: NaiveDate,
+    end: NaiveDate,
+    token: &auth::Token,
+) -> Result<Response<EngagementSeries>> {
+    let ids = ids.into_iter().map(Into::into).collect::<Vec<TweetId>>();
+    let range = Some((start, end));
+    series(links::engagement::HISTORICAL, &ids, types, range, token).await
+}
+
+/// Loads counts over time from the given endpoint, in batches of `SERIES_CHUNK_SIZE` tweets. The
+/// `range` of days is only given for `historical`, which buckets its counts by day instead of by
+/// hour.
+async fn series(
+    link: &'static str,
+    ids: &[TweetId],
+    types: &[EngagementType],
+    range: Option<(NaiveDate, NaiveDate)>,
+    token: &auth::Token,
+) -> Result<Response<EngagementSeries>> {
+    let mut chunks = ids.chunks(SERIES_CHUNK_SIZE);
+
+    let mut resp = series_chunk(link, chunks.next().unwrap_or(&[]), types, range, token).await?;
+    for chunk in chunks {
+        let later = series_chunk(link, chunk, types, range, token).await?;
+        resp.rate_limit_status = later.rate_limit_status;
+        resp.response.extend(later.response);
+    }
+
+    Ok(resp)
+}
+
+async fn series_chunk(
+    link: &'static str,
+    ids: &[TweetId],
+    types: &[EngagementType],
+    range: Option<(NaiveDate, NaiveDate)>,
+    token: &auth::Token,
+) -> Result<Response<EngagementSeries>> {
+    let bucket = if range.is_some() {
+        "engagement.day"
+    } else {
+        "engagement.hour"
+    };
+    let mut body = request_body(ids, types, Some(bucket));
+    if let Some((start, end)) = range {
+        body["start"] = start.format("%Y-%m-%d").to_string().into();
+        body["end"] = end.format("%Y-%m-%d").to_string().into();
+    }
+    let req = post_json(link, token, body);
+    let resp = request_with_json_response::<RawSeries>(req).await?;
+    Response::try_map(resp, EngagementSeries::from_raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_engagement() {
+        let content = load_file("sample_payloads/sample-engagement-totals.json");
+        let raw = ::serde_json::from_str::<RawTotals>(&content).unwrap();
+        let totals = EngagementTotals::from_raw(raw).unwrap();
+
+        let metrics = &totals.tweets[&TweetId(1184191501121523712)];
+        assert_eq!(metrics[&EngagementType::Impressions], 19870);
+        assert_eq!(metrics[&EngagementType::QuoteTweets], 4);
+        assert_eq!(totals.unavailable_tweet_ids, vec![TweetId(20)]);
+
+        let content = load_file("sample_payloads/sample-engagement-28hr.json");
+        let raw = ::serde_json::from_str::<RawSeries>(&content).unwrap();
+        let series = EngagementSeries::from_raw(raw).unwrap();
+
+        let impressions =
+            &series.tweets[&TweetId(1184191501121523712)][&EngagementType::Impressions];
+        assert_eq!(impressions.len(), 3);
+        assert_eq!(impressions[0].0, parse_bucket("2019-10-15 20:00").unwrap());
+        assert_eq!(impressions[0].1, 1203);
+        assert!(impressions.windows(2).all(|w| w[0].0 < w[1].0));
+
+        assert_eq!(
+            parse_bucket("2019-10-15").unwrap(),
+            parse_bucket("2019-10-15 00:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_chunks() {
+        let content = load_file("sample_payloads/sample-engagement-totals.json");
+        let raw = ::serde_json::from_str::<RawTotals>(&content).unwrap();
+        let mut totals = EngagementTotals::from_raw(raw).unwrap();
+
+        let mut later = EngagementTotals::default();
+        later.tweets.insert(TweetId(30), HashMap::new());
+        later.unavailable_tweet_ids.push(TweetId(40));
+        let count = totals.tweets.len();
+        totals.extend(later);
+        assert_eq!(totals.tweets.len(), count + 1);
+        assert_eq!(totals.unavailable_tweet_ids, vec![TweetId(20), TweetId(40)]);
+    }
+}
@@ -46,6 +46,8 @@ use mime;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::common::serde_via_string;
+use crate::tweet::TweetId;
+use crate::user::UserId;
 
 ///Represents a hashtag or symbol extracted from another piece of text.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -97,7 +99,7 @@ pub struct MediaEntity {
     pub sizes: MediaSizes,
     ///For tweets containing media that was originally associated with a different tweet, this
     ///contains the ID of the original tweet.
-    pub source_status_id: Option<u64>,
+    pub source_status_id: Option<TweetId>,
     ///The type of media being represented.
     #[serde(rename = "type")]
     pub media_type: MediaType,
@@ -207,7 +209,7 @@ pub struct UrlEntity {
 pub struct MentionEntity {
     ///Numeric ID of the mentioned user.
     #[serde(deserialize_with = "nullable_id")] // Very rarely this field is null
-    pub id: u64,
+    pub id: UserId,
     ///The byte offsets where the user mention is located in the original text. The first index is
     ///the location of the @ symbol; the second is the location of the first character following
     ///the user screen name.
@@ -220,12 +222,12 @@ pub struct MentionEntity {
     pub screen_name: String,
 }
 
-fn nullable_id<'de, D>(deserializer: D) -> Result<u64, D::Error>
+fn nullable_id<'de, D>(deserializer: D) -> Result<UserId, D::Error>
 where
     D: Deserializer<'de>,
 {
     let opt = Option::deserialize(deserializer)?;
-    Ok(opt.unwrap_or_default())
+    Ok(UserId(opt.unwrap_or_default()))
 }
 
 fn nullable_str<'de, D>(deserializer: D) -> Result<String, D::Error>
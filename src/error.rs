@@ -75,6 +75,11 @@ impl fmt::Display for TwitterErrorCode {
     }
 }
 
+fn display_v2_errors(errors: &[crate::v2::PartialError]) -> String {
+    let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    errors.join(", ")
+}
+
 /// Represents an error that can occur during media processing.
 #[derive(Debug, Clone, PartialEq, Deserialize, thiserror::Error)]
 #[error("Media error {code} ({name}) - {message}")]
@@ -93,6 +98,11 @@ pub enum Error {
     ///A URL was passed to a shortcut function that didn't match the method being called.
     #[error("URL given did not match API method")]
     BadUrl,
+    ///A parameter given to a function, request builder, or draft tweet was rejected before the
+    ///request was sent, because Twitter would not accept it. The enclosed value describes the
+    ///problem.
+    #[error("Invalid parameter: {0}")]
+    InvalidParam(&'static str),
     ///The response from Twitter was formatted incorrectly or in an unexpected manner. The enclosed
     ///values are an explanatory string and, if applicable, the input that caused the error.
     ///
@@ -114,6 +124,13 @@ pub enum Error {
     ///enclosed value was the response from Twitter.
     #[error("Errors returned by Twitter: {_1}")]
     TwitterError(Headers, TwitterErrors),
+    ///A v2 endpoint returned a list of errors instead of the expected response. The enclosed
+    ///value was the list of errors.
+    ///
+    ///The v2 API doesn't give its errors numeric codes, so they're kept separate from
+    ///`TwitterError`.
+    #[error("Errors returned by Twitter: {}", display_v2_errors(_0))]
+    TwitterErrorV2(Vec<crate::v2::PartialError>),
     ///The response returned from Twitter contained an error indicating that the rate limit for
     ///that method has been reached. The enclosed value is the Unix timestamp in UTC when the next
     ///rate-limit window will open.
@@ -123,6 +140,11 @@ pub enum Error {
     ///post-processing. The enclosed value contains the error message from Twitter.
     #[error("Error processing media: {}", _0)]
     MediaError(#[from] MediaError),
+    ///Twitter was still processing uploaded media when it expired, so it can no longer be used.
+    ///This is returned by `media::wait_for_processing`, which stops waiting once the media
+    ///expires.
+    #[error("Media expired before it finished processing")]
+    MediaExpired,
     ///The response from Twitter gave a response code that indicated an error. The enclosed value
     ///was the response code.
     ///
@@ -81,6 +81,19 @@
 //! compile error will result. If you need to use `rustls` or `rustls_webpki`, remember to set
 //! `default-features = false` in your Cargo.toml.
 //!
+//! Separately from the TLS features, there is also the following:
+//!
+//! * `raw-json`: Off by default. With this feature on, `Tweet`, `TwitterUser`, `List`, `Place`,
+//!   and `TrendLocation` keep the full JSON they were loaded from, available through their `raw()`
+//!   methods. This lets you read fields that Twitter has added but egg-mode doesn't parse yet, at
+//!   the cost of extra memory and slower parsing. The `raw()` methods are there with or without
+//!   the feature, so enabling it doesn't change any types; without it, they return `None`. Types
+//!   that egg-mode assembles from several pieces of a response, like `DirectMessage`, don't have a
+//!   single JSON object to keep, so they don't have a `raw()` method.
+//! * `webhook-server`: Off by default. With this feature on, `activity::serve_webhook` runs a
+//!   small HTTP server that answers the Account Activity API's checks and hands the events it
+//!   receives to your app as a `Stream`.
+//!
 //! # Types and Functions
 //!
 //! All of the main content of egg-mode is in submodules, but there are a few things here in the
@@ -140,6 +153,14 @@
 //!
 //! * `place`: Here are actions that look up physical locations that can be attached to tweets, as
 //!   well at the `Place` struct that appears on tweets with locations attached.
+//! * `engagement`: If your app has access to Twitter's premium Engagement API, this module loads
+//!   impressions and other engagement metrics for tweets.
+//! * `activity`: If your app has access to the Account Activity API, this module registers the
+//!   webhooks Twitter sends account events to, instead of having to poll for them.
+//! * `collection`: This module lets you act on collections, the timelines of tweets a user has
+//!   picked out by hand, from creating them to adding, removing, and reordering their tweets.
+//! * `compliance`: If your app stores tweets or user information, this module finds the ones that
+//!   have since been deleted, protected, or withheld, so they can be removed or updated.
 //! * `service`: These are some miscellaneous methods that show information about the Twitter
 //!   service as a whole, like loading the maximum length of t.co URLs or loading the current Terms
 //!   of Service or Privacy Policy.
@@ -153,6 +174,12 @@
 //!   through certain collections of results from Twitter.
 //! * `entities`: Whenever some text can be returned that may contain links, hashtags, media, or
 //!   user mentions, its metadata is parsed into something that lives in this module.
+//! * `state`: Some helpers in egg-mode can save their progress between runs of your program. They
+//!   all do so through the `StateStore` trait in this module, which comes with filesystem and
+//!   in-memory implementations.
+//! * `v2`: The functions that call Twitter's v2 API return their own set of types, with optional
+//!   fields and related objects that need to be requested by name. Those types, and the `Fields`
+//!   builder that requests them, live in this module.
 //! * `error`: Any interaction with Twitter may result in an error condition, be it from finding a
 //!   tweet or user that doesn't exist or the network connection being unavailable. All the error
 //!   types are aggregated into an enum in this module.
@@ -164,9 +191,13 @@
 #[macro_use]
 mod common;
 pub mod account;
+pub mod activity;
 pub mod auth;
+pub mod collection;
+pub mod compliance;
 pub mod cursor;
 pub mod direct;
+pub mod engagement;
 pub mod entities;
 pub mod error;
 mod links;
@@ -176,10 +207,12 @@ pub mod place;
 pub mod raw;
 pub mod search;
 pub mod service;
+pub mod state;
 pub mod stream;
 pub mod trend;
 pub mod tweet;
 pub mod user;
+pub mod v2;
 
 pub use crate::auth::{KeyPair, Token};
 pub use crate::common::{RateLimit, Response, ResponseIter};
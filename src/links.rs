@@ -39,6 +39,7 @@ pub mod users {
     pub const REPORT_SPAM: &str = "https://api.twitter.com/1.1/users/report_spam.json";
     pub const MUTE: &str = "https://api.twitter.com/1.1/mutes/users/create.json";
     pub const UNMUTE: &str = "https://api.twitter.com/1.1/mutes/users/destroy.json";
+    pub const V2_USERS_STEM: &str = "https://api.twitter.com/2/users";
 }
 
 pub mod statuses {
@@ -53,17 +54,38 @@ pub mod statuses {
     pub const RETWEETERS_OF: &str = "https://api.twitter.com/1.1/statuses/retweeters/ids.json";
     pub const LIKES_OF: &str = "https://api.twitter.com/1.1/favorites/list.json";
     pub const SEARCH: &str = "https://api.twitter.com/1.1/search/tweets.json";
+    pub const PREMIUM_SEARCH_STEM: &str = "https://api.twitter.com/1.1/tweets/search";
     pub const RETWEET_STEM: &str = "https://api.twitter.com/1.1/statuses/retweet";
     pub const UNRETWEET_STEM: &str = "https://api.twitter.com/1.1/statuses/unretweet";
     pub const LIKE: &str = "https://api.twitter.com/1.1/favorites/create.json";
     pub const UNLIKE: &str = "https://api.twitter.com/1.1/favorites/destroy.json";
     pub const UPDATE: &str = "https://api.twitter.com/1.1/statuses/update.json";
     pub const DELETE_STEM: &str = "https://api.twitter.com/1.1/statuses/destroy";
+    pub const OEMBED: &str = "https://api.twitter.com/1.1/statuses/oembed.json";
+    pub const V2_TWEETS_STEM: &str = "https://api.twitter.com/2/tweets";
+    pub const V2_SEARCH_RECENT: &str = "https://api.twitter.com/2/tweets/search/recent";
+    pub const V2_SEARCH_ALL: &str = "https://api.twitter.com/2/tweets/search/all";
+    pub const V2_COUNTS_RECENT: &str = "https://api.twitter.com/2/tweets/counts/recent";
+    pub const V2_COUNTS_ALL: &str = "https://api.twitter.com/2/tweets/counts/all";
 }
 
 pub mod media {
     pub const UPLOAD: &str = "https://upload.twitter.com/1.1/media/upload.json";
     pub const METADATA: &str = "https://upload.twitter.com/1.1/media/metadata/create.json";
+    pub const SUBTITLES_CREATE: &str = "https://upload.twitter.com/1.1/media/subtitles/create.json";
+    pub const SUBTITLES_DELETE: &str = "https://upload.twitter.com/1.1/media/subtitles/delete.json";
+}
+
+pub mod collections {
+    pub const SHOW: &str = "https://api.twitter.com/1.1/collections/show.json";
+    pub const CREATE: &str = "https://api.twitter.com/1.1/collections/create.json";
+    pub const UPDATE: &str = "https://api.twitter.com/1.1/collections/update.json";
+    pub const DESTROY: &str = "https://api.twitter.com/1.1/collections/destroy.json";
+    pub const ENTRIES: &str = "https://api.twitter.com/1.1/collections/entries.json";
+    pub const ADD: &str = "https://api.twitter.com/1.1/collections/entries/add.json";
+    pub const REMOVE: &str = "https://api.twitter.com/1.1/collections/entries/remove.json";
+    pub const MOVE: &str = "https://api.twitter.com/1.1/collections/entries/move.json";
+    pub const CURATE: &str = "https://api.twitter.com/1.1/collections/entries/curate.json";
 }
 
 pub mod lists {
@@ -86,6 +108,7 @@ pub mod lists {
     pub const ADD_LIST: &str = "https://api.twitter.com/1.1/lists/members/create_all.json";
     pub const REMOVE_LIST: &str = "https://api.twitter.com/1.1/lists/members/destroy_all.json";
     pub const UPDATE: &str = "https://api.twitter.com/1.1/lists/update.json";
+    pub const V2_LISTS_STEM: &str = "https://api.twitter.com/2/lists";
 }
 
 pub mod account {
@@ -94,6 +117,7 @@ pub mod account {
     pub const UPDATE_PROFILE_BNNER: &str =
         "https://api.twitter.com/1.1/account/update_profile_banner.json";
     pub const UPDATE_PROFILE: &str = "https://api.twitter.com/1.1/account/update_profile.json";
+    pub const SETTINGS: &str = "https://api.twitter.com/1.1/account/settings.json";
 }
 
 pub mod place {
@@ -110,6 +134,29 @@ pub mod direct {
     pub const MARK_READ: &str = "https://api.twitter.com/1.1/direct_messages/mark_read.json";
     pub const INDICATE_TYPING: &str =
         "https://api.twitter.com/1.1/direct_messages/indicate_typing.json";
+    pub const WELCOME_NEW: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/new.json";
+    pub const WELCOME_SHOW: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/show.json";
+    pub const WELCOME_LIST: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/list.json";
+    pub const WELCOME_UPDATE: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/update.json";
+    pub const WELCOME_DELETE: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/destroy.json";
+    pub const WELCOME_RULE_NEW: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/rules/new.json";
+    pub const WELCOME_RULE_SHOW: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/rules/show.json";
+    pub const WELCOME_RULE_LIST: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/rules/list.json";
+    pub const WELCOME_RULE_DELETE: &str =
+        "https://api.twitter.com/1.1/direct_messages/welcome_messages/rules/destroy.json";
+    pub const CUSTOM_PROFILE_NEW: &str = "https://api.twitter.com/1.1/custom_profiles/new.json";
+    pub const CUSTOM_PROFILE_STEM: &str = "https://api.twitter.com/1.1/custom_profiles";
+    pub const CUSTOM_PROFILE_LIST: &str = "https://api.twitter.com/1.1/custom_profiles/list.json";
+    pub const CUSTOM_PROFILE_DELETE: &str =
+        "https://api.twitter.com/1.1/custom_profiles/destroy.json";
 }
 
 pub mod service {
@@ -123,9 +170,31 @@ pub mod service {
 pub mod stream {
     pub const SAMPLE: &str = "https://stream.twitter.com/1.1/statuses/sample.json";
     pub const FILTER: &str = "https://stream.twitter.com/1.1/statuses/filter.json";
+    pub const V2_FILTER: &str = "https://api.twitter.com/2/tweets/search/stream";
+    pub const V2_SAMPLE: &str = "https://api.twitter.com/2/tweets/sample/stream";
+    pub const V2_SAMPLE10: &str = "https://api.twitter.com/2/tweets/sample10/stream";
+    pub const V2_FIREHOSE: &str = "https://api.twitter.com/2/tweets/firehose/stream";
+}
+
+pub mod activity {
+    pub const STEM: &str = "https://api.twitter.com/1.1/account_activity/all";
+    pub const REPLAY_STEM: &str = "https://api.twitter.com/1.1/account_activity/replay/webhooks";
+}
+
+pub mod compliance {
+    pub const JOBS: &str = "https://api.twitter.com/2/compliance/jobs";
+    pub const TWEETS_STREAM: &str = "https://api.twitter.com/2/tweets/compliance/stream";
+    pub const USERS_STREAM: &str = "https://api.twitter.com/2/users/compliance/stream";
+}
+
+pub mod engagement {
+    pub const TOTALS: &str = "https://data-api.twitter.com/insights/engagement/totals";
+    pub const HOURS_28: &str = "https://data-api.twitter.com/insights/engagement/28hr";
+    pub const HISTORICAL: &str = "https://data-api.twitter.com/insights/engagement/historical";
 }
 
 pub mod trend {
     pub const CLOSEST: &str = "https://api.twitter.com/1.1/trends/closest.json";
     pub const AVAILABLE: &str = "https://api.twitter.com/1.1/trends/available.json";
+    pub const PLACE: &str = "https://api.twitter.com/1.1/trends/place.json";
 }
@@ -15,12 +15,41 @@ use crate::{auth, links, tweet};
 ///Look up the lists the given user has been added to.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use futures::TryStreamExt;
+///
+///let lists = egg_mode::list::memberships("rustlang", &token)
+///    .with_page_size(1000)
+///    .map_ok(|r| r.response.full_name)
+///    .try_collect::<Vec<_>>()
+///    .await
+///    .unwrap();
+///# }
+///```
 pub fn memberships<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIter<ListCursor> {
     let params = ParamList::new().add_user_param(user.into());
     CursorIter::new(links::lists::MEMBERSHIPS, token, Some(params), Some(20))
 }
 
+///Look up the lists owned by the authenticated user that the given user has been added to.
+///
+///This works like `memberships`, but skips lists that belong to other users. It's a quick way to
+///check which of your own lists an account is on.
+pub fn owned_memberships<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIter<ListCursor> {
+    let params = ParamList::new()
+        .add_user_param(user.into())
+        .add_param("filter_to_owned_lists", "true");
+    CursorIter::new(links::lists::MEMBERSHIPS, token, Some(params), Some(20))
+}
+
 ///Return up to 100 lists the given user is subscribed to, including those the user made
 ///themselves.
 ///
@@ -32,7 +61,7 @@ pub fn memberships<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIter<
 ///
 ///If the user has more than 100 lists total like this, you'll need to call `ownerships` and
 ///`subscriptions` separately to be able to properly load everything.
-pub async fn list<'id, T: Into<UserID>>(
+pub async fn list<T: Into<UserID>>(
     user: T,
     owned_first: bool,
     token: &auth::Token,
@@ -49,7 +78,7 @@ pub async fn list<'id, T: Into<UserID>>(
 ///Look up the lists the given user is subscribed to, but not ones the user made themselves.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
 pub fn subscriptions<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIter<ListCursor> {
     let params = ParamList::new().add_user_param(user.into());
     CursorIter::new(links::lists::SUBSCRIPTIONS, token, Some(params), Some(20))
@@ -58,15 +87,15 @@ pub fn subscriptions<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIte
 ///Look up the lists created by the given user.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
 pub fn ownerships<T: Into<UserID>>(user: T, token: &auth::Token) -> CursorIter<ListCursor> {
     let params = ParamList::new().add_user_param(user.into());
     CursorIter::new(links::lists::OWNERSHIPS, token, Some(params), Some(20))
 }
 
 ///Look up information for a single list.
-pub async fn show(list: ListID, token: &auth::Token) -> Result<Response<List>> {
-    let params = ParamList::new().add_list_param(list);
+pub async fn show(list: impl Into<ListID>, token: &auth::Token) -> Result<Response<List>> {
+    let params = ParamList::new().add_list_param(list.into());
 
     let req = get(links::lists::SHOW, token, Some(&params));
 
@@ -75,77 +104,111 @@ pub async fn show(list: ListID, token: &auth::Token) -> Result<Response<List>> {
 
 ///Look up the users that have been added to the given list.
 ///
-///This function returns a `Stream` over the users returned by Twitter. This method defaults to
-///reeturning 20 users in a single network call; the maximum is 5000.
-pub fn members(list: ListID, token: &auth::Token) -> CursorIter<UserCursor> {
-    let params = ParamList::new().add_list_param(list);
+///This function returns a `Stream` over the users returned by Twitter, which loads the next page
+///whenever the current one runs out. This method defaults to returning 20 users in a single
+///network call; the maximum is 5000, which can be set with `with_page_size`. Raising the page size
+///is the easiest way to load a large list without running into the rate limit.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::list::{self, ListID};
+///use futures::TryStreamExt;
+///
+///let names = list::members(ListID::from_slug("Scobleizer", "tech-news-people"), &token)
+///    .with_page_size(5000)
+///    .map_ok(|r| r.response.screen_name)
+///    .try_collect::<Vec<_>>()
+///    .await
+///    .unwrap();
+///# }
+///```
+pub fn members(list: impl Into<ListID>, token: &auth::Token) -> CursorIter<UserCursor> {
+    let params = ParamList::new().add_list_param(list.into());
 
     CursorIter::new(links::lists::MEMBERS, token, Some(params), Some(20))
 }
 
 ///Look up the users that have subscribed to the given list.
 ///
-///This function returns a `Stream` over the users returned by Twitter. This method defaults to
-///reeturning 20 users in a single network call; the maximum is 5000.
-pub fn subscribers(list: ListID, token: &auth::Token) -> CursorIter<UserCursor> {
-    let params = ParamList::new().add_list_param(list);
+///This function returns a `Stream` over the users returned by Twitter, which loads the next page
+///whenever the current one runs out. This method defaults to returning 20 users in a single
+///network call; the maximum is 5000, which can be set with `with_page_size`. See `members` for an
+///example.
+pub fn subscribers(list: impl Into<ListID>, token: &auth::Token) -> CursorIter<UserCursor> {
+    let params = ParamList::new().add_list_param(list.into());
 
     CursorIter::new(links::lists::SUBSCRIBERS, token, Some(params), Some(20))
 }
 
 ///Check whether the given user is subscribed to the given list.
-pub async fn is_subscribed<'id, T: Into<UserID>>(
+///
+///To also load the user's information if they are, use `show_subscriber`.
+pub async fn is_subscribed<T: Into<UserID>>(
     user: T,
-    list: ListID,
+    list: impl Into<ListID>,
     token: &auth::Token,
 ) -> Result<Response<bool>> {
-    let params = ParamList::new()
-        .add_list_param(list)
-        .add_user_param(user.into());
-
-    let req = get(links::lists::IS_SUBSCRIBER, token, Some(&params));
+    let resp = show_subscriber(user, list, token).await?;
+    Ok(Response::map(resp, |user| user.is_some()))
+}
 
-    let out = request_with_json_response::<TwitterUser>(req).await;
+///Check whether the given user has been added to the given list.
+///
+///To also load the user's information if they have, use `show_member`.
+pub async fn is_member<T: Into<UserID>>(
+    user: T,
+    list: impl Into<ListID>,
+    token: &auth::Token,
+) -> Result<Response<bool>> {
+    let resp = show_member(user, list, token).await?;
+    Ok(Response::map(resp, |user| user.is_some()))
+}
 
-    match out {
-        Ok(user) => Ok(Response::map(user, |_| true)),
-        Err(TwitterError(headers, terrs)) => {
-            if terrs.errors.iter().any(|e| e.code == 109) {
-                // here's a fun conundrum: since "is not in this list" is returned as an error code,
-                // the rate limit info that would otherwise be part of the response isn't there. the
-                // rate_headers method was factored out specifically for this location, since it's
-                // still there, just accompanying an error response instead of a user.
-                Ok(Response::new(RateLimit::try_from(&headers)?, false))
-            } else {
-                Err(TwitterError(headers, terrs))
-            }
-        }
-        Err(err) => Err(err),
-    }
+///Loads the given user's information if they're subscribed to the given list, or returns `None`
+///if they aren't.
+pub async fn show_subscriber<T: Into<UserID>>(
+    user: T,
+    list: impl Into<ListID>,
+    token: &auth::Token,
+) -> Result<Response<Option<TwitterUser>>> {
+    show_list_user(links::lists::IS_SUBSCRIBER, user.into(), list.into(), token).await
 }
 
-///Check whether the given user has been added to the given list.
-pub async fn is_member<'id, T: Into<UserID>>(
+///Loads the given user's information if they've been added to the given list, or returns `None`
+///if they haven't.
+pub async fn show_member<T: Into<UserID>>(
     user: T,
+    list: impl Into<ListID>,
+    token: &auth::Token,
+) -> Result<Response<Option<TwitterUser>>> {
+    show_list_user(links::lists::IS_MEMBER, user.into(), list.into(), token).await
+}
+
+async fn show_list_user(
+    link: &'static str,
+    user: UserID,
     list: ListID,
     token: &auth::Token,
-) -> Result<Response<bool>> {
-    let params = ParamList::new()
-        .add_list_param(list)
-        .add_user_param(user.into());
+) -> Result<Response<Option<TwitterUser>>> {
+    let params = ParamList::new().add_list_param(list).add_user_param(user);
 
-    let req = get(links::lists::IS_MEMBER, token, Some(&params));
+    let req = get(link, token, Some(&params));
     let out = request_with_json_response::<TwitterUser>(req).await;
 
     match out {
-        Ok(resp) => Ok(Response::map(resp, |_| true)),
+        Ok(resp) => Ok(Response::map(resp, Some)),
         Err(TwitterError(headers, errors)) => {
             if errors.errors.iter().any(|e| e.code == 109) {
                 // here's a fun conundrum: since "is not in this list" is returned as an error code,
                 // the rate limit info that would otherwise be part of the response isn't there. the
                 // rate_headers method was factored out specifically for this location, since it's
                 // still there, just accompanying an error response instead of a user.
-                Ok(Response::new(RateLimit::try_from(&headers)?, false))
+                Ok(Response::new(RateLimit::try_from(&headers)?, None))
             } else {
                 Err(TwitterError(headers, errors))
             }
@@ -157,12 +220,34 @@ pub async fn is_member<'id, T: Into<UserID>>(
 ///Begin navigating the collection of tweets made by the users added to the given list.
 ///
 ///The interface for loading statuses from a list is exactly the same as loading from a personal
-///timeline. see the [`Timeline`] docs for details.
+///timeline, including `older`/`newer` to page by `max_id`/`since_id`, and adapters like
+///`into_stream` and `poll`. See the [`Timeline`] docs for details. If `with_rts` is false,
+///retweets by list members are left out.
 ///
 ///[`Timeline`]: ../tweet/struct.Timeline.html
-pub fn statuses(list: ListID, with_rts: bool, token: &auth::Token) -> tweet::Timeline {
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::list::{self, ListID};
+///
+///let timeline = list::statuses(ListID::from_id(99924643), false, &token).with_page_size(50);
+///let (timeline, feed) = timeline.start().await.unwrap();
+///for tweet in feed.iter() {
+///    println!("<@{}> {}", tweet.user.as_ref().unwrap().screen_name, tweet.text);
+///}
+///
+/////later on, load only the tweets posted since the first page
+///let (timeline, new_tweets) = timeline.newer(None).await.unwrap();
+///# }
+///```
+pub fn statuses(list: impl Into<ListID>, with_rts: bool, token: &auth::Token) -> tweet::Timeline {
     let params = ParamList::new()
-        .add_list_param(list)
+        .add_list_param(list.into())
         .add_param("include_rts", with_rts.to_string());
 
     tweet::Timeline::new(links::lists::STATUSES, Some(params), token)
@@ -173,13 +258,13 @@ pub fn statuses(list: ListID, with_rts: bool, token: &auth::Token) -> tweet::Tim
 ///Note that lists cannot have more than 5000 members.
 ///
 ///Upon success, the future returned by this function yields the freshly-modified list.
-pub async fn add_member<'id, T: Into<UserID>>(
-    list: ListID,
+pub async fn add_member<T: Into<UserID>>(
+    list: impl Into<ListID>,
     user: T,
     token: &auth::Token,
 ) -> Result<Response<List>> {
     let params = ParamList::new()
-        .add_list_param(list)
+        .add_list_param(list.into())
         .add_user_param(user.into());
 
     let req = post(links::lists::ADD, token, Some(&params));
@@ -194,54 +279,37 @@ pub async fn add_member<'id, T: Into<UserID>>(
 ///
 ///[`user::lookup`]: ../user/fn.lookup.html
 ///
-///Note that you cannot add more than 100 members to a list at a time, and that lists in general
-///cannot have more than 5000 members.
+///Twitter only accepts 100 members per call, so if more than that are given, they're added in
+///several calls of up to 100 members each. If one of these calls fails, the error is returned and
+///the remaining members aren't added, though the ones in earlier calls will have been. Lists in
+///general cannot have more than 5000 members.
+///
+///Upon success, the future returned by this function yields the list as of the last call, with
+///all the members added.
 ///
 ///When using this method, take care not to add and remove many members in rapid succession; there
 ///are no guarantees that the result of a `add_member_list` or `remove_member_list` will be
 ///immediately available for a corresponding removal or addition, respectively.
-pub async fn add_member_list<'id, T, I>(
+pub async fn add_member_list<T, I>(
     members: I,
-    list: ListID,
+    list: impl Into<ListID>,
     token: &auth::Token,
 ) -> Result<Response<List>>
 where
     T: Into<UserID>,
     I: IntoIterator<Item = T>,
 {
-    let (id_param, name_param) = multiple_names_param(members);
-    let params = ParamList::new()
-        .add_list_param(list)
-        .add_opt_param(
-            "user_id",
-            if !id_param.is_empty() {
-                Some(id_param)
-            } else {
-                None
-            },
-        )
-        .add_opt_param(
-            "screen_name",
-            if !name_param.is_empty() {
-                Some(name_param)
-            } else {
-                None
-            },
-        );
-
-    let req = post(links::lists::ADD_LIST, token, Some(&params));
-
-    request_with_json_response(req).await
+    member_list_chunks(links::lists::ADD_LIST, members, list.into(), token).await
 }
 
 ///Removes the given user from the given list.
-pub async fn remove_member<'id, T: Into<UserID>>(
-    list: ListID,
+pub async fn remove_member<T: Into<UserID>>(
+    list: impl Into<ListID>,
     user: T,
     token: &auth::Token,
 ) -> Result<Response<List>> {
     let params = ParamList::new()
-        .add_list_param(list)
+        .add_list_param(list.into())
         .add_user_param(user.into());
 
     let req = post(links::lists::REMOVE_MEMBER, token, Some(&params));
@@ -256,12 +324,28 @@ pub async fn remove_member<'id, T: Into<UserID>>(
 ///
 ///[`user::lookup`]: ../user/fn.lookup.html
 ///
-///This method is limited to removing 100 members at a time.
+///Twitter only accepts 100 members per call, so if more than that are given, they're removed in
+///several calls of up to 100 members each, the same way as `add_member_list`.
 ///
 ///When using this method, take care not to add and remove many members in rapid succession; there
 ///are no guarantees that the result of a `add_member_list` or `remove_member_list` will be
 ///immediately available for a corresponding removal or addition, respectively.
 pub async fn remove_member_list<T, I>(
+    members: I,
+    list: impl Into<ListID>,
+    token: &auth::Token,
+) -> Result<Response<List>>
+where
+    T: Into<UserID>,
+    I: IntoIterator<Item = T>,
+{
+    member_list_chunks(links::lists::REMOVE_LIST, members, list.into(), token).await
+}
+
+///Sends the given members to the given bulk add/remove endpoint, 100 at a time, returning the
+///list from the last call.
+async fn member_list_chunks<T, I>(
+    link: &'static str,
     members: I,
     list: ListID,
     token: &auth::Token,
@@ -270,7 +354,25 @@ where
     T: Into<UserID>,
     I: IntoIterator<Item = T>,
 {
-    let (id_param, name_param) = multiple_names_param(members);
+    let members = members.into_iter().map(Into::into).collect::<Vec<UserID>>();
+    let mut chunks = members.chunks(LOOKUP_CHUNK_SIZE);
+
+    let mut resp =
+        member_list_chunk(link, chunks.next().unwrap_or(&[]), list.clone(), token).await?;
+    for chunk in chunks {
+        resp = member_list_chunk(link, chunk, list.clone(), token).await?;
+    }
+
+    Ok(resp)
+}
+
+async fn member_list_chunk(
+    link: &'static str,
+    members: &[UserID],
+    list: ListID,
+    token: &auth::Token,
+) -> Result<Response<List>> {
+    let (id_param, name_param) = multiple_names_param(members.iter().cloned());
     let params = ParamList::new()
         .add_list_param(list)
         .add_opt_param(
@@ -290,7 +392,7 @@ where
             },
         );
 
-    let req = post(links::lists::REMOVE_LIST, token, Some(&params));
+    let req = post(link, token, Some(&params));
 
     request_with_json_response(req).await
 }
@@ -300,14 +402,28 @@ where
 ///The new list is owned by the authenticated user, and its slug can be created with their handle
 ///and the name given to `name`. Twitter places an upper limit on 1000 lists owned by a single
 ///account.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///let list = egg_mode::list::create("rustaceans", true, Some("Rust folks I follow".into()), &token)
+///    .await
+///    .unwrap();
+///println!("created list {}", list.full_name);
+///# }
+///```
 pub async fn create(
-    name: String,
+    name: impl Into<String>,
     public: bool,
     desc: Option<String>,
     token: &auth::Token,
 ) -> Result<Response<List>> {
     let params = ParamList::new()
-        .add_param("name", name)
+        .add_param("name", name.into())
         .add_param("mode", if public { "public" } else { "private" })
         .add_opt_param("description", desc);
 
@@ -319,8 +435,8 @@ pub async fn create(
 ///Deletes the given list.
 ///
 ///The authenticated user must have created the list.
-pub async fn delete(list: ListID, token: &auth::Token) -> Result<Response<List>> {
-    let params = ParamList::new().add_list_param(list);
+pub async fn delete(list: impl Into<ListID>, token: &auth::Token) -> Result<Response<List>> {
+    let params = ParamList::new().add_list_param(list.into());
 
     let req = post(links::lists::DELETE, token, Some(&params));
 
@@ -330,9 +446,31 @@ pub async fn delete(list: ListID, token: &auth::Token) -> Result<Response<List>>
 ///Subscribes the authenticated user to the given list.
 ///
 ///Subscribing to a list is a way to make it available in the "Lists" section of a user's profile
-///without having to create it themselves.
-pub async fn subscribe(list: ListID, token: &auth::Token) -> Result<Response<List>> {
-    let params = ParamList::new().add_list_param(list);
+///without having to create it themselves. Only public lists, and private lists owned by the
+///authenticated user, can be subscribed to.
+///
+///Upon success, the future returned by this function yields the list that was subscribed to.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::list::{self, ListID};
+///
+///let list = list::subscribe(ListID::from_slug("rustlang", "rust-team"), &token)
+///    .await
+///    .unwrap();
+///println!("now following {} ({} members)", list.full_name, list.member_count);
+///
+///// a list that's already been loaded can be passed back in directly
+///list::unsubscribe(&*list, &token).await.unwrap();
+///# }
+///```
+pub async fn subscribe(list: impl Into<ListID>, token: &auth::Token) -> Result<Response<List>> {
+    let params = ParamList::new().add_list_param(list.into());
 
     let req = post(links::lists::SUBSCRIBE, token, Some(&params));
 
@@ -340,8 +478,10 @@ pub async fn subscribe(list: ListID, token: &auth::Token) -> Result<Response<Lis
 }
 
 ///Unsubscribes the authenticated user from the given list.
-pub async fn unsubscribe(list: ListID, token: &auth::Token) -> Result<Response<List>> {
-    let params = ParamList::new().add_list_param(list);
+///
+///Upon success, the future returned by this function yields the list that was unsubscribed from.
+pub async fn unsubscribe(list: impl Into<ListID>, token: &auth::Token) -> Result<Response<List>> {
+    let params = ParamList::new().add_list_param(list.into());
 
     let req = post(links::lists::UNSUBSCRIBE, token, Some(&params));
 
@@ -353,9 +493,9 @@ pub async fn unsubscribe(list: ListID, token: &auth::Token) -> Result<Response<L
 ///This method is exposed using a builder struct. See the [`ListUpdate`] docs for details.
 ///
 ///[`ListUpdate`]: struct.ListUpdate.html
-pub fn update(list: ListID) -> ListUpdate {
+pub fn update(list: impl Into<ListID>) -> ListUpdate {
     ListUpdate {
-        list,
+        list: list.into(),
         name: None,
         public: None,
         desc: None,
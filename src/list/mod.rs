@@ -54,6 +54,7 @@
 //! - `subscribe`/`unsubscribe`
 //! - `add_member`/`remove_member`
 //! - `add_member_list`/`remove_member_list`
+//! - `sync_members`: This adds and removes members so a list matches a given set of accounts
 //!
 //! ### Basic queries
 //!
@@ -61,20 +62,43 @@
 //!
 //! - `ownerships`/`subscriptions`/`list`: Note that `list` will only return the most recent 100
 //!   lists in the `ownerships`/`subscriptions` sets.
-//! - `memberships`
-//! - `members`/`is_member`
-//! - `subscribers`/`is_subscriber`
+//! - `memberships`/`owned_memberships`
+//! - `members`/`is_member`/`show_member`
+//! - `subscribers`/`is_subscribed`/`show_subscriber`
 //! - `show`
 //! - `statuses`
+//!
+//! ### v2 API
+//!
+//! These functions call Twitter's v2 API instead, and return the objects from the [`v2`] module.
+//! They take a `Fields` to request optional fields and related objects.
+//!
+//! - `show_v2`/`statuses_v2`
+//! - `pinned_lists`/`followed_lists`
+//!
+//! [`v2`]: ../v2/index.html
 
 use chrono;
-use serde::Deserialize;
 
 use crate::common::*;
 use crate::{auth, links, user};
 
 mod fun;
+mod sync;
+mod v2;
+
 pub use self::fun::*;
+pub use self::sync::{sync_members, MemberSync};
+pub use self::v2::{followed_lists, pinned_lists, show_v2, statuses_v2};
+
+id_newtype! {
+    /// The numeric ID of a list.
+    ///
+    /// This is a thin wrapper around the `u64` that Twitter uses, so that list IDs can't be
+    /// accidentally passed to functions that expect a user or tweet ID. It converts freely to and
+    /// from `u64`.
+    ListId
+}
 
 /// Convenience enum to refer to a list via its owner and name or via numeric ID.
 ///
@@ -90,6 +114,10 @@ pub use self::fun::*;
 /// have a user/name combo, use `ListID::from_slug` when looking for the list. If you have the
 /// list's ID instead, then you can use `ListID::from_id`.
 ///
+/// Since they can't be mistaken for anything else, a `ListId` or a `List` you've already loaded
+/// can also be converted into a `ListID` with `From`/`Into`. The functions in this module take
+/// anything that converts into a `ListID`, so these can be passed to them directly.
+///
 /// # Example
 ///
 /// ```rust
@@ -104,7 +132,7 @@ pub enum ListID {
     ///Referring via the list's owner and its "slug" or name.
     Slug(user::UserID, CowStr),
     ///Referring via the list's numeric ID.
-    ID(u64),
+    ID(ListId),
 }
 
 impl ListID {
@@ -114,54 +142,70 @@ impl ListID {
     }
 
     ///Make a new `ListID` by supplying its numeric ID.
-    pub fn from_id(list_id: u64) -> ListID {
-        ListID::ID(list_id)
+    pub fn from_id(list_id: impl Into<ListId>) -> ListID {
+        ListID::ID(list_id.into())
     }
 }
 
-/// Represents the metadata for a list.
-///
-/// Because of the myriad ways to reference a list, there are a few seemingly-redundant fields on
-/// here. It's worthwhile to understand all the referential fields:
-///
-/// * `name` is the human-readable name of the list. Notably, this can contain spaces and uppercase
-///   letters.
-/// * `slug` is simply `name` converted to a format that can be put into a URL and used to
-///   reference the list for API calls.
-/// * `full_name` is how you'd link the list as a @mention, in the form `@screen_name/slug`.
-/// * `id` is the numeric ID, which can be used with `ListID::from_id` to make a `ListID` for the
-///   list.
-/// * `uri` is how you assemble a link to the list. Start with `"https://twitter.com"`, concat this
-///   field to the end, and you have a full URL. Note that the field does start with its own slash.
-/// * `user` is a mostly-populated `TwitterUser` corresponding to the creator of the list. If you
-///   combine `user.screen_name` or `user.id` with `slug`, you can send them to `ListID::from_slug`
-///   to make a `ListID` for the list.
-#[derive(Clone, Debug, Deserialize)]
-pub struct List {
-    ///The name of the list.
-    pub name: String,
-    ///The user who created the list.
-    pub user: user::TwitterUser,
-    ///The "slug" of a list, that can be combined with its creator's `UserID` to refer to the list.
-    pub slug: String,
-    ///The numeric ID of the list.
-    pub id: u64,
-    ///The number of accounts "subscribed" to the list, for whom it will appear in their collection
-    ///of available lists.
-    pub subscriber_count: u64,
-    ///The number of accounts added to the list.
-    pub member_count: u64,
-    ///The full name of the list, preceded by `@`, that can be used to link to the list as part of
-    ///a tweet, direct message, or other place on Twitter where @mentions are parsed.
-    pub full_name: String,
-    ///The description of the list, as entered by its creator.
-    pub description: String,
-    ///The full name of the list, preceded by `/`, that can be preceded with `https://twitter.com`
-    ///to create a link to the list.
-    pub uri: String,
-    ///UTC timestamp of when the list was created.
-    #[serde(with = "serde_datetime")]
-    pub created_at: chrono::DateTime<chrono::Utc>,
+impl From<ListId> for ListID {
+    fn from(id: ListId) -> ListID {
+        ListID::ID(id)
+    }
+}
+
+impl From<&List> for ListID {
+    fn from(list: &List) -> ListID {
+        ListID::ID(list.id)
+    }
+}
+
+raw_json! {
+    /// Represents the metadata for a list.
+    ///
+    /// Because of the myriad ways to reference a list, there are a few seemingly-redundant fields
+    /// on here. It's worthwhile to understand all the referential fields:
+    ///
+    /// * `name` is the human-readable name of the list. Notably, this can contain spaces and
+    ///   uppercase letters.
+    /// * `slug` is simply `name` converted to a format that can be put into a URL and used to
+    ///   reference the list for API calls.
+    /// * `full_name` is how you'd link the list as a @mention, in the form `@screen_name/slug`.
+    /// * `id` is the numeric ID, which can be used with `ListID::from_id` to make a `ListID` for
+    ///   the list.
+    /// * `uri` is how you assemble a link to the list. Start with `"https://twitter.com"`, concat
+    ///   this field to the end, and you have a full URL. Note that the field does start with its
+    ///   own slash.
+    /// * `user` is a mostly-populated `TwitterUser` corresponding to the creator of the list. If
+    ///   you combine `user.screen_name` or `user.id` with `slug`, you can send them to
+    ///   `ListID::from_slug` to make a `ListID` for the list.
+    #[derive(Clone, Debug)]
+    pub struct List {
+        ///The name of the list.
+        pub name: String,
+        ///The user who created the list.
+        pub user: user::TwitterUser,
+        ///The "slug" of a list, that can be combined with its creator's `UserID` to refer to the
+        ///list.
+        pub slug: String,
+        ///The numeric ID of the list.
+        pub id: ListId,
+        ///The number of accounts "subscribed" to the list, for whom it will appear in their
+        ///collection of available lists.
+        pub subscriber_count: u64,
+        ///The number of accounts added to the list.
+        pub member_count: u64,
+        ///The full name of the list, preceded by `@`, that can be used to link to the list as part
+        ///of a tweet, direct message, or other place on Twitter where @mentions are parsed.
+        pub full_name: String,
+        ///The description of the list, as entered by its creator.
+        pub description: String,
+        ///The full name of the list, preceded by `/`, that can be preceded with
+        ///`https://twitter.com` to create a link to the list.
+        pub uri: String,
+        ///UTC timestamp of when the list was created.
+        #[serde(with = "serde_datetime")]
+        pub created_at: chrono::DateTime<chrono::Utc>,
+    }
 }
 
 /// Represents a pending update to a list's metadata.
@@ -209,9 +253,9 @@ impl ListUpdate {
     }
 
     ///Updates the description of the list.
-    pub fn desc(self, desc: String) -> ListUpdate {
+    pub fn desc(self, desc: impl Into<String>) -> ListUpdate {
         ListUpdate {
-            desc: Some(desc),
+            desc: Some(desc.into()),
             ..self
         }
     }
@@ -234,8 +278,9 @@ impl ListUpdate {
 
 #[cfg(test)]
 mod tests {
-    use super::List;
+    use super::{List, ListID};
     use crate::common::tests::load_file;
+    use crate::common::ParamList;
 
     #[test]
     fn parse_list_sample() {
@@ -244,4 +289,31 @@ mod tests {
         assert_eq!(list.full_name, "@Scobleizer/all-people-in-spatial-2");
         assert_eq!(list.user.screen_name, "Scobleizer")
     }
+
+    #[cfg(feature = "raw-json")]
+    #[test]
+    fn raw_json() {
+        let content = load_file("sample_payloads/sample-list.json");
+        let list = ::serde_json::from_str::<List>(&content).unwrap();
+        assert_eq!(list.raw().unwrap()["id_str"], "1122308540973010944");
+        assert_eq!(list.user.raw().unwrap()["id_str"], "13348");
+    }
+
+    #[test]
+    fn list_id_params() {
+        let content = load_file("sample_payloads/sample-list.json");
+        let list = ::serde_json::from_str::<List>(&content).unwrap();
+
+        let params = ParamList::new().add_list_param(ListID::from(&list));
+        assert_eq!(
+            params.get("list_id").map(|id| id.as_ref()),
+            Some(list.id.to_string().as_str())
+        );
+        assert!(params.get("slug").is_none());
+
+        let params = ParamList::new().add_list_param(ListID::from_slug("Scobleizer", "tech"));
+        assert_eq!(params.get("owner_screen_name").unwrap(), "Scobleizer");
+        assert_eq!(params.get("slug").unwrap(), "tech");
+        assert!(params.get("list_id").is_none());
+    }
 }
@@ -0,0 +1,224 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{HashMap, HashSet};
+
+use futures::TryStreamExt;
+
+use crate::auth;
+use crate::error::Result;
+use crate::user::{self, UserID, UserId};
+
+use super::{add_member_list, members, remove_member_list, ListID};
+
+/// The changes made to a list by [`sync_members`].
+///
+/// [`sync_members`]: fn.sync_members.html
+#[derive(Debug, Clone)]
+pub struct MemberSync {
+    /// The accounts that were added to the list, as they were given to `sync_members`.
+    pub added: Vec<UserID>,
+    /// The IDs of the accounts that were removed from the list.
+    pub removed: Vec<UserId>,
+}
+
+/// Adds and removes members of the given list so its membership matches the given set of
+/// accounts.
+///
+/// This loads the current members of the list, works out which accounts in `desired` are missing
+/// and which current members aren't in `desired`, then adds and removes them with
+/// `add_member_list` and `remove_member_list`. If the list already matches, nothing is changed.
+/// Accounts in `desired` can be given either by ID or by screen name; screen names are compared
+/// without regard to case, the same way Twitter treats them. If accounts that need to be added are
+/// given both by ID and by screen name, the screen names are looked up with `user::lookup` first,
+/// so an account given both ways is only added once.
+///
+/// Additions are made before removals, and each is split into calls of up to 100 accounts. If one
+/// of these calls fails, the error is returned and the list is left partially synced; since the
+/// diff is recalculated every time, calling `sync_members` again picks up where it left off.
+///
+/// Note that Twitter can take some time to reflect changes to a list's members, so calling this
+/// several times in quick succession may try to make the same changes again.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::list::{self, ListID};
+/// use egg_mode::user::UserID;
+///
+/// let desired: Vec<UserID> = vec!["rustlang".into(), "rustjobs".into(), 1234u64.into()];
+/// let sync = list::sync_members(ListID::from_slug("me", "rust"), &desired, &token)
+///     .await
+///     .unwrap();
+/// println!("added {}, removed {}", sync.added.len(), sync.removed.len());
+/// # }
+/// ```
+pub async fn sync_members(
+    list: impl Into<ListID>,
+    desired: &[UserID],
+    token: &auth::Token,
+) -> Result<MemberSync> {
+    let list = list.into();
+    let current = members(list.clone(), token)
+        .with_page_size(5000)
+        .map_ok(|user| (user.id, user.screen_name.clone()))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut sync = diff_members(&current, desired);
+
+    let names = sync
+        .added
+        .iter()
+        .filter_map(|acct| match acct {
+            UserID::ScreenName(name) => Some(name.clone()),
+            UserID::ID(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if !names.is_empty() && names.len() < sync.added.len() {
+        let resolved = user::lookup(names, token)
+            .await?
+            .response
+            .into_iter()
+            .map(|user| (user.screen_name.to_lowercase(), user.id))
+            .collect::<HashMap<_, _>>();
+        sync.added = dedup_resolved(sync.added, &resolved);
+    }
+
+    if !sync.added.is_empty() {
+        add_member_list(sync.added.iter().cloned(), list.clone(), token).await?;
+    }
+    if !sync.removed.is_empty() {
+        remove_member_list(sync.removed.iter().cloned(), list, token).await?;
+    }
+
+    Ok(sync)
+}
+
+/// Compares the current members of a list, as (ID, screen name) pairs, against the desired set of
+/// accounts.
+fn diff_members(current: &[(UserId, String)], desired: &[UserID]) -> MemberSync {
+    let mut wanted_ids = HashSet::new();
+    let mut wanted_names = HashSet::new();
+    for acct in desired {
+        match acct {
+            UserID::ID(id) => {
+                wanted_ids.insert(*id);
+            }
+            UserID::ScreenName(name) => {
+                wanted_names.insert(name.to_lowercase());
+            }
+        }
+    }
+
+    let is_wanted = |&(id, ref name): &(UserId, String)| {
+        wanted_ids.contains(&id) || wanted_names.contains(&name.to_lowercase())
+    };
+    let removed = current
+        .iter()
+        .filter(|member| !is_wanted(member))
+        .map(|&(id, _)| id)
+        .collect();
+
+    let current_ids = current.iter().map(|&(id, _)| id).collect::<HashSet<_>>();
+    let current_names = current
+        .iter()
+        .map(|(_, name)| name.to_lowercase())
+        .collect::<HashSet<_>>();
+    let mut seen_ids = HashSet::new();
+    let mut seen_names = HashSet::new();
+    let added = desired
+        .iter()
+        .filter(|acct| match acct {
+            UserID::ID(id) => !current_ids.contains(id) && seen_ids.insert(*id),
+            UserID::ScreenName(name) => {
+                let name = name.to_lowercase();
+                !current_names.contains(&name) && seen_names.insert(name)
+            }
+        })
+        .cloned()
+        .collect();
+
+    MemberSync { added, removed }
+}
+
+/// Drops accounts from `added` that turn out to be the same as an earlier one, using the given map
+/// of lowercased screen names to IDs. Screen names missing from the map are kept.
+fn dedup_resolved(added: Vec<UserID>, resolved: &HashMap<String, UserId>) -> Vec<UserID> {
+    let mut seen = HashSet::new();
+    added
+        .into_iter()
+        .filter(|acct| {
+            let id = match acct {
+                UserID::ID(id) => Some(*id),
+                UserID::ScreenName(name) => resolved.get(&name.to_lowercase()).cloned(),
+            };
+            id.map_or(true, |id| seen.insert(id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn member_diff() {
+        let current = vec![
+            (UserId(1), "rustlang".to_string()),
+            (UserId(2), "rustjobs".to_string()),
+            (UserId(3), "old_account".to_string()),
+        ];
+        let desired: Vec<UserID> = vec![
+            1u64.into(),
+            "RustJobs".into(),
+            4u64.into(),
+            "newcomer".into(),
+            "Newcomer".into(),
+        ];
+
+        let sync = diff_members(&current, &desired);
+        assert_eq!(sync.removed, vec![UserId(3)]);
+
+        let added = sync
+            .added
+            .iter()
+            .map(|acct| match acct {
+                UserID::ID(id) => id.to_string(),
+                UserID::ScreenName(name) => name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(added, vec!["4", "newcomer"]);
+
+        let sync = diff_members(&current, &[1u64.into(), 2u64.into(), 3u64.into()]);
+        assert!(sync.added.is_empty());
+        assert!(sync.removed.is_empty());
+    }
+
+    #[test]
+    fn mixed_member_dedup() {
+        let resolved = vec![("newcomer".to_string(), UserId(4))]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let added: Vec<UserID> = vec![
+            4u64.into(),
+            "NewComer".into(),
+            "unknown".into(),
+            5u64.into(),
+        ];
+
+        let added = dedup_resolved(added, &resolved)
+            .iter()
+            .map(|acct| match acct {
+                UserID::ID(id) => id.to_string(),
+                UserID::ScreenName(name) => name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(added, vec!["4", "unknown", "5"]);
+    }
+}
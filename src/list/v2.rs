@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::common::*;
+use crate::error::Result;
+use crate::user::UserId;
+use crate::{auth, links, v2};
+
+use super::ListId;
+
+/// Look up the list with the given ID, using the v2 API.
+///
+/// This is the v2 counterpart to [`show`]. Use `fields` to request optional list fields, and the
+/// `OwnerId` expansion to load the list's owner into the `includes` of the response. If the list
+/// couldn't be loaded, like when it doesn't exist, the errors Twitter sent back are returned as
+/// `Error::TwitterErrorV2`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::v2::{Expansion, Fields, ListField};
+///
+/// let fields = Fields::new()
+///     .list(&[ListField::MemberCount, ListField::OwnerId])
+///     .expand(&[Expansion::OwnerId]);
+/// let list = egg_mode::list::show_v2(1253713667363123200, &fields, &token).await.unwrap();
+/// let owner = list.data.owner_id.and_then(|id| list.includes.user(id));
+/// println!(
+///     "{} has {:?} members and is owned by {:?}",
+///     list.data.name,
+///     list.data.member_count,
+///     owner.map(|u| &u.username)
+/// );
+/// # }
+/// ```
+///
+/// [`show`]: fn.show.html
+pub async fn show_v2(
+    id: impl Into<ListId>,
+    fields: &v2::Fields,
+    token: &auth::Token,
+) -> Result<Response<v2::Single<v2::List>>> {
+    let url = format!("{}/{}", links::lists::V2_LISTS_STEM, id.into());
+    let params = fields.add_params(ParamList::new());
+    let req = get(&url, token, Some(&params));
+    v2::request_single(req).await
+}
+
+/// Load a page of the recent tweets posted by members of the given list, using the v2 API.
+///
+/// This is the v2 counterpart to [`statuses`]. Pages are loaded with the maximum size of 100
+/// tweets. Pass `None` as the `pagination_token` to load the first page, then the
+/// `meta.next_token` of the returned page to load the one after it. Twitter only returns the 800
+/// most recent tweets this way.
+///
+/// [`statuses`]: fn.statuses.html
+pub async fn statuses_v2(
+    id: impl Into<ListId>,
+    fields: &v2::Fields,
+    pagination_token: Option<String>,
+    token: &auth::Token,
+) -> Result<Response<v2::Page<v2::Tweet>>> {
+    let url = format!("{}/{}/tweets", links::lists::V2_LISTS_STEM, id.into());
+    let params = ParamList::new()
+        .add_param("max_results", "100")
+        .add_opt_param("pagination_token", pagination_token);
+    let params = fields.add_params(params);
+    let req = get(&url, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+/// Look up the lists the given user has pinned, using the v2 API.
+///
+/// Pinned lists are shown on the user's Lists page above the rest. Twitter only returns the pinned
+/// lists of the authenticated user, so `user` needs to be the ID of the user the `token` belongs
+/// to.
+pub async fn pinned_lists(
+    user: impl Into<UserId>,
+    fields: &v2::Fields,
+    token: &auth::Token,
+) -> Result<Response<v2::Page<v2::List>>> {
+    let url = format!(
+        "{}/{}/pinned_lists",
+        links::users::V2_USERS_STEM,
+        user.into()
+    );
+    let params = fields.add_params(ParamList::new());
+    let req = get(&url, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+/// Load a page of the lists the given user follows, using the v2 API.
+///
+/// This is the v2 counterpart to [`subscriptions`]. Pages are loaded with the maximum size of 100
+/// lists. Pass `None` as the `pagination_token` to load the first page, then the
+/// `meta.next_token` of the returned page to load the one after it.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::v2::Fields;
+///
+/// let fields = Fields::default();
+/// let mut next = None;
+/// loop {
+///     let page = egg_mode::list::followed_lists(1234, &fields, next, &token)
+///         .await
+///         .unwrap();
+///     for list in &page.data {
+///         println!("{} ({})", list.name, list.id);
+///     }
+///     next = page.response.meta.next_token;
+///     if next.is_none() {
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`subscriptions`]: fn.subscriptions.html
+pub async fn followed_lists(
+    user: impl Into<UserId>,
+    fields: &v2::Fields,
+    pagination_token: Option<String>,
+    token: &auth::Token,
+) -> Result<Response<v2::Page<v2::List>>> {
+    let url = format!(
+        "{}/{}/followed_lists",
+        links::users::V2_USERS_STEM,
+        user.into()
+    );
+    let params = ParamList::new()
+        .add_param("max_results", "100")
+        .add_opt_param("pagination_token", pagination_token);
+    let params = fields.add_params(params);
+    let req = get(&url, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_v2_lists() {
+        let content = load_file("sample_payloads/v2-followed-lists.json");
+        let page = ::serde_json::from_str::<v2::Page<v2::List>>(&content).unwrap();
+
+        assert_eq!(page.data.len(), 2);
+        assert_eq!(page.data[0].id, ListId(1253713667363123200));
+        assert_eq!(page.data[0].name, "Rust folks");
+        assert_eq!(page.data[0].member_count, Some(12));
+        assert_eq!(page.data[0].private, Some(false));
+        assert_eq!(page.data[1].description, None);
+
+        let owner = page.data[0].owner_id.unwrap();
+        assert_eq!(owner, UserId(2244994945));
+        assert_eq!(page.includes.user(owner).unwrap().username, "TwitterDev");
+        assert_eq!(page.meta.result_count, 2);
+        assert_eq!(page.meta.next_token.as_deref(), Some("1710819323648428707"));
+    }
+}
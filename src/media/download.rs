@@ -0,0 +1,174 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use hyper::body::HttpBody;
+use hyper::{Body, Request};
+
+use crate::common::get_response;
+use crate::entities::{MediaEntity, MediaSizes, MediaType};
+use crate::error::{self, Error};
+
+use super::media_types;
+
+/// Returns the URL of the best version of the given media: the largest size of a photo, or the
+/// MP4 variant with the highest bitrate of a video or GIF.
+///
+/// This is the URL that [`download`] loads. It can be used to load the media with another HTTP
+/// client, or to save a link to it. Twitter's media URLs can be loaded without authentication.
+///
+/// Returns `None` if a video or GIF has no MP4 variants.
+///
+/// [`download`]: fn.download.html
+pub fn download_url(media: &MediaEntity) -> Option<String> {
+    match media.media_type {
+        MediaType::Photo => Some(format!(
+            "{}:{}",
+            media.media_url_https,
+            largest_size(&media.sizes)
+        )),
+        MediaType::Video | MediaType::Gif => {
+            let mp4 = media_types::video_mp4();
+            media
+                .video_info
+                .as_ref()?
+                .variants
+                .iter()
+                .filter(|variant| variant.content_type == mp4)
+                .max_by_key(|variant| variant.bitrate.unwrap_or(0))
+                .map(|variant| variant.url.clone())
+        }
+    }
+}
+
+/// Returns the name of the largest size in the given set, as appended to photo URLs.
+fn largest_size(sizes: &MediaSizes) -> &'static str {
+    let all = [
+        ("thumb", &sizes.thumb),
+        ("small", &sizes.small),
+        ("medium", &sizes.medium),
+        ("large", &sizes.large),
+    ];
+    all.iter()
+        .max_by_key(|(_, size)| i64::from(size.w) * i64::from(size.h))
+        .map_or("large", |(name, _)| name)
+}
+
+/// Download the best version of the given media, writing it to the given writer. Returns the
+/// number of bytes written.
+///
+/// The version to download is picked by [`download_url`]: the largest size of a photo, or the MP4
+/// variant with the highest bitrate of a video or GIF. The media is written as it arrives, so even
+/// long videos don't need to be held in memory.
+///
+/// If a video or GIF has no MP4 variants, this returns `Error::MissingValue`, and if the URL it
+/// picks can't be parsed, this returns `Error::InvalidResponse`. If Twitter's media server returns
+/// an error status, this returns `Error::BadStatus`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let token: Token = unimplemented!();
+/// let tweet = egg_mode::tweet::show(1261253754969640960, &token).await?;
+/// let media = tweet.extended_entities.as_ref().map(|e| &e.media[..]).unwrap_or(&[]);
+/// for (ix, entity) in media.iter().enumerate() {
+///     let mut file = Vec::new();
+///     egg_mode::media::download(entity, &mut file).await?;
+///     std::fs::write(format!("media-{}", ix), file)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`download_url`]: fn.download_url.html
+pub async fn download<W: AsyncWrite + Unpin>(
+    media: &MediaEntity,
+    writer: &mut W,
+) -> error::Result<u64> {
+    let url = download_url(media).ok_or(Error::MissingValue("video_info.variants"))?;
+    let req = Request::get(&url).body(Body::empty()).map_err(|_| {
+        Error::InvalidResponse(
+            "media entity has an invalid download URL",
+            Some(url.clone()),
+        )
+    })?;
+    let resp = get_response(req).await?;
+    if !resp.status().is_success() {
+        return Err(Error::BadStatus(resp.status()));
+    }
+
+    let mut body = resp.into_body();
+    let mut written = 0;
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+    }
+    writer.flush().await?;
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+    use crate::entities::VideoVariant;
+    use crate::tweet::Tweet;
+
+    #[test]
+    fn media_download_url() {
+        let content = load_file("sample_payloads/extended_classic_hidden_13761.json");
+        let tweet = ::serde_json::from_str::<Tweet>(&content).unwrap();
+        let mut gif = tweet.extended_entities.unwrap().media.remove(0);
+        assert_eq!(
+            download_url(&gif).as_deref(),
+            Some("https://pbs.twimg.com/tweet_video/ClFejl_VAAAo9Xk.mp4")
+        );
+
+        let mut photo = gif.clone();
+        photo.media_type = MediaType::Photo;
+        assert_eq!(
+            download_url(&photo).as_deref(),
+            Some("https://pbs.twimg.com/tweet_video_thumb/ClFejl_VAAAo9Xk.jpg:large")
+        );
+
+        let variants = &mut gif.video_info.as_mut().unwrap().variants;
+        variants.push(VideoVariant {
+            bitrate: None,
+            content_type: "application/x-mpegURL".parse().unwrap(),
+            url: "https://video.twimg.com/playlist.m3u8".to_string(),
+        });
+        variants.push(VideoVariant {
+            bitrate: Some(832000),
+            content_type: media_types::video_mp4(),
+            url: "https://video.twimg.com/832k.mp4".to_string(),
+        });
+        assert_eq!(
+            download_url(&gif).as_deref(),
+            Some("https://video.twimg.com/832k.mp4")
+        );
+
+        gif.video_info = None;
+        assert_eq!(download_url(&gif), None);
+    }
+
+    #[tokio::test]
+    async fn download_invalid_url() {
+        let content = load_file("sample_payloads/extended_classic_hidden_13761.json");
+        let tweet = ::serde_json::from_str::<Tweet>(&content).unwrap();
+        let mut photo = tweet.extended_entities.unwrap().media.remove(0);
+        photo.media_type = MediaType::Photo;
+        photo.media_url_https = "not a url".to_string();
+
+        let mut file = Vec::new();
+        match download(&photo, &mut file).await {
+            Err(Error::InvalidResponse(_, Some(url))) => assert_eq!(url, "not a url:large"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
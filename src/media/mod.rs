@@ -22,10 +22,17 @@
 //! let tweet = draft.send(&token).await?;
 //! # }
 //! ```
+//!
+//! `upload_media` takes the whole file as a byte buffer. To read the media from a file or other
+//! reader as it uploads, or to change the size of the segments it's sent in, use
+//! [`UploadBuilder`].
+//!
+//! [`upload_media`]: fn.upload_media.html
+//! [`UploadBuilder`]: struct.UploadBuilder.html
 
 use std::time::{Duration, Instant};
 
-use base64;
+use hyper::Method;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
 
@@ -34,6 +41,21 @@ use crate::{auth, error, links};
 
 use mime;
 
+mod download;
+mod resume;
+mod retry;
+mod subtitles;
+mod upload;
+
+pub use self::download::{download, download_url};
+pub use self::resume::{upload_media_resumable, UploadState};
+pub use self::retry::RetryPolicy;
+pub use self::subtitles::{create_subtitles, delete_subtitles, Subtitle};
+pub use self::upload::{UploadBuilder, UploadPhase, UploadProgress};
+
+// divide uploads into 5MB chunks, the largest segment Twitter accepts
+const CHUNK_SIZE: usize = 5 * 1024 * 1024;
+
 /// A collection of convenience functions that return media types accepted by Twitter.
 ///
 /// These are convenience types that can be handed to [`upload_media`] to set the right
@@ -42,7 +64,10 @@ use mime;
 ///
 /// Note that using `image_gif` and `video_mp4` will automatically set the upload's
 /// `media_category` to `tweet_gif` and `tweet_video` respectively, allowing
-/// larger file sizes and extra processing time.
+/// larger file sizes and extra processing time. Likewise, `text_srt` sets the
+/// `media_category` to `subtitles`, for use with [`create_subtitles`].
+///
+/// [`create_subtitles`]: ../fn.create_subtitles.html
 pub mod media_types {
     use mime::{self, Mime};
 
@@ -70,6 +95,11 @@ pub mod media_types {
     pub fn video_mp4() -> Mime {
         "video/mp4".parse().unwrap()
     }
+
+    /// SRT subtitle files.
+    pub fn text_srt() -> Mime {
+        "application/x-subrip".parse().unwrap()
+    }
 }
 
 /// Upload progress info.
@@ -100,6 +130,7 @@ enum RawProgressInfoTag {
 #[derive(Debug, Deserialize)]
 struct RawProgressInfo {
     state: RawProgressInfoTag,
+    #[allow(dead_code)]
     progress_percent: Option<f64>,
     check_after_secs: Option<u64>,
     error: Option<error::MediaError>,
@@ -148,6 +179,18 @@ struct RawMedia {
 /// An opaque type representing a media id.
 pub struct MediaId(pub(crate) String);
 
+impl From<u64> for MediaId {
+    fn from(id: u64) -> MediaId {
+        MediaId(id.to_string())
+    }
+}
+
+impl From<&u64> for MediaId {
+    fn from(id: &u64) -> MediaId {
+        MediaId(id.to_string())
+    }
+}
+
 /// A handle representing uploaded media.
 #[derive(Debug, Clone)]
 pub struct MediaHandle {
@@ -181,65 +224,89 @@ impl MediaHandle {
 }
 
 /// Represents the kind of media that Twitter will accept.
-/// `.to_string()` will return a string suitable for use in API calls
-#[derive(Debug, Copy, Clone, PartialEq, Eq, derive_more::Display)]
-enum MediaCategory {
+///
+/// Twitter processes uploads differently depending on their category, and media can only be
+/// attached where its category allows: `Tweet*` media to tweets, and `Dm*` media to Direct
+/// Messages. Videos and GIFs also need to be uploaded with their category to get Twitter's larger
+/// size limits. By default, uploads use the `Tweet*` category for their media type, as given by
+/// `MediaCategory::from`; use [`UploadBuilder::media_category`] to pick a different one.
+///
+/// `.to_string()` will return a string suitable for use in API calls.
+///
+/// [`UploadBuilder::media_category`]: struct.UploadBuilder.html#method.media_category
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, derive_more::Display)]
+pub enum MediaCategory {
     /// Static image. Four can be attached to a single tweet.
     #[display(fmt = "tweet_image")]
-    Image,
+    TweetImage,
     /// Animated GIF.
     #[display(fmt = "tweet_gif")]
-    Gif,
+    TweetGif,
     /// Video.
     #[display(fmt = "tweet_video")]
-    Video,
+    TweetVideo,
+    /// Static image, to be attached to a Direct Message.
+    #[display(fmt = "dm_image")]
+    DmImage,
+    /// Animated GIF, to be attached to a Direct Message.
+    #[display(fmt = "dm_gif")]
+    DmGif,
+    /// Video, to be attached to a Direct Message.
+    #[display(fmt = "dm_video")]
+    DmVideo,
+    /// SRT subtitles, to be attached to a video.
+    #[display(fmt = "subtitles")]
+    Subtitles,
 }
 
 impl From<&mime::Mime> for MediaCategory {
     fn from(mime: &mime::Mime) -> Self {
         if mime == &media_types::image_gif() {
-            MediaCategory::Gif
+            MediaCategory::TweetGif
         } else if mime == &media_types::video_mp4() {
-            MediaCategory::Video
+            MediaCategory::TweetVideo
+        } else if mime == &media_types::text_srt() {
+            MediaCategory::Subtitles
         } else {
             // fallthrough
-            MediaCategory::Image
+            MediaCategory::TweetImage
         }
     }
 }
 
 impl MediaCategory {
-    fn dm_category(&self) -> &'static str {
+    /// Returns the matching category for media to be attached to a Direct Message.
+    ///
+    /// `Tweet*` categories are converted to their `Dm*` equivalents. `Dm*` categories are returned
+    /// as-is, as is `Subtitles`, which has no DM equivalent.
+    pub fn for_dm(self) -> MediaCategory {
         match self {
-            MediaCategory::Image => "dm_image",
-            MediaCategory::Gif => "dm_gif",
-            MediaCategory::Video => "dm_video",
+            MediaCategory::TweetImage => MediaCategory::DmImage,
+            MediaCategory::TweetGif => MediaCategory::DmGif,
+            MediaCategory::TweetVideo => MediaCategory::DmVideo,
+            other => other,
         }
     }
 }
 
 /// Upload media to the server.
 ///
-/// The upload proceeds in 1MB chunks until completed. After completion,
-/// be sure to check the status of the uploaded media with [`get_status`].
-/// Twitter often needs time to post-process media before it can be attached
-/// to a tweet.
+/// The upload proceeds in 5MB chunks until completed. After completion, Twitter often needs time
+/// to post-process videos and GIFs before they can be attached to a tweet, so this waits for that
+/// processing to finish, as with [`wait_for_processing`].
+///
+/// This is a shortcut for `UploadBuilder::new(data, media_type.clone()).call(token)`; see
+/// [`UploadBuilder`] for more options.
+///
+/// [`UploadBuilder`]: struct.UploadBuilder.html
 pub async fn upload_media(
     data: &[u8],
     media_type: &mime::Mime,
     token: &auth::Token,
 ) -> error::Result<MediaHandle> {
-    let media_category = MediaCategory::from(media_type);
-    let params = ParamList::new()
-        .add_param("command", "INIT")
-        .add_param("total_bytes", data.len().to_string())
-        .add_param("media_type", media_type.to_string())
-        .add_param("media_category", media_category.to_string());
-    let req = post(links::media::UPLOAD, token, Some(&params));
-
-    let media = request_with_json_response::<RawMedia>(req).await?.response;
-
-    finish_upload(media, data, token).await
+    UploadBuilder::new(data, media_type.clone())
+        .call(token)
+        .await
 }
 
 /// Upload media to the server, for use in a Direct Message.
@@ -256,49 +323,134 @@ pub async fn upload_media(
 /// the resulting media can only be attached to messages from the same user. The default (and
 /// recommended) value for `shared` is `false`.
 ///
-/// The upload proceeds in 1MB chunks until completed. After completion, be sure to check the
-/// status of the uploaded media with [`get_status`]. Twitter often needs time to post-process
-/// media before it can be attached to a message.
+/// The upload proceeds in 5MB chunks until completed. After completion, this waits for Twitter to
+/// finish processing the media, as with [`wait_for_processing`].
+///
+/// This is a shortcut for an `UploadBuilder` with the media type's `MediaCategory::for_dm` and
+/// `shared` set; see [`UploadBuilder`] for more options.
+///
+/// [`UploadBuilder`]: struct.UploadBuilder.html
 pub async fn upload_media_for_dm(
     data: &[u8],
     media_type: &mime::Mime,
     shared: bool,
     token: &auth::Token,
 ) -> error::Result<MediaHandle> {
-    let media_category = MediaCategory::from(media_type);
-    let params = ParamList::new()
-        .add_param("command", "INIT")
-        .add_param("total_bytes", data.len().to_string())
-        .add_param("media_type", media_type.to_string())
-        .add_param("media_category", media_category.dm_category())
-        .add_param("shared", shared.to_string());
-    let req = post(links::media::UPLOAD, token, Some(&params));
+    UploadBuilder::new(data, media_type.clone())
+        .media_category(MediaCategory::from(media_type).for_dm())
+        .shared(shared)
+        .call(token)
+        .await
+}
+
+/// Upload an animated GIF to the server, checking it against Twitter's limits first.
+///
+/// Twitter accepts animated GIFs of up to 15MB, with frames up to 1280x1080 pixels, when they're
+/// uploaded with the `tweet_gif` category. This function checks the size of the data and the
+/// dimensions in the GIF's header before starting the upload, returning `Error::InvalidParam` if
+/// the data isn't a GIF or is outside these limits, rather than waiting for Twitter to reject it
+/// after it's been sent. Twitter also limits the number of frames, which isn't checked here.
+///
+/// The GIF is then uploaded in chunks with the `tweet_gif` category, like [`upload_media`] does,
+/// and this waits for Twitter to finish processing it.
+pub async fn upload_gif(data: &[u8], token: &auth::Token) -> error::Result<MediaHandle> {
+    check_gif(data)?;
+    UploadBuilder::new(data, media_types::image_gif())
+        .media_category(MediaCategory::TweetGif)
+        .call(token)
+        .await
+}
+
+const MAX_GIF_BYTES: usize = 15 * 1024 * 1024;
+const MAX_GIF_WIDTH: u16 = 1280;
+const MAX_GIF_HEIGHT: u16 = 1080;
 
-    let media = request_with_json_response::<RawMedia>(req).await?.response;
+/// Checks that the given data is a GIF within Twitter's size limits for animated GIFs.
+fn check_gif(data: &[u8]) -> error::Result<()> {
+    use crate::error::Error::InvalidParam;
 
-    finish_upload(media, data, token).await
+    if data.len() > MAX_GIF_BYTES {
+        return Err(InvalidParam("GIFs must be at most 15MB"));
+    }
+    // the header is the signature "GIF87a" or "GIF89a", then the width and height of the image as
+    // little-endian 16-bit integers
+    if data.len() < 10 || !(data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")) {
+        return Err(InvalidParam("data is not a GIF"));
+    }
+    let width = u16::from_le_bytes([data[6], data[7]]);
+    let height = u16::from_le_bytes([data[8], data[9]]);
+    if width > MAX_GIF_WIDTH || height > MAX_GIF_HEIGHT {
+        return Err(InvalidParam("GIFs must be at most 1280x1080 pixels"));
+    }
+    Ok(())
 }
 
-async fn finish_upload(
-    media: RawMedia,
+/// Sends the APPEND commands for the given data, starting at the given segment, retrying failed
+/// segments according to `retry`. After each segment is sent, `on_append` is called with the
+/// number of segments that have been sent so far.
+async fn append_chunks<F>(
+    media_id: &str,
     data: &[u8],
+    chunk_size: usize,
+    first_segment: usize,
+    retry: &RetryPolicy,
+    mut on_append: F,
     token: &auth::Token,
-) -> error::Result<MediaHandle> {
-    // divide into 1MB chunks
-    for (ix, chunk) in data.chunks(1024 * 1024).enumerate() {
-        let params = ParamList::new()
-            .add_param("command", "APPEND")
-            .add_param("media_id", media.id.clone())
-            .add_param("media_data", base64::encode(chunk))
-            .add_param("segment_index", ix.to_string());
-        let req = post(links::media::UPLOAD, token, Some(&params));
-        // This request has no response (upon success)
-        raw_request(req).await?;
+) -> error::Result<()>
+where
+    F: FnMut(usize) -> error::Result<()>,
+{
+    for (ix, chunk) in data.chunks(chunk_size).enumerate().skip(first_segment) {
+        retry
+            .run(|| append_segment(media_id, ix, chunk, token))
+            .await?;
+        on_append(ix + 1)?;
     }
 
+    Ok(())
+}
+
+/// Sends a single APPEND command with the given segment of the upload.
+///
+/// The segment is sent as raw bytes in a `multipart/form-data` body rather than as a base64
+/// `media_data` parameter, because Twitter's 5MB limit on segments applies to the encoded size.
+async fn append_segment(
+    media_id: &str,
+    segment_index: usize,
+    chunk: &[u8],
+    token: &auth::Token,
+) -> error::Result<()> {
+    let params = ParamList::new()
+        .add_param("command", "APPEND")
+        .add_param("media_id", media_id.to_string())
+        .add_param("segment_index", segment_index.to_string());
+
+    let boundary = format!("egg-mode-{:032x}", rand::random::<u128>());
+    let mut body = Vec::with_capacity(chunk.len() + 256);
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"media\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            boundary
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(chunk);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    let req = auth::raw::RequestBuilder::new(Method::POST, links::media::UPLOAD)
+        .with_query_params(&params)
+        .with_body(body, format!("multipart/form-data; boundary={}", boundary))
+        .request_token(token);
+    // This request has no response (upon success)
+    raw_request(req).await?;
+    Ok(())
+}
+
+async fn finalize_upload(media_id: &str, token: &auth::Token) -> error::Result<MediaHandle> {
     let params = ParamList::new()
         .add_param("command", "FINALIZE")
-        .add_param("media_id", media.id.clone());
+        .add_param("media_id", media_id.to_string());
     let req = post(links::media::UPLOAD, token, Some(&params));
     Ok(request_with_json_response::<RawMedia>(req)
         .await?
@@ -318,17 +470,94 @@ pub async fn get_status(media_id: MediaId, token: &auth::Token) -> error::Result
         .into())
 }
 
+/// Wait for Twitter to finish processing the given media, returning its final status.
+///
+/// Videos and GIFs are processed by Twitter after their upload is finalized, and can't be attached
+/// to a tweet until that processing succeeds. This checks the status of the media with
+/// [`get_status`] until it succeeds, waiting between checks for as long as Twitter asks in its
+/// `check_after_secs` hint. In case Twitter's hint is too optimistic, the wait also doubles after
+/// each check, starting at one second and going up to a minute, and the longer of the two is used.
+///
+/// If the media needs no processing, or has already finished processing, the handle is returned
+/// immediately without contacting Twitter. If processing fails, the failure is returned as
+/// `Error::MediaError`. Uploaded media can only be used until the handle's `expires_at`, so if
+/// processing still isn't done by the time the next check would happen after that, this stops
+/// waiting and returns `Error::MediaExpired`.
+///
+/// `upload_media`, `upload_media_for_dm`, `upload_media_resumable`, and `UploadBuilder` already
+/// call this after finalizing their upload; it's mainly useful to pick up an upload that was
+/// started elsewhere, with a handle from `get_status`.
+pub async fn wait_for_processing(
+    handle: MediaHandle,
+    token: &auth::Token,
+) -> error::Result<MediaHandle> {
+    let mut handle = handle;
+    let deadline = handle.expires_at;
+    let mut backoff = MIN_POLL_DELAY;
+    while let Some(delay) = poll_delay(&handle.progress, backoff, deadline)? {
+        tokio::time::sleep(delay).await;
+        backoff = (backoff * 2).min(MAX_POLL_DELAY);
+        handle = get_status(handle.id, token).await?;
+    }
+    Ok(handle)
+}
+
+const MIN_POLL_DELAY: Duration = Duration::from_secs(1);
+const MAX_POLL_DELAY: Duration = Duration::from_secs(60);
+
+/// Returns how long to wait before checking the status of media with the given progress again, or
+/// `None` if processing is done. Returns `Error::MediaExpired` if the next check would be after the
+/// given deadline.
+fn poll_delay(
+    progress: &Option<ProgressInfo>,
+    backoff: Duration,
+    deadline: Instant,
+) -> error::Result<Option<Duration>> {
+    match progress {
+        None | Some(ProgressInfo::Success) => Ok(None),
+        Some(ProgressInfo::Failed(err)) => Err(err.clone().into()),
+        Some(ProgressInfo::Pending(secs)) | Some(ProgressInfo::InProgress(secs)) => {
+            let delay = Duration::from_secs(*secs).max(backoff);
+            if Instant::now() + delay >= deadline {
+                Err(error::Error::MediaExpired)
+            } else {
+                Ok(Some(delay))
+            }
+        }
+    }
+}
+
 /// Set metadata for a media upload. At the moment the only attribute that may
 /// be set is `alt_text`.
+///
+/// This is the same as [`set_alt_text`].
 pub async fn set_metadata(
     media_id: &MediaId,
     alt_text: &str,
     token: &auth::Token,
 ) -> error::Result<()> {
+    set_alt_text(media_id, alt_text, token).await
+}
+
+/// Set the alt text of the given uploaded media, a description of the media for users of screen
+/// readers and other accessibility tools.
+///
+/// Alt text can be set on images and GIFs, and should be set before the media is attached to a
+/// tweet. Twitter allows up to 1000 characters of alt text; longer text causes this function to
+/// return `Error::InvalidParam` without contacting Twitter. To set the alt text as part of an
+/// upload, use [`UploadBuilder::alt_text`].
+///
+/// [`UploadBuilder::alt_text`]: struct.UploadBuilder.html#method.alt_text
+pub async fn set_alt_text(
+    media_id: &MediaId,
+    text: &str,
+    token: &auth::Token,
+) -> error::Result<()> {
+    check_alt_text(text)?;
     let payload = serde_json::json!({
         "media_id": media_id.0,
         "alt_text": {
-            "text": alt_text
+            "text": text
         }
     });
     let req = post_json(links::media::METADATA, token, payload);
@@ -336,8 +565,20 @@ pub async fn set_metadata(
     Ok(())
 }
 
+fn check_alt_text(text: &str) -> error::Result<()> {
+    if text.chars().count() > 1000 {
+        Err(error::Error::InvalidParam(
+            "alt text must be at most 1000 characters",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
     use super::RawMedia;
     use crate::common::tests::load_file;
 
@@ -364,7 +605,7 @@ mod tests {
 
         match media.progress {
             Some(super::ProgressInfo::Pending(5)) => (),
-            other => assert!(false, "Unexpected value of progress={:?}", other),
+            other => panic!("Unexpected value of progress={:?}", other),
         }
     }
 
@@ -378,7 +619,7 @@ mod tests {
 
         match media.progress {
             Some(super::ProgressInfo::InProgress(10)) => (),
-            other => assert!(false, "Unexpected value of progress={:?}", other),
+            other => panic!("Unexpected value of progress={:?}", other),
         }
     }
 
@@ -399,7 +640,81 @@ mod tests {
                     message: "Unsupported video format".to_string(),
                 }
             ),
-            other => assert!(false, "Unexpected value of progress={:?}", other),
+            other => panic!("Unexpected value of progress={:?}", other),
+        }
+    }
+
+    #[test]
+    fn processing_poll_delay() {
+        let second = Duration::from_secs(1);
+        let later = Instant::now() + Duration::from_secs(3600);
+        let pending = load_media("sample_payloads/media_pending.json");
+        assert_eq!(
+            super::poll_delay(&pending.progress, second, later).unwrap(),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            super::poll_delay(&pending.progress, 8 * second, later).unwrap(),
+            Some(8 * second)
+        );
+        match super::poll_delay(&pending.progress, second, Instant::now() + 2 * second) {
+            Err(crate::error::Error::MediaExpired) => (),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let done = load_media("sample_payloads/media.json");
+        assert_eq!(
+            super::poll_delay(&done.progress, second, Instant::now()).unwrap(),
+            None
+        );
+
+        let failed = load_media("sample_payloads/media_fail.json");
+        match super::poll_delay(&failed.progress, second, later) {
+            Err(crate::error::Error::MediaError(err)) => assert_eq!(err.code, 1),
+            other => panic!("Unexpected result {:?}", other),
         }
     }
+
+    #[test]
+    fn alt_text_length() {
+        assert!(super::check_alt_text("A cat sitting in a cardboard box").is_ok());
+        assert!(super::check_alt_text(&"猫".repeat(1000)).is_ok());
+        assert!(super::check_alt_text(&"a".repeat(1001)).is_err());
+    }
+
+    #[test]
+    fn media_categories() {
+        use super::{media_types, MediaCategory};
+
+        let video = MediaCategory::from(&media_types::video_mp4());
+        assert_eq!(video, MediaCategory::TweetVideo);
+        assert_eq!(video.for_dm().to_string(), "dm_video");
+        assert_eq!(MediaCategory::DmGif.for_dm(), MediaCategory::DmGif);
+        assert_eq!(
+            MediaCategory::from(&media_types::image_webp()).to_string(),
+            "tweet_image"
+        );
+    }
+
+    #[test]
+    fn gif_limits() {
+        use super::check_gif;
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&480u16.to_le_bytes());
+        gif.extend_from_slice(&268u16.to_le_bytes());
+        gif.extend_from_slice(&[0xf7, 0x00, 0x00]);
+        assert!(check_gif(&gif).is_ok());
+
+        let mut wide = gif.clone();
+        wide[6..8].copy_from_slice(&1920u16.to_le_bytes());
+        assert!(check_gif(&wide).is_err());
+
+        assert!(check_gif(b"\x89PNG\r\n\x1a\n\0\0").is_err());
+        assert!(check_gif(&gif[..8]).is_err());
+
+        let mut large = gif;
+        large.resize(15 * 1024 * 1024 + 1, 0);
+        assert!(check_gif(&large).is_err());
+    }
 }
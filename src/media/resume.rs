@@ -0,0 +1,158 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::state::{load_json, save_json, StateStore};
+use crate::{auth, error, links};
+
+use super::{
+    append_chunks, finalize_upload, wait_for_processing, MediaCategory, MediaHandle, RawMedia,
+    RetryPolicy, CHUNK_SIZE,
+};
+
+/// The progress of an interrupted chunked upload, as saved by [`upload_media_resumable`].
+///
+/// [`upload_media_resumable`]: fn.upload_media_resumable.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UploadState {
+    /// The media ID Twitter assigned to the upload when it was started.
+    pub media_id: String,
+    /// The time at which Twitter will discard the upload if it hasn't been finalized.
+    pub expires_at: DateTime<Utc>,
+    /// The total size of the upload, in bytes.
+    pub total_bytes: usize,
+    /// The size of each APPEND segment, in bytes.
+    pub chunk_size: usize,
+    /// The number of segments that have been successfully sent.
+    pub segments_sent: usize,
+}
+
+impl UploadState {
+    /// Returns whether this saved state can be used to continue uploading the given data: that
+    /// is, the data is the same size as when the upload was started, and the media ID hasn't
+    /// expired yet.
+    pub fn can_resume(&self, data: &[u8]) -> bool {
+        self.total_bytes == data.len() && self.chunk_size > 0 && Utc::now() < self.expires_at
+    }
+}
+
+/// Upload media to the server, saving its progress so it can be resumed if interrupted.
+///
+/// This works like [`upload_media`], but after each segment of the upload is sent, its progress
+/// is saved in the given [`StateStore`] under a key derived from `key`, such as the path of the
+/// file being uploaded. If the process crashes or the upload fails partway through, calling this
+/// function again with the same key and data will pick up where the previous attempt left off,
+/// rather than starting over - as long as the media ID from the first attempt hasn't expired. If
+/// the saved upload has expired, or if the data is a different size than before, a fresh upload is
+/// started.
+///
+/// [`upload_media`]: fn.upload_media.html
+/// [`StateStore`]: ../state/trait.StateStore.html
+///
+/// Once the upload has been finalized, its saved state is removed from the store. Like
+/// `upload_media`, this then waits for Twitter to finish processing the media with
+/// [`wait_for_processing`], so the returned `MediaHandle` is ready to be used right away. Failed
+/// segments are retried according to the default [`RetryPolicy`].
+///
+/// [`wait_for_processing`]: fn.wait_for_processing.html
+/// [`RetryPolicy`]: struct.RetryPolicy.html
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::media::{upload_media_resumable, media_types};
+/// use egg_mode::state::FileStore;
+///
+/// let video = std::fs::read("cat.mp4")?;
+/// let store = FileStore::new("bot-state");
+/// let handle =
+///     upload_media_resumable(&video, &media_types::video_mp4(), "cat.mp4", &store, &token)
+///         .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn upload_media_resumable<S: StateStore + ?Sized>(
+    data: &[u8],
+    media_type: &mime::Mime,
+    key: &str,
+    store: &S,
+    token: &auth::Token,
+) -> error::Result<MediaHandle> {
+    let key = format!("media-upload:{}", key);
+    let mut state = match load_json::<UploadState>(store, &key)? {
+        Some(state) if state.can_resume(data) => state,
+        _ => {
+            let media_category = MediaCategory::from(media_type);
+            let params = ParamList::new()
+                .add_param("command", "INIT")
+                .add_param("total_bytes", data.len().to_string())
+                .add_param("media_type", media_type.to_string())
+                .add_param("media_category", media_category.to_string());
+            let req = post(links::media::UPLOAD, token, Some(&params));
+            let media = request_with_json_response::<RawMedia>(req).await?.response;
+
+            let state = UploadState {
+                media_id: media.id,
+                expires_at: Utc::now() + Duration::seconds(media.expires_after as i64),
+                total_bytes: data.len(),
+                chunk_size: CHUNK_SIZE,
+                segments_sent: 0,
+            };
+            save_json(store, &key, &state)?;
+            state
+        }
+    };
+
+    let media_id = state.media_id.clone();
+    append_chunks(
+        &media_id,
+        data,
+        state.chunk_size,
+        state.segments_sent,
+        &RetryPolicy::default(),
+        |sent| {
+            state.segments_sent = sent;
+            Ok(save_json(store, &key, &state)?)
+        },
+        token,
+    )
+    .await?;
+
+    let handle = finalize_upload(&media_id, token).await?;
+    store.remove(&key)?;
+
+    wait_for_processing(handle, token).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_state() {
+        let state = UploadState {
+            media_id: "710511363345354753".to_string(),
+            expires_at: Utc::now() + Duration::hours(1),
+            total_bytes: 3 * CHUNK_SIZE,
+            chunk_size: CHUNK_SIZE,
+            segments_sent: 2,
+        };
+
+        assert!(state.can_resume(&vec![0; 3 * CHUNK_SIZE]));
+        assert!(!state.can_resume(&vec![0; CHUNK_SIZE]));
+
+        let expired = UploadState {
+            expires_at: Utc::now() - Duration::hours(1),
+            ..state
+        };
+        assert!(!expired.can_resume(&vec![0; 3 * CHUNK_SIZE]));
+    }
+}
@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::{self, Error};
+
+/// How many times, and how patiently, to retry a segment of a chunked upload that fails.
+///
+/// Sending a large video means sending many `APPEND` requests, and a temporary network problem
+/// or server error on any one of them would otherwise fail the whole upload. With a retry policy
+/// set through [`UploadBuilder::retry`], a segment that fails this way is sent again after a
+/// delay, without starting the upload over. The delay starts at `initial_delay` and doubles after
+/// each failed attempt, up to `max_delay`.
+///
+/// Only errors that may go away on their own are retried: network errors, and server errors
+/// (with a 5xx status code) from Twitter. Other errors, like rejected credentials or media
+/// that's too large, are returned right away.
+///
+/// The default policy doesn't retry at all.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use egg_mode::media::RetryPolicy;
+///
+/// let retry = RetryPolicy::new(5).initial_delay(Duration::from_millis(500));
+/// ```
+///
+/// [`UploadBuilder::retry`]: struct.UploadBuilder.html#method.retry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that tries each segment up to `max_retries` more times after it first
+    /// fails, waiting one second before the first retry and up to 30 seconds between later ones.
+    pub fn new(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Creates a policy that never retries.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::new(0)
+    }
+
+    /// Sets how long to wait before the first retry.
+    pub fn initial_delay(self, initial_delay: Duration) -> RetryPolicy {
+        RetryPolicy {
+            initial_delay,
+            ..self
+        }
+    }
+
+    /// Sets the longest time to wait between retries.
+    pub fn max_delay(self, max_delay: Duration) -> RetryPolicy {
+        RetryPolicy { max_delay, ..self }
+    }
+
+    /// Returns how long to wait before the given retry, counting from 0, or `None` if the policy
+    /// doesn't allow that many retries.
+    fn delay(&self, retry: u32) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(retry);
+        Some(
+            self.initial_delay
+                .checked_mul(factor)
+                .map_or(self.max_delay, |delay| delay.min(self.max_delay)),
+        )
+    }
+
+    /// Runs the given operation until it succeeds, retrying transient failures according to this
+    /// policy.
+    pub(crate) async fn run<F, Fut, T>(&self, mut op: F) -> error::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = error::Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Err(e) if is_transient(&e) => match self.delay(retry) {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        retry += 1;
+                    }
+                    None => return Err(e),
+                },
+                res => return res,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::none()
+    }
+}
+
+/// Returns whether the given error may go away if the request is sent again.
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::NetError(_) => true,
+        Error::BadStatus(status) => status.is_server_error(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delays() {
+        let policy = RetryPolicy::new(4).max_delay(Duration::from_secs(5));
+        let delays = (0..5).map(|retry| policy.delay(retry)).collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(5)),
+                None,
+            ]
+        );
+        assert_eq!(RetryPolicy::default().delay(0), None);
+    }
+
+    #[tokio::test]
+    async fn retry_transient_errors() {
+        let policy = RetryPolicy::new(2).initial_delay(Duration::from_millis(1));
+
+        let mut attempts = 0;
+        let res = policy
+            .run(|| {
+                attempts += 1;
+                let res = if attempts < 3 {
+                    Err(Error::BadStatus(hyper::StatusCode::BAD_GATEWAY))
+                } else {
+                    Ok(attempts)
+                };
+                async move { res }
+            })
+            .await;
+        assert_eq!(res.unwrap(), 3);
+
+        let mut attempts = 0;
+        let res = policy
+            .run(|| {
+                attempts += 1;
+                async { Err::<(), _>(Error::BadStatus(hyper::StatusCode::FORBIDDEN)) }
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
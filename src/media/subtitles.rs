@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::Serialize;
+
+use crate::common::*;
+use crate::{auth, error, links};
+
+use super::MediaId;
+
+/// A subtitle track to attach to a video with [`create_subtitles`].
+///
+/// [`create_subtitles`]: fn.create_subtitles.html
+#[derive(Debug, Clone)]
+pub struct Subtitle {
+    /// The media ID of the uploaded SRT file.
+    pub media_id: MediaId,
+    /// The BCP 47 language code of the subtitles, like `"en"`.
+    pub language_code: CowStr,
+    /// The name of the subtitles' language, as shown to viewers, like `"English"`.
+    pub display_name: CowStr,
+}
+
+impl Subtitle {
+    /// Creates a new `Subtitle` from the given uploaded SRT file, with the given language code and
+    /// display name.
+    pub fn new(
+        media_id: MediaId,
+        language_code: impl Into<CowStr>,
+        display_name: impl Into<CowStr>,
+    ) -> Subtitle {
+        Subtitle {
+            media_id,
+            language_code: language_code.into(),
+            display_name: display_name.into(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SubtitleRequest<'a> {
+    media_id: &'a str,
+    media_category: &'static str,
+    subtitle_info: SubtitleInfo<'a>,
+}
+
+#[derive(Serialize)]
+struct SubtitleInfo<'a> {
+    subtitles: Vec<SubtitleEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct SubtitleEntry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_id: Option<&'a str>,
+    language_code: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<&'a str>,
+}
+
+/// Attach the given subtitles to an uploaded video.
+///
+/// Subtitles are uploaded like any other media, as an SRT file with
+/// [`media_types::text_srt`], and then attached to the video with this function. Attach them after
+/// the video has finished processing, and before it's attached to a tweet. A video can have one
+/// set of subtitles per language.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::media::{self, media_types, Subtitle};
+///
+/// # let video_bytes: &[u8] = unimplemented!();
+/// # let srt_bytes: &[u8] = unimplemented!();
+/// let video = media::upload_media(video_bytes, &media_types::video_mp4(), &token).await?;
+/// let srt = media::upload_media(srt_bytes, &media_types::text_srt(), &token).await?;
+///
+/// let english = Subtitle::new(srt.id, "en", "English");
+/// media::create_subtitles(&video.id, &[english], &token).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`media_types::text_srt`]: media_types/fn.text_srt.html
+pub async fn create_subtitles(
+    video_id: &MediaId,
+    subtitles: &[Subtitle],
+    token: &auth::Token,
+) -> error::Result<()> {
+    let entries = subtitles
+        .iter()
+        .map(|sub| SubtitleEntry {
+            media_id: Some(&sub.media_id.0),
+            language_code: &sub.language_code,
+            display_name: Some(&sub.display_name),
+        })
+        .collect();
+    send_subtitles(links::media::SUBTITLES_CREATE, video_id, entries, token).await
+}
+
+/// Remove the subtitles in the given languages from an uploaded video.
+///
+/// `language_codes` are the same codes given as `Subtitle::language_code` when the subtitles were
+/// attached with [`create_subtitles`].
+///
+/// [`create_subtitles`]: fn.create_subtitles.html
+pub async fn delete_subtitles(
+    video_id: &MediaId,
+    language_codes: &[&str],
+    token: &auth::Token,
+) -> error::Result<()> {
+    let entries = language_codes
+        .iter()
+        .map(|&language_code| SubtitleEntry {
+            media_id: None,
+            language_code,
+            display_name: None,
+        })
+        .collect();
+    send_subtitles(links::media::SUBTITLES_DELETE, video_id, entries, token).await
+}
+
+async fn send_subtitles(
+    url: &'static str,
+    video_id: &MediaId,
+    subtitles: Vec<SubtitleEntry<'_>>,
+    token: &auth::Token,
+) -> error::Result<()> {
+    let req = post_json(url, token, subtitle_request(video_id, subtitles));
+    // These requests have no response (upon success)
+    raw_request(req).await?;
+    Ok(())
+}
+
+fn subtitle_request<'a>(
+    video_id: &'a MediaId,
+    subtitles: Vec<SubtitleEntry<'a>>,
+) -> SubtitleRequest<'a> {
+    SubtitleRequest {
+        media_id: &video_id.0,
+        // subtitles can only be attached to tweet videos
+        media_category: "TweetVideo",
+        subtitle_info: SubtitleInfo { subtitles },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtitle_payload() {
+        let video = MediaId::from(560049056024465408);
+        let entries = vec![SubtitleEntry {
+            media_id: Some("560049056024465409"),
+            language_code: "en",
+            display_name: Some("English"),
+        }];
+        let json = serde_json::to_value(subtitle_request(&video, entries)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "media_id": "560049056024465408",
+                "media_category": "TweetVideo",
+                "subtitle_info": {
+                    "subtitles": [{
+                        "media_id": "560049056024465409",
+                        "language_code": "en",
+                        "display_name": "English"
+                    }]
+                }
+            })
+        );
+
+        let entries = vec![SubtitleEntry {
+            media_id: None,
+            language_code: "en",
+            display_name: None,
+        }];
+        let json = serde_json::to_value(subtitle_request(&video, entries)).unwrap();
+        assert_eq!(
+            json["subtitle_info"]["subtitles"],
+            serde_json::json!([{ "language_code": "en" }])
+        );
+    }
+}
@@ -0,0 +1,493 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::pin::Pin;
+
+use futures::io::{AsyncRead, AsyncReadExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+use futures::{Stream, TryStreamExt};
+use hyper::body::Bytes;
+
+use crate::common::*;
+use crate::error::{self, Error::InvalidParam};
+use crate::{auth, links, user};
+
+use super::{
+    append_segment, check_alt_text, finalize_upload, set_alt_text, wait_for_processing,
+    MediaCategory, MediaHandle, ProgressInfo, RawMedia, RetryPolicy, CHUNK_SIZE,
+};
+
+/// Where an `UploadBuilder` reads its media from.
+enum UploadSource<'a> {
+    Bytes(Cow<'a, [u8]>),
+    Reader {
+        reader: ChunkReader<'a>,
+        total_bytes: usize,
+    },
+}
+
+/// A reader that an `UploadBuilder` reads one segment at a time.
+enum ChunkReader<'a> {
+    Sync(Box<dyn Read + Send + 'a>),
+    Async(Pin<Box<dyn AsyncRead + Send + 'a>>),
+}
+
+impl ChunkReader<'_> {
+    /// Fills the given buffer from the reader, failing if the reader ends before the buffer is
+    /// full.
+    async fn read_chunk(&mut self, buf: &mut [u8]) -> error::Result<()> {
+        let res = match self {
+            ChunkReader::Sync(reader) => reader.read_exact(buf),
+            ChunkReader::Async(reader) => reader.read_exact(buf).await,
+        };
+        match res {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Err(InvalidParam(
+                "the reader ended before total_bytes were read",
+            )),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Represents a chunked media upload before it is started.
+///
+/// Twitter accepts media in pieces: an `INIT` command announces the size and type of the upload,
+/// a series of `APPEND` commands send the media in segments of up to 5MB, and a `FINALIZE`
+/// command completes the upload and returns the media ID to attach to a tweet. This is the only
+/// way to upload videos and large GIFs, since they're too large to send in one request.
+/// `UploadBuilder` runs this whole process for you.
+///
+/// The media can be given as a byte buffer with `new`, as a reader with `from_reader` or
+/// `from_async_reader`, or as a stream of bytes with `from_stream`. Readers and streams are read
+/// one segment at a time, so only one segment needs to be held in memory at once, no matter how
+/// large the upload is. Note that reads from a `std::io::Read` block the thread they run on, so
+/// prefer `from_async_reader` or `from_stream` for readers that may wait a long time, like network
+/// sockets.
+///
+/// After the upload is finalized, Twitter may still need to process videos and GIFs before they
+/// can be attached to a tweet. `call` waits for this processing to finish, as with
+/// [`wait_for_processing`], so the returned `MediaHandle` is ready to be used right away.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::media::{media_types, UploadBuilder};
+/// use egg_mode::tweet::DraftTweet;
+///
+/// let file = std::fs::File::open("cat.mp4")?;
+/// let size = file.metadata()?.len() as usize;
+/// let handle = UploadBuilder::from_reader(file, size, media_types::video_mp4())
+///     .call(&token)
+///     .await?;
+///
+/// let draft = DraftTweet::new("Look at this cat go!").media_ids(vec![handle.id]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`wait_for_processing`]: fn.wait_for_processing.html
+#[must_use = "UploadBuilder is lazy and won't do anything unless `call`ed"]
+pub struct UploadBuilder<'a> {
+    source: UploadSource<'a>,
+    media_type: mime::Mime,
+    media_category: Option<MediaCategory>,
+    shared: Option<bool>,
+    additional_owners: Vec<user::UserId>,
+    chunk_size: usize,
+    concurrency: usize,
+    retry: RetryPolicy,
+    alt_text: Option<CowStr>,
+    on_progress: Option<Box<dyn FnMut(UploadProgress) + Send + 'a>>,
+}
+
+impl<'a> UploadBuilder<'a> {
+    fn with_source(source: UploadSource<'a>, media_type: mime::Mime) -> Self {
+        UploadBuilder {
+            source,
+            media_type,
+            media_category: None,
+            shared: None,
+            additional_owners: vec![],
+            chunk_size: CHUNK_SIZE,
+            concurrency: 1,
+            retry: RetryPolicy::default(),
+            alt_text: None,
+            on_progress: None,
+        }
+    }
+
+    /// Begin setting up an upload of the given bytes, with the given media type.
+    pub fn new(data: impl Into<Cow<'a, [u8]>>, media_type: mime::Mime) -> Self {
+        UploadBuilder::with_source(UploadSource::Bytes(data.into()), media_type)
+    }
+
+    /// Begin setting up an upload of `total_bytes` read from the given reader, with the given
+    /// media type.
+    ///
+    /// Twitter needs to know the size of the upload before it starts, so `total_bytes` needs to be
+    /// exact. If the reader ends early, the upload fails with `Error::InvalidParam`; if it has
+    /// more data than that, the rest is left unread.
+    pub fn from_reader(
+        reader: impl Read + Send + 'a,
+        total_bytes: usize,
+        media_type: mime::Mime,
+    ) -> Self {
+        let reader = ChunkReader::Sync(Box::new(reader));
+        UploadBuilder::with_source(
+            UploadSource::Reader {
+                reader,
+                total_bytes,
+            },
+            media_type,
+        )
+    }
+
+    /// Begin setting up an upload of `total_bytes` read from the given async reader, with the
+    /// given media type.
+    ///
+    /// This works like `from_reader`, except that the reader is read without blocking, which
+    /// makes it suitable for files opened with an async runtime's file API (through a
+    /// compatibility wrapper, if the runtime uses its own `AsyncRead` trait) or other sources of
+    /// data that arrive over time.
+    pub fn from_async_reader(
+        reader: impl AsyncRead + Send + 'a,
+        total_bytes: usize,
+        media_type: mime::Mime,
+    ) -> Self {
+        let reader = ChunkReader::Async(Box::pin(reader));
+        UploadBuilder::with_source(
+            UploadSource::Reader {
+                reader,
+                total_bytes,
+            },
+            media_type,
+        )
+    }
+
+    /// Begin setting up an upload of `total_bytes` taken from the given stream of bytes, with the
+    /// given media type.
+    ///
+    /// The stream's pieces don't need to line up with the upload's segments; they're combined or
+    /// split as needed. This means a `hyper::Body` can be passed in directly, to forward media
+    /// from another server without holding all of it in memory. As with `from_reader`,
+    /// `total_bytes` needs to be exact. An error from the stream stops the upload and is returned
+    /// as `Error::IOError`.
+    pub fn from_stream<S, E>(stream: S, total_bytes: usize, media_type: mime::Mime) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'a,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let reader =
+            Box::pin(stream.map_err(|e| io::Error::new(io::ErrorKind::Other, e))).into_async_read();
+        UploadBuilder::from_async_reader(reader, total_bytes, media_type)
+    }
+
+    /// Set the category of the media, which determines how Twitter processes it and where it can
+    /// be attached.
+    ///
+    /// By default, the `Tweet*` category for the media type is used, as given by
+    /// `MediaCategory::from`. Media to be attached to a Direct Message needs one of the `Dm*`
+    /// categories instead, which `MediaCategory::for_dm` can pick.
+    pub fn media_category(self, media_category: MediaCategory) -> Self {
+        UploadBuilder {
+            media_category: Some(media_category),
+            ..self
+        }
+    }
+
+    /// Set whether media uploaded for a Direct Message can be attached to more than one message.
+    ///
+    /// This only applies to the `Dm*` media categories. Shared media can be viewed by anyone with
+    /// its URL, so Twitter requires you to tell the user this and get their permission before
+    /// setting it. See [`upload_media_for_dm`] for details.
+    ///
+    /// [`upload_media_for_dm`]: fn.upload_media_for_dm.html
+    pub fn shared(self, shared: bool) -> Self {
+        UploadBuilder {
+            shared: Some(shared),
+            ..self
+        }
+    }
+
+    /// Allow the given users to attach the media to their own tweets, along with the user who
+    /// uploads it.
+    ///
+    /// This lets one account upload media on behalf of others, for example in a publishing tool
+    /// that prepares media before the tweet is posted from another account. Twitter allows up to
+    /// 100 additional owners; more than that causes `call` to return `Error::InvalidParam` without
+    /// contacting Twitter. Calling this again replaces the list of additional owners.
+    pub fn additional_owners<I, T>(self, user_ids: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<user::UserId>,
+    {
+        UploadBuilder {
+            additional_owners: user_ids.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Set the size of each segment sent with `APPEND`. The default, and maximum, is 5MB.
+    ///
+    /// Smaller segments mean less data is lost when a request fails, at the cost of more
+    /// requests. A size of 0 or more than 5MB causes `call` to return `Error::InvalidParam`
+    /// without contacting Twitter.
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        UploadBuilder { chunk_size, ..self }
+    }
+
+    /// Set how many segments can be sent at once. The default is 1, which sends each segment after
+    /// the previous one has finished.
+    ///
+    /// Sending several segments at once can make large uploads finish much sooner, especially
+    /// over connections with high latency. When uploading from a reader, each segment in flight
+    /// needs its own buffer, so up to `concurrency` segments are held in memory at once. A value
+    /// of 0 causes `call` to return `Error::InvalidParam` without contacting Twitter.
+    ///
+    /// When more than one segment is sent at once, the `bytes_sent` reported to `on_progress`
+    /// counts the segments that have finished, which may not be the first ones in the media.
+    pub fn concurrency(self, concurrency: usize) -> Self {
+        UploadBuilder {
+            concurrency,
+            ..self
+        }
+    }
+
+    /// Set how to retry segments of the upload that fail because of a network problem or server
+    /// error, as described in [`RetryPolicy`]. By default, failed segments aren't retried.
+    ///
+    /// Retries pick up from the failed segment, so the segments that were already sent don't need
+    /// to be sent again.
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub fn retry(self, retry: RetryPolicy) -> Self {
+        UploadBuilder { retry, ..self }
+    }
+
+    /// Set the alt text of the media once it's uploaded, as with [`set_alt_text`].
+    ///
+    /// The length of the text is checked before the upload begins, so text that's too long causes
+    /// `call` to return `Error::InvalidParam` without contacting Twitter.
+    ///
+    /// [`set_alt_text`]: fn.set_alt_text.html
+    pub fn alt_text(self, text: impl Into<CowStr>) -> Self {
+        UploadBuilder {
+            alt_text: Some(text.into()),
+            ..self
+        }
+    }
+
+    /// Set a function to be called as the upload progresses, to display a progress bar or
+    /// similar.
+    ///
+    /// The function is called with an `UploadProgress` when the upload begins, after each segment
+    /// is sent, when the upload is finalized, and when Twitter starts processing the media, if it
+    /// needs to.
+    pub fn on_progress(self, on_progress: impl FnMut(UploadProgress) + Send + 'a) -> Self {
+        UploadBuilder {
+            on_progress: Some(Box::new(on_progress)),
+            ..self
+        }
+    }
+
+    /// Upload the media, returning a `MediaHandle` for it once Twitter has finished processing it.
+    ///
+    /// If Twitter fails to process the media, this returns `Error::MediaError` with the reason.
+    pub async fn call(self, token: &auth::Token) -> error::Result<MediaHandle> {
+        if self.chunk_size == 0 || self.chunk_size > CHUNK_SIZE {
+            return Err(InvalidParam("chunk_size must be between 1 byte and 5MB"));
+        }
+        if self.concurrency == 0 {
+            return Err(InvalidParam("concurrency must be at least 1"));
+        }
+        if let Some(ref text) = self.alt_text {
+            check_alt_text(text)?;
+        }
+        if self.additional_owners.len() > 100 {
+            return Err(InvalidParam("at most 100 additional_owners are allowed"));
+        }
+
+        let total_bytes = match self.source {
+            UploadSource::Bytes(ref data) => data.len(),
+            UploadSource::Reader { total_bytes, .. } => total_bytes,
+        };
+        let chunk_size = self.chunk_size;
+        let concurrency = self.concurrency;
+        let retry = self.retry;
+        let mut on_progress = self.on_progress;
+        let mut report = move |phase, bytes_sent| {
+            if let Some(ref mut on_progress) = on_progress {
+                on_progress(UploadProgress {
+                    phase,
+                    bytes_sent,
+                    total_bytes,
+                });
+            }
+        };
+
+        report(UploadPhase::Init, 0);
+        let media_type = &self.media_type;
+        let media_category = self
+            .media_category
+            .unwrap_or_else(|| MediaCategory::from(media_type));
+        let params = ParamList::new()
+            .add_param("command", "INIT")
+            .add_param("total_bytes", total_bytes.to_string())
+            .add_param("media_type", self.media_type.to_string())
+            .add_param("media_category", media_category.to_string())
+            .add_opt_param("shared", self.shared.map_string())
+            .add_opt_param("additional_owners", owner_list(&self.additional_owners));
+        let req = post(links::media::UPLOAD, token, Some(&params));
+        let media = request_with_json_response::<RawMedia>(req).await?.response;
+
+        // a buffer is sliced into segments directly, while a reader is read into a new buffer for
+        // each segment, so several can be in flight at once
+        enum Segments<'s, 'a> {
+            Slice(&'s [u8]),
+            Read(&'s mut ChunkReader<'a>),
+        }
+        let mut source = self.source;
+        let mut segments = match source {
+            UploadSource::Bytes(ref data) => Segments::Slice(data),
+            UploadSource::Reader { ref mut reader, .. } => Segments::Read(reader),
+        };
+        let mut in_flight = FuturesUnordered::new();
+        let mut offset = 0;
+        let mut segment_index = 0;
+        let mut bytes_sent = 0;
+        while offset < total_bytes {
+            let len = chunk_size.min(total_bytes - offset);
+            let chunk = match segments {
+                Segments::Slice(data) => Cow::Borrowed(&data[offset..offset + len]),
+                Segments::Read(ref mut reader) => {
+                    let mut buf = vec![0; len];
+                    reader.read_chunk(&mut buf).await?;
+                    Cow::Owned(buf)
+                }
+            };
+            offset += len;
+
+            let (media_id, retry) = (&media.id, &retry);
+            in_flight.push(async move {
+                retry
+                    .run(|| append_segment(media_id, segment_index, &chunk, token))
+                    .await
+                    .map(|()| chunk.len())
+            });
+            segment_index += 1;
+
+            if in_flight.len() >= concurrency {
+                if let Some(sent) = in_flight.next().await {
+                    bytes_sent += sent?;
+                    report(UploadPhase::Append, bytes_sent);
+                }
+            }
+        }
+        while let Some(sent) = in_flight.next().await {
+            bytes_sent += sent?;
+            report(UploadPhase::Append, bytes_sent);
+        }
+
+        report(UploadPhase::Finalize, total_bytes);
+        let handle = finalize_upload(&media.id, token).await?;
+        if matches!(
+            handle.progress,
+            Some(ProgressInfo::Pending(_)) | Some(ProgressInfo::InProgress(_))
+        ) {
+            report(UploadPhase::Processing, total_bytes);
+        }
+        let handle = wait_for_processing(handle, token).await?;
+        if let Some(text) = self.alt_text {
+            set_alt_text(&handle.id, &text, token).await?;
+        }
+        Ok(handle)
+    }
+}
+
+/// Formats the given user IDs as a comma-separated list, or `None` if there are none.
+fn owner_list(user_ids: &[user::UserId]) -> Option<String> {
+    if user_ids.is_empty() {
+        None
+    } else {
+        let ids = user_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        Some(ids.join(","))
+    }
+}
+
+/// The steps of an upload, as reported to `UploadBuilder::on_progress`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UploadPhase {
+    /// The upload is being started with an `INIT` command.
+    Init,
+    /// The media is being sent in segments with `APPEND` commands.
+    Append,
+    /// All the media has been sent, and the upload is being completed with a `FINALIZE` command.
+    Finalize,
+    /// Twitter is processing the media before it can be attached to a tweet.
+    Processing,
+}
+
+/// The progress of an upload, as reported to `UploadBuilder::on_progress`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// The step the upload is on.
+    pub phase: UploadPhase,
+    /// The number of bytes of the media that have been sent so far.
+    pub bytes_sent: usize,
+    /// The size of the media, in bytes.
+    pub total_bytes: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_segments() {
+        futures::executor::block_on(async {
+            let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+            let pieces = data
+                .chunks(300)
+                .map(|piece| Ok::<_, io::Error>(Bytes::copy_from_slice(piece)))
+                .collect::<Vec<_>>();
+
+            let readers = vec![
+                ChunkReader::Sync(Box::new(&data[..])),
+                ChunkReader::Async(Box::pin(&data[..])),
+                ChunkReader::Async(Box::pin(futures::stream::iter(pieces).into_async_read())),
+            ];
+            for mut reader in readers {
+                let mut first = [0; 600];
+                reader.read_chunk(&mut first).await.unwrap();
+                assert_eq!(&first[..], &data[..600]);
+
+                let mut second = [0; 400];
+                reader.read_chunk(&mut second).await.unwrap();
+                assert_eq!(&second[..], &data[600..]);
+
+                let mut past_end = [0; 1];
+                match reader.read_chunk(&mut past_end).await {
+                    Err(InvalidParam(_)) => (),
+                    other => panic!("Unexpected result {:?}", other),
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn additional_owner_list() {
+        assert_eq!(owner_list(&[]), None);
+        assert_eq!(
+            owner_list(&[user::UserId(783214), user::UserId(6253282)]).as_deref(),
+            Some("783214,6253282")
+        );
+    }
+}
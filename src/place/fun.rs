@@ -64,11 +64,16 @@ fn parse_url(base: &'static str, full: &str) -> Result<ParamList> {
     }
 
     if let Some(list) = iter.next() {
+        // the parameters will be encoded again when the request is signed, so decode them here
+        // to avoid encoding them twice
         list.split('&').try_fold(ParamList::new(), |p, pair| {
-            let mut kv_iter = pair.split('=');
-            let k = kv_iter.next().ok_or(Error::BadUrl)?;
-            let v = kv_iter.next().ok_or(Error::BadUrl)?;
-            Ok(p.add_param(k.to_string(), v.to_string()))
+            if !pair.contains('=') {
+                return Err(Error::BadUrl);
+            }
+            let (k, v) = url::form_urlencoded::parse(pair.as_bytes())
+                .next()
+                .ok_or(Error::BadUrl)?;
+            Ok(p.add_param(k.into_owned(), v.into_owned()))
         })
     } else {
         Err(Error::BadUrl)
@@ -146,6 +151,29 @@ pub fn search_ip(query: impl Into<CowStr>) -> SearchBuilder {
 ///the given URL is not a valid `search` query URL.
 pub async fn search_url(url: &str, token: &auth::Token) -> Result<Response<SearchResult>> {
     let params = parse_url(links::place::SEARCH, url)?;
-    let req = get(links::place::REVERSE_GEOCODE, token, Some(&params));
+    let req = get(links::place::SEARCH, token, Some(&params));
     request_with_json_response(req).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_search_url() {
+        let url = format!(
+            "{}?query=San%20Francisco&granularity=city&attribute%3Astreet_address=123+Main+St",
+            links::place::SEARCH
+        );
+        let params = parse_url(links::place::SEARCH, &url).unwrap();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params["query"], "San Francisco");
+        assert_eq!(params["granularity"], "city");
+        assert_eq!(params["attribute:street_address"], "123 Main St");
+
+        assert!(parse_url(links::place::REVERSE_GEOCODE, &url).is_err());
+        assert!(parse_url(links::place::SEARCH, links::place::SEARCH).is_err());
+        let missing_value = format!("{}?query", links::place::SEARCH);
+        assert!(parse_url(links::place::SEARCH, &missing_value).is_err());
+    }
+}
@@ -41,31 +41,39 @@ mod fun;
 pub use self::fun::*;
 
 // https://developer.twitter.com/en/docs/tweets/data-dictionary/overview/geo-objects#place
-///Represents a named location.
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Place {
-    ///Alphanumeric ID of the location.
-    pub id: String,
-    ///Map of miscellaneous information about this place. See [Twitter's documentation][attrib] for
-    ///details and common attribute keys.
-    ///
-    ///[attrib]: https://developer.twitter.com/en/docs/tweets/data-dictionary/overview/geo-objects#place
-    pub attributes: HashMap<String, String>,
-    ///A bounding box of latitude/longitude coordinates that encloses this place.
-    #[serde(with = "serde_bounding_box")]
-    pub bounding_box: Vec<(f64, f64)>,
-    ///Name of the country containing this place.
-    pub country: String,
-    ///Shortened country code representing the country containing this place.
-    pub country_code: String,
-    ///Full human-readable name of this place.
-    pub full_name: String,
-    ///Short human-readable name of this place.
-    pub name: String,
-    ///The type of location represented by this place.
-    pub place_type: PlaceType,
-    ///If present, the country or administrative region that contains this place.
-    pub contained_within: Option<Vec<Place>>,
+raw_json! {
+    ///Represents a named location.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct Place {
+        ///Alphanumeric ID of the location.
+        pub id: String,
+        ///Map of miscellaneous information about this place. See [Twitter's documentation][attrib]
+        ///for details and common attribute keys.
+        ///
+        ///[attrib]: https://developer.twitter.com/en/docs/tweets/data-dictionary/overview/geo-objects#place
+        pub attributes: HashMap<String, String>,
+        ///A bounding box of latitude/longitude coordinates that encloses this place.
+        #[serde(with = "serde_bounding_box")]
+        pub bounding_box: Vec<(f64, f64)>,
+        ///If present, the center of this place, as a longitude/latitude coordinate.
+        ///
+        ///This is included when loading a place with `show`, but not in the places attached to
+        ///tweets.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub centroid: Option<(f64, f64)>,
+        ///Name of the country containing this place.
+        pub country: String,
+        ///Shortened country code representing the country containing this place.
+        pub country_code: String,
+        ///Full human-readable name of this place.
+        pub full_name: String,
+        ///Short human-readable name of this place.
+        pub name: String,
+        ///The type of location represented by this place.
+        pub place_type: PlaceType,
+        ///If present, the country or administrative region that contains this place.
+        pub contained_within: Option<Vec<Place>>,
+    }
 }
 
 ///Represents the type of region represented by a given place.
@@ -115,6 +123,7 @@ impl<'de> Deserialize<'de> for SearchResult {
         let url = raw
             .get("query")
             .and_then(|obj| obj.get("url"))
+            .and_then(|url| url.as_str())
             .ok_or_else(|| D::Error::custom("Malformed search result"))?
             .to_string();
         let results = raw
@@ -412,4 +421,52 @@ mod tests {
         let serialized_value = ::serde_json::to_value(&bounding_box).unwrap();
         assert_eq!(raw_value, serialized_value);
     }
+
+    #[test]
+    fn parse_reverse_geocode() {
+        let content = load_file("sample_payloads/reverse_geocode.json");
+        let result = ::serde_json::from_str::<SearchResult>(&content).unwrap();
+
+        // the URL needs to come through without its JSON quotes, to be handed back to
+        // `reverse_geocode_url`
+        assert!(result.url.starts_with(links::place::REVERSE_GEOCODE));
+        assert_eq!(result.results.len(), 1);
+
+        let place = &result.results[0];
+        assert_eq!(place.id, "5a110d312052166f");
+        assert_eq!(place.full_name, "San Francisco, CA");
+        assert!(matches!(place.place_type, PlaceType::City));
+        assert_eq!(place.bounding_box.len(), 4);
+
+        let parents = place.contained_within.as_ref().unwrap();
+        assert_eq!(parents[0].full_name, "California, US");
+        assert!(matches!(parents[0].place_type, PlaceType::Admin));
+    }
+
+    #[test]
+    fn parse_place_details() {
+        let content = load_file("sample_payloads/place_show.json");
+        let place = ::serde_json::from_str::<Place>(&content).unwrap();
+
+        assert_eq!(place.id, "5a110d312052166f");
+        assert_eq!(place.attributes["geotagCount"], "6");
+        assert_eq!(place.bounding_box.len(), 4);
+        assert_eq!(place.centroid, Some((-122.4461400159226, 37.759829)));
+
+        let parents = place.contained_within.as_ref().unwrap();
+        assert_eq!(parents[0].id, "fbd6d2f5a4e4a15e");
+        assert_eq!(parents[0].centroid, Some((-119.3541771311184, 37.2552185)));
+        assert!(parents[0].contained_within.is_none());
+    }
+
+    #[cfg(feature = "raw-json")]
+    #[test]
+    fn raw_json() {
+        let content = load_file("sample_payloads/place_show.json");
+        let place = ::serde_json::from_str::<Place>(&content).unwrap();
+
+        assert_eq!(place.raw().unwrap()["attributes"]["geotagCount"], "6");
+        let parents = place.contained_within.as_ref().unwrap();
+        assert_eq!(parents[0].raw().unwrap()["id"], "fbd6d2f5a4e4a15e");
+    }
 }
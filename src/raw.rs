@@ -23,7 +23,7 @@
 //! [`Token`]: ../auth/enum.Token.html
 //! [`ParamList`]: struct.ParamList.html
 //!
-//! There are three basic request functions, based on how the endpoint expects to be called:
+//! There are a few basic request functions, based on how the endpoint expects to be called:
 //!
 //! * `request_get` assembles a GET request, with the given parameters appended to the URL as a
 //!   query string. All GET endpoints that egg-mode currently wraps use this function to encode and
@@ -35,8 +35,11 @@
 //!   takes arbitrary data and formats it in the POST body as JSON. The provided data is *not* used
 //!   as part of the OAuth signature. At time of writing (between releases 0.14 and 0.15) the only
 //!   egg-mode endpoint that uses this function is [`media::set_metadata`].
+//! * `request_put_json` is the same as `request_post_json`, but assembles a PUT request instead.
+//!   This is used by [`tweet::hide_reply`].
 //!
 //! [`media::set_metadata`]: ../media/fn.set_metadata.html
+//! [`tweet::hide_reply`]: ../tweet/fn.hide_reply.html
 //!
 //! Once you have a `Request`, you can hand it to the `response_*` functions in this module to
 //! process it. Which one you select depends on how much processing you want egg-mode to do with
@@ -85,6 +88,7 @@ pub use crate::auth::raw::delete as request_delete;
 pub use crate::auth::raw::get as request_get;
 pub use crate::auth::raw::post as request_post;
 pub use crate::auth::raw::post_json as request_post_json;
+pub use crate::auth::raw::put_json as request_put_json;
 
 /// Assemble a GET request and convert it to a `Timeline` of tweets.
 ///
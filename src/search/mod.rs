@@ -0,0 +1,521 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and methods for searching for tweets.
+//!
+//! Since there are several optional parameters for searches, egg-mode handles it with a builder
+//! pattern. To begin, call `search` with your requested search term. Additional parameters can be
+//! added onto the `SearchBuilder` struct that is returned. When you're ready to load the first
+//! page of results, hand your tokens to `call`.
+//!
+//! ```rust,no_run
+//! # use egg_mode::Token;
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let token: Token = unimplemented!();
+//! use egg_mode::search::{self, ResultType};
+//!
+//! let search = search::search("rustlang")
+//!     .result_type(ResultType::Recent)
+//!     .call(&token)
+//!     .await
+//!     .unwrap();
+//!
+//! for tweet in &search.statuses {
+//!     println!("(@{}) {}", tweet.user.as_ref().unwrap().screen_name, tweet.text);
+//! }
+//! # }
+//! ```
+//!
+//! Once you have your `SearchResult`, you can navigate the search results by calling `older` and
+//! `newer` to get the next and previous pages, respsectively. In addition, you can see your
+//! original query in the search result struct as well, so you can categorize multiple searches by
+//! their query. While this is given as a regular field, note that modifying `query` will not
+//! change what is searched for when you call `older` or `newer`; the `SearchResult` keeps its
+//! search arguments in a separate private field.
+//!
+//! If you just want every matching tweet, `SearchBuilder::into_stream` turns the search into a
+//! `Stream` of tweets that loads older pages for you as it goes.
+//!
+//! The search parameter given in the initial call to `search` has several options itself. A full
+//! reference is available in [Twitter's Search API documentation][search-doc]. This listing by
+//! itself does not include the search by Place ID, as mentioned on [a separate Tweets by Place
+//! page][search-place]. Rather than writing these operators by hand, you can build the query with
+//! [`Query`], which quotes keywords and groups alternatives for you:
+//!
+//! ```rust,no_run
+//! # use egg_mode::Token;
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let token: Token = unimplemented!();
+//! use egg_mode::search::{self, Filter, Query};
+//!
+//! let query = Query::new()
+//!     .hashtag("rustlang")
+//!     .exclude(Query::new().filter(Filter::Retweets));
+//! let search = search::search(query).call(&token).await.unwrap();
+//! # }
+//! ```
+//!
+//! [`Query`]: struct.Query.html
+//!
+//! To search with the v2 API instead, call `recent_v2`. It returns the tweet objects from the
+//! [`v2`] module, where you can request fields like engagement metrics and conversation IDs, and
+//! load the authors of the tweets alongside them. Apps with Academic Research access can also
+//! search every tweet since 2006 with `all_v2`, and load the number of matching tweets over time
+//! with `counts_recent_v2` and `counts_all_v2`.
+//!
+//! [`v2`]: ../v2/index.html
+//!
+//! If your app has access to Twitter's premium search API, `premium_counts` loads the number of
+//! tweets matching a query over time, bucketed by minute, hour, or day, without needing to load
+//! the tweets themselves.
+//!
+//! [search-doc]: https://developer.twitter.com/en/docs/tweets/search/api-reference/get-search-tweets
+//! [search-place]: https://developer.twitter.com/en/docs/tweets/search/guides/tweets-by-place
+
+use std::fmt;
+
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Deserializer};
+
+use crate::common::*;
+use crate::error::Error::InvalidParam;
+use crate::tweet::{Tweet, TweetId};
+use crate::{auth, error, links};
+
+mod premium;
+mod query;
+mod v2;
+
+pub use self::premium::{premium_counts, Bucket, CountsBuilder, SearchProduct, TweetCounts};
+pub use self::query::{Filter, Query};
+pub use self::v2::{
+    all_v2, counts_all_v2, counts_recent_v2, recent_v2, CountsV2Builder, SearchV2Builder,
+};
+
+///Begin setting up a tweet search with the given query.
+pub fn search<S: Into<CowStr>>(query: S) -> SearchBuilder {
+    SearchBuilder {
+        query: query.into(),
+        lang: None,
+        locale: None,
+        result_type: None,
+        count: None,
+        until: None,
+        geocode: None,
+        since_id: None,
+        max_id: None,
+        include_entities: None,
+    }
+}
+
+///Represents what kind of tweets should be included in search results.
+#[derive(Debug, Copy, Clone)]
+pub enum ResultType {
+    ///Return only the most recent tweets in the response.
+    Recent,
+    ///Return only the most popular tweets in the response.
+    Popular,
+    ///Include both popular and real-time results in the response.
+    Mixed,
+}
+
+///Display impl that turns the variants into strings that can be used as search parameters.
+impl fmt::Display for ResultType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResultType::Recent => write!(f, "recent"),
+            ResultType::Popular => write!(f, "popular"),
+            ResultType::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+///Represents a radius around a given location to return search results for.
+#[derive(Debug, Copy, Clone)]
+pub enum Distance {
+    ///A radius given in miles.
+    Miles(f32),
+    ///A radius given in kilometers.
+    Kilometers(f32),
+}
+
+///Represents a tweet search query before being sent.
+#[must_use = "SearchBuilder is lazy and won't do anything unless `call`ed"]
+pub struct SearchBuilder {
+    ///The text to search for.
+    query: CowStr,
+    lang: Option<CowStr>,
+    locale: Option<CowStr>,
+    result_type: Option<ResultType>,
+    count: Option<u32>,
+    until: Option<(u32, u32, u32)>,
+    geocode: Option<(f32, f32, Distance)>,
+    since_id: Option<TweetId>,
+    max_id: Option<TweetId>,
+    include_entities: Option<bool>,
+}
+
+impl SearchBuilder {
+    ///Restrict search results to those that have been machine-parsed as the given two-letter
+    ///language code.
+    pub fn lang<S: Into<CowStr>>(self, lang: S) -> Self {
+        SearchBuilder {
+            lang: Some(lang.into()),
+            ..self
+        }
+    }
+
+    ///Specify the language of the query you're sending. Only `ja` is currently effective.
+    pub fn locale<S: Into<CowStr>>(self, locale: S) -> Self {
+        SearchBuilder {
+            locale: Some(locale.into()),
+            ..self
+        }
+    }
+
+    ///Specify the type of search results to include. The default is `Recent`.
+    pub fn result_type(self, result_type: ResultType) -> Self {
+        SearchBuilder {
+            result_type: Some(result_type),
+            ..self
+        }
+    }
+
+    ///Set the number of tweets to return per-page, up to a maximum of 100. The default is 15.
+    ///
+    ///A count of 0 or more than 100 causes `call` to return `Error::InvalidParam` without
+    ///contacting Twitter.
+    pub fn count(self, count: u32) -> Self {
+        SearchBuilder {
+            count: Some(count),
+            ..self
+        }
+    }
+
+    ///Returns tweets created before the given date. Keep in mind that search is limited to the
+    ///last 7 days of results, so giving a date here that's older than a week will return no
+    ///results.
+    ///
+    ///If the given date doesn't exist, `call` returns `Error::InvalidParam` without contacting
+    ///Twitter.
+    pub fn until(self, year: u32, month: u32, day: u32) -> Self {
+        SearchBuilder {
+            until: Some((year, month, day)),
+            ..self
+        }
+    }
+
+    ///Restricts results to users located within the given radius of the given coordinate. This is
+    ///preferably populated from location-tagged tweets, but can be filled in from the user's
+    ///profile as a fallback.
+    ///
+    ///If the latitude or longitude is out of range, or the radius isn't a positive distance,
+    ///`call` returns `Error::InvalidParam` without contacting Twitter.
+    pub fn geocode(self, latitude: f32, longitude: f32, radius: Distance) -> Self {
+        SearchBuilder {
+            geocode: Some((latitude, longitude, radius)),
+            ..self
+        }
+    }
+
+    ///Restricts results to those with higher IDs than (i.e. that were posted after) the given
+    ///tweet ID.
+    pub fn since_tweet(self, since_id: impl Into<TweetId>) -> Self {
+        SearchBuilder {
+            since_id: Some(since_id.into()),
+            ..self
+        }
+    }
+
+    ///Restricts results to those with IDs no higher than (i.e. were posted earlier than) the given
+    ///tweet ID. Will include the given tweet in search results.
+    pub fn max_tweet(self, max_id: impl Into<TweetId>) -> Self {
+        SearchBuilder {
+            max_id: Some(max_id.into()),
+            ..self
+        }
+    }
+
+    ///Set whether to include the `entities` of each tweet in the results. They're included by
+    ///default.
+    pub fn include_entities(self, include_entities: bool) -> Self {
+        SearchBuilder {
+            include_entities: Some(include_entities),
+            ..self
+        }
+    }
+
+    ///Finalize the search terms and return the first page of responses.
+    pub async fn call(self, token: &auth::Token) -> Result<Response<SearchResult>, error::Error> {
+        load_page(self.params()?, token).await
+    }
+
+    ///Finalize the search terms and return a `Stream` of every matching tweet, loading older pages
+    ///of results as needed.
+    ///
+    ///Each page is only requested once the tweets from the previous page have been consumed, so
+    ///combinators like `take` won't load any more pages than they need. This makes it
+    ///straightforward to collect a given number of tweets, or to stop once tweets get older than
+    ///some point. The stream ends once Twitter indicates that there are no more results, by
+    ///leaving out `next_results` from the search metadata or returning an empty page. Keep in mind
+    ///that standard search only covers the last 7 days of tweets.
+    ///
+    ///Each tweet is wrapped in a `Response` carrying the rate-limit information from the page it
+    ///was loaded with. If loading a page fails, the stream yields the error and then ends.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///use egg_mode::search;
+    ///use futures::{StreamExt, TryStreamExt};
+    ///
+    ///let tweets = search::search("rustlang")
+    ///    .count(100)
+    ///    .into_stream(&token)
+    ///    .take(250)
+    ///    .try_collect::<Vec<_>>()
+    ///    .await
+    ///    .unwrap();
+    ///# }
+    ///```
+    pub fn into_stream(
+        self,
+        token: &auth::Token,
+    ) -> impl Stream<Item = Result<Response<Tweet>, error::Error>> {
+        let token = token.clone();
+        stream::try_unfold(Some(self.params()), move |params: Option<Result<_, _>>| {
+            let token = token.clone();
+            async move {
+                let params = match params {
+                    Some(params) => params?,
+                    None => return Ok::<_, error::Error>(None),
+                };
+                let page = load_page(params, &token).await?;
+                let next = if page.more_results && !page.statuses.is_empty() {
+                    Some(Ok(page.older_params()))
+                } else {
+                    None
+                };
+                let statuses = Response::map(page, |p| p.statuses);
+                Ok(Some((statuses, next)))
+            }
+        })
+        .map_ok(|page| stream::iter(page).map(Ok::<_, error::Error>))
+        .try_flatten()
+    }
+
+    ///Checks the search parameters and converts them into a `ParamList`.
+    fn params(self) -> Result<ParamList, error::Error> {
+        let until = match self.until {
+            Some((year, month, day)) => Some(
+                chrono::NaiveDate::from_ymd_opt(year as i32, month, day)
+                    .ok_or(InvalidParam("`until` is not a valid date"))?
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let geocode = match self.geocode {
+            Some((lat, lon, radius)) => {
+                if !(-90.0..=90.0).contains(&lat) {
+                    return Err(InvalidParam("geocode latitude must be between -90 and 90"));
+                }
+                if !(-180.0..=180.0).contains(&lon) {
+                    return Err(InvalidParam(
+                        "geocode longitude must be between -180 and 180",
+                    ));
+                }
+                let (r, unit) = match radius {
+                    Distance::Miles(r) => (r, "mi"),
+                    Distance::Kilometers(r) => (r, "km"),
+                };
+                if !(r.is_finite() && r > 0.0) {
+                    return Err(InvalidParam("geocode radius must be a positive distance"));
+                }
+                Some(format!("{:.6},{:.6},{}{}", lat, lon, r, unit))
+            }
+            None => None,
+        };
+
+        if matches!(self.count, Some(count) if count == 0 || count > 100) {
+            return Err(InvalidParam("count must be between 1 and 100"));
+        }
+
+        Ok(ParamList::new()
+            .extended_tweets()
+            .include_cards()
+            .add_param("q", self.query)
+            .add_opt_param("lang", self.lang)
+            .add_opt_param("locale", self.locale)
+            .add_opt_param("result_type", self.result_type.map_string())
+            .add_opt_param("count", self.count.map_string())
+            .add_opt_param("since_id", self.since_id.map_string())
+            .add_opt_param("max_id", self.max_id.map_string())
+            .add_opt_param("include_entities", self.include_entities.map_string())
+            .add_opt_param("until", until)
+            .add_opt_param("geocode", geocode))
+    }
+}
+
+///Loads a page of search results with the given parameters, keeping the parameters in the
+///`SearchResult` so the next and previous pages can be loaded.
+async fn load_page(
+    params: ParamList,
+    token: &auth::Token,
+) -> Result<Response<SearchResult>, error::Error> {
+    let req = get(links::statuses::SEARCH, token, Some(&params));
+    let mut resp = request_with_json_response::<SearchResult>(req).await?;
+
+    resp.response.params = Some(params);
+    Ok(resp)
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSearch {
+    search_metadata: RawSearchMetaData,
+    statuses: Vec<Tweet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct RawSearchMetaData {
+    completed_in: f64,
+    max_id: TweetId,
+    /// absent if no more results to retrieve
+    next_results: Option<String>,
+    query: String,
+    /// absent if no results
+    refresh_url: Option<String>,
+    count: u64,
+    since_id: TweetId,
+}
+
+impl<'de> Deserialize<'de> for SearchResult {
+    fn deserialize<D>(deser: D) -> Result<SearchResult, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawSearch::deserialize(deser)?;
+        Ok(SearchResult {
+            statuses: raw.statuses,
+            query: raw.search_metadata.query,
+            max_id: raw.search_metadata.max_id,
+            since_id: raw.search_metadata.since_id,
+            more_results: raw.search_metadata.next_results.is_some(),
+            params: None,
+        })
+    }
+}
+
+///Represents a page of search results, along with metadata to request the next or previous page.
+#[derive(Debug)]
+pub struct SearchResult {
+    ///The list of statuses in this page of results.
+    pub statuses: Vec<Tweet>,
+    ///The query used to generate this page of results. Note that changing this will not affect the
+    ///`next_page` method.
+    pub query: String,
+    ///Last tweet id in this page of results. This id can be used in `SearchBuilder::since_tweet`
+    pub max_id: TweetId,
+    ///First tweet id in this page of results. This id can be used in `SearchBuilder::since_tweet`
+    pub since_id: TweetId,
+    more_results: bool,
+    params: Option<ParamList>,
+}
+
+impl SearchResult {
+    ///Load the next page of search results for the same query.
+    pub async fn older(&self, token: &auth::Token) -> Result<Response<SearchResult>, error::Error> {
+        load_page(self.older_params(), token).await
+    }
+
+    fn older_params(&self) -> ParamList {
+        let mut params = self
+            .params
+            .as_ref()
+            .cloned()
+            .unwrap_or_default()
+            .extended_tweets();
+
+        params.remove("since_id");
+
+        if let Some(min_id) = self.statuses.iter().map(|t| t.id).min() {
+            params.add_param_ref("max_id", (min_id.0 - 1).to_string());
+        } else {
+            params.remove("max_id");
+        }
+
+        params
+    }
+
+    ///Load the previous page of search results for the same query.
+    pub async fn newer(&self, token: &auth::Token) -> Result<Response<SearchResult>, error::Error> {
+        let mut params = self
+            .params
+            .as_ref()
+            .cloned()
+            .unwrap_or_default()
+            .extended_tweets();
+
+        params.remove("max_id");
+        if let Some(max_id) = self.statuses.iter().map(|t| t.id).max() {
+            params.add_param_ref("since_id", max_id.to_string());
+        } else {
+            params.remove("since_id");
+        }
+
+        load_page(params, token).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_last_page() {
+        let content = load_file("sample_payloads/search-last-page.json");
+        let page = ::serde_json::from_str::<SearchResult>(&content).unwrap();
+
+        assert!(page.statuses.is_empty());
+        assert_eq!(page.query, "%23rustlang+-filter%3Aretweets");
+        //without `next_results`, `into_stream` knows to stop
+        assert!(!page.more_results);
+    }
+
+    #[test]
+    fn search_params() {
+        let params = search("rustlang")
+            .geocode(37.75, -122.5, Distance::Miles(1.5))
+            .until(2020, 4, 9)
+            .locale("ja")
+            .include_entities(false)
+            .params()
+            .unwrap();
+        assert_eq!(
+            params.get("geocode").map(|v| &**v),
+            Some("37.750000,-122.500000,1.5mi")
+        );
+        assert_eq!(params.get("until").map(|v| &**v), Some("2020-04-09"));
+        assert_eq!(params.get("locale").map(|v| &**v), Some("ja"));
+        assert_eq!(params.get("include_entities").map(|v| &**v), Some("false"));
+
+        let bad = |b: SearchBuilder| matches!(b.params(), Err(InvalidParam(_)));
+        assert!(bad(search("x").geocode(91.0, 0.0, Distance::Miles(1.0))));
+        assert!(bad(search("x").geocode(0.0, 181.0, Distance::Miles(1.0))));
+        assert!(bad(search("x").geocode(
+            0.0,
+            0.0,
+            Distance::Kilometers(0.0)
+        )));
+        assert!(bad(search("x").until(2020, 2, 30)));
+        assert!(bad(search("x").count(101)));
+    }
+}
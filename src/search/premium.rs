@@ -0,0 +1,273 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::common::*;
+use crate::error::{Error::InvalidResponse, Result};
+use crate::{auth, links};
+
+/// The premium search products, which differ in how far back they can search.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SearchProduct {
+    /// Search the last 30 days of tweets.
+    ThirtyDay,
+    /// Search every tweet since 2006.
+    FullArchive,
+}
+
+impl fmt::Display for SearchProduct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchProduct::ThirtyDay => write!(f, "30day"),
+            SearchProduct::FullArchive => write!(f, "fullarchive"),
+        }
+    }
+}
+
+/// The length of time each count covers in a `TweetCounts` series.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Bucket {
+    /// Count tweets per minute.
+    Minute,
+    /// Count tweets per hour. This is the default.
+    Hour,
+    /// Count tweets per day.
+    Day,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Bucket::Minute => write!(f, "minute"),
+            Bucket::Hour => write!(f, "hour"),
+            Bucket::Day => write!(f, "day"),
+        }
+    }
+}
+
+/// Begin setting up a request for the number of tweets matching the given query, using the
+/// premium search API.
+///
+/// The premium search API is only available to apps that have set up a dev environment for the
+/// given product; `env_label` is the label you gave that environment. The query uses the premium
+/// search syntax, which is close to the standard syntax; [`Query`] can be used to build one.
+///
+/// By default, the counts cover the last 30 days, in hourly buckets. Use the methods on
+/// `CountsBuilder` to change this, then `call` to load the counts.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::search::{self, Bucket, SearchProduct};
+///
+/// let counts = search::premium_counts(SearchProduct::ThirtyDay, "dev", "#rustlang")
+///     .bucket(Bucket::Day)
+///     .call(&token)
+///     .await
+///     .unwrap();
+///
+/// for (day, count) in &counts.counts {
+///     println!("{}: {} tweets", day.format("%Y-%m-%d"), count);
+/// }
+/// # }
+/// ```
+///
+/// [`Query`]: struct.Query.html
+pub fn premium_counts(
+    product: SearchProduct,
+    env_label: impl Into<CowStr>,
+    query: impl Into<CowStr>,
+) -> CountsBuilder {
+    CountsBuilder {
+        product,
+        env_label: env_label.into(),
+        query: query.into(),
+        from_date: None,
+        to_date: None,
+        bucket: Bucket::Hour,
+    }
+}
+
+/// Represents a request for premium search counts before being sent.
+#[must_use = "CountsBuilder is lazy and won't do anything unless `call`ed"]
+#[derive(Debug, Clone)]
+pub struct CountsBuilder {
+    product: SearchProduct,
+    env_label: CowStr,
+    query: CowStr,
+    from_date: Option<DateTime<Utc>>,
+    to_date: Option<DateTime<Utc>>,
+    bucket: Bucket,
+}
+
+impl CountsBuilder {
+    /// Only count tweets posted at or after the given time. Twitter only looks at the time down to
+    /// the minute.
+    pub fn from_date(self, from_date: DateTime<Utc>) -> Self {
+        CountsBuilder {
+            from_date: Some(from_date),
+            ..self
+        }
+    }
+
+    /// Only count tweets posted before the given time. Twitter only looks at the time down to the
+    /// minute.
+    pub fn to_date(self, to_date: DateTime<Utc>) -> Self {
+        CountsBuilder {
+            to_date: Some(to_date),
+            ..self
+        }
+    }
+
+    /// Set the length of time each count covers.
+    pub fn bucket(self, bucket: Bucket) -> Self {
+        CountsBuilder { bucket, ..self }
+    }
+
+    /// Load the counts for the whole requested time span.
+    ///
+    /// Twitter returns counts for at most 31 days in one call, so longer spans are loaded in
+    /// several calls, which are combined into one series. The rate-limit information in the
+    /// returned `Response` is from the last call.
+    pub async fn call(self, token: &auth::Token) -> Result<Response<TweetCounts>> {
+        let url = format!(
+            "{}/{}/{}/counts.json",
+            links::statuses::PREMIUM_SEARCH_STEM,
+            self.product,
+            self.env_label
+        );
+        let params = ParamList::new()
+            .add_param("query", self.query)
+            .add_param("bucket", self.bucket.to_string())
+            .add_opt_param("fromDate", self.from_date.map(format_date))
+            .add_opt_param("toDate", self.to_date.map(format_date));
+
+        let mut counts = TweetCounts {
+            bucket: self.bucket,
+            counts: vec![],
+            total_count: 0,
+        };
+        let mut next = None;
+        loop {
+            let mut params = params.clone();
+            if let Some(next) = next.take() {
+                params.add_param_ref("next", next);
+            }
+            let req = get(&url, token, Some(&params));
+            let resp = request_with_json_response::<RawCounts>(req).await?;
+            next = resp.next.clone();
+            let resp = Response::try_map(resp, |raw| counts.append(raw))?;
+
+            if next.is_none() {
+                counts.counts.sort();
+                return Ok(Response::map(resp, |()| counts));
+            }
+        }
+    }
+}
+
+/// The number of tweets matching a query over time, as returned by `premium_counts`,
+/// `counts_recent_v2`, and `counts_all_v2`.
+#[derive(Debug, Clone)]
+pub struct TweetCounts {
+    /// The length of time each count covers.
+    pub bucket: Bucket,
+    /// A list of `(time, count)` pairs, sorted by time. `time` is the start of the minute, hour,
+    /// or day that the count covers.
+    pub counts: Vec<(DateTime<Utc>, u64)>,
+    /// The total number of matching tweets across the whole time span.
+    pub total_count: u64,
+}
+
+impl TweetCounts {
+    fn append(&mut self, raw: RawCounts) -> Result<()> {
+        for bucket in raw.results {
+            self.counts
+                .push((parse_time_period(&bucket.time_period)?, bucket.count));
+        }
+        self.total_count += raw.total_count;
+        Ok(())
+    }
+
+    pub(super) fn append_v2(&mut self, raw: super::v2::RawCounts) {
+        self.counts.extend(
+            raw.data
+                .into_iter()
+                .map(|bucket| (bucket.start, bucket.tweet_count)),
+        );
+        self.total_count += raw.meta.total_tweet_count;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCounts {
+    #[serde(default)]
+    results: Vec<RawBucket>,
+    #[serde(default)]
+    total_count: u64,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBucket {
+    time_period: String,
+    count: u64,
+}
+
+/// Premium search takes and returns times like "201701010000", in UTC.
+const DATE_FORMAT: &str = "%Y%m%d%H%M";
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn parse_time_period(time: &str) -> Result<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(time, DATE_FORMAT)
+        .map(|time| Utc.from_utc_datetime(&time))
+        .map_err(|_| InvalidResponse("invalid time period", Some(time.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_counts() {
+        let content = load_file("sample_payloads/premium-counts.json");
+        let raw = ::serde_json::from_str::<RawCounts>(&content).unwrap();
+        assert_eq!(
+            raw.next.as_deref(),
+            Some("eyJhdXRob3IiOiJ0d2l0dGVyZGV2In0=")
+        );
+
+        let mut counts = TweetCounts {
+            bucket: Bucket::Day,
+            counts: vec![],
+            total_count: 0,
+        };
+        counts.append(raw).unwrap();
+
+        assert_eq!(counts.total_count, 2027);
+        assert_eq!(counts.counts.len(), 3);
+        assert_eq!(
+            counts.counts[0],
+            (Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap(), 32)
+        );
+        assert_eq!(
+            format_date(Utc.with_ymd_and_hms(2017, 2, 3, 14, 5, 59).unwrap()),
+            "201702031405"
+        );
+    }
+}
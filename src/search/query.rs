@@ -0,0 +1,375 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::common::*;
+
+/// Built-in filters that can be applied to a search with `Query::filter`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Tweets with attached images or videos.
+    Media,
+    /// Tweets with attached images.
+    Images,
+    /// Tweets with videos uploaded directly to Twitter.
+    NativeVideo,
+    /// Tweets that contain a link.
+    Links,
+    /// Tweets that are replies to another tweet.
+    Replies,
+    /// Retweets, including old-style "RT @user" retweets.
+    Retweets,
+    /// Retweets made with the retweet button.
+    NativeRetweets,
+    /// Tweets that quote another tweet.
+    Quote,
+    /// Tweets that contain a hashtag.
+    Hashtags,
+    /// Tweets that mention another user.
+    Mentions,
+    /// Tweets posted by verified accounts.
+    Verified,
+    /// Tweets that aren't marked as potentially sensitive.
+    Safe,
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Filter::Media => "media",
+            Filter::Images => "images",
+            Filter::NativeVideo => "native_video",
+            Filter::Links => "links",
+            Filter::Replies => "replies",
+            Filter::Retweets => "retweets",
+            Filter::NativeRetweets => "nativeretweets",
+            Filter::Quote => "quote",
+            Filter::Hashtags => "hashtags",
+            Filter::Mentions => "mentions",
+            Filter::Verified => "verified",
+            Filter::Safe => "safe",
+        };
+        write!(f, "filter:{}", name)
+    }
+}
+
+/// A search query, built up from individual search operators.
+///
+/// Writing a search query by hand means remembering Twitter's operator syntax and quoting
+/// everything correctly, which is easy to get wrong when parts of the query come from user input.
+/// `Query` builds the query string for you: each method adds another condition, and all the
+/// conditions need to match for a tweet to be returned. Alternatives can be combined with `or`,
+/// and conditions can be negated with `exclude`.
+///
+/// Keywords given to `word` are quoted if they contain spaces or would otherwise be read as an
+/// operator, so they're always searched for as plain text. The text given to `phrase` is searched
+/// for as an exact phrase; since Twitter has no way to escape a quote mark inside a phrase, any
+/// double quotes in it are removed. Hashtags and screen names can't be quoted, so any characters
+/// that can't be part of them are removed instead.
+///
+/// A `Query` can be given directly to `search` or anything else that takes a query string, and
+/// its `Display` implementation prints the final query.
+///
+/// # Example
+///
+/// ```rust
+/// use egg_mode::search::{Filter, Query};
+/// use chrono::NaiveDate;
+///
+/// let query = Query::new()
+///     .hashtag("rustlang")
+///     .from("rustlang")
+///     .or(Query::new().phrase("rust 2021").filter(Filter::Media))
+///     .exclude(Query::new().filter(Filter::Retweets))
+///     .since(NaiveDate::from_ymd_opt(2021, 10, 1).unwrap());
+///
+/// assert_eq!(
+///     query.to_string(),
+///     "((#rustlang from:rustlang) OR (\"rust 2021\" filter:media)) -filter:retweets \
+///      since:2021-10-01"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    text: String,
+    //whether this clause is a set of alternatives joined with OR, which needs parentheses when
+    //combined with other clauses
+    alternatives: bool,
+}
+
+impl Query {
+    /// Creates a new, empty `Query`.
+    pub fn new() -> Query {
+        Query::default()
+    }
+
+    fn push(mut self, text: String) -> Self {
+        self.clauses.push(Clause {
+            text,
+            alternatives: false,
+        });
+        self
+    }
+
+    /// Returns whether no conditions have been added to this `Query`.
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Matches tweets containing the given keyword.
+    ///
+    /// Twitter matches keywords without regard to case, and also matches them inside hashtags and
+    /// mentions. If the keyword contains spaces or quotes, or would be read as an operator, it's
+    /// searched for as a phrase instead.
+    pub fn word(self, word: impl AsRef<str>) -> Self {
+        let word = word.as_ref();
+        if needs_quotes(word) {
+            self.phrase(word)
+        } else {
+            self.push(word.to_string())
+        }
+    }
+
+    /// Matches tweets containing the given exact phrase.
+    pub fn phrase(self, phrase: impl AsRef<str>) -> Self {
+        let phrase = phrase.as_ref().replace('"', "");
+        self.push(format!("\"{}\"", phrase.trim()))
+    }
+
+    /// Matches tweets containing the given hashtag. A leading `#` is optional.
+    ///
+    /// Characters that can't be part of a hashtag, like whitespace or the quotes and parentheses
+    /// used by other operators, are removed. If nothing is left, no condition is added.
+    pub fn hashtag(self, tag: impl AsRef<str>) -> Self {
+        let tag = clean_name(tag.as_ref(), '#', char::is_alphanumeric);
+        self.push_name("#", &tag)
+    }
+
+    /// Matches tweets mentioning the given user. A leading `@` is optional.
+    ///
+    /// Characters that can't be part of a screen name, like whitespace or the quotes and
+    /// parentheses used by other operators, are removed. If nothing is left, no condition is
+    /// added. The same goes for `from` and `to`.
+    pub fn mention(self, screen_name: impl AsRef<str>) -> Self {
+        let name = clean_screen_name(screen_name.as_ref());
+        self.push_name("@", &name)
+    }
+
+    /// Matches tweets posted by the given user. A leading `@` is optional.
+    pub fn from(self, screen_name: impl AsRef<str>) -> Self {
+        let name = clean_screen_name(screen_name.as_ref());
+        self.push_name("from:", &name)
+    }
+
+    /// Matches tweets replying to the given user. A leading `@` is optional.
+    pub fn to(self, screen_name: impl AsRef<str>) -> Self {
+        let name = clean_screen_name(screen_name.as_ref());
+        self.push_name("to:", &name)
+    }
+
+    fn push_name(self, prefix: &str, name: &str) -> Self {
+        if name.is_empty() {
+            self
+        } else {
+            self.push(format!("{}{}", prefix, name))
+        }
+    }
+
+    /// Matches tweets that pass the given filter.
+    ///
+    /// To exclude tweets that pass a filter, add it to a separate `Query` and give that to
+    /// `exclude`.
+    pub fn filter(self, filter: Filter) -> Self {
+        self.push(filter.to_string())
+    }
+
+    /// Matches tweets posted on or after the given date.
+    pub fn since(self, date: NaiveDate) -> Self {
+        self.push(format!("since:{}", date.format("%Y-%m-%d")))
+    }
+
+    /// Matches tweets posted before the given date.
+    pub fn until(self, date: NaiveDate) -> Self {
+        self.push(format!("until:{}", date.format("%Y-%m-%d")))
+    }
+
+    /// Matches tweets posted on or after `start` and before `end`.
+    pub fn between(self, start: NaiveDate, end: NaiveDate) -> Self {
+        self.since(start).until(end)
+    }
+
+    /// Matches tweets that match both this query and the given one.
+    pub fn and(mut self, other: Query) -> Self {
+        self.clauses.extend(other.clauses);
+        self
+    }
+
+    /// Matches tweets that match either this query or the given one.
+    ///
+    /// Each side of the `OR` keeps all of its conditions together in parentheses, so
+    /// `Query::new().word("a").word("b").or(Query::new().word("c"))` becomes `(a b) OR c`, which
+    /// matches tweets with both "a" and "b", or tweets with "c". Conditions added after calling
+    /// `or` apply to both sides.
+    pub fn or(self, other: Query) -> Self {
+        if self.is_empty() {
+            return other;
+        } else if other.is_empty() {
+            return self;
+        }
+
+        Query {
+            clauses: vec![Clause {
+                text: format!("{} OR {}", self.as_alternative(), other.as_alternative()),
+                alternatives: true,
+            }],
+        }
+    }
+
+    /// Excludes tweets that match the given query.
+    ///
+    /// If the given query has several conditions, tweets are only excluded if they match all of
+    /// them.
+    pub fn exclude(self, other: Query) -> Self {
+        match other.clauses.as_slice() {
+            [] => self,
+            [clause] if !clause.alternatives => self.push(format!("-{}", clause.text)),
+            _ => self.push(format!("-({})", other)),
+        }
+    }
+
+    /// Prints this query as one side of an `OR`.
+    fn as_alternative(&self) -> String {
+        match self.clauses.as_slice() {
+            [clause] => clause.text.clone(),
+            _ => format!("({})", self),
+        }
+    }
+}
+
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.alternatives {
+            write!(f, "({})", self.text)
+        } else {
+            f.write_str(&self.text)
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.clauses.as_slice() {
+            [clause] => f.write_str(&clause.text),
+            _ => {
+                for (idx, clause) in self.clauses.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{}", clause)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<Query> for CowStr {
+    fn from(query: Query) -> CowStr {
+        query.to_string().into()
+    }
+}
+
+/// Removes the given leading sigil from a hashtag or screen name, and any characters that `valid`
+/// rejects. Underscores are always kept.
+fn clean_name(name: &str, sigil: char, valid: fn(char) -> bool) -> String {
+    name.trim()
+        .trim_start_matches(sigil)
+        .chars()
+        .filter(|&c| c == '_' || valid(c))
+        .collect()
+}
+
+/// Screen names can only contain ASCII letters, digits, and underscores.
+fn clean_screen_name(name: &str) -> String {
+    clean_name(name, '@', |c| c.is_ascii_alphanumeric())
+}
+
+/// Returns whether the given keyword needs to be quoted to be searched for as plain text.
+fn needs_quotes(word: &str) -> bool {
+    word.is_empty()
+        || word == "OR"
+        || word.starts_with('-')
+        || word.contains(':')
+        || word
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '(' || c == ')')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_escaping() {
+        let query = Query::new()
+            .word("rust")
+            .word("two words")
+            .word("OR")
+            .word("-minus")
+            .word("from:someone")
+            .phrase("say \"hi\" ")
+            .hashtag("#rustlang")
+            .mention("@rustlang")
+            .to("@rustlang");
+        assert_eq!(
+            query.to_string(),
+            "rust \"two words\" \"OR\" \"-minus\" \"from:someone\" \"say hi\" #rustlang \
+             @rustlang to:rustlang"
+        );
+
+        let alternatives = Query::new()
+            .word("a")
+            .or(Query::new().word("b"))
+            .or(Query::new().word("c").word("d"));
+        assert_eq!(alternatives.to_string(), "a OR b OR (c d)");
+        assert_eq!(
+            alternatives.clone().word("e").to_string(),
+            "(a OR b OR (c d)) e"
+        );
+        assert_eq!(
+            Query::new().word("e").exclude(alternatives).to_string(),
+            "e -(a OR b OR (c d))"
+        );
+        assert_eq!(
+            Query::new()
+                .exclude(Query::new().filter(Filter::Replies).word("x"))
+                .to_string(),
+            "-(filter:replies x)"
+        );
+
+        let dates = Query::new().between(
+            NaiveDate::from_ymd_opt(2020, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2020, 2, 3).unwrap(),
+        );
+        assert_eq!(dates.to_string(), "since:2020-01-02 until:2020-02-03");
+
+        let names = Query::new()
+            .from("@rust lang")
+            .to("a OR from:b")
+            .mention(" (x) ")
+            .hashtag("#rüst\"")
+            .from("@")
+            .hashtag("");
+        assert_eq!(names.to_string(), "from:rustlang to:aORfromb @x #rüst");
+        assert_eq!(Query::new().or(dates.clone()), dates);
+    }
+}
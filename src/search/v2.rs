@@ -0,0 +1,460 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{Stream, TryStreamExt};
+
+use serde::Deserialize;
+
+use super::{Bucket, TweetCounts};
+use crate::common::*;
+use crate::error::{Error::InvalidParam, Result};
+use crate::tweet::TweetId;
+use crate::{auth, links, v2};
+
+/// Begin setting up a search of the last 7 days of tweets, using the v2 API.
+///
+/// The query uses the v2 search syntax, which is close to the standard syntax; [`Query`] can be
+/// used to build one. Use the methods on the returned `SearchV2Builder` to request optional fields
+/// and expansions or to narrow down the search, then `call` to load the first page of results or
+/// `into_stream` to load all of them.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::search;
+/// use egg_mode::v2::{Expansion, Fields, TweetField};
+///
+/// let fields = Fields::new()
+///     .tweet(&[TweetField::PublicMetrics, TweetField::ConversationId])
+///     .expand(&[Expansion::AuthorId]);
+/// let page = search::recent_v2("#rustlang -is:retweet")
+///     .fields(fields)
+///     .max_results(100)
+///     .call(&token)
+///     .await
+///     .unwrap();
+///
+/// for tweet in &page.data {
+///     let likes = tweet.public_metrics.map_or(0, |m| m.like_count);
+///     println!("{} ({} likes): {}", tweet.id, likes, tweet.text);
+/// }
+/// # }
+/// ```
+///
+/// [`Query`]: struct.Query.html
+pub fn recent_v2(query: impl Into<CowStr>) -> SearchV2Builder {
+    SearchV2Builder::new(links::statuses::V2_SEARCH_RECENT, 100, query.into())
+}
+
+/// Begin setting up a search of every tweet since 2006, using the v2 API.
+///
+/// Full-archive search is only available to apps with Academic Research access. It works the same
+/// way as [`recent_v2`], except that it can go back further, and can load up to 500 tweets per
+/// page. Without a `start_time`, Twitter only searches the last 30 days.
+///
+/// [`recent_v2`]: fn.recent_v2.html
+pub fn all_v2(query: impl Into<CowStr>) -> SearchV2Builder {
+    SearchV2Builder::new(links::statuses::V2_SEARCH_ALL, 500, query.into())
+}
+
+/// Represents a v2 tweet search before being sent.
+#[must_use = "SearchV2Builder is lazy and won't do anything unless `call`ed"]
+#[derive(Debug, Clone)]
+pub struct SearchV2Builder {
+    url: &'static str,
+    max_page_size: u32,
+    query: CowStr,
+    fields: v2::Fields,
+    max_results: Option<u32>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    since_id: Option<TweetId>,
+    until_id: Option<TweetId>,
+    next_token: Option<String>,
+}
+
+impl SearchV2Builder {
+    fn new(url: &'static str, max_page_size: u32, query: CowStr) -> SearchV2Builder {
+        SearchV2Builder {
+            url,
+            max_page_size,
+            query,
+            fields: v2::Fields::default(),
+            max_results: None,
+            start_time: None,
+            end_time: None,
+            since_id: None,
+            until_id: None,
+            next_token: None,
+        }
+    }
+
+    /// Set the optional fields and expansions to request with each tweet.
+    pub fn fields(self, fields: v2::Fields) -> Self {
+        SearchV2Builder { fields, ..self }
+    }
+
+    /// Set the number of tweets to return per page. The default is 10.
+    ///
+    /// The minimum is 10, and the maximum is 100 for recent search or 500 for full-archive search.
+    /// Other values cause `call` to return `Error::InvalidParam` without contacting Twitter.
+    pub fn max_results(self, max_results: u32) -> Self {
+        SearchV2Builder {
+            max_results: Some(max_results),
+            ..self
+        }
+    }
+
+    /// Only return tweets posted at or after the given time.
+    pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
+        SearchV2Builder {
+            start_time: Some(start_time),
+            ..self
+        }
+    }
+
+    /// Only return tweets posted before the given time.
+    pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
+        SearchV2Builder {
+            end_time: Some(end_time),
+            ..self
+        }
+    }
+
+    /// Only return tweets with higher IDs than (i.e. that were posted after) the given tweet ID.
+    pub fn since_tweet(self, since_id: impl Into<TweetId>) -> Self {
+        SearchV2Builder {
+            since_id: Some(since_id.into()),
+            ..self
+        }
+    }
+
+    /// Only return tweets with lower IDs than (i.e. that were posted before) the given tweet ID.
+    pub fn until_tweet(self, until_id: impl Into<TweetId>) -> Self {
+        SearchV2Builder {
+            until_id: Some(until_id.into()),
+            ..self
+        }
+    }
+
+    /// Start from the page with the given token, taken from the `meta.next_token` of a previous
+    /// page.
+    pub fn next_token(self, next_token: impl Into<String>) -> Self {
+        SearchV2Builder {
+            next_token: Some(next_token.into()),
+            ..self
+        }
+    }
+
+    /// Finalize the search terms and return the first page of results.
+    ///
+    /// To load the next page, give the page's `meta.next_token` to `next_token` on a copy of this
+    /// builder, or use `into_stream` to have it done for you.
+    pub async fn call(self, token: &auth::Token) -> Result<Response<v2::Page<v2::Tweet>>> {
+        let params = self.params()?;
+        let req = get(self.url, token, Some(&params));
+        request_with_json_response(req).await
+    }
+
+    /// Finalize the search terms and return a `Stream` of every page of results, following each
+    /// page's `next_token` until the last one.
+    ///
+    /// Each item is a whole page rather than a single tweet, so the `includes` returned with each
+    /// page stay together with the tweets they belong to. Each page is only requested once the
+    /// previous one has been consumed. If the search parameters are invalid or loading a page
+    /// fails, the stream yields the error and then ends.
+    pub fn into_stream(
+        self,
+        token: &auth::Token,
+    ) -> impl Stream<Item = Result<Response<v2::Page<v2::Tweet>>>> {
+        let url = self.url.to_string();
+        let params = self.params();
+        let token = token.clone();
+        futures::stream::once(async move { params })
+            .map_ok(move |params| v2::page_stream(url.clone(), params, "next_token", &token))
+            .try_flatten()
+    }
+
+    /// Checks the search parameters and converts them into a `ParamList`.
+    fn params(&self) -> Result<ParamList> {
+        if matches!(self.max_results, Some(max) if max < 10 || max > self.max_page_size) {
+            return Err(InvalidParam("max_results is out of range for this search"));
+        }
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start >= end {
+                return Err(InvalidParam("start_time must be before end_time"));
+            }
+        }
+
+        let params = ParamList::new()
+            .add_param("query", self.query.clone())
+            .add_opt_param("max_results", self.max_results.map_string())
+            .add_opt_param("start_time", self.start_time.map(format_time))
+            .add_opt_param("end_time", self.end_time.map(format_time))
+            .add_opt_param("since_id", self.since_id.map_string())
+            .add_opt_param("until_id", self.until_id.map_string())
+            .add_opt_param("next_token", self.next_token.clone());
+        Ok(self.fields.add_params(params))
+    }
+}
+
+/// Begin setting up a request for the number of tweets from the last 7 days matching the given
+/// query, using the v2 API.
+///
+/// By default, the counts cover the last 7 days in hourly buckets. Use the methods on the
+/// returned `CountsV2Builder` to change this, then `call` to load the counts.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::search::{self, Bucket};
+///
+/// let counts = search::counts_recent_v2("#rustlang")
+///     .granularity(Bucket::Day)
+///     .call(&token)
+///     .await
+///     .unwrap();
+///
+/// println!("{} tweets this week", counts.total_count);
+/// # }
+/// ```
+pub fn counts_recent_v2(query: impl Into<CowStr>) -> CountsV2Builder {
+    CountsV2Builder::new(links::statuses::V2_COUNTS_RECENT, query.into())
+}
+
+/// Begin setting up a request for the number of tweets since 2006 matching the given query, using
+/// the v2 API.
+///
+/// Full-archive counts are only available to apps with Academic Research access. Without a
+/// `start_time`, Twitter only counts the last 30 days.
+pub fn counts_all_v2(query: impl Into<CowStr>) -> CountsV2Builder {
+    CountsV2Builder::new(links::statuses::V2_COUNTS_ALL, query.into())
+}
+
+/// Represents a request for v2 tweet counts before being sent.
+#[must_use = "CountsV2Builder is lazy and won't do anything unless `call`ed"]
+#[derive(Debug, Clone)]
+pub struct CountsV2Builder {
+    url: &'static str,
+    query: CowStr,
+    granularity: Bucket,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    since_id: Option<TweetId>,
+    until_id: Option<TweetId>,
+}
+
+impl CountsV2Builder {
+    fn new(url: &'static str, query: CowStr) -> CountsV2Builder {
+        CountsV2Builder {
+            url,
+            query,
+            granularity: Bucket::Hour,
+            start_time: None,
+            end_time: None,
+            since_id: None,
+            until_id: None,
+        }
+    }
+
+    /// Set the length of time each count covers. The default is `Hour`.
+    pub fn granularity(self, granularity: Bucket) -> Self {
+        CountsV2Builder {
+            granularity,
+            ..self
+        }
+    }
+
+    /// Only count tweets posted at or after the given time.
+    pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
+        CountsV2Builder {
+            start_time: Some(start_time),
+            ..self
+        }
+    }
+
+    /// Only count tweets posted before the given time.
+    pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
+        CountsV2Builder {
+            end_time: Some(end_time),
+            ..self
+        }
+    }
+
+    /// Only count tweets with higher IDs than (i.e. that were posted after) the given tweet ID.
+    pub fn since_tweet(self, since_id: impl Into<TweetId>) -> Self {
+        CountsV2Builder {
+            since_id: Some(since_id.into()),
+            ..self
+        }
+    }
+
+    /// Only count tweets with lower IDs than (i.e. that were posted before) the given tweet ID.
+    pub fn until_tweet(self, until_id: impl Into<TweetId>) -> Self {
+        CountsV2Builder {
+            until_id: Some(until_id.into()),
+            ..self
+        }
+    }
+
+    /// Load the counts for the whole requested time span.
+    ///
+    /// Twitter splits long spans of full-archive counts into several pages, which are loaded by
+    /// following each page's `next_token` and combined into one series. The rate-limit
+    /// information in the returned `Response` is from the last page.
+    pub async fn call(self, token: &auth::Token) -> Result<Response<TweetCounts>> {
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start >= end {
+                return Err(InvalidParam("start_time must be before end_time"));
+            }
+        }
+
+        let params = ParamList::new()
+            .add_param("query", self.query)
+            .add_param("granularity", self.granularity.to_string())
+            .add_opt_param("start_time", self.start_time.map(format_time))
+            .add_opt_param("end_time", self.end_time.map(format_time))
+            .add_opt_param("since_id", self.since_id.map_string())
+            .add_opt_param("until_id", self.until_id.map_string());
+
+        let mut counts = TweetCounts {
+            bucket: self.granularity,
+            counts: vec![],
+            total_count: 0,
+        };
+        let mut next = None;
+        loop {
+            let mut params = params.clone();
+            if let Some(next) = next.take() {
+                params.add_param_ref("next_token", next);
+            }
+            let req = get(self.url, token, Some(&params));
+            let resp = request_with_json_response::<RawCounts>(req).await?;
+            next = resp.meta.next_token.clone();
+            let resp = Response::map(resp, |raw| counts.append_v2(raw));
+
+            if next.is_none() {
+                counts.counts.sort();
+                return Ok(Response::map(resp, |()| counts));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RawCounts {
+    #[serde(default)]
+    pub(super) data: Vec<RawCount>,
+    #[serde(default)]
+    pub(super) meta: RawCountsMeta,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RawCount {
+    pub(super) start: DateTime<Utc>,
+    pub(super) tweet_count: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct RawCountsMeta {
+    #[serde(default)]
+    pub(super) total_tweet_count: u64,
+    pub(super) next_token: Option<String>,
+}
+
+/// The v2 API takes times in RFC 3339 format, like "2020-04-24T16:18:56Z".
+pub(super) fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_recent_search() {
+        let content = load_file("sample_payloads/v2-search-recent.json");
+        let page = ::serde_json::from_str::<v2::Page<v2::Tweet>>(&content).unwrap();
+
+        assert_eq!(page.data.len(), 2);
+        let tweet = &page.data[0];
+        assert_eq!(tweet.id, TweetId(1255542774432063488));
+        assert_eq!(tweet.conversation_id, Some(TweetId(1255542774432063488)));
+        assert_eq!(tweet.public_metrics.unwrap().like_count, 41);
+
+        let reply = &page.data[1];
+        assert_eq!(
+            reply.referenced_tweets[0].kind,
+            v2::ReferenceKind::RepliedTo
+        );
+        assert_eq!(reply.referenced_tweets[0].id, tweet.id);
+        assert_eq!(
+            page.includes
+                .user(reply.author_id.unwrap())
+                .unwrap()
+                .username,
+            "eggmodebot"
+        );
+
+        assert_eq!(page.meta.newest_id, Some(TweetId(1255543050190110720)));
+        assert_eq!(page.meta.oldest_id, Some(TweetId(1255542774432063488)));
+        assert_eq!(
+            page.meta.next_token.as_deref(),
+            Some("b26v89c19zqg8o3fosbtyp")
+        );
+    }
+
+    #[test]
+    fn parse_v2_counts() {
+        let content = load_file("sample_payloads/v2-counts.json");
+        let raw = ::serde_json::from_str::<RawCounts>(&content).unwrap();
+        assert_eq!(raw.meta.next_token.as_deref(), Some("eyJtYXhJZCI6MTQ"));
+
+        let mut counts = TweetCounts {
+            bucket: Bucket::Day,
+            counts: vec![],
+            total_count: 0,
+        };
+        counts.append_v2(raw);
+        assert_eq!(counts.total_count, 4211);
+        assert_eq!(
+            counts.counts[1],
+            (Utc.with_ymd_and_hms(2021, 5, 25, 0, 0, 0).unwrap(), 1870)
+        );
+    }
+
+    #[test]
+    fn search_v2_params() {
+        let start = Utc.with_ymd_and_hms(2020, 4, 28, 0, 0, 0).unwrap();
+        let params = recent_v2("rust")
+            .start_time(start)
+            .max_results(50)
+            .params()
+            .unwrap();
+        assert_eq!(
+            params.get("start_time").map(|v| &**v),
+            Some("2020-04-28T00:00:00Z")
+        );
+        assert_eq!(params.get("max_results").map(|v| &**v), Some("50"));
+
+        assert!(recent_v2("rust").max_results(101).params().is_err());
+        assert!(recent_v2("rust").max_results(5).params().is_err());
+        assert!(recent_v2("rust")
+            .start_time(start)
+            .end_time(start)
+            .params()
+            .is_err());
+    }
+}
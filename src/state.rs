@@ -0,0 +1,210 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pluggable persistence for the stateful helpers in egg-mode.
+//!
+//! Several parts of egg-mode can remember what they were doing between runs of your program: a
+//! resumable media upload can pick up where it left off, an access token can be saved once and
+//! loaded back at startup, and a `Timeline` can remember the newest tweet it's seen. Rather than
+//! each of these having its own way of saving data, they all save through the [`StateStore`]
+//! trait, so you only need to implement it once to keep that state wherever you like - a
+//! database, a key/value service, or somewhere else entirely.
+//!
+//! [`StateStore`]: trait.StateStore.html
+//!
+//! A `StateStore` is a simple key/value store of raw bytes. egg-mode serializes each piece of
+//! state as JSON before saving it, and picks keys with a short prefix naming the helper that saved
+//! it, so several helpers can share one store without stepping on each other.
+//!
+//! Two implementations are provided:
+//!
+//! - `FileStore` saves each key as a file in a directory, so state survives the process exiting.
+//! - `MemoryStore` keeps everything in memory, which is handy for tests or short-lived programs.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use egg_mode::state::FileStore;
+//!
+//! let store = FileStore::new("bot-state");
+//!
+//! let token = match egg_mode::Token::load_from(&store, "main")? {
+//!     Some(token) => token,
+//!     None => {
+//!         # let token: egg_mode::Token = unimplemented!();
+//!         //go through the authentication process here, then save the token for next time
+//!         token.save_to(&store, "main")?;
+//!         token
+//!     }
+//! };
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::common::percent_encode;
+
+/// A key/value store that egg-mode's stateful helpers use to save their progress.
+///
+/// Implementations should treat keys as opaque strings; they may contain characters like `/` or
+/// `:`. A value saved with `save` should be returned verbatim by later calls to `load` with the
+/// same key, until it's replaced or removed.
+pub trait StateStore: Send + Sync {
+    /// Loads the value saved under the given key, if any.
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Saves a value under the given key, replacing whatever was there before.
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()>;
+
+    /// Removes the value saved under the given key. Removing a key that isn't present is not an
+    /// error.
+    fn remove(&self, key: &str) -> io::Result<()>;
+}
+
+impl<S: StateStore + ?Sized> StateStore for &S {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).load(key)
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        (**self).save(key, value)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        (**self).remove(key)
+    }
+}
+
+/// A `StateStore` that saves each key as a file in the given directory.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a new `FileStore` that keeps its files in the given directory. The directory is
+    /// created when the first value is saved, if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> FileStore {
+        FileStore { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", percent_encode(key)))
+    }
+}
+
+impl StateStore for FileStore {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(key), value)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A `StateStore` that keeps everything in memory.
+///
+/// Nothing saved here outlives the store itself, so this is mostly useful for testing, or for
+/// programs that only need to share state between helpers within a single run.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    values: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    /// Creates a new, empty `MemoryStore`.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl StateStore for MemoryStore {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        self.values.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Loads the value saved under the given key and deserializes it from JSON.
+pub(crate) fn load_json<T: DeserializeOwned>(
+    store: &(impl StateStore + ?Sized),
+    key: &str,
+) -> io::Result<Option<T>> {
+    match store.load(key)? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Serializes the given value as JSON and saves it under the given key.
+pub(crate) fn save_json<T: Serialize>(
+    store: &(impl StateStore + ?Sized),
+    key: &str,
+    value: &T,
+) -> io::Result<()> {
+    store.save(key, &serde_json::to_vec(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(store: &dyn StateStore) {
+        assert_eq!(store.load("upload/cat.mp4").unwrap(), None);
+        save_json(store, "upload/cat.mp4", &vec![1, 2, 3]).unwrap();
+        assert_eq!(
+            load_json::<Vec<u32>>(store, "upload/cat.mp4").unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        store.remove("upload/cat.mp4").unwrap();
+        store.remove("upload/cat.mp4").unwrap();
+        assert_eq!(store.load("upload/cat.mp4").unwrap(), None);
+    }
+
+    #[test]
+    fn memory_store() {
+        exercise(&MemoryStore::new());
+    }
+
+    #[test]
+    fn file_store() {
+        let dir = std::env::temp_dir().join(format!("egg-mode-state-{}", std::process::id()));
+        exercise(&FileStore::new(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures::future::{AbortHandle, Abortable};
+use futures::{Stream, StreamExt};
+
+use super::{StreamMessage, TwitterStream};
+use crate::error;
+
+/// What a `BufferedStream` does when a message arrives and its buffer is full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BufferPolicy {
+    /// Drop the oldest message in the buffer to make room for the new one. Dropped messages are
+    /// counted in `BufferedStream::dropped`.
+    DropOldest,
+    /// End the stream with an error. The messages already in the buffer are delivered first.
+    Error,
+    /// Stop reading from the connection until there's room in the buffer.
+    ///
+    /// Twitter keeps its own queue of messages for a connection that isn't being read, and sends
+    /// stall warnings (if asked for with `StreamBuilder::stall_warnings`) as it fills up. If the
+    /// connection falls too far behind, Twitter disconnects it.
+    Pause,
+}
+
+/// A `TwitterStream` that reads messages into a buffer in the background, so that a consumer
+/// that falls behind for a moment doesn't hold up the connection. Created with
+/// [`TwitterStream::buffered`].
+///
+/// [`TwitterStream::buffered`]: struct.TwitterStream.html#method.buffered
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct BufferedStream {
+    shared: Arc<Mutex<Shared>>,
+    reader: AbortHandle,
+}
+
+struct Shared {
+    queue: VecDeque<Result<StreamMessage, error::Error>>,
+    capacity: usize,
+    policy: BufferPolicy,
+    dropped: u64,
+    /// Whether the stream has ended, so no more messages will be added to the queue.
+    done: bool,
+    consumer: Option<Waker>,
+    reader: Option<Waker>,
+}
+
+impl Shared {
+    /// Adds the given message to the queue, following the buffer policy if it's full. Returns
+    /// `false` if the stream should end.
+    fn push(&mut self, msg: Result<StreamMessage, error::Error>) -> bool {
+        if self.queue.len() >= self.capacity {
+            match self.policy {
+                BufferPolicy::DropOldest => {
+                    self.queue.pop_front();
+                    self.dropped += 1;
+                }
+                BufferPolicy::Error => {
+                    let full = io::Error::new(io::ErrorKind::Other, "the stream's buffer is full");
+                    self.queue.push_back(Err(full.into()));
+                    return false;
+                }
+                // the reader waits for room before reading the next message
+                BufferPolicy::Pause => (),
+            }
+        }
+        self.queue.push_back(msg);
+        true
+    }
+
+    fn finish(&mut self) {
+        self.done = true;
+        if let Some(waker) = self.consumer.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl BufferedStream {
+    pub(crate) fn new(
+        stream: TwitterStream,
+        capacity: usize,
+        policy: BufferPolicy,
+    ) -> BufferedStream {
+        let capacity = capacity.max(1);
+        let shared = Arc::new(Mutex::new(Shared {
+            queue: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+            dropped: 0,
+            done: false,
+            consumer: None,
+            reader: None,
+        }));
+
+        let (reader, registration) = AbortHandle::new_pair();
+        let task = Abortable::new(read_into(stream, shared.clone()), registration);
+        tokio::spawn(task);
+
+        BufferedStream { shared, reader }
+    }
+
+    /// Returns the number of messages dropped so far because the buffer was full, with
+    /// `BufferPolicy::DropOldest`.
+    pub fn dropped(&self) -> u64 {
+        self.shared.lock().unwrap().dropped
+    }
+
+    /// Returns the number of messages waiting in the buffer.
+    pub fn buffered(&self) -> usize {
+        self.shared.lock().unwrap().queue.len()
+    }
+}
+
+/// Reads messages from the given stream into the shared buffer until the stream ends or the
+/// buffer policy ends it.
+async fn read_into(mut stream: TwitterStream, shared: Arc<Mutex<Shared>>) {
+    loop {
+        // with BufferPolicy::Pause, wait for the consumer to make room before reading on
+        futures::future::poll_fn(|cx| {
+            let mut shared = shared.lock().unwrap();
+            if shared.policy == BufferPolicy::Pause && shared.queue.len() >= shared.capacity {
+                shared.reader = Some(cx.waker().clone());
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+
+        let msg = stream.next().await;
+        let mut shared = shared.lock().unwrap();
+        // a single bad message doesn't end the stream, so keep reading past errors as long as the
+        // connection is still open
+        let keep_going = match msg {
+            Some(Ok(msg)) => shared.push(Ok(msg)),
+            Some(Err(e)) => shared.push(Err(e)) && stream.is_open(),
+            None => false,
+        };
+        if !keep_going {
+            shared.finish();
+            return;
+        }
+        if let Some(waker) = shared.consumer.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Stream for BufferedStream {
+    type Item = Result<StreamMessage, error::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(msg) = shared.queue.pop_front() {
+            if let Some(waker) = shared.reader.take() {
+                waker.wake();
+            }
+            Poll::Ready(Some(msg))
+        } else if shared.done {
+            Poll::Ready(None)
+        } else {
+            shared.consumer = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for BufferedStream {
+    fn drop(&mut self) {
+        // stop reading from the connection once nobody is reading from the buffer
+        self.reader.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tweet::TweetId;
+    use crate::user::UserId;
+
+    fn shared(capacity: usize, policy: BufferPolicy) -> Shared {
+        Shared {
+            queue: VecDeque::new(),
+            capacity,
+            policy,
+            dropped: 0,
+            done: false,
+            consumer: None,
+            reader: None,
+        }
+    }
+
+    #[test]
+    fn buffer_policies() {
+        let mut drop_oldest = shared(2, BufferPolicy::DropOldest);
+        for id in 1..=3 {
+            let delete = StreamMessage::Delete {
+                status_id: TweetId(id),
+                user_id: UserId(1),
+            };
+            assert!(drop_oldest.push(Ok(delete)));
+        }
+        assert_eq!(drop_oldest.dropped, 1);
+        assert!(matches!(
+            drop_oldest.queue[0],
+            Ok(StreamMessage::Delete {
+                status_id: TweetId(2),
+                ..
+            })
+        ));
+
+        let mut error = shared(1, BufferPolicy::Error);
+        assert!(error.push(Ok(StreamMessage::Ping)));
+        assert!(!error.push(Ok(StreamMessage::Ping)));
+        assert_eq!(error.queue.len(), 2);
+        assert!(matches!(error.queue[1], Err(error::Error::IOError(_))));
+    }
+
+    #[tokio::test]
+    async fn buffered_stream() {
+        let body = hyper::Body::from("\r\n{\"limit\":{\"track\":5}}\r\n");
+        let stream = TwitterStream {
+            body: Some(body),
+            ..TwitterStream::new_empty()
+        };
+
+        let msgs = stream
+            .buffered(10, BufferPolicy::Pause)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(msgs.len(), 2);
+        assert!(matches!(msgs[0], Ok(StreamMessage::Ping)));
+        assert!(matches!(msgs[1], Ok(StreamMessage::Limit(5))));
+    }
+
+    #[tokio::test]
+    async fn buffered_stream_errors() {
+        let body = hyper::Body::from("{\"limit\":\r\n{\"limit\":{\"track\":5}}\r\n");
+        let stream = TwitterStream {
+            body: Some(body),
+            ..TwitterStream::new_empty()
+        };
+
+        let msgs = stream
+            .buffered(10, BufferPolicy::Pause)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(msgs.len(), 2);
+        assert!(msgs[0].is_err());
+        assert!(matches!(msgs[1], Ok(StreamMessage::Limit(5))));
+    }
+}
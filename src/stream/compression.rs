@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io::{self, Write};
+
+use flate2::write::{GzDecoder, ZlibDecoder};
+use hyper::header::{HeaderMap, CONTENT_ENCODING};
+
+/// The encodings a `TwitterStream` asks for, as its `Accept-Encoding` header.
+pub(crate) const ACCEPT_ENCODING: &str = "deflate, gzip";
+
+/// Decompresses the body of a streaming connection as its chunks arrive.
+pub(crate) enum Decoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+}
+
+impl Decoder {
+    /// Returns a decoder for the `Content-Encoding` in the given response headers, or `None` if
+    /// the response isn't compressed (or uses an encoding this doesn't know).
+    pub(crate) fn for_headers(headers: &HeaderMap) -> Option<Decoder> {
+        let encoding = headers.get(CONTENT_ENCODING)?.to_str().ok()?;
+        Decoder::for_encoding(encoding.trim())
+    }
+
+    pub(crate) fn for_encoding(encoding: &str) -> Option<Decoder> {
+        if encoding.eq_ignore_ascii_case("gzip") {
+            Some(Decoder::Gzip(GzDecoder::new(Vec::new())))
+        } else if encoding.eq_ignore_ascii_case("deflate") {
+            // HTTP's "deflate" is the zlib format, not a raw deflate stream
+            Some(Decoder::Deflate(ZlibDecoder::new(Vec::new())))
+        } else {
+            None
+        }
+    }
+
+    /// Decompresses the given chunk, adding whatever can be decompressed so far to `out`.
+    pub(crate) fn decode(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Decoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+            Decoder::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn decode_chunks() {
+        let body = b"{\"limit\":{\"track\":5}}\r\n\r\n";
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(body).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(body).unwrap();
+        let zlib = zlib.finish().unwrap();
+
+        for (encoding, compressed) in [("gzip", gzip), ("deflate", zlib)].iter() {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+            let mut decoder = Decoder::for_headers(&headers).unwrap();
+
+            let mut out = Vec::new();
+            for chunk in compressed.chunks(7) {
+                decoder.decode(chunk, &mut out).unwrap();
+            }
+            assert_eq!(&out[..], &body[..]);
+        }
+
+        assert!(Decoder::for_headers(&HeaderMap::new()).is_none());
+        assert!(Decoder::for_encoding("br").is_none());
+    }
+}
@@ -36,21 +36,56 @@
 //! account of random disconnects, networks resets and stalls. The key points are:
 //!
 //! * The Twitter API sends a Ping message every 30 seconds of message inactivity. So set a timeout
-//! such that after (say) 1 minute of inactivity, the client bounces the connection. This will protect
-//! against network stalls
-//! * Twitter will rate-limit reconnect attempts. So attempt conenctions with a linear or exponential
-//! backoff strategy
+//!   such that after (say) 1 minute of inactivity, the client bounces the connection. This will
+//!   protect against network stalls. `TwitterStream` does this after 90 seconds, as Twitter
+//!   suggests, ending the stream with an `IOError` of kind `TimedOut` (or reconnecting, with
+//!   `auto_reconnect`). To keep your own watch on the connection, check
+//!   `TwitterStream::last_activity`
+//! * Twitter will rate-limit reconnect attempts. So attempt conenctions with a linear or
+//!   exponential backoff strategy. [`TwitterStream::auto_reconnect`] follows Twitter's rules for
+//!   this for you
 //! * In the case of an unreliable connection (e.g. mobile network), fall back to the polling API
+//! * Busy streams carry a lot of data, so the streams opened in this module ask Twitter to
+//!   compress it with gzip, and decompress it as it arrives
 //!
 //! The [official guide](https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/connecting) has more information.
+//!
+//! ### Sample stream
+//! [`sample`] connects to the sample stream, a random sample of about 1% of all public tweets,
+//! which makes a good baseline when studying the tweets picked out by a filter. It needs no
+//! filters, so it returns a `TwitterStream` directly; the stream works the same way as one from
+//! [`filter`], with the same messages and the same connection notes above.
+//!
+//! [`sample`]: fn.sample.html
+//! [`filter`]: fn.filter.html
+//!
+//! ### v2 streams
+//! [`filter_v2`] connects to the v2 filtered stream, which delivers tweets matching the rules
+//! registered for your app rather than taking its filters as parameters, and [`sample_v2`]
+//! connects to the v2 sampled stream. They take the same [`Fields`] as the other v2 endpoints,
+//! and return a [`TweetStreamV2`], which parses each message into a v2 tweet. Tweets from the
+//! filtered stream list the rules they matched, which [`RuleStats`] can keep count of.
+//!
+//! The 10% sample and firehose streams are split into partitions, each with its own connection.
+//! [`partitioned_v2`] opens the given partitions and merges them into one stream.
+//!
+//! [`TwitterStream::auto_reconnect`]: struct.TwitterStream.html#method.auto_reconnect
+//! [`partitioned_v2`]: fn.partitioned_v2.html
+//! [`filter_v2`]: fn.filter_v2.html
+//! [`sample_v2`]: fn.sample_v2.html
+//! [`Fields`]: ../v2/struct.Fields.html
+//! [`TweetStreamV2`]: struct.TweetStreamV2.html
+//! [`RuleStats`]: struct.RuleStats.html
 use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{self, io};
 
 use futures::Stream;
 use hyper::client::ResponseFuture;
+use hyper::header::{HeaderValue, ACCEPT_ENCODING};
 use hyper::{Body, Request};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -58,19 +93,32 @@ use serde_json;
 
 use crate::auth::Token;
 use crate::common::*;
-use crate::tweet::Tweet;
+use crate::tweet::{Tweet, TweetId};
+use crate::user::UserId;
 use crate::{error, links};
 
+mod buffer;
+mod compression;
+mod rules;
+mod v2;
+pub use self::buffer::{BufferPolicy, BufferedStream};
+pub use self::rules::{MatchingRule, RuleHits, RuleStats};
+pub use self::v2::{
+    filter_v2, partitioned_v2, sample_v2, PartitionedEndpoint, PartitionedStreamV2, StreamTweetV2,
+    TweetStreamV2,
+};
+
 // TODO rewrite this
 // https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/streaming-message-types
 /// Represents the kinds of messages that can be sent over Twitter's Streaming API.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum StreamMessage {
     /// A blank line, sent periodically to keep the connection alive.
     Ping,
     /// A list of accounts the authenticated user follows, sent at the beginning of the session for
     /// user streams.
-    FriendList(Vec<u64>),
+    FriendList(Vec<UserId>),
     /// A new tweet.
     ///
     /// Note that the `entities` inside the `user` field will be empty for tweets received via the
@@ -83,9 +131,9 @@ pub enum StreamMessage {
     /// the stream than the Tweet it references."
     Delete {
         /// The status that was deleted.
-        status_id: u64,
+        status_id: TweetId,
         /// The user that deleted the status.
-        user_id: u64,
+        user_id: UserId,
     },
     /// Notice given when a user removes geolocation information from their profile.
     ///
@@ -95,17 +143,17 @@ pub enum StreamMessage {
     /// the specified range, although this is rare."
     ScrubGeo {
         /// The user whose geolocation information needs to be scrubbed.
-        user_id: u64,
+        user_id: UserId,
         /// The last status ID to scrub information from.
-        up_to_status_id: u64,
+        up_to_status_id: TweetId,
     },
     /// Placeholder message used to indicate that a specific tweet has been withheld in certain
     /// countries.
     StatusWithheld {
         /// The status that was withheld.
-        status_id: u64,
+        status_id: TweetId,
         /// The user that posted the status.
-        user_id: u64,
+        user_id: UserId,
         /// A list of uppercase two-character country codes listing the countries where the tweet
         /// was withheld.
         withheld_in_countries: Vec<String>,
@@ -114,11 +162,29 @@ pub enum StreamMessage {
     /// certain countries.
     UserWithheld {
         /// The user whose content was withheld.
-        user_id: u64,
+        user_id: UserId,
         /// A list of uppercase two-character country codes listing the countries where the content
         /// was withheld.
         withheld_in_countries: Vec<String>,
     },
+    /// Notice given when the stream matched more tweets than Twitter will deliver. The enclosed
+    /// value is the total number of matching tweets that weren't delivered since the connection
+    /// was opened.
+    ///
+    /// Filter streams are capped at a share of all public tweets, so very broad filters can match
+    /// more tweets than the stream sends. These notices tell you how many were left out.
+    Limit(u64),
+    /// A warning that the client is reading messages too slowly, and is at risk of being
+    /// disconnected. These are only sent if requested with `StreamBuilder::stall_warnings`.
+    StallWarning {
+        /// A code identifying the warning, like `"FALLING_BEHIND"`.
+        code: String,
+        /// A description of the warning.
+        message: String,
+        /// How full Twitter's queue of messages for this stream is, as a percentage. The stream is
+        /// disconnected when it reaches 100.
+        percent_full: u8,
+    },
     /// An error message that may be delivered immediately prior to Twitter disconnecting the
     /// stream.
     ///
@@ -131,12 +197,24 @@ pub enum StreamMessage {
     ///
     /// [stream-doc]: https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/streaming-message-types
     Disconnect(u64, String),
+    /// Not a message from Twitter, but a notice that the stream's connection was lost and is about
+    /// to be reopened. This is only sent by streams set to `TwitterStream::auto_reconnect`.
+    ///
+    /// Any messages sent while the stream is reconnecting are missed, so a gap in the stream may
+    /// need to be filled in by other means, like a search.
+    Reconnecting {
+        /// The error that ended the previous connection.
+        error: error::Error,
+        /// The number of connection failures in a row, including this one.
+        attempt: u32,
+        /// How long the stream will wait before reconnecting.
+        delay: Duration,
+    },
     /// An unhandled message payload.
     ///
     /// Twitter can add new streaming messages to the API, and egg-mode includes them here so that
     /// they can be used before egg-mode has a chance to handle them.
     Unknown(serde_json::Value),
-    //TODO: stall warnings? "follows over limit" warnings? (other warnings?)
 }
 
 impl<'de> Deserialize<'de> for StreamMessage {
@@ -175,6 +253,17 @@ impl<'de> Deserialize<'de> for StreamMessage {
                 user_id: fetch!(user, "id")?,
                 withheld_in_countries: fetch!(user, "withheld_in_countries")?,
             }
+        } else if let Some(limit) = input.get("limit") {
+            StreamMessage::Limit(fetch!(limit, "track")?)
+        } else if let Some(warning) = input
+            .get("warning")
+            .filter(|w| w.get("percent_full").is_some())
+        {
+            StreamMessage::StallWarning {
+                code: fetch!(warning, "code")?,
+                message: fetch!(warning, "message")?,
+                percent_full: fetch!(warning, "percent_full")?,
+            }
         } else if let Some(err) = input.get("disconnect") {
             StreamMessage::Disconnect(fetch!(err, "code")?, fetch!(err, "reason")?)
         } else if let Some(friends) = input.get("friends") {
@@ -205,21 +294,297 @@ impl FromStr for StreamMessage {
 }
 
 /// A `Stream` that represents a connection to the Twitter Streaming API.
+///
+/// By default, the stream ends when its connection fails or Twitter closes it. Streams opened with
+/// the functions in this module can instead reconnect automatically, following Twitter's rules for
+/// backing off between attempts; see [`auto_reconnect`] for details.
+///
+/// To notice when a connection stalls, a `TwitterStream` uses Tokio's timer, so it needs to be
+/// polled from inside a Tokio runtime with the time driver enabled.
+///
+/// [`auto_reconnect`]: #method.auto_reconnect
 #[must_use = "Streams are lazy and do nothing unless polled"]
 pub struct TwitterStream {
     buf: Vec<u8>,
     request: Option<Request<Body>>,
     response: Option<ResponseFuture>,
     body: Option<Body>,
+    connect: Option<Box<dyn FnMut() -> Request<Body> + Send>>,
+    reconnect: bool,
+    failures: u32,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+    stall: Option<Pin<Box<tokio::time::Sleep>>>,
+    last_activity: Option<Instant>,
+    decoder: Option<compression::Decoder>,
 }
 
+/// How long to wait for data, including keep-alive messages, before treating the connection as
+/// stalled. Twitter sends a keep-alive message every 30 seconds on an idle connection.
+const STALL_TIMEOUT: Duration = Duration::from_secs(90);
+
 impl TwitterStream {
     pub(crate) fn new(request: Request<Body>) -> TwitterStream {
         TwitterStream {
-            buf: vec![],
             request: Some(request),
+            ..TwitterStream::new_empty()
+        }
+    }
+
+    /// Creates a stream that signs a new request from the given function each time it connects,
+    /// so that it can reconnect if asked to. The requests ask for a compressed response, which the
+    /// stream decompresses as it arrives.
+    pub(crate) fn with_connector(
+        mut connect: impl FnMut() -> Request<Body> + Send + 'static,
+    ) -> TwitterStream {
+        let mut connect = move || {
+            let mut request = connect();
+            request.headers_mut().insert(
+                ACCEPT_ENCODING,
+                HeaderValue::from_static(compression::ACCEPT_ENCODING),
+            );
+            request
+        };
+        TwitterStream {
+            request: Some(connect()),
+            connect: Some(Box::new(connect)),
+            ..TwitterStream::new_empty()
+        }
+    }
+
+    pub(crate) fn new_empty() -> TwitterStream {
+        TwitterStream {
+            buf: vec![],
+            request: None,
             response: None,
             body: None,
+            connect: None,
+            reconnect: false,
+            failures: 0,
+            delay: None,
+            stall: None,
+            last_activity: None,
+            decoder: None,
+        }
+    }
+
+    /// Returns when data last arrived on the stream's connection, including the keep-alive
+    /// messages Twitter sends every 30 seconds, or `None` if nothing has arrived yet.
+    ///
+    /// The keep-alive messages themselves are returned as `StreamMessage::Ping`. Since they arrive
+    /// even when no tweets match, this can be checked from elsewhere in your app to see whether the
+    /// connection is still alive, or to report how long ago it last heard from Twitter.
+    pub fn last_activity(&self) -> Option<Instant> {
+        self.last_activity
+    }
+
+    /// Sets the stream to reconnect automatically when its connection fails or is closed, rather
+    /// than ending.
+    ///
+    /// Before each reconnection, the stream yields a `StreamMessage::Reconnecting` with the error
+    /// that ended the connection, and then waits before connecting again. As Twitter asks, the
+    /// wait depends on what went wrong, and grows with each failure in a row:
+    ///
+    /// * For network errors, or when Twitter closes the connection or it stalls for 90 seconds
+    ///   without sending anything, the wait starts at 250 milliseconds and grows by 250
+    ///   milliseconds each time, up to 16 seconds.
+    /// * For server errors from Twitter (a 5xx status code), the wait starts at 5 seconds and
+    ///   doubles each time, up to 320 seconds.
+    /// * When rate-limited (a 420 or 429 status code), the wait starts at 1 minute and doubles
+    ///   each time.
+    ///
+    /// Once a connection succeeds, the wait goes back to its starting point for the next failure.
+    /// Other errors, like a 401 status from a bad token or a message that can't be read, are still
+    /// returned from the stream as they are, since reconnecting wouldn't fix them.
+    ///
+    /// Streams created with `raw::response_as_stream` can't sign a new request, so they ignore
+    /// this setting.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// # let token: egg_mode::Token = unimplemented!();
+    /// use egg_mode::stream::{self, StreamMessage};
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut stream = stream::sample(&token).auto_reconnect();
+    /// while let Some(msg) = stream.try_next().await.unwrap() {
+    ///     match msg {
+    ///         StreamMessage::Tweet(tweet) => println!("{}", tweet.text),
+    ///         StreamMessage::Reconnecting { error, delay, .. } => {
+    ///             eprintln!("stream dropped ({}), reconnecting in {:?}", error, delay)
+    ///         }
+    ///         _ => (),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn auto_reconnect(self) -> TwitterStream {
+        TwitterStream {
+            reconnect: true,
+            ..self
+        }
+    }
+
+    /// Reads the stream's messages into a buffer of up to `capacity` messages in the background,
+    /// returning a stream that takes messages from that buffer.
+    ///
+    /// A `TwitterStream` only reads from its connection when it's polled, so a consumer that takes
+    /// a while to handle a message also stops the connection from being read. If Twitter's queue
+    /// for the connection fills up, Twitter disconnects it. A `BufferedStream` keeps reading while
+    /// the consumer is busy, and `policy` decides what happens when its buffer fills up: drop the
+    /// oldest messages, end the stream with an error, or stop reading until there's room again.
+    /// That way, a consumer that can't keep up doesn't make memory use grow without bound.
+    ///
+    /// Errors from this stream are passed along in the buffer. An error for a single message, like
+    /// one that couldn't be parsed, doesn't end the buffered stream, but it ends once this stream's
+    /// connection fails for good; set `auto_reconnect` on this stream first to keep it going
+    /// through connection failures. This spawns a task on the current Tokio runtime to read the
+    /// stream, so it needs to be called from inside one. The task stops when the `BufferedStream`
+    /// is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// # let token: egg_mode::Token = unimplemented!();
+    /// use egg_mode::stream::{self, BufferPolicy};
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut stream = stream::sample(&token)
+    ///     .auto_reconnect()
+    ///     .buffered(10_000, BufferPolicy::DropOldest);
+    /// while let Some(msg) = stream.try_next().await.unwrap() {
+    ///     // handle the message, slowly
+    ///     # drop(msg);
+    /// }
+    /// println!("dropped {} messages", stream.dropped());
+    /// # }
+    /// ```
+    pub fn buffered(self, capacity: usize, policy: BufferPolicy) -> BufferedStream {
+        BufferedStream::new(self, capacity, policy)
+    }
+
+    /// Handles an error that ended the connection: if the stream can reconnect, schedules the
+    /// reconnection and returns the message announcing it; otherwise, returns the error.
+    fn connection_lost(
+        &mut self,
+        error: error::Error,
+        kind: FailureKind,
+    ) -> Result<StreamMessage, error::Error> {
+        if !self.reconnect || self.connect.is_none() {
+            return Err(error);
+        }
+
+        self.buf.clear();
+        self.response = None;
+        self.body = None;
+        self.decoder = None;
+        self.stall = None;
+        self.failures += 1;
+        let delay = kind.delay(self.failures);
+        self.delay = Some(Box::pin(tokio::time::sleep(delay)));
+        Ok(StreamMessage::Reconnecting {
+            error,
+            attempt: self.failures,
+            delay,
+        })
+    }
+
+    /// Returns whether the stream is still connected, connecting, or waiting to reconnect. Once a
+    /// connection fails for good, this returns `false`, and the stream won't yield anything else.
+    fn is_open(&self) -> bool {
+        self.request.is_some()
+            || self.response.is_some()
+            || self.body.is_some()
+            || self.delay.is_some()
+    }
+
+    /// Restarts the wait for the connection to stall, since data has just arrived.
+    fn reset_stall(&mut self) {
+        let deadline = tokio::time::Instant::now() + STALL_TIMEOUT;
+        match self.stall.as_mut() {
+            Some(stall) => stall.as_mut().reset(deadline),
+            None => self.stall = Some(Box::pin(tokio::time::sleep_until(deadline))),
+        }
+    }
+
+    /// Checks whether the connection has stalled while waiting for data. If it has, the
+    /// connection is dropped, and this returns the result of `connection_lost`.
+    fn poll_stall(&mut self, cx: &mut Context) -> Poll<Result<StreamMessage, error::Error>> {
+        if self.stall.is_none() {
+            self.reset_stall();
+        }
+        match self.stall.as_mut().map(|stall| stall.as_mut().poll(cx)) {
+            Some(Poll::Ready(())) => {
+                self.response = None;
+                self.body = None;
+                self.stall = None;
+                let stalled = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no data was received from the stream for 90 seconds",
+                );
+                Poll::Ready(self.connection_lost(stalled.into(), FailureKind::Network))
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    /// Takes the first complete message out of the buffer, if there is one. Messages are delimited
+    /// by `\r\n`, and keep-alive messages are empty lines.
+    fn next_message(&mut self) -> Option<Result<StreamMessage, error::Error>> {
+        let pos = self.buf.windows(2).position(|w| w == b"\r\n")? + 2;
+        let resp = if let Ok(msg_str) = std::str::from_utf8(&self.buf[..pos]) {
+            StreamMessage::from_str(msg_str)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+            .into())
+        };
+
+        self.buf.drain(..pos);
+        Some(resp)
+    }
+}
+
+/// The kinds of connection failures that Twitter asks clients to back off from differently.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FailureKind {
+    /// A network error, or the connection being closed.
+    Network,
+    /// An HTTP error status from Twitter's servers.
+    Http,
+    /// A rate-limiting status, 420 or 429.
+    RateLimited,
+}
+
+impl FailureKind {
+    /// Returns the kind of failure for the given error status, or `None` if reconnecting won't
+    /// help.
+    fn for_status(status: hyper::StatusCode) -> Option<FailureKind> {
+        match status.as_u16() {
+            420 | 429 => Some(FailureKind::RateLimited),
+            500..=599 => Some(FailureKind::Http),
+            _ => None,
+        }
+    }
+
+    /// Returns how long to wait before reconnecting after the given number of failures in a row,
+    /// counting from 1.
+    fn delay(self, failures: u32) -> Duration {
+        let doublings = failures.saturating_sub(1).min(16);
+        match self {
+            FailureKind::Network => {
+                (Duration::from_millis(250) * failures).min(Duration::from_secs(16))
+            }
+            FailureKind::Http => {
+                (Duration::from_secs(5) * 2u32.pow(doublings)).min(Duration::from_secs(320))
+            }
+            FailureKind::RateLimited => Duration::from_secs(60) * 2u32.pow(doublings),
         }
     }
 }
@@ -228,67 +593,105 @@ impl Stream for TwitterStream {
     type Item = Result<StreamMessage, error::Error>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if let Some(delay) = self.delay.as_mut() {
+            match delay.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(()) => {
+                    self.delay = None;
+                    let req = self.connect.as_mut().map(|connect| connect());
+                    self.request = req;
+                }
+            }
+        }
+
         if let Some(req) = self.request.take() {
             self.response = Some(get_response(req));
+            self.reset_stall();
         }
 
         if let Some(mut resp) = self.response.take() {
             match Pin::new(&mut resp).poll(cx) {
                 Poll::Pending => {
                     self.response = Some(resp);
-                    return Poll::Pending;
+                    return self.poll_stall(cx).map(Some);
+                }
+                Poll::Ready(Err(e)) => {
+                    let msg = self.connection_lost(e.into(), FailureKind::Network);
+                    return Poll::Ready(Some(msg));
                 }
-                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
                 Poll::Ready(Ok(resp)) => {
                     let status = resp.status();
                     if !status.is_success() {
                         //TODO: should i try to pull the response regardless?
-                        return Poll::Ready(Some(Err(error::Error::BadStatus(status))));
+                        let err = error::Error::BadStatus(status);
+                        let msg = match FailureKind::for_status(status) {
+                            Some(kind) => self.connection_lost(err, kind),
+                            None => Err(err),
+                        };
+                        return Poll::Ready(Some(msg));
                     }
 
+                    self.failures = 0;
+                    self.decoder = compression::Decoder::for_headers(resp.headers());
                     self.body = Some(resp.into_body());
+                    self.reset_stall();
                 }
             }
         }
 
+        // a single chunk can carry several messages, so hand out any that are already buffered
+        // before waiting on the connection again
+        if let Some(msg) = self.next_message() {
+            return Poll::Ready(Some(msg));
+        }
+
         if let Some(mut body) = self.body.take() {
             loop {
                 match Pin::new(&mut body).poll_next(cx) {
                     Poll::Pending => {
                         self.body = Some(body);
-                        return Poll::Pending;
+                        return self.poll_stall(cx).map(Some);
                     }
                     Poll::Ready(None) => {
+                        if self.reconnect && self.connect.is_some() {
+                            let closed = io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "the stream was closed by Twitter",
+                            );
+                            let msg = self.connection_lost(closed.into(), FailureKind::Network);
+                            return Poll::Ready(Some(msg));
+                        }
                         return Poll::Ready(None);
                     }
                     Poll::Ready(Some(Err(e))) => {
                         self.body = Some(body);
-                        return Poll::Ready(Some(Err(e.into())));
+                        let msg = self.connection_lost(e.into(), FailureKind::Network);
+                        return Poll::Ready(Some(msg));
                     }
                     Poll::Ready(Some(Ok(chunk))) => {
-                        self.buf.extend(&*chunk);
+                        let this = &mut *self;
+                        match this.decoder.as_mut() {
+                            Some(decoder) => {
+                                if let Err(e) = decoder.decode(&chunk, &mut this.buf) {
+                                    let msg = self.connection_lost(e.into(), FailureKind::Network);
+                                    return Poll::Ready(Some(msg));
+                                }
+                            }
+                            None => this.buf.extend(&*chunk),
+                        }
+                        self.last_activity = Some(Instant::now());
+                        self.reset_stall();
 
-                        if let Some(pos) = self.buf.windows(2).position(|w| w == b"\r\n") {
+                        if let Some(msg) = self.next_message() {
                             self.body = Some(body);
-                            let pos = pos + 2;
-                            let resp = if let Ok(msg_str) = std::str::from_utf8(&self.buf[..pos]) {
-                                StreamMessage::from_str(msg_str)
-                            } else {
-                                Err(io::Error::new(
-                                    io::ErrorKind::InvalidData,
-                                    "stream did not contain valid UTF-8",
-                                )
-                                .into())
-                            };
-
-                            self.buf.drain(..pos);
-                            return Poll::Ready(Some(Ok(resp?)));
+                            return Poll::Ready(Some(msg));
                         }
                     }
                 }
             }
         } else {
-            Poll::Ready(Some(Err(error::Error::FutureAlreadyCompleted)))
+            // the connection failed for good, and any error about it has already been returned
+            Poll::Ready(None)
         }
     }
 }
@@ -298,7 +701,7 @@ impl Stream for TwitterStream {
 /// According to Twitter's documentation, "When displaying a stream of Tweets to end users
 /// (dashboards or live feeds at a presentation or conference, for example) it is suggested that
 /// you set this value to medium."
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum FilterLevel {
     /// No filtering.
     #[serde(rename = "none")]
@@ -336,11 +739,12 @@ impl ::std::fmt::Display for FilterLevel {
 /// the stream will __fail__ at point of connection.
 pub struct StreamBuilder {
     url: &'static str,
-    follow: Vec<u64>,
+    follow: Vec<UserId>,
     track: Vec<String>,
     language: Vec<String>,
     locations: Vec<BoundingBox>,
     filter_level: Option<FilterLevel>,
+    stall_warnings: bool,
 }
 
 impl StreamBuilder {
@@ -352,6 +756,7 @@ impl StreamBuilder {
             language: Vec::new(),
             locations: Vec::new(),
             filter_level: None,
+            stall_warnings: false,
         }
     }
 
@@ -367,8 +772,12 @@ impl StreamBuilder {
     ///     .start(&token);
     /// # }
     /// ```
-    pub fn follow(mut self, to_follow: &[u64]) -> Self {
-        self.follow.extend(to_follow.iter());
+    pub fn follow<T, I>(mut self, to_follow: I) -> Self
+    where
+        T: Into<UserId>,
+        I: IntoIterator<Item = T>,
+    {
+        self.follow.extend(to_follow.into_iter().map(Into::into));
         self
     }
 
@@ -428,6 +837,15 @@ impl StreamBuilder {
         }
     }
 
+    /// Asks Twitter to send `StreamMessage::StallWarning` messages when the stream is being read
+    /// too slowly and is at risk of being disconnected. By default, no warnings are sent.
+    pub fn stall_warnings(self, stall_warnings: bool) -> StreamBuilder {
+        StreamBuilder {
+            stall_warnings,
+            ..self
+        }
+    }
+
     /// Finalizes the stream parameters and returns the resulting `TwitterStream`.
     pub fn start(self, token: &Token) -> TwitterStream {
         // Re connection failure, arguably this library should check that either 'track' or
@@ -438,6 +856,10 @@ impl StreamBuilder {
         let mut params =
             ParamList::new().add_opt_param("filter_level", self.filter_level.map_string());
 
+        if self.stall_warnings {
+            params.add_param_ref("stall_warnings", "true");
+        }
+
         if !self.follow.is_empty() {
             let to_follow = self
                 .follow
@@ -468,9 +890,8 @@ impl StreamBuilder {
             params.add_param_ref("locations", locs);
         }
 
-        let req = post(self.url, token, Some(&params));
-
-        TwitterStream::new(req)
+        let (url, token) = (self.url, token.clone());
+        TwitterStream::with_connector(move || post(url, &token, Some(&params)))
     }
 }
 
@@ -489,8 +910,8 @@ pub fn filter() -> StreamBuilder {
 /// [`StreamBuilder`]: struct.StreamBuilder.html
 /// [`filter`]: fn.filter.html
 pub fn sample(token: &Token) -> TwitterStream {
-    let req = get(links::stream::SAMPLE, token, None);
-    TwitterStream::new(req)
+    let token = token.clone();
+    TwitterStream::with_connector(move || get(links::stream::SAMPLE, &token, None))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -545,6 +966,187 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn split_stream_messages() {
+        let tweet = load_file("sample_payloads/sample-stream.json").replace("\n", "");
+        let (head, tail) = tweet.split_at(100);
+        // two messages and a ping in the first chunk, and a message split across chunks
+        let chunks = vec![
+            Ok::<_, io::Error>(format!(
+                "{{\"delete\":{{\"status\":{{\"id\":1,\"user_id\":2}}}}}}\r\n\r\n{}",
+                head
+            )),
+            Ok(format!("{}\r\n", tail)),
+        ];
+        let mut stream = TwitterStream {
+            body: Some(Body::wrap_stream(futures::stream::iter(chunks))),
+            ..TwitterStream::new_empty()
+        };
+
+        let mut msgs = vec![];
+        while let Some(msg) = futures::StreamExt::next(&mut stream).await {
+            msgs.push(msg.unwrap());
+        }
+        assert_eq!(msgs.len(), 3);
+        assert!(matches!(
+            msgs[0],
+            StreamMessage::Delete {
+                status_id: TweetId(1),
+                user_id: UserId(2)
+            }
+        ));
+        assert!(matches!(msgs[1], StreamMessage::Ping));
+        assert!(matches!(msgs[2], StreamMessage::Tweet(_)));
+    }
+
+    #[test]
+    fn reconnect_delays() {
+        let delays = |kind: FailureKind| (1..=8).map(|n| kind.delay(n)).collect::<Vec<_>>();
+        let ms = Duration::from_millis;
+        let secs = Duration::from_secs;
+
+        assert_eq!(
+            delays(FailureKind::Network),
+            [
+                ms(250),
+                ms(500),
+                ms(750),
+                ms(1000),
+                ms(1250),
+                ms(1500),
+                ms(1750),
+                ms(2000)
+            ]
+        );
+        assert_eq!(FailureKind::Network.delay(100), secs(16));
+        assert_eq!(
+            delays(FailureKind::Http),
+            [
+                secs(5),
+                secs(10),
+                secs(20),
+                secs(40),
+                secs(80),
+                secs(160),
+                secs(320),
+                secs(320)
+            ]
+        );
+        assert_eq!(FailureKind::RateLimited.delay(3), secs(240));
+
+        assert_eq!(
+            FailureKind::for_status(hyper::StatusCode::from_u16(420).unwrap()),
+            Some(FailureKind::RateLimited)
+        );
+        assert_eq!(
+            FailureKind::for_status(hyper::StatusCode::SERVICE_UNAVAILABLE),
+            Some(FailureKind::Http)
+        );
+        assert_eq!(
+            FailureKind::for_status(hyper::StatusCode::UNAUTHORIZED),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn reconnect_when_closed() {
+        use futures::StreamExt;
+
+        let closed = || TwitterStream {
+            body: Some(Body::from("\r\n")),
+            connect: Some(Box::new(|| Request::new(Body::empty()))),
+            ..TwitterStream::new_empty()
+        };
+
+        let mut stream = closed();
+        assert!(stream.last_activity().is_none());
+        assert!(matches!(stream.next().await, Some(Ok(StreamMessage::Ping))));
+        assert!(stream.last_activity().is_some());
+        assert!(stream.next().await.is_none());
+
+        let mut stream = closed().auto_reconnect();
+        assert!(matches!(stream.next().await, Some(Ok(StreamMessage::Ping))));
+        match stream.next().await {
+            Some(Ok(StreamMessage::Reconnecting { attempt, delay, .. })) => {
+                assert_eq!(attempt, 1);
+                assert_eq!(delay, Duration::from_millis(250));
+            }
+            other => panic!("Not a reconnect: {:?}", other),
+        }
+        assert!(stream.delay.is_some());
+    }
+
+    #[tokio::test]
+    async fn reconnect_when_stalled() {
+        use futures::StreamExt;
+
+        // keep the sender alive so the body waits for data that never comes
+        let (_sender, body) = Body::channel();
+        let mut stream = TwitterStream {
+            body: Some(body),
+            connect: Some(Box::new(|| Request::new(Body::empty()))),
+            stall: Some(Box::pin(tokio::time::sleep(Duration::from_secs(0)))),
+            ..TwitterStream::new_empty()
+        }
+        .auto_reconnect();
+
+        match stream.next().await {
+            Some(Ok(StreamMessage::Reconnecting { error, .. })) => {
+                assert!(
+                    matches!(error, error::Error::IOError(e) if e.kind() == io::ErrorKind::TimedOut)
+                );
+            }
+            other => panic!("Not a reconnect: {:?}", other),
+        }
+        assert!(stream.body.is_none());
+    }
+
+    #[tokio::test]
+    async fn end_when_stalled() {
+        use futures::StreamExt;
+
+        let (_sender, body) = Body::channel();
+        let mut stream = TwitterStream {
+            body: Some(body),
+            stall: Some(Box::pin(tokio::time::sleep(Duration::from_secs(0)))),
+            ..TwitterStream::new_empty()
+        };
+
+        match stream.next().await {
+            Some(Err(error::Error::IOError(e))) => assert_eq!(e.kind(), io::ErrorKind::TimedOut),
+            other => panic!("Not a timeout: {:?}", other),
+        }
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn parse_notices() {
+        let limit =
+            StreamMessage::from_str(r#"{"limit":{"track":1234,"timestamp_ms":"1415138006519"}}"#);
+        assert!(matches!(limit.unwrap(), StreamMessage::Limit(1234)));
+
+        let warning = StreamMessage::from_str(
+            r#"{"warning":{"code":"FALLING_BEHIND","message":"Your connection is falling behind and messages are being queued for delivery to you. Your queue is now over 60% full. You will be disconnected when the queue is full.","percent_full":60}}"#,
+        );
+        match warning.unwrap() {
+            StreamMessage::StallWarning {
+                code, percent_full, ..
+            } => {
+                assert_eq!(code, "FALLING_BEHIND");
+                assert_eq!(percent_full, 60);
+            }
+            other => panic!("Not a stall warning: {:?}", other),
+        }
+
+        let disconnect = StreamMessage::from_str(
+            r#"{"disconnect":{"code":7,"stream_name":"user-12","reason":"admin logout"}}"#,
+        );
+        assert!(matches!(
+            disconnect.unwrap(),
+            StreamMessage::Disconnect(7, _)
+        ));
+    }
+
     #[test]
     fn parse_empty_stream() {
         let msg = StreamMessage::from_str("").unwrap();
@@ -0,0 +1,198 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::StreamTweetV2;
+
+/// A rule from the v2 filtered stream that caused a tweet to be delivered.
+///
+/// Each tweet from [`filter_v2`] carries a `matching_rules` list with every rule that the tweet
+/// matched.
+///
+/// [`filter_v2`]: fn.filter_v2.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MatchingRule {
+    /// The ID Twitter assigned to the rule when it was created.
+    pub id: String,
+    /// The tag given to the rule when it was created, if any.
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+/// The match history of a single filtered stream rule, as tracked by [`RuleStats`].
+///
+/// [`RuleStats`]: struct.RuleStats.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleHits {
+    /// The tag of the rule, as last seen in a stream message.
+    pub tag: Option<String>,
+    /// The number of messages that matched this rule.
+    pub count: u64,
+    /// The time at which the most recent matching message was recorded.
+    pub last_match: DateTime<Utc>,
+}
+
+/// Per-rule match counts for a v2 filtered stream.
+///
+/// The v2 filtered stream lets you register a set of rules and delivers every tweet that matches
+/// any of them, but Twitter doesn't report how often each rule fires. Since the number of rules
+/// you can register is limited, it's useful to know which ones never match so they can be pruned.
+/// A `RuleStats` keeps that accounting locally: pass each tweet from the stream to `record`, and
+/// it counts a hit (and the time of the hit) against every rule that tweet matched.
+///
+/// Stats are keyed by rule ID, since tags are optional and don't need to be unique. Use `by_tag`
+/// to add up the hits of every rule sharing a tag.
+///
+/// `RuleStats` implements `Serialize` and `Deserialize`, so you can save it between runs if you
+/// want to keep counting over a longer period than a single connection.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::stream::{self, RuleStats};
+/// use egg_mode::v2::Fields;
+/// use futures::TryStreamExt;
+///
+/// let mut stats = RuleStats::new();
+/// let mut stream = stream::filter_v2(&Fields::new(), &token);
+///
+/// while let Some(tweet) = stream.try_next().await.unwrap() {
+///     stats.record(&tweet);
+/// }
+///
+/// for rule in stats.never_matched(&["1273026480692322304", "1273026480692322305"]) {
+///     println!("rule {} never matched", rule);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleStats {
+    rules: HashMap<String, RuleHits>,
+}
+
+impl RuleStats {
+    /// Creates a new, empty `RuleStats`.
+    pub fn new() -> RuleStats {
+        RuleStats::default()
+    }
+
+    /// Records a hit against every rule the given tweet matched, returning the number of rules
+    /// recorded.
+    pub fn record(&mut self, tweet: &StreamTweetV2) -> usize {
+        self.record_rules(&tweet.matching_rules, Utc::now());
+        tweet.matching_rules.len()
+    }
+
+    /// Records a hit at the given time against each of the given rules.
+    pub fn record_rules(&mut self, rules: &[MatchingRule], at: DateTime<Utc>) {
+        for rule in rules {
+            let hits = self.rules.entry(rule.id.clone()).or_insert(RuleHits {
+                tag: None,
+                count: 0,
+                last_match: at,
+            });
+            hits.count += 1;
+            if at > hits.last_match {
+                hits.last_match = at;
+            }
+            if rule.tag.is_some() {
+                hits.tag = rule.tag.clone();
+            }
+        }
+    }
+
+    /// Returns the match history of the rule with the given ID, if it has matched anything yet.
+    pub fn get(&self, rule_id: &str) -> Option<&RuleHits> {
+        self.rules.get(rule_id)
+    }
+
+    /// Returns the number of messages that matched the rule with the given ID.
+    pub fn count(&self, rule_id: &str) -> u64 {
+        self.get(rule_id).map_or(0, |hits| hits.count)
+    }
+
+    /// Returns the total number of hits and the most recent match of every rule with the given
+    /// tag, or `None` if no rule with that tag has matched anything.
+    pub fn by_tag(&self, tag: &str) -> Option<(u64, DateTime<Utc>)> {
+        self.rules
+            .values()
+            .filter(|hits| hits.tag.as_deref() == Some(tag))
+            .fold(None, |acc, hits| match acc {
+                None => Some((hits.count, hits.last_match)),
+                Some((count, last)) => Some((count + hits.count, last.max(hits.last_match))),
+            })
+    }
+
+    /// Returns an iterator over the ID and match history of every rule that has matched anything.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RuleHits)> {
+        self.rules.iter().map(|(id, hits)| (id.as_str(), hits))
+    }
+
+    /// Returns the IDs from the given list of rules that haven't matched anything.
+    ///
+    /// Pass in the IDs of every rule currently registered on the stream to find the ones that can
+    /// be pruned.
+    pub fn never_matched<'a, S: AsRef<str> + ?Sized + 'a>(
+        &self,
+        rule_ids: impl IntoIterator<Item = &'a S>,
+    ) -> Vec<&'a str> {
+        rule_ids
+            .into_iter()
+            .map(|id| id.as_ref())
+            .filter(|id| !self.rules.contains_key(*id))
+            .collect()
+    }
+
+    /// Returns the IDs of the rules whose most recent match was before the given time.
+    pub fn idle_since(&self, since: DateTime<Utc>) -> Vec<&str> {
+        self.iter()
+            .filter(|(_, hits)| hits.last_match < since)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Forgets every recorded hit.
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    use chrono::Duration;
+
+    #[test]
+    fn count_rule_hits() {
+        let sample = load_file("sample_payloads/sample-stream-v2.json");
+        let mut tweet: StreamTweetV2 = ::serde_json::from_str(&sample).unwrap();
+        let rules = tweet.matching_rules.clone();
+
+        let mut stats = RuleStats::new();
+        assert_eq!(stats.record(&tweet), 2);
+        assert_eq!(stats.record(&tweet), 2);
+        tweet.matching_rules.clear();
+        assert_eq!(stats.record(&tweet), 0);
+
+        assert_eq!(stats.count("1273028376882589696"), 2);
+        assert_eq!(stats.by_tag("cats").unwrap().0, 2);
+        assert_eq!(
+            stats.never_matched(&["1273028376882589696", "1273028376882589697"]),
+            vec!["1273028376882589697"]
+        );
+
+        let later = Utc::now() + Duration::hours(1);
+        stats.record_rules(&rules[..1], later);
+        assert_eq!(stats.idle_since(later), vec!["1273026480692322304"]);
+    }
+}
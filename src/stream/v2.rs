@@ -0,0 +1,567 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ops::RangeInclusive;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures::Stream;
+use serde::Deserialize;
+
+use super::{MatchingRule, StreamMessage, TwitterStream};
+use crate::auth::Token;
+use crate::common::*;
+use crate::{error, links, v2};
+
+/// A tweet delivered by a v2 streaming endpoint, as returned by [`TweetStreamV2`] and
+/// [`PartitionedStreamV2`].
+///
+/// [`TweetStreamV2`]: struct.TweetStreamV2.html
+/// [`PartitionedStreamV2`]: struct.PartitionedStreamV2.html
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamTweetV2 {
+    /// The tweet.
+    pub data: v2::Tweet,
+    /// The related objects requested with `Fields::expand`.
+    #[serde(default)]
+    pub includes: v2::Includes,
+    /// Any problems loading parts of the tweet.
+    #[serde(default)]
+    pub errors: Vec<v2::PartialError>,
+    /// The rules the tweet matched, for tweets from [`filter_v2`]. This is empty for tweets from
+    /// the other streams.
+    ///
+    /// [`filter_v2`]: fn.filter_v2.html
+    #[serde(default)]
+    pub matching_rules: Vec<MatchingRule>,
+}
+
+/// A `Stream` of the tweets delivered by a v2 streaming endpoint, like [`filter_v2`] or
+/// [`sample_v2`].
+///
+/// Each item is a v2 `Tweet`, along with the `includes` and partial errors for the fields and
+/// expansions that were requested when the stream was opened, and the rules it matched if it came
+/// from the filtered stream. If Twitter sends a list of errors in
+/// place of a tweet, like when the stream is about to be disconnected, it's returned as
+/// `Error::TwitterErrorV2`. Keep-alive messages, and any other message without a tweet or errors
+/// in it, are skipped.
+///
+/// [`filter_v2`]: fn.filter_v2.html
+/// [`sample_v2`]: fn.sample_v2.html
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct TweetStreamV2 {
+    inner: TwitterStream,
+}
+
+impl TweetStreamV2 {
+    /// Sets the stream to reconnect automatically when its connection fails or is closed, rather
+    /// than ending. This follows the same rules as [`TwitterStream::auto_reconnect`], but since
+    /// this stream only yields tweets, the reconnection notices are skipped.
+    ///
+    /// [`TwitterStream::auto_reconnect`]: struct.TwitterStream.html#method.auto_reconnect
+    pub fn auto_reconnect(self) -> TweetStreamV2 {
+        TweetStreamV2 {
+            inner: self.inner.auto_reconnect(),
+        }
+    }
+
+    /// Returns when data last arrived on the stream's connection, including keep-alive messages,
+    /// or `None` if nothing has arrived yet. Since this stream skips the keep-alive messages, this
+    /// is the way to see that the connection is still alive when no tweets are arriving.
+    pub fn last_activity(&self) -> Option<Instant> {
+        self.inner.last_activity()
+    }
+}
+
+/// Pulls the tweet out of a message from a v2 stream, if it has one, or the errors Twitter sent
+/// in its place.
+fn parse_tweet(msg: StreamMessage) -> Option<error::Result<StreamTweetV2>> {
+    match msg {
+        StreamMessage::Unknown(value) if value.get("data").is_some() => {
+            Some(serde_json::from_value(value).map_err(Into::into))
+        }
+        StreamMessage::Unknown(value) => {
+            let errors = value.get("errors")?;
+            match Vec::<v2::PartialError>::deserialize(errors) {
+                Ok(errors) => Some(Err(error::Error::TwitterErrorV2(errors))),
+                Err(e) => Some(Err(e.into())),
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Stream for TweetStreamV2 {
+    type Item = error::Result<StreamTweetV2>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(msg))) => {
+                    if let Some(tweet) = parse_tweet(msg) {
+                        return Poll::Ready(Some(tweet));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Opens a stream of about 1% of all public tweets, in real time, using the v2 API.
+///
+/// This is the v2 counterpart to [`sample`]. The tweets are returned as v2 `Tweet`s, with the
+/// optional fields and expansions requested in `fields`. The v2 sampled stream requires a Bearer
+/// token rather than a user access token.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::v2::{Expansion, Fields, TweetField};
+/// use futures::TryStreamExt;
+///
+/// let fields = Fields::new()
+///     .tweet(&[TweetField::CreatedAt, TweetField::Lang])
+///     .expand(&[Expansion::AuthorId]);
+/// let mut stream = egg_mode::stream::sample_v2(&fields, &token).auto_reconnect();
+/// while let Some(tweet) = stream.try_next().await.unwrap() {
+///     let author = tweet.data.author_id.and_then(|id| tweet.includes.user(id));
+///     println!("{:?}: {}", author.map(|u| &u.username), tweet.data.text);
+/// }
+/// # }
+/// ```
+///
+/// [`sample`]: fn.sample.html
+pub fn sample_v2(fields: &v2::Fields, token: &Token) -> TweetStreamV2 {
+    let params = fields.add_params(ParamList::new());
+    connect(links::stream::V2_SAMPLE, params, token)
+}
+
+/// Opens a stream of the tweets that match the rules registered for your app, in real time, using
+/// the v2 filtered stream.
+///
+/// Rather than taking its filters as parameters like [`filter`], the v2 filtered stream delivers
+/// every tweet that matches any of the rules registered for your app. Each tweet's
+/// `matching_rules` lists the rules it matched; pass it to [`RuleStats::record`] to keep count of
+/// how often each one fires. The tweets are returned as v2 `Tweet`s, with the optional fields and
+/// expansions requested in `fields`. The v2 filtered stream requires a Bearer token rather than a
+/// user access token.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::v2::Fields;
+/// use futures::TryStreamExt;
+///
+/// let mut stream = egg_mode::stream::filter_v2(&Fields::new(), &token).auto_reconnect();
+/// while let Some(tweet) = stream.try_next().await.unwrap() {
+///     let tags = tweet.matching_rules.iter().filter_map(|r| r.tag.as_ref());
+///     println!("{:?}: {}", tags.collect::<Vec<_>>(), tweet.data.text);
+/// }
+/// # }
+/// ```
+///
+/// [`filter`]: fn.filter.html
+/// [`RuleStats::record`]: struct.RuleStats.html#method.record
+pub fn filter_v2(fields: &v2::Fields, token: &Token) -> TweetStreamV2 {
+    let params = fields.add_params(ParamList::new());
+    connect(links::stream::V2_FILTER, params, token)
+}
+
+fn connect(url: &'static str, params: ParamList, token: &Token) -> TweetStreamV2 {
+    let token = token.clone();
+    let inner = TwitterStream::with_connector(move || get(url, &token, Some(&params)));
+    TweetStreamV2 { inner }
+}
+
+/// The v2 streaming endpoints whose tweets are split across several connections, or partitions.
+///
+/// These endpoints deliver more tweets than one connection can comfortably carry, so each tweet
+/// is only sent on one of their partitions. Access to them is only available to some enterprise
+/// accounts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PartitionedEndpoint {
+    /// A random sample of about 10% of all public tweets, split across 2 partitions.
+    Sample10,
+    /// Every public tweet, split across 20 partitions.
+    Firehose,
+}
+
+impl PartitionedEndpoint {
+    /// Returns the partitions this endpoint is split into, numbered from 1.
+    pub fn partitions(self) -> RangeInclusive<u8> {
+        match self {
+            PartitionedEndpoint::Sample10 => 1..=2,
+            PartitionedEndpoint::Firehose => 1..=20,
+        }
+    }
+
+    fn url(self) -> &'static str {
+        match self {
+            PartitionedEndpoint::Sample10 => links::stream::V2_SAMPLE10,
+            PartitionedEndpoint::Firehose => links::stream::V2_FIREHOSE,
+        }
+    }
+}
+
+/// A `Stream` that merges the tweets from several partitions of a [`PartitionedEndpoint`].
+/// Created with [`partitioned_v2`].
+///
+/// Each partition has its own connection, so with `auto_reconnect`, a partition that fails is
+/// reconnected on its own, with its own backoff, while the others keep delivering tweets. Without
+/// it, a partition's errors are returned as they happen, and the partition is dropped once its
+/// connection ends or fails, with the stream going on with the rest; the stream ends once every
+/// partition has ended.
+///
+/// [`PartitionedEndpoint`]: enum.PartitionedEndpoint.html
+/// [`partitioned_v2`]: fn.partitioned_v2.html
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct PartitionedStreamV2 {
+    partitions: Vec<(u8, TweetStreamV2)>,
+    /// The index of the partition to poll first, rotated on each poll so every partition gets
+    /// its turn.
+    next: usize,
+}
+
+impl PartitionedStreamV2 {
+    /// Sets every partition to reconnect automatically when its connection fails or is closed.
+    /// See [`TwitterStream::auto_reconnect`] for the rules this follows.
+    ///
+    /// [`TwitterStream::auto_reconnect`]: struct.TwitterStream.html#method.auto_reconnect
+    pub fn auto_reconnect(self) -> PartitionedStreamV2 {
+        PartitionedStreamV2 {
+            partitions: self
+                .partitions
+                .into_iter()
+                .map(|(partition, stream)| (partition, stream.auto_reconnect()))
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Returns when data last arrived on any of the partitions' connections, including keep-alive
+    /// messages, or `None` if nothing has arrived yet.
+    pub fn last_activity(&self) -> Option<Instant> {
+        self.partitions
+            .iter()
+            .filter_map(|(_, stream)| stream.last_activity())
+            .max()
+    }
+
+    /// Returns the partitions that are still connected or reconnecting.
+    pub fn partitions(&self) -> Vec<u8> {
+        self.partitions
+            .iter()
+            .map(|&(partition, _)| partition)
+            .collect()
+    }
+}
+
+impl Stream for PartitionedStreamV2 {
+    type Item = error::Result<StreamTweetV2>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let count = this.partitions.len();
+        for offset in 0..count {
+            let ix = (this.next + offset) % count;
+            match Pin::new(&mut this.partitions[ix].1).poll_next(cx) {
+                Poll::Ready(Some(Ok(tweet))) => {
+                    this.next = (ix + 1) % count;
+                    return Poll::Ready(Some(Ok(tweet)));
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    //an error doesn't necessarily end the partition's stream (a single message
+                    //can fail to parse, for example), so only drop it if its connection is gone
+                    if this.partitions[ix].1.inner.is_open() {
+                        this.next = (ix + 1) % count;
+                    } else {
+                        drop(this.partitions.remove(ix));
+                        this.next = ix;
+                    }
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => {
+                    drop(this.partitions.remove(ix));
+                    this.next = ix;
+                    // the rest of the partitions have shifted down, so start over from here
+                    return self.poll_next(cx);
+                }
+                Poll::Pending => (),
+            }
+        }
+
+        if this.partitions.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Opens the given partitions of a partitioned v2 stream, and merges their tweets into one
+/// `Stream`.
+///
+/// Pass `endpoint.partitions()` to open every partition, or a subset of them to split the stream
+/// across several processes. The tweets are returned as v2 `Tweet`s, with the optional fields and
+/// expansions requested in `fields`.
+///
+/// Returns `Error::InvalidParam` if no partitions are given, or if one of them isn't a partition
+/// of the endpoint.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::stream::{self, PartitionedEndpoint};
+/// use egg_mode::v2::Fields;
+/// use futures::TryStreamExt;
+///
+/// let endpoint = PartitionedEndpoint::Sample10;
+/// let mut stream = stream::partitioned_v2(endpoint, endpoint.partitions(), &Fields::new(), &token)
+///     .unwrap()
+///     .auto_reconnect();
+/// while let Some(tweet) = stream.try_next().await.unwrap() {
+///     println!("{}", tweet.data.text);
+/// }
+/// # }
+/// ```
+pub fn partitioned_v2(
+    endpoint: PartitionedEndpoint,
+    partitions: impl IntoIterator<Item = u8>,
+    fields: &v2::Fields,
+    token: &Token,
+) -> error::Result<PartitionedStreamV2> {
+    let partitions = check_partitions(endpoint, partitions)?;
+    let partitions = partitions
+        .into_iter()
+        .map(|partition| {
+            let params = ParamList::new().add_param("partition", partition.to_string());
+            let params = fields.add_params(params);
+            (partition, connect(endpoint.url(), params, token))
+        })
+        .collect();
+    Ok(PartitionedStreamV2 {
+        partitions,
+        next: 0,
+    })
+}
+
+/// Checks that the given partitions all belong to the given endpoint, and removes duplicates.
+fn check_partitions(
+    endpoint: PartitionedEndpoint,
+    partitions: impl IntoIterator<Item = u8>,
+) -> error::Result<Vec<u8>> {
+    let mut checked = Vec::new();
+    for partition in partitions {
+        if !endpoint.partitions().contains(&partition) {
+            return Err(error::Error::InvalidParam(
+                "partition is out of range for this endpoint",
+            ));
+        }
+        if !checked.contains(&partition) {
+            checked.push(partition);
+        }
+    }
+    if checked.is_empty() {
+        return Err(error::Error::InvalidParam(
+            "at least one partition is required",
+        ));
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn stream_from(body: &'static str) -> TweetStreamV2 {
+        TweetStreamV2 {
+            inner: TwitterStream {
+                body: Some(hyper::Body::from(body)),
+                ..TwitterStream::new_empty()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn sampled_tweets() {
+        let body = hyper::Body::from(concat!(
+            "{\"data\":{\"id\":\"1067094924124872705\",\"text\":\"Just getting started\"}}\r\n",
+            "\r\n",
+            "{\"data\":{\"id\":\"1067094924124872706\",\"text\":\"hi\",",
+            "\"author_id\":\"2244994945\"},",
+            "\"includes\":{\"users\":[{\"id\":\"2244994945\",\"name\":\"Twitter Dev\",",
+            "\"username\":\"TwitterDev\"}]}}\r\n",
+        ));
+        let stream = TweetStreamV2 {
+            inner: TwitterStream {
+                body: Some(body),
+                ..TwitterStream::new_empty()
+            },
+        };
+
+        let tweets = stream.collect::<Vec<_>>().await;
+        assert_eq!(tweets.len(), 2);
+        let first = tweets[0].as_ref().unwrap();
+        assert_eq!(first.data.id, 1067094924124872705);
+        assert_eq!(first.data.text, "Just getting started");
+
+        let second = tweets[1].as_ref().unwrap();
+        let author = second
+            .data
+            .author_id
+            .and_then(|id| second.includes.user(id));
+        assert_eq!(author.unwrap().username, "TwitterDev");
+    }
+
+    #[tokio::test]
+    async fn filtered_tweets() {
+        let sample = crate::common::tests::load_file("sample_payloads/sample-stream-v2.json");
+        let body = format!("{}\r\n", sample.replace('\n', ""));
+        let stream = TweetStreamV2 {
+            inner: TwitterStream {
+                body: Some(hyper::Body::from(body)),
+                ..TwitterStream::new_empty()
+            },
+        };
+
+        let tweets = stream.collect::<Vec<_>>().await;
+        let tweet = tweets[0].as_ref().unwrap();
+        assert_eq!(tweet.data.id, 1273027188993327104);
+        assert_eq!(tweet.matching_rules.len(), 2);
+        assert_eq!(tweet.matching_rules[0].tag.as_deref(), Some("cats"));
+    }
+
+    #[tokio::test]
+    async fn stream_errors() {
+        let stream = stream_from(concat!(
+            "{\"errors\":[{\"title\":\"operational-disconnect\",",
+            "\"detail\":\"This stream has been disconnected upstream for operational reasons.\"}]}\r\n",
+            "{\"data\":{\"id\":\"1\",\"text\":\"one\"}}\r\n",
+        ));
+
+        let items = stream.collect::<Vec<_>>().await;
+        assert_eq!(items.len(), 2);
+        match items[0] {
+            Err(error::Error::TwitterErrorV2(ref errors)) => {
+                assert_eq!(errors[0].title, "operational-disconnect")
+            }
+            ref other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(items[1].as_ref().unwrap().data.id, 1);
+    }
+
+    #[test]
+    fn partition_ranges() {
+        let sample10 = PartitionedEndpoint::Sample10;
+        assert_eq!(
+            check_partitions(sample10, vec![2, 1, 2]).unwrap(),
+            vec![2, 1]
+        );
+        assert_eq!(
+            check_partitions(
+                PartitionedEndpoint::Firehose,
+                PartitionedEndpoint::Firehose.partitions()
+            )
+            .unwrap()
+            .len(),
+            20
+        );
+        assert!(check_partitions(sample10, vec![1, 3]).is_err());
+        assert!(check_partitions(sample10, vec![0]).is_err());
+        assert!(check_partitions(sample10, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn merged_partitions() {
+        let stream = PartitionedStreamV2 {
+            partitions: vec![
+                (
+                    1,
+                    stream_from(concat!(
+                        "{\"data\":{\"id\":\"1\",\"text\":\"one\"}}\r\n",
+                        "{\"data\":{\"id\":\"3\",\"text\":\"three\"}}\r\n",
+                    )),
+                ),
+                (
+                    2,
+                    stream_from("{\"data\":{\"id\":\"2\",\"text\":\"two\"}}\r\n"),
+                ),
+            ],
+            next: 0,
+        };
+
+        let ids = stream
+            .map(|tweet| tweet.unwrap().data.id)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn partition_errors() {
+        let stream = PartitionedStreamV2 {
+            partitions: vec![
+                (
+                    1,
+                    stream_from(concat!(
+                        "{\"data\":{\"id\":\"1\"}}\r\n",
+                        "{\"data\":{\"id\":\"3\",\"text\":\"three\"}}\r\n",
+                    )),
+                ),
+                (
+                    2,
+                    stream_from("{\"data\":{\"id\":\"2\",\"text\":\"two\"}}\r\n"),
+                ),
+            ],
+            next: 0,
+        };
+
+        //the message that fails to parse doesn't take the rest of its partition with it
+        let tweets = stream.collect::<Vec<_>>().await;
+        assert_eq!(tweets.len(), 3);
+        assert!(tweets[0].is_err());
+        assert_eq!(tweets[1].as_ref().unwrap().data.id, 2);
+        assert_eq!(tweets[2].as_ref().unwrap().data.id, 3);
+
+        //but a partition whose connection is gone is dropped after its error is returned
+        let (_sender, body) = hyper::Body::channel();
+        let stalled = TwitterStream {
+            body: Some(body),
+            stall: Some(Box::pin(tokio::time::sleep(
+                std::time::Duration::from_secs(0),
+            ))),
+            ..TwitterStream::new_empty()
+        };
+        let stream = PartitionedStreamV2 {
+            partitions: vec![
+                (1, TweetStreamV2 { inner: stalled }),
+                (
+                    2,
+                    stream_from("{\"data\":{\"id\":\"2\",\"text\":\"two\"}}\r\n"),
+                ),
+            ],
+            next: 0,
+        };
+        let tweets = stream.collect::<Vec<_>>().await;
+        assert_eq!(tweets.len(), 2);
+        assert_eq!(tweets.iter().filter(|tweet| tweet.is_err()).count(), 1);
+        assert!(tweets
+            .iter()
+            .any(|tweet| matches!(tweet, Ok(tweet) if tweet.data.id == 2)));
+    }
+}
@@ -1,10 +1,29 @@
 use crate::common::*;
 use crate::error::Result;
-use crate::trend::TrendLocation;
+use crate::trend::{TrendList, TrendLocation};
 use crate::{auth, links};
 
 ///Returns the locations that Twitter has trending topic information for, closest to a
 ///specified location.
+///
+///The result is usually a single location, whose `woeid` can be used to look up the trends around
+///the given coordinate.
+///
+///## Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///let locations = egg_mode::trend::closest(37.781157, -122.400612, &token)
+///    .await
+///    .unwrap();
+///for location in locations.iter() {
+///    println!("{} ({}): {}", location.name, location.place_type.name, location.woeid);
+///}
+///# }
+///```
 pub async fn closest(
     lat: f32,
     long: f32,
@@ -20,7 +39,73 @@ pub async fn closest(
 }
 
 ///Returns the locations that Twitter has trending topic information for.
+///
+///This includes the "Worldwide" location, with a `woeid` of 1, as well as the countries and
+///cities that have their own trends.
+///
+///## Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///let locations = egg_mode::trend::available(&token).await.unwrap();
+///let cities = locations.iter().filter(|loc| loc.place_type.name == "Town");
+///for city in cities {
+///    println!("{}, {}", city.name, city.country);
+///}
+///# }
+///```
 pub async fn available(token: &auth::Token) -> Result<Response<Vec<TrendLocation>>> {
     let req = get(links::trend::AVAILABLE, token, None);
     request_with_json_response(req).await
 }
+
+///Returns the trending topics for the given location.
+///
+///The location is given by its "where on earth identifier", as found in the `woeid` of the
+///locations returned by `available` or `closest`, or 1 for trends across the world. Trends are
+///cached for five minutes, so there's no point in loading them more often than that. If
+///`exclude_hashtags` is true, the list will leave out hashtags, and only include other kinds of
+///trending topics.
+///
+///## Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///let list = egg_mode::trend::place(1, false, &token).await.unwrap();
+///println!("trending worldwide as of {}:", list.as_of);
+///for trend in list.trends.iter() {
+///    match trend.tweet_volume {
+///        Some(volume) => println!("{} ({} tweets)", trend.name, volume),
+///        None => println!("{}", trend.name),
+///    }
+///}
+///# }
+///```
+pub async fn place(
+    woeid: u32,
+    exclude_hashtags: bool,
+    token: &auth::Token,
+) -> Result<Response<TrendList>> {
+    let params = ParamList::new()
+        .add_param("id", woeid.to_string())
+        .add_opt_param(
+            "exclude",
+            if exclude_hashtags {
+                Some("hashtags")
+            } else {
+                None
+            },
+        );
+
+    let req = get(links::trend::PLACE, token, Some(&params));
+
+    // the response is an array that only ever holds the one list
+    let resp = request_with_json_response::<(TrendList,)>(req).await?;
+    Ok(Response::map(resp, |(list,)| list))
+}
@@ -1,11 +1,15 @@
 //! Sturcts and functions for working with trending topic in Twitter.
 //!
-//! In this module, you are able to get locations with trending topics.
+//! In this module, you are able to get locations with trending topics, and the trending topics
+//! for each of those locations.
 //!
 //! ## Types
 //! - `TrendLocation`: the element of trending information returned by trend API
 //! - `PlaceType`: a member in `TrendLocation`, which includes the code and related name
 //!   to specify the kind of place
+//! - `TrendList`: the trending topics for a location, returned by `place`
+//! - `Trend`: a single trending topic in a `TrendList`
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 mod fun;
@@ -46,6 +50,7 @@ impl From<raw::RawTrendLocation> for TrendLocation {
             place_type: raw.place_type,
             url: raw.url,
             woeid: raw.woeid,
+            raw: None,
         }
     }
 }
@@ -58,3 +63,88 @@ pub struct PlaceType {
     ///The name of the location type
     pub name: String,
 }
+
+///The trending topics for a location, as returned by `place`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrendList {
+    ///The trending topics, in order of how much they're trending.
+    pub trends: Vec<Trend>,
+    ///UTC timestamp from when this list of trends was last updated.
+    pub as_of: DateTime<Utc>,
+    ///UTC timestamp from when this list of trends was first created.
+    pub created_at: DateTime<Utc>,
+    ///The location these trends are for.
+    pub locations: Vec<TrendPlace>,
+}
+
+///A single trending topic, as part of a `TrendList`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Trend {
+    ///The topic itself, as a hashtag or phrase.
+    pub name: String,
+    ///The URL of a Twitter search for this topic.
+    pub url: String,
+    ///The topic, formatted to be used as the query for a search. This is already
+    ///percent-encoded, as it would appear in `url`.
+    pub query: String,
+    ///If present, the number of tweets about this topic in the last 24 hours.
+    pub tweet_volume: Option<u64>,
+}
+
+///The location a `TrendList` is for.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrendPlace {
+    ///The name of the location.
+    pub name: String,
+    ///The "where on earth identifier" of the location, as given to `place`.
+    pub woeid: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_trend_locations() {
+        let content = load_file("sample_payloads/trends_available.json");
+        let locations = ::serde_json::from_str::<Vec<TrendLocation>>(&content).unwrap();
+        assert_eq!(locations.len(), 3);
+
+        let worldwide = &locations[0];
+        assert_eq!(worldwide.name, "Worldwide");
+        assert_eq!(worldwide.woeid, 1);
+        assert_eq!(worldwide.country_code, None);
+        assert_eq!(worldwide.place_type.name, "Supername");
+
+        let city = &locations[2];
+        assert_eq!(city.name, "San Francisco");
+        assert_eq!(city.country_code.as_deref(), Some("US"));
+        assert_eq!(city.parentid, locations[1].woeid);
+        assert_eq!(city.place_type.code, 7);
+    }
+
+    #[test]
+    fn parse_trend_list() {
+        use chrono::TimeZone;
+
+        let content = load_file("sample_payloads/trends_place.json");
+        let (list,) = ::serde_json::from_str::<(TrendList,)>(&content).unwrap();
+
+        assert_eq!(list.trends.len(), 3);
+        assert_eq!(list.trends[0].name, "#GiftAGamer");
+        assert_eq!(list.trends[0].query, "%23GiftAGamer");
+        assert_eq!(list.trends[0].tweet_volume, None);
+        assert_eq!(list.trends[1].tweet_volume, Some(48857));
+        assert_eq!(
+            list.as_of,
+            Utc.with_ymd_and_hms(2017, 2, 8, 16, 18, 18).unwrap()
+        );
+        assert_eq!(
+            list.created_at,
+            Utc.with_ymd_and_hms(2017, 2, 8, 16, 10, 33).unwrap()
+        );
+        assert_eq!(list.locations[0].name, "Worldwide");
+        assert_eq!(list.locations[0].woeid, 1);
+    }
+}
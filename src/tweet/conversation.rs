@@ -0,0 +1,245 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+
+use crate::error::{Error, Result};
+use crate::{auth, search};
+
+use super::{show, Tweet, TweetId};
+
+/// The error codes Twitter returns when a tweet can't be loaded because it was deleted, or because
+/// its author is protected or suspended.
+const MISSING_TWEET_CODES: &[i32] = &[63, 144, 179, 421, 422];
+
+/// Returns whether the given error means the requested tweet isn't available, as opposed to the
+/// request itself failing.
+fn is_missing_tweet(err: &Error) -> bool {
+    match err {
+        Error::TwitterError(_, errs) => errs
+            .errors
+            .iter()
+            .any(|e| MISSING_TWEET_CODES.contains(&e.code)),
+        Error::BadStatus(status) => status.as_u16() == 403 || status.as_u16() == 404,
+        _ => false,
+    }
+}
+
+/// How many pages of search results `conversation` loads by default when looking for replies.
+const DEFAULT_REPLY_PAGES: u32 = 5;
+
+/// How far [`conversation`] searches for replies to the requested tweet.
+///
+/// Each page of search results holds up to 100 tweets and uses up one call of the Search API's
+/// rate limit, so on a busy account, reading every page back to the requested tweet could use up
+/// the whole rate-limit window. The default loads up to 5 pages. A `bool` converts into this type,
+/// with `true` meaning the default and `false` meaning `Skip`.
+///
+/// [`conversation`]: fn.conversation.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplySearch {
+    /// Don't search for replies.
+    Skip,
+    /// Load up to the given number of pages of search results.
+    MaxPages(u32),
+}
+
+impl Default for ReplySearch {
+    fn default() -> ReplySearch {
+        ReplySearch::MaxPages(DEFAULT_REPLY_PAGES)
+    }
+}
+
+impl From<bool> for ReplySearch {
+    fn from(with_replies: bool) -> ReplySearch {
+        if with_replies {
+            ReplySearch::default()
+        } else {
+            ReplySearch::Skip
+        }
+    }
+}
+
+/// A reconstructed conversation, as returned by [`conversation`].
+///
+/// [`conversation`]: fn.conversation.html
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    /// The tweets in the conversation, in the order they were posted.
+    ///
+    /// This starts with the oldest tweet that could be reached by following replies upward from
+    /// the requested tweet, and ends with the requested tweet or (if replies were requested) the
+    /// newest reply that was found.
+    pub tweets: Vec<Tweet>,
+    /// If the chain of replies above the requested tweet was cut short because a tweet in it
+    /// couldn't be loaded, contains the ID of that tweet.
+    ///
+    /// This happens when a tweet in the chain has been deleted, or its author has protected their
+    /// account or been suspended. The first tweet in `tweets` is the reply to this missing tweet.
+    pub missing_parent: Option<TweetId>,
+}
+
+/// Reconstructs the conversation around the given tweet.
+///
+/// This loads the given tweet, then follows its `in_reply_to_status_id` upward one tweet at a
+/// time until it reaches the start of the conversation, so it makes one call to `show` for each
+/// tweet above the given one. If a tweet along the way has been deleted or otherwise can't be
+/// loaded, the walk stops there and the missing ID is saved in `missing_parent`, rather than
+/// returning an error.
+///
+/// Unless `replies` is `ReplySearch::Skip` (or `false`), this also searches for replies posted
+/// after the given tweet, using the Search API to find tweets directed at its author. Any that
+/// reply to a tweet in the conversation (including other replies that were found) are added to
+/// the end. The search stops after the number of pages given in `replies`, so on a busy account
+/// some replies may be missed; see [`ReplySearch`] for details. Note that the standard Search API
+/// only goes back about a week, and only replies that mention the author of the given tweet can
+/// be found this way.
+///
+/// [`ReplySearch`]: enum.ReplySearch.html
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet;
+///
+/// let convo = tweet::conversation(782349500404862976, false, &token).await.unwrap();
+/// for tweet in &convo.tweets {
+///     println!("<@{}> {}", tweet.user.as_ref().unwrap().screen_name, tweet.text);
+/// }
+/// # }
+/// ```
+pub async fn conversation(
+    id: impl Into<TweetId>,
+    replies: impl Into<ReplySearch>,
+    token: &auth::Token,
+) -> Result<Conversation> {
+    let id: TweetId = id.into();
+    let replies = replies.into();
+    let target = show(id, token).await?.response;
+
+    let mut tweets = vec![target.clone()];
+    let mut missing_parent = None;
+    let mut parent = target.in_reply_to_status_id;
+    while let Some(parent_id) = parent {
+        match show(parent_id, token).await {
+            Ok(tweet) => {
+                parent = tweet.in_reply_to_status_id;
+                tweets.push(tweet.response);
+            }
+            Err(e) if is_missing_tweet(&e) => {
+                missing_parent = Some(parent_id);
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    tweets.reverse();
+
+    if let ReplySearch::MaxPages(max_pages) = replies {
+        if let Some(ref author) = target.user {
+            let replies = find_replies(&target, &author.screen_name, max_pages, token).await?;
+            tweets.extend(replies);
+        }
+    }
+
+    Ok(Conversation {
+        tweets,
+        missing_parent,
+    })
+}
+
+/// Searches for replies to the given tweet (and replies to those replies), which were directed at
+/// the given screen name, loading at most `max_pages` pages of search results.
+async fn find_replies(
+    target: &Tweet,
+    screen_name: &str,
+    max_pages: u32,
+    token: &auth::Token,
+) -> Result<Vec<Tweet>> {
+    if max_pages == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut found = Vec::new();
+    let mut page = search::search(format!("to:{}", screen_name))
+        .since_tweet(target.id)
+        .count(100)
+        .call(token)
+        .await?;
+    let mut pages = 1;
+
+    loop {
+        found.extend(page.statuses.iter().filter(|t| t.id > target.id).cloned());
+        match page.statuses.iter().map(|t| t.id).min() {
+            Some(oldest) if oldest > target.id && pages < max_pages => {
+                page = page.older(token).await?;
+                pages += 1;
+            }
+            _ => break,
+        }
+    }
+
+    Ok(thread_replies(target.id, found))
+}
+
+/// Picks out the tweets that reply to the given tweet, either directly or through other replies in
+/// the set, and sorts them in the order they were posted.
+fn thread_replies(root: TweetId, mut candidates: Vec<Tweet>) -> Vec<Tweet> {
+    candidates.sort_by_key(|t| t.id);
+    candidates.dedup_by_key(|t| t.id);
+
+    let mut known = HashSet::new();
+    known.insert(root);
+
+    //since replies are always newer than the tweet they reply to, walking the candidates from
+    //oldest to newest sees each parent before its replies
+    candidates
+        .into_iter()
+        .filter(|t| match t.in_reply_to_status_id {
+            Some(parent) if known.contains(&parent) => {
+                known.insert(t.id);
+                true
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn reply_search_from_bool() {
+        assert_eq!(ReplySearch::from(false), ReplySearch::Skip);
+        assert_eq!(
+            ReplySearch::from(true),
+            ReplySearch::MaxPages(DEFAULT_REPLY_PAGES)
+        );
+    }
+
+    #[test]
+    fn thread_reply_order() {
+        let content = load_file("sample_payloads/sample-reply.json");
+        let reply: Tweet = ::serde_json::from_str(&content).unwrap();
+        let root = reply.in_reply_to_status_id.unwrap();
+
+        let mut nested = reply.clone();
+        nested.id = TweetId(reply.id.0 + 10);
+        nested.in_reply_to_status_id = Some(reply.id);
+
+        let mut unrelated = reply.clone();
+        unrelated.id = TweetId(reply.id.0 + 5);
+        unrelated.in_reply_to_status_id = Some(TweetId(1));
+
+        let replies = thread_replies(root, vec![nested, unrelated, reply.clone(), reply.clone()]);
+        let ids = replies.iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![reply.id, TweetId(reply.id.0 + 10)]);
+    }
+}
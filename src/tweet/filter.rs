@@ -0,0 +1,208 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use futures::TryStreamExt;
+
+use crate::auth;
+use crate::error::Result;
+use crate::stream::StreamMessage;
+use crate::user::{self, UserId};
+
+use super::Tweet;
+
+/// A filter stage that drops tweets from accounts the authenticated user has blocked or muted.
+///
+/// Twitter doesn't remove tweets by blocked or muted accounts from the data it returns over the
+/// API, so clients that want to honor those settings need to do it themselves. A
+/// `RelationshipFilter` keeps a cached copy of the IDs returned by [`user::blocks_ids`] and
+/// [`user::mutes_ids`], and can be used to screen tweets from a `Timeline`, a search, or a stream.
+///
+/// [`user::blocks_ids`]: ../user/fn.blocks_ids.html
+/// [`user::mutes_ids`]: ../user/fn.mutes_ids.html
+///
+/// A new filter starts out empty, so it won't drop anything until it's been loaded with `refresh`.
+/// Since the block and mute lists can change while your application is running, the filter
+/// records when it was last loaded; `refresh_if_stale` will only reload the lists once the
+/// refresh interval has passed. The default interval is 15 minutes, to line up with the rate-limit
+/// window of the underlying endpoints.
+///
+/// Along with the author of the tweet itself, the filter also checks the authors of a retweeted or
+/// quoted tweet, so that a retweet of a muted account is dropped alongside that account's own
+/// tweets.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet::{self, RelationshipFilter};
+///
+/// let mut filter = RelationshipFilter::new();
+/// filter.refresh(&token).await.unwrap();
+///
+/// let timeline = tweet::home_timeline(&token).with_page_size(50);
+/// let (_timeline, feed) = timeline.start().await.unwrap();
+///
+/// for tweet in filter.filter(feed.response) {
+///     println!("{}", tweet.text);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RelationshipFilter {
+    blocked: HashSet<UserId>,
+    muted: HashSet<UserId>,
+    refresh_interval: Duration,
+    last_refresh: Option<Instant>,
+}
+
+impl Default for RelationshipFilter {
+    fn default() -> Self {
+        RelationshipFilter::new()
+    }
+}
+
+impl RelationshipFilter {
+    /// Creates a new, empty filter with the default refresh interval of 15 minutes.
+    pub fn new() -> RelationshipFilter {
+        RelationshipFilter {
+            blocked: HashSet::new(),
+            muted: HashSet::new(),
+            refresh_interval: Duration::from_secs(15 * 60),
+            last_refresh: None,
+        }
+    }
+
+    /// Sets how long the cached block and mute lists are considered fresh.
+    pub fn with_refresh_interval(self, refresh_interval: Duration) -> RelationshipFilter {
+        RelationshipFilter {
+            refresh_interval,
+            ..self
+        }
+    }
+
+    /// Returns the set of blocked account IDs currently held by the filter.
+    pub fn blocked(&self) -> &HashSet<UserId> {
+        &self.blocked
+    }
+
+    /// Returns the set of muted account IDs currently held by the filter.
+    pub fn muted(&self) -> &HashSet<UserId> {
+        &self.muted
+    }
+
+    /// Returns whether the filter has never been loaded, or whether its refresh interval has
+    /// passed since it was last loaded.
+    pub fn is_stale(&self) -> bool {
+        match self.last_refresh {
+            Some(at) => at.elapsed() >= self.refresh_interval,
+            None => true,
+        }
+    }
+
+    /// Reloads the full block and mute lists of the authenticated user.
+    ///
+    /// This walks through every page of `user::blocks_ids` and `user::mutes_ids`. If either call
+    /// fails, the filter keeps its previous contents.
+    pub async fn refresh(&mut self, token: &auth::Token) -> Result<()> {
+        let blocked = user::blocks_ids(token)
+            .map_ok(|r| r.response)
+            .try_collect::<HashSet<UserId>>()
+            .await?;
+        let muted = user::mutes_ids(token)
+            .map_ok(|r| r.response)
+            .try_collect::<HashSet<UserId>>()
+            .await?;
+
+        self.blocked = blocked;
+        self.muted = muted;
+        self.last_refresh = Some(Instant::now());
+
+        Ok(())
+    }
+
+    /// Reloads the block and mute lists if the filter is stale, otherwise does nothing.
+    pub async fn refresh_if_stale(&mut self, token: &auth::Token) -> Result<()> {
+        if self.is_stale() {
+            self.refresh(token).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns whether the given account is blocked or muted.
+    pub fn is_hidden(&self, user_id: impl Into<UserId>) -> bool {
+        let user_id = user_id.into();
+        self.blocked.contains(&user_id) || self.muted.contains(&user_id)
+    }
+
+    /// Returns whether the given tweet should be kept, i.e. neither it nor any tweet it retweets
+    /// or quotes was posted by a blocked or muted account.
+    pub fn allows(&self, tweet: &Tweet) -> bool {
+        if matches!(tweet.user, Some(ref u) if self.is_hidden(u.id)) {
+            return false;
+        }
+
+        if let Some(ref rt) = tweet.retweeted_status {
+            if !self.allows(rt) {
+                return false;
+            }
+        }
+
+        if let Some(ref quote) = tweet.quoted_status {
+            if !self.allows(quote) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns whether the given stream message should be kept. Only `StreamMessage::Tweet`
+    /// messages are ever dropped.
+    pub fn allows_message(&self, msg: &StreamMessage) -> bool {
+        match msg {
+            StreamMessage::Tweet(tweet) => self.allows(tweet),
+            _ => true,
+        }
+    }
+
+    /// Removes all the tweets from the given collection that were posted by (or that retweet or
+    /// quote) blocked or muted accounts.
+    pub fn filter(&self, tweets: Vec<Tweet>) -> Vec<Tweet> {
+        tweets.into_iter().filter(|t| self.allows(t)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RelationshipFilter;
+    use crate::common::tests::load_file;
+    use crate::tweet::Tweet;
+
+    #[test]
+    fn filter_retweet_of_muted() {
+        let content = load_file("sample_payloads/sample-retweet.json");
+        let tweet: Tweet = ::serde_json::from_str(&content).unwrap();
+        let rt_author = tweet
+            .retweeted_status
+            .as_ref()
+            .and_then(|rt| rt.user.as_ref())
+            .unwrap()
+            .id;
+
+        let mut filter = RelationshipFilter::new();
+        assert!(filter.is_stale());
+        assert!(filter.allows(&tweet));
+
+        filter.muted.insert(rt_author);
+        assert!(!filter.allows(&tweet));
+        assert!(filter.filter(vec![tweet]).is_empty());
+    }
+}
@@ -4,6 +4,8 @@
 
 use std::collections::HashMap;
 
+use hyper::{Body, Request};
+
 use crate::common::*;
 use crate::error::{Error::InvalidResponse, Result};
 use crate::user::UserID;
@@ -13,12 +15,14 @@ use serde_json;
 use super::*;
 
 ///Lookup a single tweet by numeric ID.
-pub async fn show(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+pub async fn show(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new()
         .extended_tweets()
         .add_param("id", id.to_string())
         .add_param("include_my_retweet", "true")
-        .add_param("include_ext_alt_text", "true");
+        .add_param("include_ext_alt_text", "true")
+        .include_cards();
     let req = get(links::statuses::SHOW, token, Some(&params));
     request_with_json_response(req).await
 }
@@ -27,7 +31,37 @@ pub async fn show(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
 ///
 ///Use the `count` parameter to indicate how many retweets you would like to retrieve. If `count`
 ///is 0 or greater than 100, it will be defaulted to 100 before making the call.
-pub async fn retweets_of(id: u64, count: u32, token: &auth::Token) -> Result<Response<Vec<Tweet>>> {
+///
+///Each of the returned tweets is a retweet of the given tweet, so the accounts that retweeted it
+///are available from the `user` field of each one, and the original tweet from
+///`retweeted_status`. If you only need the IDs of those accounts, `retweeters_of` can load them
+///without the rest of the tweet data.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::tweet;
+///
+///let retweets = tweet::retweets_of(782349500404862976, 100, &token).await.unwrap();
+///for rt in retweets.iter() {
+///    println!("retweeted by @{}", rt.user.as_ref().unwrap().screen_name);
+///}
+///# }
+///```
+pub async fn retweets_of(
+    id: impl Into<TweetId>,
+    count: u32,
+    token: &auth::Token,
+) -> Result<Response<Vec<Tweet>>> {
+    let req = retweets_of_request(id.into(), count, token);
+    request_with_json_response(req).await
+}
+
+fn retweets_of_request(id: TweetId, count: u32, token: &auth::Token) -> Request<Body> {
     let params = ParamList::new().extended_tweets().add_param(
         "count",
         if count == 0 || count > 100 {
@@ -39,8 +73,7 @@ pub async fn retweets_of(id: u64, count: u32, token: &auth::Token) -> Result<Res
     );
 
     let url = format!("{}/{}.json", links::statuses::RETWEETS_OF_STEM, id);
-    let req = get(&url, token, Some(&params));
-    request_with_json_response(req).await
+    get(&url, token, Some(&params))
 }
 
 ///Lookup the user IDs that have retweeted the given tweet.
@@ -49,7 +82,30 @@ pub async fn retweets_of(id: u64, count: u32, token: &auth::Token) -> Result<Res
 ///set the page size. Calling `with_page_size` on the iterator returned by this function will not
 ///change the page size used by the network call. Setting `page_size` manually may result in an
 ///error from Twitter.
-pub fn retweeters_of(id: u64, token: &auth::Token) -> cursor::CursorIter<cursor::IDCursor> {
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::tweet;
+///use futures::TryStreamExt;
+///
+///let retweeters = tweet::retweeters_of(782349500404862976, &token)
+///    .map_ok(|r| r.response)
+///    .try_collect::<Vec<egg_mode::user::UserId>>()
+///    .await
+///    .unwrap();
+///println!("{} accounts retweeted this", retweeters.len());
+///# }
+///```
+pub fn retweeters_of(
+    id: impl Into<TweetId>,
+    token: &auth::Token,
+) -> cursor::CursorIter<cursor::IDCursor> {
+    let id: TweetId = id.into();
     let params = ParamList::new().add_param("id", id.to_string());
     cursor::CursorIter::new(links::statuses::RETWEETERS_OF, token, Some(params), None)
 }
@@ -58,21 +114,32 @@ pub fn retweeters_of(id: u64, token: &auth::Token) -> cursor::CursorIter<cursor:
 ///
 ///This function differs from `lookup_map` in how it handles protected or nonexistent tweets.
 ///`lookup` gives a Vec of just the tweets it could load, leaving out any that it couldn't find.
-pub async fn lookup<I: IntoIterator<Item = u64>>(
-    ids: I,
-    token: &auth::Token,
-) -> Result<Response<Vec<Tweet>>> {
-    let id_param = ids.into_iter().fold(String::new(), |mut acc, x| {
-        if !acc.is_empty() {
-            acc.push(',');
-        }
-        acc.push_str(&x.to_string());
-        acc
-    });
+///
+///Twitter only allows 100 tweets to be loaded in a single call, so if more IDs than that are
+///given, this function will make several calls and combine the results. The rate-limit
+///information on the returned `Response` comes from the last call.
+pub async fn lookup<T, I>(ids: I, token: &auth::Token) -> Result<Response<Vec<Tweet>>>
+where
+    T: Into<TweetId>,
+    I: IntoIterator<Item = T>,
+{
+    let ids = ids.into_iter().map(Into::into).collect::<Vec<TweetId>>();
+    let mut chunks = ids.chunks(LOOKUP_CHUNK_SIZE);
+
+    let mut resp = lookup_chunk(chunks.next().unwrap_or(&[]), token).await?;
+    for chunk in chunks {
+        resp.extend(lookup_chunk(chunk, token).await?);
+    }
+
+    Ok(resp)
+}
+
+async fn lookup_chunk(ids: &[TweetId], token: &auth::Token) -> Result<Response<Vec<Tweet>>> {
     let params = ParamList::new()
         .extended_tweets()
-        .add_param("id", id_param)
-        .add_param("include_ext_alt_text", "true");
+        .add_param("id", lookup_id_param(ids))
+        .add_param("include_ext_alt_text", "true")
+        .include_cards();
 
     let req = post(links::statuses::LOOKUP, token, Some(&params));
     request_with_json_response(req).await
@@ -85,40 +152,71 @@ pub async fn lookup<I: IntoIterator<Item = u64>>(
 ///`lookup_map` gives a map containing every ID in the input slice; tweets that don't exist or
 ///can't be read by the authenticated user store `None` in the map, whereas tweets that could be
 ///loaded store `Some` and the requested status.
-pub async fn lookup_map<I: IntoIterator<Item = u64>>(
+///
+///Like `lookup`, if more than 100 IDs are given, this function will make several calls and
+///combine the results, with the rate-limit information coming from the last call.
+pub async fn lookup_map<T, I>(
     ids: I,
     token: &auth::Token,
-) -> Result<Response<HashMap<u64, Option<Tweet>>>> {
-    let id_param = ids.into_iter().fold(String::new(), |mut acc, x| {
-        if !acc.is_empty() {
-            acc.push(',');
-        }
-        acc.push_str(&x.to_string());
-        acc
-    });
+) -> Result<Response<HashMap<TweetId, Option<Tweet>>>>
+where
+    T: Into<TweetId>,
+    I: IntoIterator<Item = T>,
+{
+    let ids = ids.into_iter().map(Into::into).collect::<Vec<TweetId>>();
+    let mut chunks = ids.chunks(LOOKUP_CHUNK_SIZE);
+
+    let mut resp = lookup_map_chunk(chunks.next().unwrap_or(&[]), token).await?;
+    for chunk in chunks {
+        let later = lookup_map_chunk(chunk, token).await?;
+        resp.rate_limit_status = later.rate_limit_status;
+        resp.response.extend(later.response);
+    }
+
+    Ok(resp)
+}
+
+async fn lookup_map_chunk(
+    ids: &[TweetId],
+    token: &auth::Token,
+) -> Result<Response<HashMap<TweetId, Option<Tweet>>>> {
     let params = ParamList::new()
         .extended_tweets()
-        .add_param("id", id_param)
+        .add_param("id", lookup_id_param(ids))
         .add_param("map", "true")
-        .add_param("include_ext_alt_text", "true");
+        .add_param("include_ext_alt_text", "true")
+        .include_cards();
 
     let req = post(links::statuses::LOOKUP, token, Some(&params));
     let parsed = request_with_json_response::<serde_json::Value>(req).await?;
+    Response::try_map(parsed, parse_lookup_map)
+}
+
+///Joins the given IDs into the comma-separated form `statuses/lookup` expects.
+fn lookup_id_param(ids: &[TweetId]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+///Parses the response of `statuses/lookup` in "map" mode, where each requested ID is a key whose
+///value is either the tweet or `null`.
+fn parse_lookup_map(response: serde_json::Value) -> Result<HashMap<TweetId, Option<Tweet>>> {
     let mut map = HashMap::new();
 
-    for (key, val) in parsed
-        .response
+    for (key, val) in response
         .get("id")
         .and_then(|v| v.as_object())
         .ok_or_else(|| {
             InvalidResponse(
                 "unexpected response for lookup_map",
-                Some(parsed.response.to_string()),
+                Some(response.to_string()),
             )
         })?
     {
         let id = key
-            .parse::<u64>()
+            .parse::<TweetId>()
             .map_err(|_| InvalidResponse("could not parse id as integer", Some(key.to_string())))?;
         if val.is_null() {
             map.insert(id, None);
@@ -128,7 +226,7 @@ pub async fn lookup_map<I: IntoIterator<Item = u64>>(
         }
     }
 
-    Ok(Response::map(parsed, |_| map))
+    Ok(map)
 }
 
 ///Make a `Timeline` struct for navigating the collection of tweets posted by the authenticated
@@ -147,6 +245,10 @@ pub fn home_timeline(token: &auth::Token) -> Timeline {
 ///This method has a default page size of 20 tweets, with a maximum of 200.
 ///
 ///Twitter will only return the most recent 800 tweets by navigating this method.
+///
+///Besides the page size, the returned `Timeline` can be set to leave out the full user object
+///in each tweet with `trim_user`. For reply bots, `with_filter` can drop mentions you don't want
+///to answer (like retweets, or tweets from specific accounts) before they're returned.
 pub fn mentions_timeline(token: &auth::Token) -> Timeline {
     Timeline::new(links::statuses::MENTIONS_TIMELINE, None, token)
 }
@@ -188,7 +290,35 @@ pub fn retweets_of_me(token: &auth::Token) -> Timeline {
 
 ///Make a `Timeline` struct for navigating the collection of tweets liked by the given user.
 ///
+///This wraps the `favorites/list` endpoint, so it works for any account whose likes are visible to
+///the authenticated user: any public account, or a protected account the authenticated user
+///follows. As with the other `Timeline` functions, the returned `Timeline` keeps track of
+///`max_id` and `since_id` for you, so you can page back through older likes with `older` or check
+///for new ones with `newer`.
+///
+///Note that the timeline is ordered by the IDs of the liked tweets, not by when they were liked.
+///
 ///This method has a default page size of 20 tweets, with a maximum of 200.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::tweet;
+///
+///let timeline = tweet::liked_by("rustlang", &token).with_page_size(200);
+///let (timeline, feed) = timeline.start().await.unwrap();
+///for tweet in feed.iter() {
+///    println!("{}", tweet.text);
+///}
+///
+///let (_timeline, older) = timeline.older(None).await.unwrap();
+///println!("and {} more", older.len());
+///# }
+///```
 pub fn liked_by<T: Into<UserID>>(acct: T, token: &auth::Token) -> Timeline {
     let params = ParamList::new()
         .extended_tweets()
@@ -200,7 +330,8 @@ pub fn liked_by<T: Into<UserID>>(acct: T, token: &auth::Token) -> Timeline {
 ///
 ///On success, the future returned by this function yields the retweet, with the original status
 ///contained in `retweeted_status`.
-pub async fn retweet(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+pub async fn retweet(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new().extended_tweets();
     let url = format!("{}/{}.json", links::statuses::RETWEET_STEM, id);
     let req = post(&url, token, Some(&params));
@@ -212,8 +343,10 @@ pub async fn retweet(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
 ///The given ID may either be the original status, or the ID of the authenticated user's retweet of
 ///it.
 ///
-///On success, the future returned by this function yields the original tweet.
-pub async fn unretweet(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+///On success, the future returned by this function yields the original tweet. This undoes
+///`retweet`.
+pub async fn unretweet(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new().extended_tweets();
     let url = format!("{}/{}.json", links::statuses::UNRETWEET_STEM, id);
     let req = post(&url, token, Some(&params));
@@ -223,7 +356,11 @@ pub async fn unretweet(id: u64, token: &auth::Token) -> Result<Response<Tweet>>
 ///Like the given status as the authenticated user.
 ///
 ///On success, the future returned by this function yields the liked tweet.
-pub async fn like(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+///
+///Likes were called "favorites" in earlier versions of Twitter, and the underlying endpoint still
+///uses that name.
+pub async fn like(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new()
         .extended_tweets()
         .add_param("id", id.to_string());
@@ -234,7 +371,10 @@ pub async fn like(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
 ///Clears a like of the given status as the authenticated user.
 ///
 ///On success, the future returned by this function yields the given tweet.
-pub async fn unlike(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+///
+///This undoes `like`, and corresponds to the "unfavorite" action in earlier versions of Twitter.
+pub async fn unlike(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new()
         .extended_tweets()
         .add_param("id", id.to_string());
@@ -245,9 +385,101 @@ pub async fn unlike(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
 ///Delete the given tweet. The authenticated user must be the user who posted the given tweet.
 ///
 ///On success, the future returned by this function yields the given tweet.
-pub async fn delete(id: u64, token: &auth::Token) -> Result<Response<Tweet>> {
+pub async fn delete(id: impl Into<TweetId>, token: &auth::Token) -> Result<Response<Tweet>> {
+    let id: TweetId = id.into();
     let params = ParamList::new().extended_tweets();
     let url = format!("{}/{}.json", links::statuses::DELETE_STEM, id);
     let req = post(&url, token, Some(&params));
     request_with_json_response(req).await
 }
+
+///Hides or unhides the given reply to one of the authenticated user's tweets.
+///
+///Hidden replies are still visible to anyone who looks for them, but they're tucked away behind a
+///"hidden replies" link instead of being shown with the rest of the conversation. Only the author
+///of the tweet at the start of a conversation can hide replies in it.
+///
+///This uses the v2 API (`PUT /2/tweets/:id/hidden`), so it requires an access token for a user
+///whose app has v2 access. On success, the future returned by this function yields whether the
+///reply is now hidden.
+///
+///# Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///# #[tokio::main]
+///# async fn main() {
+///# let token: Token = unimplemented!();
+///use egg_mode::tweet;
+///
+///let hidden = tweet::hide_reply(1184191501121523712, true, &token).await.unwrap();
+///assert!(*hidden);
+///# }
+///```
+pub async fn hide_reply(
+    id: impl Into<TweetId>,
+    hidden: bool,
+    token: &auth::Token,
+) -> Result<Response<bool>> {
+    #[derive(Deserialize)]
+    struct HiddenData {
+        hidden: bool,
+    }
+    #[derive(Deserialize)]
+    struct HiddenResponse {
+        data: HiddenData,
+    }
+
+    let id: TweetId = id.into();
+    let url = format!("{}/{}/hidden", links::statuses::V2_TWEETS_STEM, id);
+    let req = put_json(&url, token, serde_json::json!({ "hidden": hidden }));
+    let resp = request_with_json_response::<HiddenResponse>(req).await?;
+    Ok(Response::map(resp, |r| r.data.hidden))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup_id_param, parse_lookup_map, retweets_of_request};
+    use crate::common::tests::load_file;
+    use crate::links;
+
+    #[test]
+    fn parse_lookup_map_missing() {
+        let content = load_file("sample_payloads/sample-lookup-map.json");
+        let map = parse_lookup_map(::serde_json::from_str(&content).unwrap()).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert!(map[&782349500404862976.into()].is_some());
+        assert!(map[&20.into()].is_none());
+        assert!(!map.contains_key(&21.into()));
+    }
+
+    #[test]
+    fn lookup_ids() {
+        assert_eq!(lookup_id_param(&[]), "");
+        assert_eq!(lookup_id_param(&[20.into(), 21.into()]), "20,21");
+    }
+
+    #[test]
+    fn retweets_of_count() {
+        let token = crate::auth::Token::Bearer("token".to_string());
+        let count_of = |count| {
+            let req = retweets_of_request(782349500404862976.into(), count, &token);
+            let uri = req.uri().to_string();
+            assert!(uri.starts_with(&format!(
+                "{}/782349500404862976.json?",
+                links::statuses::RETWEETS_OF_STEM
+            )));
+            assert!(uri.contains("tweet_mode=extended"));
+            url::form_urlencoded::parse(req.uri().query().unwrap().as_bytes())
+                .find(|(key, _)| key == "count")
+                .map(|(_, value)| value.into_owned())
+                .unwrap()
+        };
+
+        assert_eq!(count_of(25), "25");
+        assert_eq!(count_of(100), "100");
+        assert_eq!(count_of(0), "100");
+        assert_eq!(count_of(500), "100");
+    }
+}
@@ -0,0 +1,175 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use crate::common::*;
+use crate::error::Result;
+
+use super::{Timeline, TimelineStream, Tweet, TweetId};
+
+/// Merges several timelines into a single `Stream` of tweets, newest first.
+///
+/// Each timeline is turned into a stream with `Timeline::into_stream`, and the returned stream
+/// yields tweets from all of them in one sequence ordered by tweet ID (which is the same as the
+/// order they were posted, newest first). A tweet that appears in more than one timeline, such as
+/// one posted by a member of two lists, is only yielded once.
+///
+/// Pages are still loaded lazily, as tweets from each timeline are needed. Since the stream can't
+/// know which timeline holds the next-newest tweet until it has a tweet from each one in hand,
+/// each timeline's first page is loaded before the first tweet is yielded.
+///
+/// If loading a page of one timeline fails, the error is yielded from the merged stream, that
+/// timeline is dropped from the merge, and the others carry on.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet;
+/// use futures::{StreamExt, TryStreamExt};
+///
+/// let timelines = vec![
+///     tweet::user_timeline("rustlang", true, true, &token),
+///     tweet::user_timeline("rustjobs", true, true, &token),
+/// ];
+/// let feed = tweet::merge_timelines(timelines)
+///     .take(100)
+///     .try_collect::<Vec<_>>()
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub fn merge_timelines<I: IntoIterator<Item = Timeline>>(timelines: I) -> TimelineMerge {
+    TimelineMerge::new(timelines.into_iter().map(Timeline::into_stream).collect())
+}
+
+/// `Stream` which merges several timelines into one, as returned by [`merge_timelines`].
+///
+/// [`merge_timelines`]: fn.merge_timelines.html
+#[must_use = "streams do nothing unless polled"]
+pub struct TimelineMerge<S = TimelineStream> {
+    streams: Vec<S>,
+    heads: Vec<Option<Response<Tweet>>>,
+    done: Vec<bool>,
+    last_id: Option<TweetId>,
+}
+
+impl<S> TimelineMerge<S> {
+    pub(crate) fn new(streams: Vec<S>) -> TimelineMerge<S> {
+        let count = streams.len();
+        TimelineMerge {
+            streams,
+            heads: (0..count).map(|_| None).collect(),
+            done: vec![false; count],
+            last_id: None,
+        }
+    }
+}
+
+impl<S> Stream for TimelineMerge<S>
+where
+    S: Stream<Item = Result<Response<Tweet>>> + Unpin,
+{
+    type Item = Result<Response<Tweet>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        loop {
+            //make sure every stream that isn't finished has a tweet waiting
+            let mut pending = false;
+            for idx in 0..this.streams.len() {
+                if this.done[idx] || this.heads[idx].is_some() {
+                    continue;
+                }
+                match Pin::new(&mut this.streams[idx]).poll_next(cx) {
+                    Poll::Pending => pending = true,
+                    Poll::Ready(Some(Ok(tweet))) => this.heads[idx] = Some(tweet),
+                    Poll::Ready(Some(Err(e))) => {
+                        this.done[idx] = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    Poll::Ready(None) => this.done[idx] = true,
+                }
+            }
+            if pending {
+                return Poll::Pending;
+            }
+
+            let newest = this
+                .heads
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, head)| head.as_ref().map(|tweet| (idx, tweet.id)))
+                .max_by_key(|&(_, id)| id);
+
+            match newest {
+                None => return Poll::Ready(None),
+                Some((idx, id)) => {
+                    let tweet = this.heads[idx].take();
+                    //since tweets come out in order, a duplicate always directly follows the first
+                    //copy
+                    if this.last_id != Some(id) {
+                        this.last_id = Some(id);
+                        return Poll::Ready(tweet.map(Ok));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    use futures::{stream, StreamExt};
+
+    fn tweet_with_id(base: &Tweet, id: u64) -> Result<Response<Tweet>> {
+        let mut tweet = base.clone();
+        tweet.id = TweetId(id);
+        Ok(Response {
+            rate_limit_status: RateLimit {
+                limit: -1,
+                remaining: -1,
+                reset: -1,
+            },
+            response: tweet,
+        })
+    }
+
+    #[tokio::test]
+    async fn merge_in_order() {
+        let content = load_file("sample_payloads/sample-reply.json");
+        let base: Tweet = ::serde_json::from_str(&content).unwrap();
+
+        let streams = vec![
+            stream::iter(vec![
+                tweet_with_id(&base, 90),
+                tweet_with_id(&base, 50),
+                tweet_with_id(&base, 10),
+            ]),
+            stream::iter(vec![
+                tweet_with_id(&base, 70),
+                tweet_with_id(&base, 50),
+                tweet_with_id(&base, 20),
+            ]),
+            stream::iter(vec![]),
+        ];
+
+        let ids = TimelineMerge::new(streams)
+            .map(|tweet| tweet.unwrap().id.0)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(ids, vec![90, 70, 50, 20, 10]);
+    }
+}
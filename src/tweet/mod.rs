@@ -19,8 +19,17 @@
 //! - `DraftTweet`: This is what you use to post a new tweet. At present, not all available options
 //!   are supported, but basics like marking the tweet as a reply and attaching a location
 //!   coordinate are available.
+//! - `ThreadDraft`: Posts text that's too long for a single tweet as a thread of replies, splitting
+//!   it with the same character counting rules Twitter uses (see `weighted_length`).
+//! - `Poll`/`PollOption`: A poll attached to a tweet, with its choices and vote counts.
+//! - `TweetId`: The numeric ID of a tweet. Functions that take a tweet ID accept anything that
+//!   converts into one, including a plain `u64`.
 //! - `Timeline`: Returned by several functions in this module, this is how you cursor through a
-//!   collection of tweets. See the struct-level documentation for details.
+//!   collection of tweets. See the struct-level documentation for details. To read through a
+//!   timeline one tweet at a time instead, turn it into a `TimelineStream` with `into_stream`, or
+//!   combine several timelines into one stream with `merge_timelines`.
+//! - `RelationshipFilter`: A cache of the authenticated user's blocked and muted accounts, which
+//!   can be used to drop tweets from those accounts out of timelines and streams.
 //!
 //! ## Functions
 //!
@@ -32,6 +41,7 @@
 //! - `delete` (for creating a tweet, see `DraftTweet`)
 //! - `like`/`unlike`
 //! - `retweet`/`unretweet`
+//! - `hide_reply`, to hide replies to the authenticated user's tweets
 //!
 //! ### Metadata lookup
 //!
@@ -43,6 +53,8 @@
 //!   documentations.)
 //! - `retweeters_of`
 //! - `retweets_of`
+//! - `oembed` (see `EmbedOptions` for the ways the embedded tweet can be customized)
+//! - `conversation`, which follows a chain of replies to reconstruct the thread around a tweet
 //!
 //! ### `Timeline` cursors
 //!
@@ -58,9 +70,11 @@ use std::convert::TryFrom;
 use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
-use std::task::{Context, Poll};
+use std::sync::Arc;
+use std::task::{self, Context};
 
 use chrono;
+use futures::Stream;
 use hyper::{Body, Request};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -68,12 +82,84 @@ use serde::{Deserialize, Deserializer, Serialize};
 use crate::common::*;
 use crate::error::{Error::InvalidResponse, Result};
 use crate::stream::FilterLevel;
-use crate::{auth, entities, error, links, media, place, user};
+use crate::{auth, entities, error, links, media, place, state, user};
 
+mod conversation;
+mod filter;
 mod fun;
+mod merge;
+mod oembed;
+mod poll;
 mod raw;
+mod thread;
 
+pub use self::conversation::{conversation, Conversation, ReplySearch};
+pub use self::filter::RelationshipFilter;
 pub use self::fun::*;
+pub use self::merge::{merge_timelines, TimelineMerge};
+pub use self::oembed::{oembed, EmbedAlign, EmbedOptions, EmbedTheme, TweetEmbed};
+pub use self::poll::{Poll, PollOption};
+pub use self::thread::{weighted_length, ThreadDraft, MAX_TWEET_LENGTH};
+
+id_newtype! {
+    ///The numeric ID of a tweet.
+    ///
+    ///This is a thin wrapper around the `u64` that Twitter uses, so that tweet IDs can't be
+    ///accidentally passed to functions that expect a user or list ID. It converts freely to and from
+    ///`u64`, so functions that take an `impl Into<TweetId>` can still be called with a plain number.
+    ///
+    ///```rust
+    ///use egg_mode::tweet::TweetId;
+    ///
+    ///let id = TweetId::from(782349500404862976);
+    ///assert_eq!(id, 782349500404862976);
+    ///assert_eq!(u64::from(id), 782349500404862976);
+    ///```
+    TweetId
+}
+
+///The Unix timestamp, in milliseconds, that Twitter's "snowflake" IDs count from.
+const SNOWFLAKE_EPOCH: i64 = 1_288_834_974_657;
+
+impl TweetId {
+    ///Returns the smallest tweet ID that could have been assigned at the given time.
+    ///
+    ///Since late 2010, tweet IDs have been "snowflake" IDs, which begin with the time the tweet
+    ///was posted. This means you can use a timestamp to find bounds to give to `since_id` or
+    ///`max_id` parameters. For times before snowflake IDs were introduced, this returns zero.
+    ///
+    ///```rust
+    ///use egg_mode::tweet::TweetId;
+    ///
+    ///let id = TweetId::from(782349500404862976);
+    ///let posted = id.timestamp().unwrap();
+    ///assert!(TweetId::from_timestamp(posted) <= id);
+    ///```
+    pub fn from_timestamp(time: chrono::DateTime<chrono::Utc>) -> TweetId {
+        let millis = time.timestamp_millis() - SNOWFLAKE_EPOCH;
+        if millis <= 0 {
+            TweetId(0)
+        } else {
+            TweetId((millis as u64) << 22)
+        }
+    }
+
+    ///Returns the time encoded in this snowflake ID, in other words the time the tweet was posted.
+    ///
+    ///This returns `None` for IDs that were assigned before snowflake IDs were introduced in late
+    ///2010, since those don't contain a timestamp.
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::TimeZone;
+
+        //the earliest snowflake IDs were just under 30 billion
+        if self.0 < 29_700_859_247 {
+            return None;
+        }
+
+        let millis = (self.0 >> 22) as i64 + SNOWFLAKE_EPOCH;
+        chrono::Utc.timestamp_millis_opt(millis).single()
+    }
+}
 
 round_trip! { raw::RawTweet,
     ///Represents a single status update.
@@ -123,13 +209,12 @@ round_trip! { raw::RawTweet,
     ///parent tweet without having to make another call to `show`.
     ///
     ///* `retweeted_status`
-    ///* `quoted_status`/`quoted_status_id`
+    ///* `is_quote_status`/`quoted_status`/`quoted_status_id`
     ///
     ///## Media
     ///
     ///As a tweet can attach an image, GIF, or video, these fields allow you to access information
-    ///about the attached media. Note that polls are not surfaced to the Public API at the time of this
-    ///writing (2016-09-01). For more information about how to use attached media, see the
+    ///about the attached media. For more information about how to use attached media, see the
     ///documentation for [`MediaEntity`][].
     ///
     ///[`MediaEntity`]: ../entities/struct.MediaEntity.html
@@ -145,6 +230,7 @@ round_trip! { raw::RawTweet,
     ///* `withheld_copyright`
     ///* `withheld_in_countries`
     ///* `withheld_scope`
+    ///* `poll`
     #[derive(Debug, Clone)]
     pub struct Tweet {
         //If the user has contributors enabled, this will show which accounts contributed to this
@@ -156,7 +242,7 @@ round_trip! { raw::RawTweet,
         #[serde(with = "serde_datetime")]
         pub created_at: chrono::DateTime<chrono::Utc>,
         ///If the authenticated user has retweeted this tweet, contains the ID of the retweet.
-        pub current_user_retweet: Option<u64>,
+        pub current_user_retweet: Option<TweetId>,
         ///If this tweet is an extended tweet with "hidden" metadata and entities, contains the byte
         ///offsets between which the "displayable" tweet text is.
         pub display_text_range: Option<(usize, usize)>,
@@ -177,24 +263,40 @@ round_trip! { raw::RawTweet,
         ///this tweet.
         pub filter_level: Option<FilterLevel>,
         ///Numeric ID for this tweet.
-        pub id: u64,
+        pub id: TweetId,
         ///If the tweet is a reply, contains the ID of the user that was replied to.
-        pub in_reply_to_user_id: Option<u64>,
+        pub in_reply_to_user_id: Option<user::UserId>,
         ///If the tweet is a reply, contains the screen name of the user that was replied to.
         pub in_reply_to_screen_name: Option<String>,
         ///If the tweet is a reply, contains the ID of the tweet that was replied to.
-        pub in_reply_to_status_id: Option<u64>,
+        pub in_reply_to_status_id: Option<TweetId>,
+        ///Indicates whether this tweet quotes another tweet.
+        ///
+        ///This can be `true` even if `quoted_status` is absent, for example if the quoted tweet has
+        ///since been deleted or is from a protected account.
+        #[serde(default)]
+        pub is_quote_status: bool,
         ///Can contain a language ID indicating the machine-detected language of the text, or "und" if
         ///no language could be detected.
         pub lang: Option<String>,
         ///When present, the `Place` that this tweet is associated with (but not necessarily where it
         ///originated from).
         pub place: Option<place::Place>,
+        ///If the tweet has a poll attached, contains its choices and vote counts.
+        ///
+        ///Twitter sends polls as a "card" on the tweet, which the v1.1 API only includes when it's
+        ///asked for with the undocumented `include_cards` and `cards_platform` parameters.
+        ///egg-mode sets these when loading tweets with `show`, `lookup`, `lookup_map`, a
+        ///`Timeline`, or a search, so polls are filled in for tweets loaded that way. Tweets
+        ///loaded elsewhere (like streams, or the tweet returned by `retweet` or `like`) won't
+        ///have one; a `raw` request can ask for cards with `ParamList::include_cards`.
+        #[serde(default)]
+        pub poll: Option<Poll>,
         ///If the tweet has a link, indicates whether the link may contain content that could be
         ///identified as sensitive.
         pub possibly_sensitive: Option<bool>,
         ///If this tweet is quoting another by link, contains the ID of the quoted tweet.
-        pub quoted_status_id: Option<u64>,
+        pub quoted_status_id: Option<TweetId>,
         ///If this tweet is quoting another by link, contains the quoted tweet.
         pub quoted_status: Option<Box<Tweet>>,
         //"A set of key-value pairs indicating the intended contextual delivery of the containing
@@ -220,7 +322,7 @@ round_trip! { raw::RawTweet,
         ///full text is longer than 280 characters.
         pub truncated: bool,
         ///The user who posted this tweet. This field will be absent on tweets included as part of a
-        ///`TwitterUser`.
+        ///`TwitterUser`, or loaded from a `Timeline` with `trim_user` set.
         pub user: Option<Box<user::TwitterUser>>,
         ///If present and `true`, indicates that this tweet has been withheld due to a DMCA complaint.
         pub withheld_copyright: bool,
@@ -240,12 +342,21 @@ impl TryFrom<raw::RawTweet> for Tweet {
     type Error = error::Error;
 
     fn try_from(mut raw: raw::RawTweet) -> Result<Tweet> {
-        let extended_full_text = raw.extended_tweet.map(|xt| xt.full_text);
-        let text = raw
-            .full_text
-            .or(extended_full_text)
-            .or(raw.text)
-            .ok_or(error::Error::MissingValue("text"))?;
+        //tweets loaded in "compatibility" mode (e.g. from a stream) carry their full text and its
+        //metadata in a separate `extended_tweet` object, which replaces the truncated versions
+        let text = if let Some(xt) = raw.extended_tweet.take() {
+            raw.display_text_range = xt.display_text_range;
+            raw.entities = xt.entities;
+            if xt.extended_entities.is_some() {
+                raw.extended_entities = xt.extended_entities;
+            }
+            xt.full_text
+        } else {
+            raw.full_text
+                .take()
+                .or_else(|| raw.text.take())
+                .ok_or(error::Error::MissingValue("text"))?
+        };
         let current_user_retweet = raw.current_user_retweet.map(|cur| cur.id);
 
         if let Some(ref mut range) = raw.display_text_range {
@@ -287,8 +398,10 @@ impl TryFrom<raw::RawTweet> for Tweet {
             in_reply_to_user_id: raw.in_reply_to_user_id,
             in_reply_to_screen_name: raw.in_reply_to_screen_name,
             in_reply_to_status_id: raw.in_reply_to_status_id,
+            is_quote_status: raw.is_quote_status,
             lang: raw.lang,
             place: raw.place,
+            poll: raw.card.and_then(|card| card.into_poll()),
             possibly_sensitive: raw.possibly_sensitive,
             quoted_status_id: raw.quoted_status_id,
             quoted_status: raw.quoted_status,
@@ -303,6 +416,36 @@ impl TryFrom<raw::RawTweet> for Tweet {
             withheld_scope: raw.withheld_scope,
             text,
             current_user_retweet,
+            raw: None,
+        })
+    }
+}
+
+impl Tweet {
+    ///Returns whether this tweet is withheld from viewers in the given country.
+    ///
+    ///The country is given as a two-letter country code, like `"DE"`. This checks
+    ///`withheld_in_countries` for the given code or the special code `XX`, which means the tweet is
+    ///withheld in every country, and also returns true if the tweet was withheld due to a DMCA
+    ///complaint.
+    pub fn is_withheld_in(&self, country: &str) -> bool {
+        self.withheld_copyright
+            || self
+                .withheld_in_countries
+                .iter()
+                .flatten()
+                .any(|c| c == "XX" || c.eq_ignore_ascii_case(country))
+    }
+
+    ///Returns an iterator over the chain of tweets quoted by this one: first the tweet this one
+    ///quotes, then the tweet that one quotes, and so on.
+    ///
+    ///Twitter only includes one level of quoted tweet in its responses, so to walk a longer chain,
+    ///load the last tweet of this iterator with `show` and continue from there. (Its
+    ///`quoted_status_id` will tell you whether there's more to load.)
+    pub fn quote_chain(&self) -> impl Iterator<Item = &Tweet> {
+        std::iter::successors(self.quoted_status.as_deref(), |t| {
+            t.quoted_status.as_deref()
         })
     }
 }
@@ -314,13 +457,31 @@ impl TryFrom<raw::RawTweet> for Tweet {
 ///yourself.
 ///
 ///Note that if you're going to reconstruct a link from this, the source URL has `rel="nofollow"`
-///in the anchor tag.
+///in the anchor tag. If you'd rather use the anchor tag as Twitter sent it, it's saved in the
+///`raw` field.
+///
+///```rust
+///use egg_mode::tweet::TweetSource;
+///
+///let source = r#"<a href="https://mobile.twitter.com" rel="nofollow">Twitter Web App</a>"#;
+///let source: TweetSource = source.parse().unwrap();
+///
+///assert_eq!(source.name, "Twitter Web App");
+///assert_eq!(source.url, "https://mobile.twitter.com");
+///assert_eq!(source.raw.as_deref(), Some(r#"<a href="https://mobile.twitter.com" rel="nofollow">Twitter Web App</a>"#));
+///```
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TweetSource {
     ///The name of the app, given by its developer.
     pub name: String,
     ///The URL for the app, given by its developer.
     pub url: String,
+    ///The original `source` string this was parsed from, usually an HTML anchor tag.
+    ///
+    ///This is `None` when the app information didn't come from a `source` string, as with the
+    ///`source_app` of a `DirectMessage`.
+    #[serde(default)]
+    pub raw: Option<String>,
 }
 
 impl FromStr for TweetSource {
@@ -337,6 +498,7 @@ impl FromStr for TweetSource {
             return Ok(TweetSource {
                 name: "Twitter Web Client".to_string(),
                 url: "https://twitter.com".to_string(),
+                raw: Some(full.to_string()),
             });
         }
 
@@ -356,7 +518,11 @@ impl FromStr for TweetSource {
                 InvalidResponse("TweetSource had no link text", Some(full.to_string()))
             })?;
 
-        Ok(TweetSource { name, url })
+        Ok(TweetSource {
+            name,
+            url,
+            raw: Some(full.to_string()),
+        })
     }
 }
 
@@ -495,11 +661,16 @@ pub struct Timeline {
     ///collection of tweets.
     pub count: i32,
     ///The largest/most recent tweet ID returned in the last call to `start`, `older`, or `newer`.
-    pub max_id: Option<u64>,
+    pub max_id: Option<TweetId>,
     ///The smallest/oldest tweet ID returned in the last call to `start`, `older`, or `newer`.
-    pub min_id: Option<u64>,
+    pub min_id: Option<TweetId>,
+    ///Optional client-side filter to apply to the tweets loaded by this timeline.
+    filter: Option<TweetFilter>,
 }
 
+///A client-side filter on the tweets loaded by a `Timeline`.
+type TweetFilter = Arc<dyn Fn(&Tweet) -> bool + Send + Sync>;
+
 impl Timeline {
     ///Clear the saved IDs on this timeline.
     pub fn reset(&mut self) {
@@ -516,8 +687,8 @@ impl Timeline {
 
     ///Return the set of tweets older than the last set pulled, optionally placing a minimum tweet
     ///ID to bound with.
-    pub fn older(self, since_id: Option<u64>) -> TimelineFuture {
-        let req = self.request(since_id, self.min_id.map(|id| id - 1));
+    pub fn older(self, since_id: Option<TweetId>) -> TimelineFuture {
+        let req = self.request(since_id, self.min_id.map(|id| TweetId(id.0 - 1)));
         let loader = Box::pin(request_with_json_response(req));
 
         TimelineFuture {
@@ -528,7 +699,7 @@ impl Timeline {
 
     ///Return the set of tweets newer than the last set pulled, optionall placing a maximum tweet
     ///ID to bound with.
-    pub fn newer(self, max_id: Option<u64>) -> TimelineFuture {
+    pub fn newer(self, max_id: Option<TweetId>) -> TimelineFuture {
         let req = self.request(self.max_id, max_id);
         let loader = Box::pin(request_with_json_response(req));
 
@@ -547,14 +718,314 @@ impl Timeline {
     ///of tweets will be returned.
     pub async fn call(
         &self,
-        since_id: Option<u64>,
-        max_id: Option<u64>,
+        since_id: Option<TweetId>,
+        max_id: Option<TweetId>,
     ) -> Result<Response<Vec<Tweet>>> {
         request_with_json_response(self.request(since_id, max_id)).await
     }
 
+    ///Loads every tweet in the timeline that was posted between the given times.
+    ///
+    ///This converts `start` and `end` into tweet IDs (see `TweetId::from_timestamp`) and pages
+    ///backward from `end` until Twitter stops returning tweets, so only the pages covering the
+    ///window are requested. Tweets posted at `start` are included; those posted at `end` are not.
+    ///The results are returned newest-first, like the other timeline methods, and the timeline's
+    ///`max_id` and `min_id` are set to cover them so you can carry on with `older` or `newer`.
+    ///
+    ///Twitter only keeps a limited number of tweets available in each timeline (roughly 3,200 for
+    ///user timelines and 800 for the home timeline), so a window that reaches further back than
+    ///that will be cut short.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///use chrono::{Duration, Utc};
+    ///
+    ///let end = Utc::now() - Duration::days(1);
+    ///let start = end - Duration::days(1);
+    ///let timeline = egg_mode::tweet::user_timeline("rustlang", false, true, &token);
+    ///let (_timeline, tweets) = timeline.between(start, end).await.unwrap();
+    ///println!("{} tweets yesterday", tweets.len());
+    ///# }
+    ///```
+    pub async fn between(
+        mut self,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(Self, Response<Vec<Tweet>>)> {
+        let since_id = TweetId::from_timestamp(start).0.checked_sub(1).map(TweetId);
+        let max_id = TweetId::from_timestamp(end).0.checked_sub(1).map(TweetId);
+
+        let mut tweets = self.load_range(since_id, max_id).await?;
+        tweets
+            .response
+            .retain(|t| t.created_at >= start && t.created_at < end);
+        self.map_ids(&tweets.response);
+        self.apply_filter(&mut tweets.response);
+
+        Ok((self, tweets))
+    }
+
+    ///Saves this timeline's position in the given [`StateStore`] under the given name, so a later
+    ///run of your program can pick up from the same place with `load_checkpoint`.
+    ///
+    ///[`StateStore`]: ../state/trait.StateStore.html
+    ///
+    ///This saves `max_id`, the newest tweet the timeline has seen. If `max_id` is currently unset
+    ///(for example, because the last call to `newer` returned no tweets and cleared it), any
+    ///checkpoint already saved under this name is left alone, so saving after every call is safe.
+    pub fn save_checkpoint<S: state::StateStore + ?Sized>(
+        &self,
+        store: &S,
+        name: &str,
+    ) -> Result<()> {
+        match self.max_id {
+            Some(id) => Ok(state::save_json(store, &format!("timeline:{}", name), &id)?),
+            None => Ok(()),
+        }
+    }
+
+    ///Loads a position previously saved with `save_checkpoint` under the given name, returning
+    ///whether one was found.
+    ///
+    ///If a checkpoint was saved, this sets `max_id` and `min_id` to the saved tweet ID, so the next
+    ///call to `newer(None)` returns only the tweets posted since the checkpoint. This lets a
+    ///polling bot resume where it left off after a restart, without seeing any tweet twice:
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///use egg_mode::state::FileStore;
+    ///
+    ///let store = FileStore::new("bot-state");
+    ///let mut timeline = egg_mode::tweet::mentions_timeline(&token);
+    ///
+    ///let (timeline, mentions) = if timeline.load_checkpoint(&store, "mentions").unwrap() {
+    ///    timeline.newer(None).await.unwrap()
+    ///} else {
+    ///    timeline.start().await.unwrap()
+    ///};
+    ///
+    ///for tweet in mentions.iter() {
+    ///    println!("<@{}> {}", tweet.user.as_ref().unwrap().screen_name, tweet.text);
+    ///}
+    ///timeline.save_checkpoint(&store, "mentions").unwrap();
+    ///# }
+    ///```
+    pub fn load_checkpoint<S: state::StateStore + ?Sized>(
+        &mut self,
+        store: &S,
+        name: &str,
+    ) -> Result<bool> {
+        let id = state::load_json::<TweetId>(store, &format!("timeline:{}", name))?;
+        if id.is_some() {
+            self.max_id = id;
+            self.min_id = id;
+        }
+        Ok(id.is_some())
+    }
+
+    ///Converts this `Timeline` into a `Stream` of individual tweets, loading older pages as needed.
+    ///
+    ///The stream picks up from wherever the timeline currently is: for a new (or `reset`)
+    ///timeline it starts with the newest tweets, and for one that's already been paged through it
+    ///continues with the tweets older than `min_id`. Each page is only requested once the tweets
+    ///from the previous page have been consumed, so combinators like `take` won't load any more
+    ///pages than they need. The stream ends when Twitter returns an empty page, which is how it
+    ///signals that the timeline's depth limit (roughly 3,200 tweets for user timelines and 800
+    ///for the home timeline) has been reached.
+    ///
+    ///Each tweet is wrapped in a `Response` carrying the rate-limit information from the page it
+    ///was loaded with. If loading a page fails, the stream yields the error and then ends.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///use futures::{StreamExt, TryStreamExt};
+    ///
+    ///let timeline = egg_mode::tweet::user_timeline("rustlang", true, true, &token)
+    ///    .with_page_size(200);
+    ///let tweets = timeline.into_stream().take(5000).try_collect::<Vec<_>>().await.unwrap();
+    ///println!("loaded {} tweets", tweets.len());
+    ///# }
+    ///```
+    pub fn into_stream(self) -> TimelineStream {
+        TimelineStream {
+            timeline: Some(self),
+            loader: None,
+            buffer: Vec::new().into_iter(),
+        }
+    }
+
+    ///Loads every tweet in the timeline newer than the given ID, paging backward from the newest
+    ///tweet until it's reached.
+    ///
+    ///This is the "catch up after downtime" pattern: give it the ID of the last tweet you saw, and
+    ///it returns everything posted since then, newest-first, requesting as many pages as it takes.
+    ///The given tweet itself is not included. Afterward, the timeline's `max_id` and `min_id` cover
+    ///the returned tweets, so you can keep polling with `newer`. If nothing new was posted, both
+    ///are set to the given ID instead, so `newer` still picks up from the right place.
+    ///
+    ///Twitter only keeps a limited number of tweets available in each timeline (roughly 3,200 for
+    ///user timelines and 800 for the home timeline). If more tweets than that were posted since the
+    ///given ID, the oldest ones can't be loaded, and the results will stop short of it.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///let last_seen = 1184191501121523712;
+    ///let timeline = egg_mode::tweet::home_timeline(&token).with_page_size(200);
+    ///let (timeline, missed) = timeline.backfill_until(last_seen).await.unwrap();
+    ///println!("{} tweets since we last checked", missed.len());
+    ///# }
+    ///```
+    pub async fn backfill_until(
+        mut self,
+        since_id: impl Into<TweetId>,
+    ) -> Result<(Self, Response<Vec<Tweet>>)> {
+        let since_id: TweetId = since_id.into();
+        let mut tweets = self.load_range(Some(since_id), None).await?;
+
+        if tweets.is_empty() {
+            self.max_id = Some(since_id);
+            self.min_id = Some(since_id);
+        } else {
+            self.map_ids(&tweets.response);
+            self.apply_filter(&mut tweets.response);
+        }
+
+        Ok((self, tweets))
+    }
+
+    ///Converts this `Timeline` into an endless `Stream` that checks for new tweets every
+    ///`interval`, yielding them as they're posted.
+    ///
+    ///If the timeline hasn't been loaded yet, the first check yields the newest page of tweets;
+    ///after that (or right away, if the timeline already has a `max_id`, e.g. from
+    ///`load_checkpoint`), each check loads every tweet posted since the newest one seen so far,
+    ///however many pages that takes. Within each check, tweets are yielded oldest-first, so the
+    ///stream as a whole runs in the order the tweets were posted.
+    ///
+    ///The stream keeps an eye on the rate-limit headers of each response: if a check uses up the
+    ///last call in the current window, or Twitter reports that the rate limit was reached, the
+    ///next check waits until the window resets instead. Other errors are yielded from the stream,
+    ///and polling carries on after the next interval.
+    ///
+    ///The home timeline allows 15 calls per 15-minute window, so an `interval` of at least a minute
+    ///is a good idea there.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///use futures::StreamExt;
+    ///use std::time::Duration;
+    ///
+    ///let timeline = egg_mode::tweet::home_timeline(&token).with_page_size(200);
+    ///let mut tweets = Box::pin(timeline.poll(Duration::from_secs(90)));
+    ///while let Some(tweet) = tweets.next().await {
+    ///    match tweet {
+    ///        Ok(tweet) => println!("<@{}> {}", tweet.user.as_ref().unwrap().screen_name, tweet.text),
+    ///        Err(e) => eprintln!("error loading the timeline: {}", e),
+    ///    }
+    ///}
+    ///# }
+    ///```
+    pub fn poll(
+        self,
+        interval: std::time::Duration,
+    ) -> impl Stream<Item = Result<Response<Tweet>>> {
+        use futures::stream::{self, StreamExt};
+
+        fn until_reset(reset: i32) -> std::time::Duration {
+            let secs = reset as i64 - chrono::Utc::now().timestamp();
+            std::time::Duration::from_secs(secs.max(0) as u64 + 1)
+        }
+
+        stream::unfold((self, None), move |(mut timeline, delay)| async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+
+            let (tweets, delay) = match timeline.poll_page().await {
+                Ok(page) => {
+                    let rate_limit_status = page.rate_limit_status;
+                    let delay = if rate_limit_status.remaining == 0 {
+                        interval.max(until_reset(rate_limit_status.reset))
+                    } else {
+                        interval
+                    };
+                    let tweets = page
+                        .response
+                        .into_iter()
+                        .rev()
+                        .map(|response| {
+                            Ok(Response {
+                                rate_limit_status,
+                                response,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    (tweets, delay)
+                }
+                Err(error::Error::RateLimit(reset)) => {
+                    (Vec::new(), interval.max(until_reset(reset)))
+                }
+                Err(e) => (vec![Err(e)], interval),
+            };
+
+            Some((stream::iter(tweets), (timeline, Some(delay))))
+        })
+        .flatten()
+    }
+
+    ///Helper function for `poll` that loads every tweet newer than `max_id`, or the newest page
+    ///of tweets if it's unset, and updates the tracked IDs if there were any.
+    async fn poll_page(&mut self) -> Result<Response<Vec<Tweet>>> {
+        let mut tweets = match self.max_id {
+            Some(since_id) => self.load_range(Some(since_id), None).await?,
+            None => self.call(None, None).await?,
+        };
+        if !tweets.is_empty() {
+            self.map_ids(&tweets.response);
+            self.apply_filter(&mut tweets.response);
+        }
+        Ok(tweets)
+    }
+
+    ///Helper function to load every tweet between the given IDs, by paging backward from `max_id`
+    ///until Twitter stops returning tweets.
+    async fn load_range(
+        &self,
+        since_id: Option<TweetId>,
+        mut max_id: Option<TweetId>,
+    ) -> Result<Response<Vec<Tweet>>> {
+        let mut tweets = self.call(since_id, max_id).await?;
+        let mut last = tweets.last().map(|t| t.id);
+        while let Some(oldest) = last {
+            if oldest.0 == 0 {
+                break;
+            }
+            max_id = Some(TweetId(oldest.0 - 1));
+            let page = self.call(since_id, max_id).await?;
+            last = page.last().map(|t| t.id);
+            tweets.extend(page);
+        }
+
+        Ok(tweets)
+    }
+
     ///Helper function to construct a `Request` from the current state.
-    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> Request<Body> {
+    fn request(&self, since_id: Option<TweetId>, max_id: Option<TweetId>) -> Request<Body> {
         let params = self
             .params_base
             .as_ref()
@@ -563,6 +1034,7 @@ impl Timeline {
             .add_param("count", self.count.to_string())
             .add_param("tweet_mode", "extended")
             .add_param("include_ext_alt_text", "true")
+            .include_cards()
             .add_opt_param("since_id", since_id.map(|v| v.to_string()))
             .add_opt_param("max_id", max_id.map(|v| v.to_string()));
 
@@ -577,6 +1049,91 @@ impl Timeline {
         }
     }
 
+    ///Sets whether to leave the full user object out of each tweet.
+    ///
+    ///When set, Twitter only sends the ID of each tweet's author, which makes each page
+    ///considerably smaller. Since that isn't enough to fill in a `TwitterUser`, the `user` field
+    ///of the loaded tweets will be `None`.
+    pub fn trim_user(self, trim_user: bool) -> Self {
+        self.with_param("trim_user", trim_user.to_string())
+    }
+
+    ///Sets whether to include native retweets in the timeline.
+    ///
+    ///This only applies to user and list timelines, which include retweets by default (though
+    ///`user_timeline` sets it from its `with_rts` argument).
+    pub fn include_rts(self, include_rts: bool) -> Self {
+        self.with_param("include_rts", include_rts.to_string())
+    }
+
+    ///Sets whether to leave replies out of the timeline.
+    ///
+    ///This applies to user and home timelines. Note that Twitter removes the replies after loading
+    ///each page, so a page may contain fewer tweets than the page size.
+    pub fn exclude_replies(self, exclude_replies: bool) -> Self {
+        self.with_param("exclude_replies", exclude_replies.to_string())
+    }
+
+    ///Helper builder function to set a parameter to include with every request.
+    fn with_param(self, key: &'static str, value: String) -> Self {
+        let params = self.params_base.unwrap_or_default().add_param(key, value);
+        Timeline {
+            params_base: Some(params),
+            ..self
+        }
+    }
+
+    ///Sets a filter to apply to every set of tweets this timeline loads.
+    ///
+    ///Tweets for which the given function returns `false` are dropped before they're returned from
+    ///`start`, `older`, `newer`, `between`, `backfill_until`, `into_stream`, or `poll`. (The
+    ///lower-level `call` returns everything Twitter sent.) If a filter was already set, tweets
+    ///will need to pass both filters to be returned.
+    ///
+    ///The filter runs on your end, after each page is loaded, so it doesn't save any calls to
+    ///Twitter, and pages can come back with fewer tweets than the page size (or none at all).
+    ///However, the tracked `max_id` and `min_id` still cover the whole page, so paging through the
+    ///timeline works the same as without a filter.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() {
+    ///# let token: Token = unimplemented!();
+    ///let mentions = egg_mode::tweet::mentions_timeline(&token)
+    ///    .with_filter(|tweet| tweet.retweeted_status.is_none())
+    ///    .with_filter(|tweet| {
+    ///        tweet.user.as_ref().map_or(true, |user| user.screen_name != "spam_account")
+    ///    });
+    ///
+    ///let (_mentions, feed) = mentions.start().await.unwrap();
+    ///# }
+    ///```
+    pub fn with_filter<F>(self, filter: F) -> Self
+    where
+        F: Fn(&Tweet) -> bool + Send + Sync + 'static,
+    {
+        let filter: TweetFilter = match self.filter {
+            Some(ref prev) => {
+                let prev = prev.clone();
+                Arc::new(move |tweet| prev(tweet) && filter(tweet))
+            }
+            None => Arc::new(filter),
+        };
+
+        Timeline {
+            filter: Some(filter),
+            ..self
+        }
+    }
+
+    ///Drops the tweets that don't pass the filter, if one is set.
+    fn apply_filter(&self, tweets: &mut Vec<Tweet>) {
+        if let Some(ref filter) = self.filter {
+            tweets.retain(|tweet| filter(tweet));
+        }
+    }
+
     ///With the returned slice of Tweets, set the min_id and max_id on self.
     fn map_ids(&mut self, resp: &[Tweet]) {
         self.max_id = resp.first().map(|status| status.id);
@@ -596,6 +1153,7 @@ impl Timeline {
             count: 20,
             max_id: None,
             min_id: None,
+            filter: None,
         }
     }
 }
@@ -614,22 +1172,79 @@ pub struct TimelineFuture {
 impl Future for TimelineFuture {
     type Output = Result<(Timeline, Response<Vec<Tweet>>)>;
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> task::Poll<Self::Output> {
         match Pin::new(&mut self.loader).poll(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
-            Poll::Ready(Ok(resp)) => {
+            task::Poll::Pending => task::Poll::Pending,
+            task::Poll::Ready(Err(e)) => task::Poll::Ready(Err(e)),
+            task::Poll::Ready(Ok(mut resp)) => {
                 if let Some(mut timeline) = self.timeline.take() {
                     timeline.map_ids(&resp.response);
-                    Poll::Ready(Ok((timeline, resp)))
+                    timeline.apply_filter(&mut resp.response);
+                    task::Poll::Ready(Ok((timeline, resp)))
                 } else {
-                    Poll::Ready(Err(error::Error::FutureAlreadyCompleted))
+                    task::Poll::Ready(Err(error::Error::FutureAlreadyCompleted))
                 }
             }
         }
     }
 }
 
+/// `Stream` which yields the tweets in a `Timeline`, loading pages of older tweets as it goes.
+///
+/// This is returned by `Timeline::into_stream`; see that method for details.
+#[must_use = "streams do nothing unless polled"]
+pub struct TimelineStream {
+    timeline: Option<Timeline>,
+    loader: Option<TimelineFuture>,
+    buffer: std::vec::IntoIter<Response<Tweet>>,
+}
+
+impl Stream for TimelineStream {
+    type Item = Result<Response<Tweet>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> task::Poll<Option<Self::Item>> {
+        loop {
+            if let Some(tweet) = self.buffer.next() {
+                return task::Poll::Ready(Some(Ok(tweet)));
+            }
+
+            if let Some(ref mut loader) = self.loader {
+                match Pin::new(loader).poll(cx) {
+                    task::Poll::Pending => return task::Poll::Pending,
+                    task::Poll::Ready(Err(e)) => {
+                        self.loader = None;
+                        return task::Poll::Ready(Some(Err(e)));
+                    }
+                    task::Poll::Ready(Ok((timeline, page))) => {
+                        self.loader = None;
+                        //check the timeline's IDs rather than the page itself, since a filter may
+                        //have emptied out a page that still had tweets
+                        if timeline.min_id.is_none() {
+                            return task::Poll::Ready(None);
+                        }
+                        self.timeline = Some(timeline);
+
+                        let rate_limit_status = page.rate_limit_status;
+                        self.buffer = page
+                            .response
+                            .into_iter()
+                            .map(|response| Response {
+                                rate_limit_status,
+                                response,
+                            })
+                            .collect::<Vec<_>>()
+                            .into_iter();
+                    }
+                }
+            } else if let Some(timeline) = self.timeline.take() {
+                self.loader = Some(timeline.older(None));
+            } else {
+                return task::Poll::Ready(None);
+            }
+        }
+    }
+}
+
 /// Represents an in-progress tweet before it is sent.
 ///
 /// This is your entry point to posting new tweets to Twitter. To begin, make a new `DraftTweet` by
@@ -683,18 +1298,18 @@ pub struct DraftTweet {
     ///The text of the draft tweet.
     pub text: Cow<'static, str>,
     ///If present, the ID of the tweet this draft is replying to.
-    pub in_reply_to: Option<u64>,
+    pub in_reply_to: Option<TweetId>,
     ///If present, whether to automatically fill reply mentions from the metadata of the
     ///`in_reply_to` tweet.
     pub auto_populate_reply_metadata: Option<bool>,
     ///If present, the list of user IDs to exclude from the automatically-populated metadata pulled
     ///when `auto_populate_reply_metadata` is true.
-    pub exclude_reply_user_ids: Option<Cow<'static, [u64]>>,
+    pub exclude_reply_user_ids: Option<Cow<'static, [user::UserId]>>,
     ///If present, the tweet link to quote or a [DM deep link][] to include in the tweet's
     ///attachment metadata.
     ///
-    ///Note that if this link is not a tweet link or a [DM deep link][], Twitter will return an
-    ///error when the draft is sent.
+    ///Note that if this link is not a tweet link or a [DM deep link][], `send` will return an
+    ///error.
     ///
     ///[DM deep link]: https://business.twitter.com/en/help/campaign-editing-and-optimization/public-to-private-conversation.html
     pub attachment_url: Option<CowStr>,
@@ -713,7 +1328,8 @@ pub struct DraftTweet {
     ///
     ///[the `media` module]: ../media/index.html
     ///
-    ///`DraftTweet` treats zeros in this array as if the media were not present.
+    ///If more than four media IDs are present, `send` will return an error without contacting
+    ///Twitter.
     pub media_ids: Vec<media::MediaId>,
     ///States whether the media attached with `media_ids` should be labeled as "possibly
     ///sensitive", to mask the media by default.
@@ -741,9 +1357,9 @@ impl DraftTweet {
     ///
     ///Note that this will only properly take effect if the user who posted the given status is
     ///@mentioned in the status text, or if the given status was posted by the authenticated user.
-    pub fn in_reply_to(self, in_reply_to: u64) -> Self {
+    pub fn in_reply_to(self, in_reply_to: impl Into<TweetId>) -> Self {
         DraftTweet {
-            in_reply_to: Some(in_reply_to),
+            in_reply_to: Some(in_reply_to.into()),
             ..self
         }
     }
@@ -770,9 +1386,41 @@ impl DraftTweet {
     ///
     ///Note that you cannot use this parameter to remove the author of the parent tweet from the
     ///reply list. Twitter will silently ignore the author's ID in that scenario.
-    pub fn exclude_reply_user_ids<V: Into<Cow<'static, [u64]>>>(self, user_ids: V) -> Self {
+    ///
+    ///# Example
+    ///
+    ///```rust
+    ///use egg_mode::tweet::DraftTweet;
+    ///
+    /////reply to a thread, but leave one of the participants out of the mentions
+    ///let draft = DraftTweet::new("I agree with you!")
+    ///    .in_reply_to(782349500404862976)
+    ///    .auto_populate_reply_metadata(true)
+    ///    .exclude_reply_user_ids(&[19603208]);
+    ///assert_eq!(draft.exclude_reply_user_ids.unwrap().len(), 1);
+    ///```
+    pub fn exclude_reply_user_ids<I, T>(self, user_ids: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<user::UserId>,
+    {
+        let user_ids = user_ids.into_iter().map(Into::into).collect::<Vec<_>>();
         DraftTweet {
-            exclude_reply_user_ids: Some(user_ids.into()),
+            exclude_reply_user_ids: Some(Cow::Owned(user_ids)),
+            ..self
+        }
+    }
+
+    ///Adds a single user ID to the list of users excluded from the automatically-populated reply
+    ///mentions. See `exclude_reply_user_ids` for details.
+    pub fn exclude_reply_user(self, user_id: impl Into<user::UserId>) -> Self {
+        let mut user_ids = self
+            .exclude_reply_user_ids
+            .map(Cow::into_owned)
+            .unwrap_or_default();
+        user_ids.push(user_id.into());
+        DraftTweet {
+            exclude_reply_user_ids: Some(Cow::Owned(user_ids)),
             ..self
         }
     }
@@ -780,8 +1428,9 @@ impl DraftTweet {
     ///Attaches the given tweet URL or [DM deep link][] to the tweet draft, which lets it be used
     ///outside the 280 character text limit.
     ///
-    ///Note that if this link is not a tweet URL or a DM deep link, then Twitter will return an
-    ///error when this draft is sent.
+    ///If this link is not a tweet URL or a DM deep link, then `send` will return an
+    ///`Error::InvalidParam` without contacting Twitter. To quote a tweet you already have on hand,
+    ///`quote` will build the URL for you.
     ///
     ///[DM deep link]: https://business.twitter.com/en/help/campaign-editing-and-optimization/public-to-private-conversation.html
     pub fn attachment_url<S: Into<Cow<'static, str>>>(self, url: S) -> Self {
@@ -791,6 +1440,23 @@ impl DraftTweet {
         }
     }
 
+    ///Quotes the given tweet, by attaching its permalink with `attachment_url`.
+    ///
+    ///This only needs the tweet's ID and the screen name of its author, so the given tweet must
+    ///have its `user` field populated. If it doesn't, the draft is returned unchanged.
+    pub fn quote(self, tweet: &Tweet) -> Self {
+        match tweet.user {
+            Some(ref user) => {
+                let url = format!(
+                    "https://twitter.com/{}/status/{}",
+                    user.screen_name, tweet.id
+                );
+                self.attachment_url(url)
+            }
+            None => self,
+        }
+    }
+
     ///Attach a lat/lon coordinate to this tweet, and mark whether a pin should be placed on the
     ///exact coordinate when the tweet is displayed.
     ///
@@ -807,6 +1473,16 @@ impl DraftTweet {
         }
     }
 
+    ///Marks whether a pin should be placed on the exact coordinate attached with `coordinates` when
+    ///the tweet is displayed. This overrides the choice given to `coordinates`, and has no effect
+    ///if no coordinate is attached.
+    pub fn display_coordinates(self, display: bool) -> Self {
+        DraftTweet {
+            display_coordinates: Some(display),
+            ..self
+        }
+    }
+
     ///Attach a Place to this tweet. This field will take precedence over `coordinates` in terms of
     ///what location is displayed with the tweet.
     ///
@@ -830,6 +1506,66 @@ impl DraftTweet {
         self.media_ids.push(media_id);
     }
 
+    ///Sets the media attached to this tweet, replacing any media that was attached before.
+    ///
+    ///Media IDs can be given either as the `MediaId` from an uploaded `MediaHandle`, or as the raw
+    ///numeric ID. Twitter will only allow one GIF, one video, or up to four images to be attached
+    ///to a single tweet; if more than four IDs are given, `send` will return an
+    ///`Error::InvalidParam` rather than sending the tweet.
+    ///
+    ///# Example
+    ///
+    ///```rust
+    ///use egg_mode::tweet::DraftTweet;
+    ///
+    ///let draft = DraftTweet::new("Look at these pictures!")
+    ///    .media_ids(&[710511363345354753, 710511363345354754]);
+    ///assert_eq!(draft.media_ids.len(), 2);
+    ///```
+    pub fn media_ids<I, T>(self, media_ids: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<media::MediaId>,
+    {
+        DraftTweet {
+            media_ids: media_ids.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    ///Waits for the given upload to finish, then attaches the resulting media to this tweet.
+    ///
+    ///This is a convenience for passing the future returned by `media::upload_media` (or any other
+    ///upload function) straight into the draft. If the upload fails, its error is returned and the
+    ///draft is dropped.
+    ///
+    ///# Example
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# #[tokio::main]
+    ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///# let token: Token = unimplemented!();
+    ///use egg_mode::media::{media_types, upload_media};
+    ///use egg_mode::tweet::DraftTweet;
+    ///
+    ///let image = std::fs::read("cat.png")?;
+    ///let draft = DraftTweet::new("Look at this cat!")
+    ///    .attach_media(upload_media(&image, &media_types::image_png(), &token))
+    ///    .await?;
+    ///draft.send(&token).await?;
+    ///# Ok(())
+    ///# }
+    ///```
+    pub async fn attach_media<F>(mut self, upload: F) -> Result<Self>
+    where
+        F: Future<Output = Result<media::MediaHandle>>,
+    {
+        let handle = upload.await?;
+        self.media_ids.push(handle.id);
+        Ok(self)
+    }
+
     ///Marks the media attached with `media_ids` as being sensitive, so it can be hidden by
     ///default.
     pub fn possibly_sensitive(self, sensitive: bool) -> Self {
@@ -840,8 +1576,31 @@ impl DraftTweet {
     }
 
     ///Send the assembled tweet as the authenticated user.
+    ///
+    ///If more than four media IDs are attached, if `attachment_url` is not a tweet permalink or DM
+    ///deep link, or if the coordinate given to `coordinates` is out of range, this returns
+    ///`Error::InvalidParam` without contacting Twitter.
     pub async fn send(&self, token: &auth::Token) -> Result<Response<Tweet>> {
+        if self.media_ids.len() > 4 {
+            return Err(error::Error::InvalidParam(
+                "a tweet can have at most four media attachments",
+            ));
+        }
+
+        if let Some((lat, long)) = self.coordinates {
+            check_coordinates(lat, long)?;
+        }
+
+        if let Some(ref url) = self.attachment_url {
+            if !is_attachment_url(url) {
+                return Err(error::Error::InvalidParam(
+                    "attachment_url must be a tweet permalink or a DM deep link",
+                ));
+            }
+        }
+
         let mut params = ParamList::new()
+            .extended_tweets()
             .add_param("status", self.text.clone())
             .add_opt_param("in_reply_to_status_id", self.in_reply_to.map_string())
             .add_opt_param(
@@ -885,9 +1644,23 @@ impl DraftTweet {
     }
 }
 
+///Returns whether the given URL can be used as the `attachment_url` of a tweet: either the
+///permalink of a tweet, or a DM deep link.
+fn is_attachment_url(url: &str) -> bool {
+    use lazy_static::lazy_static;
+    lazy_static! {
+        static ref RE_ATTACHMENT: Regex = Regex::new(
+            r"^https?://(www\.|mobile\.)?twitter\.com/(\w+/status(es)?/\d+|messages/compose\?recipient_id=\d+)([/?#&].*)?$"
+        )
+        .unwrap();
+    }
+
+    RE_ATTACHMENT.is_match(url)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Tweet;
+    use super::{is_attachment_url, DraftTweet, FilterLevel, Timeline, Tweet, TweetId};
     use crate::common::tests::load_file;
 
     use chrono::{Datelike, Timelike, Weekday};
@@ -909,6 +1682,10 @@ mod tests {
         let source = sample.source.as_ref().unwrap();
         assert_eq!(source.name, "Tweetbot for iΟS"); //note that's an omicron, not an O
         assert_eq!(source.url, "http://tapbots.com/tweetbot");
+        assert_eq!(
+            source.raw.as_deref(),
+            Some("<a href=\"http://tapbots.com/tweetbot\" rel=\"nofollow\">Tweetbot for iΟS</a>")
+        );
         assert_eq!(sample.created_at.weekday(), Weekday::Sat);
         assert_eq!(sample.created_at.year(), 2016);
         assert_eq!(sample.created_at.month(), 10);
@@ -939,7 +1716,24 @@ mod tests {
         assert_eq!(&sample.text[range.0..range.1],
                    ".@Serrayak said he’d use what-ev-er I came up with as his Halloween avatar so I’m just making sure you all know he said that"
         );
-        assert_eq!(sample.truncated, false);
+        assert!(!sample.truncated);
+    }
+
+    #[test]
+    fn parse_compatibility_extended() {
+        let sample = load_tweet("sample_payloads/compatibilityplus_classic_hidden_13797.json");
+
+        assert!(sample
+            .text
+            .ends_with("from the owl's nest https://t.co/Q0pkaU4ORH"));
+        let (start, end) = sample.display_text_range.unwrap();
+        assert_eq!(&sample.text[..start], "@jeremycloud ");
+        assert_eq!(end, sample.text.len() - " https://t.co/Q0pkaU4ORH".len());
+        assert!(sample
+            .entities
+            .urls
+            .iter()
+            .all(|u| sample.text[u.range.0..u.range.1] == u.url));
     }
 
     #[test]
@@ -955,6 +1749,77 @@ mod tests {
         load_tweet("sample_payloads/nullable_user_mention.json");
     }
 
+    #[test]
+    fn timeline_checkpoint() {
+        use crate::state::MemoryStore;
+
+        let store = MemoryStore::new();
+        let token = crate::Token::Bearer("token".to_string());
+        let mut timeline = Timeline::new(crate::links::statuses::HOME_TIMELINE, None, &token);
+
+        assert!(!timeline.load_checkpoint(&store, "home").unwrap());
+        timeline.save_checkpoint(&store, "home").unwrap();
+        assert!(!timeline.load_checkpoint(&store, "home").unwrap());
+
+        timeline.max_id = Some(TweetId(782349500404862976));
+        timeline.save_checkpoint(&store, "home").unwrap();
+        timeline.reset();
+        timeline.save_checkpoint(&store, "home").unwrap();
+
+        let mut resumed = Timeline::new(crate::links::statuses::HOME_TIMELINE, None, &token);
+        assert!(resumed.load_checkpoint(&store, "home").unwrap());
+        assert_eq!(resumed.max_id, Some(TweetId(782349500404862976)));
+        assert_eq!(resumed.min_id, resumed.max_id);
+        assert!(!resumed.load_checkpoint(&store, "mentions").unwrap());
+    }
+
+    #[test]
+    fn parse_trimmed_user() {
+        let sample = load_file("sample_payloads/sample-reply.json");
+        let mut sample: serde_json::Value = ::serde_json::from_str(&sample).unwrap();
+        sample["user"] = serde_json::json!({
+            "id": 2977334326u64,
+            "id_str": "2977334326",
+        });
+
+        let tweet: Tweet = ::serde_json::from_value(sample).unwrap();
+        assert!(tweet.user.is_none());
+        assert_eq!(tweet.id, 782644334671691776);
+    }
+
+    #[test]
+    fn timeline_filter() {
+        let token = crate::Token::Bearer("token".to_string());
+        let timeline = Timeline::new(crate::links::statuses::MENTIONS_TIMELINE, None, &token)
+            .with_filter(|t| t.retweeted_status.is_none())
+            .with_filter(|t| t.in_reply_to_status_id.is_some());
+
+        let mut tweets = vec![
+            load_tweet("sample_payloads/sample-reply.json"),
+            load_tweet("sample_payloads/sample-retweet.json"),
+            load_tweet("sample_payloads/sample-quote.json"),
+        ];
+        timeline.apply_filter(&mut tweets);
+        assert_eq!(tweets.len(), 1);
+        assert_eq!(tweets[0].id, 782644334671691776);
+    }
+
+    #[test]
+    fn parse_withheld() {
+        let sample = load_tweet("sample_payloads/sample-withheld.json");
+
+        assert_eq!(sample.possibly_sensitive, Some(true));
+        assert_eq!(sample.filter_level, Some(FilterLevel::Medium));
+        assert!(!sample.withheld_copyright);
+        assert_eq!(sample.withheld_scope.as_deref(), Some("status"));
+        assert!(sample.is_withheld_in("de"));
+        assert!(!sample.is_withheld_in("US"));
+
+        let sample = load_tweet("sample_payloads/sample-reply.json");
+        assert_eq!(sample.withheld_in_countries, None);
+        assert!(!sample.is_withheld_in("DE"));
+    }
+
     #[test]
     fn parse_reply() {
         let sample = load_tweet("sample_payloads/sample-reply.json");
@@ -963,16 +1828,21 @@ mod tests {
             sample.in_reply_to_screen_name,
             Some("QuietMisdreavus".to_string())
         );
-        assert_eq!(sample.in_reply_to_user_id, Some(2977334326));
-        assert_eq!(sample.in_reply_to_status_id, Some(782643731665080322));
+        assert_eq!(sample.in_reply_to_user_id, Some(2977334326.into()));
+        assert_eq!(
+            sample.in_reply_to_status_id,
+            Some(782643731665080322.into())
+        );
     }
 
     #[test]
     fn parse_quote() {
         let sample = load_tweet("sample_payloads/sample-quote.json");
 
-        assert_eq!(sample.quoted_status_id, Some(783004145485840384));
+        assert_eq!(sample.quoted_status_id, Some(783004145485840384.into()));
+        assert!(sample.is_quote_status);
         assert!(sample.quoted_status.is_some());
+        assert_eq!(sample.quote_chain().count(), 1);
         assert_eq!(sample.quoted_status.unwrap().text,
                    "@chalkboardsband hot damn i should call up my friends in austin, i might actually be able to make one of these now :D");
     }
@@ -1007,4 +1877,108 @@ mod tests {
 
         assert_eq!(json1, json2);
     }
+
+    #[test]
+    fn liked_by_params() {
+        let token = crate::auth::Token::Bearer("token".to_string());
+        let mut timeline = super::liked_by("rustlang", &token).with_page_size(200);
+        timeline.min_id = Some(TweetId(782349500404862976));
+
+        let req = timeline.request(None, timeline.min_id.map(|id| TweetId(id.0 - 1)));
+        let uri = req.uri().to_string();
+        assert!(uri.starts_with(crate::links::statuses::LIKES_OF));
+        assert!(uri.contains("screen_name=rustlang"));
+        assert!(uri.contains("count=200"));
+        assert!(uri.contains("max_id=782349500404862975"));
+        assert!(!uri.contains("since_id"));
+    }
+
+    #[tokio::test]
+    async fn draft_coordinates() {
+        let draft = DraftTweet::new("here")
+            .coordinates(37.76893497, -122.42284884, false)
+            .display_coordinates(true)
+            .place_id("5a110d312052166f");
+        assert_eq!(draft.coordinates, Some((37.76893497, -122.42284884)));
+        assert_eq!(draft.display_coordinates, Some(true));
+        assert_eq!(draft.place_id.as_deref(), Some("5a110d312052166f"));
+
+        let token = crate::auth::Token::Bearer("not a real token".to_string());
+        let draft = DraftTweet::new("nowhere").coordinates(-122.42284884, 37.76893497, true);
+        match draft.send(&token).await {
+            Err(crate::error::Error::InvalidParam(_)) => (),
+            other => panic!(
+                "expected InvalidParam, got {:?}",
+                other.map(|r| r.response.id)
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn draft_media_limit() {
+        let token = crate::auth::Token::Bearer("not a real token".to_string());
+        let draft = DraftTweet::new("too many pictures").media_ids(vec![1u64, 2, 3, 4, 5]);
+
+        match draft.send(&token).await {
+            Err(crate::error::Error::InvalidParam(_)) => (),
+            other => panic!(
+                "expected InvalidParam, got {:?}",
+                other.map(|r| r.response.id)
+            ),
+        }
+    }
+
+    #[test]
+    fn snowflake_timestamps() {
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        let posted = sample.id.timestamp().unwrap();
+
+        assert_eq!(posted.timestamp(), sample.created_at.timestamp());
+        assert!(TweetId::from_timestamp(sample.created_at) <= sample.id);
+        assert!(
+            TweetId::from_timestamp(sample.created_at + chrono::Duration::seconds(1)) > sample.id
+        );
+        assert_eq!(TweetId::from(20).timestamp(), None);
+    }
+
+    #[cfg(feature = "raw-json")]
+    #[test]
+    fn raw_json() {
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        let raw = sample.raw().unwrap();
+        assert_eq!(raw["id_str"], "782349500404862976");
+
+        let user = sample.user.as_ref().unwrap();
+        assert_eq!(user.raw().unwrap()["screen_name"], "0xabad1dea");
+
+        let copy: Tweet = serde_json::from_value(serde_json::to_value(&sample).unwrap()).unwrap();
+        assert!(copy.raw().unwrap().get("id_str").is_none());
+    }
+
+    #[cfg(not(feature = "raw-json"))]
+    #[test]
+    fn no_raw_json() {
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        assert!(sample.raw().is_none());
+        assert!(sample.user.as_ref().unwrap().raw().is_none());
+    }
+
+    #[test]
+    fn attachment_urls() {
+        assert!(is_attachment_url(
+            "https://twitter.com/0xabad1dea/status/782349500404862976"
+        ));
+        assert!(is_attachment_url(
+            "https://mobile.twitter.com/0xabad1dea/status/782349500404862976?s=20"
+        ));
+        assert!(is_attachment_url(
+            "https://twitter.com/messages/compose?recipient_id=3805104374&text=hi"
+        ));
+        assert!(!is_attachment_url("https://twitter.com/0xabad1dea"));
+        assert!(!is_attachment_url("https://example.com/a/status/1"));
+
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        let draft = DraftTweet::new("look at this").quote(&sample);
+        assert!(is_attachment_url(draft.attachment_url.as_ref().unwrap()));
+    }
 }
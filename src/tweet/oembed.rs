@@ -0,0 +1,291 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::error::Result;
+use crate::{auth, links};
+
+use super::TweetId;
+
+/// The color scheme to render an embedded tweet with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmbedTheme {
+    /// Dark text on a light background. This is the default.
+    Light,
+    /// Light text on a dark background.
+    Dark,
+}
+
+impl std::fmt::Display for EmbedTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            EmbedTheme::Light => write!(f, "light"),
+            EmbedTheme::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+/// How to float an embedded tweet within the surrounding text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmbedAlign {
+    /// Float the tweet to the left.
+    Left,
+    /// Float the tweet to the right.
+    Right,
+    /// Center the tweet, without floating it.
+    Center,
+    /// Don't apply any alignment. This is the default.
+    None,
+}
+
+impl std::fmt::Display for EmbedAlign {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            EmbedAlign::Left => write!(f, "left"),
+            EmbedAlign::Right => write!(f, "right"),
+            EmbedAlign::Center => write!(f, "center"),
+            EmbedAlign::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Options for rendering an embedded tweet with [`oembed`].
+///
+/// [`oembed`]: fn.oembed.html
+///
+/// All the options are unset by default, leaving Twitter to pick its own defaults. Use the builder
+/// methods to set the ones you want to change.
+///
+/// # Example
+///
+/// ```rust
+/// use egg_mode::tweet::{EmbedOptions, EmbedTheme};
+///
+/// let options = EmbedOptions::new()
+///     .theme(EmbedTheme::Dark)
+///     .hide_thread(true)
+///     .lang("fr");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EmbedOptions {
+    /// The maximum width of the rendered tweet, in pixels. Twitter accepts values between 220 and
+    /// 550.
+    pub max_width: Option<u32>,
+    /// Whether to hide photos, videos, and link previews attached to the tweet.
+    pub hide_media: Option<bool>,
+    /// Whether to hide the tweet this one is replying to, if any.
+    pub hide_thread: Option<bool>,
+    /// Whether to leave out the `<script>` tag that loads Twitter's widget JavaScript. Set this if
+    /// you're embedding several tweets on one page and loading the script yourself.
+    pub omit_script: Option<bool>,
+    /// How to float the tweet within the surrounding text.
+    pub align: Option<EmbedAlign>,
+    /// A list of screen names to suggest to the reader after they interact with the tweet.
+    pub related: Vec<String>,
+    /// The language code to render the tweet's surrounding text in, such as `"en"` or `"ja"`.
+    pub lang: Option<CowStr>,
+    /// The color scheme to render the tweet with.
+    pub theme: Option<EmbedTheme>,
+    /// Whether the embedded tweet should opt out of Twitter's tailoring and personalization.
+    pub dnt: Option<bool>,
+}
+
+impl EmbedOptions {
+    /// Creates a new `EmbedOptions` with all options unset.
+    pub fn new() -> EmbedOptions {
+        EmbedOptions::default()
+    }
+
+    /// Sets the maximum width of the rendered tweet, in pixels.
+    pub fn max_width(self, max_width: u32) -> Self {
+        EmbedOptions {
+            max_width: Some(max_width),
+            ..self
+        }
+    }
+
+    /// Sets whether to hide the media attached to the tweet.
+    pub fn hide_media(self, hide_media: bool) -> Self {
+        EmbedOptions {
+            hide_media: Some(hide_media),
+            ..self
+        }
+    }
+
+    /// Sets whether to hide the tweet this one is replying to.
+    pub fn hide_thread(self, hide_thread: bool) -> Self {
+        EmbedOptions {
+            hide_thread: Some(hide_thread),
+            ..self
+        }
+    }
+
+    /// Sets whether to leave out the widget `<script>` tag from the returned HTML.
+    pub fn omit_script(self, omit_script: bool) -> Self {
+        EmbedOptions {
+            omit_script: Some(omit_script),
+            ..self
+        }
+    }
+
+    /// Sets how to float the tweet within the surrounding text.
+    pub fn align(self, align: EmbedAlign) -> Self {
+        EmbedOptions {
+            align: Some(align),
+            ..self
+        }
+    }
+
+    /// Adds the given screen names to the accounts suggested to the reader.
+    pub fn related<I: IntoIterator<Item = S>, S: AsRef<str>>(mut self, screen_names: I) -> Self {
+        self.related
+            .extend(screen_names.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Sets the language to render the tweet's surrounding text in.
+    pub fn lang(self, lang: impl Into<CowStr>) -> Self {
+        EmbedOptions {
+            lang: Some(lang.into()),
+            ..self
+        }
+    }
+
+    /// Sets the color scheme to render the tweet with.
+    pub fn theme(self, theme: EmbedTheme) -> Self {
+        EmbedOptions {
+            theme: Some(theme),
+            ..self
+        }
+    }
+
+    /// Sets whether the embedded tweet should opt out of Twitter's personalization.
+    pub fn dnt(self, dnt: bool) -> Self {
+        EmbedOptions {
+            dnt: Some(dnt),
+            ..self
+        }
+    }
+
+    fn into_params(self) -> ParamList {
+        let mut params = ParamList::new()
+            .add_opt_param("maxwidth", self.max_width.map_string())
+            .add_opt_param("hide_media", self.hide_media.map_string())
+            .add_opt_param("hide_thread", self.hide_thread.map_string())
+            .add_opt_param("omit_script", self.omit_script.map_string())
+            .add_opt_param("align", self.align.map_string())
+            .add_opt_param("lang", self.lang)
+            .add_opt_param("theme", self.theme.map_string())
+            .add_opt_param("dnt", self.dnt.map_string());
+
+        if !self.related.is_empty() {
+            params.add_param_ref("related", self.related.join(","));
+        }
+
+        params
+    }
+}
+
+/// The HTML and metadata needed to embed a tweet on a web page, as returned by [`oembed`].
+///
+/// [`oembed`]: fn.oembed.html
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TweetEmbed {
+    /// The URL of the embedded tweet.
+    pub url: String,
+    /// The HTML snippet to place in your page to display the tweet.
+    pub html: String,
+    /// The display name of the tweet's author.
+    pub author_name: String,
+    /// The URL of the author's profile.
+    pub author_url: String,
+    /// The width of the rendered tweet, in pixels, if Twitter gave one.
+    pub width: Option<u32>,
+    /// The height of the rendered tweet, in pixels, if Twitter gave one. This is usually absent,
+    /// since the height depends on the tweet's content.
+    pub height: Option<u32>,
+    /// How long, in seconds, the HTML can be cached before it should be requested again.
+    #[serde(default, with = "serde_cache_age")]
+    pub cache_age: Option<u64>,
+    /// The name of the service providing the embed, i.e. "Twitter".
+    pub provider_name: String,
+    /// The URL of the service providing the embed.
+    pub provider_url: String,
+    /// The version of the oEmbed specification the response follows.
+    pub version: String,
+}
+
+/// Twitter returns `cache_age` as a string, so this parses the number out of it.
+mod serde_cache_age {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deser: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let age = Option::<String>::deserialize(deser)?;
+        Ok(age.and_then(|age| age.parse().ok()))
+    }
+
+    pub fn serialize<S>(src: &Option<u64>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match src {
+            Some(age) => ser.serialize_some(&age.to_string()),
+            None => ser.serialize_none(),
+        }
+    }
+}
+
+/// Loads the HTML needed to embed the given tweet on a web page.
+///
+/// The returned `TweetEmbed` contains a snippet of HTML that renders the tweet like it appears on
+/// Twitter, along with some information about its author. See `EmbedOptions` for the ways you can
+/// change how the tweet is rendered.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet::{self, EmbedOptions, EmbedTheme};
+///
+/// let options = EmbedOptions::new().theme(EmbedTheme::Dark);
+/// let embed = tweet::oembed(782349500404862976, options, &token).await.unwrap();
+/// println!("{}", embed.html);
+/// # }
+/// ```
+pub async fn oembed(
+    id: impl Into<TweetId>,
+    options: EmbedOptions,
+    token: &auth::Token,
+) -> Result<Response<TweetEmbed>> {
+    let id: TweetId = id.into();
+    let params = options.into_params().add_param("id", id.to_string());
+    let req = get(links::statuses::OEMBED, token, Some(&params));
+    request_with_json_response(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_file;
+
+    #[test]
+    fn parse_oembed() {
+        let content = load_file("sample_payloads/sample-oembed.json");
+        let embed = ::serde_json::from_str::<TweetEmbed>(&content).unwrap();
+
+        assert_eq!(embed.author_name, "US Dept of Interior");
+        assert_eq!(embed.width, Some(550));
+        assert_eq!(embed.height, None);
+        assert_eq!(embed.cache_age, Some(3153600000));
+        assert!(embed.html.starts_with("<blockquote"));
+    }
+}
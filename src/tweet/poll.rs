@@ -0,0 +1,155 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A poll attached to a tweet.
+///
+/// Twitter delivers polls as a "card" attached to the tweet. Cards are only included when they're
+/// specifically requested, so this is only present on tweets loaded from a source that includes
+/// them. See the `poll` field on `Tweet` for details.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Poll {
+    /// The choices available in the poll, in the order they're displayed.
+    pub options: Vec<PollOption>,
+    /// The time at which voting on the poll closes (or closed).
+    pub end_time: Option<DateTime<Utc>>,
+    /// How long the poll was open for voting, in minutes.
+    pub duration_minutes: Option<u32>,
+    /// Whether the poll has closed and the vote counts in `options` are final.
+    pub counts_are_final: bool,
+}
+
+impl Poll {
+    /// Returns the total number of votes cast in the poll.
+    pub fn total_votes(&self) -> u64 {
+        self.options.iter().map(|opt| opt.votes).sum()
+    }
+
+    /// Returns whether the poll is still open for voting at the given time.
+    pub fn is_open_at(&self, time: DateTime<Utc>) -> bool {
+        !self.counts_are_final && matches!(self.end_time, Some(end) if time < end)
+    }
+}
+
+/// A single choice in a `Poll`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PollOption {
+    /// The position of this choice in the poll, starting from 1.
+    pub position: u32,
+    /// The text of this choice.
+    pub label: String,
+    /// The number of votes this choice has received.
+    pub votes: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawCard {
+    pub name: String,
+    #[serde(default)]
+    pub binding_values: HashMap<String, RawBindingValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawBindingValue {
+    pub string_value: Option<String>,
+    pub boolean_value: Option<bool>,
+}
+
+impl RawCard {
+    fn string(&self, key: &str) -> Option<&str> {
+        self.binding_values
+            .get(key)
+            .and_then(|val| val.string_value.as_deref())
+    }
+
+    /// Pulls the poll out of this card, or returns `None` if the card isn't a poll.
+    pub fn into_poll(self) -> Option<Poll> {
+        //poll cards are named after the number of choices they have, e.g. "poll2choice_text_only"
+        //or "poll4choice_image"
+        if !self.name.starts_with("poll") {
+            return None;
+        }
+
+        let mut options = Vec::new();
+        for position in 1.. {
+            let label = match self.string(&format!("choice{}_label", position)) {
+                Some(label) => label.to_string(),
+                None => break,
+            };
+            let votes = self
+                .string(&format!("choice{}_count", position))
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(0);
+            options.push(PollOption {
+                position,
+                label,
+                votes,
+            });
+        }
+
+        if options.is_empty() {
+            return None;
+        }
+
+        Some(Poll {
+            options,
+            end_time: self
+                .string("end_datetime_utc")
+                .and_then(|end| end.parse().ok()),
+            duration_minutes: self
+                .string("duration_minutes")
+                .and_then(|mins| mins.parse().ok()),
+            counts_are_final: self
+                .binding_values
+                .get("counts_are_final")
+                .and_then(|val| val.boolean_value)
+                .unwrap_or(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::tests::load_file;
+    use crate::tweet::Tweet;
+
+    use chrono::{DateTime, Duration, Utc};
+
+    #[test]
+    fn parse_poll() {
+        let content = load_file("sample_payloads/sample-poll.json");
+        let tweet: Tweet = ::serde_json::from_str(&content).unwrap();
+        let poll = tweet.poll.as_ref().unwrap();
+
+        assert_eq!(poll.options.len(), 3);
+        assert_eq!(poll.options[0].label, "async/await");
+        assert_eq!(poll.options[2].position, 3);
+        assert_eq!(poll.total_votes(), 95);
+        assert_eq!(poll.duration_minutes, Some(1440));
+        assert!(poll.counts_are_final);
+
+        let end: DateTime<Utc> = "2019-11-08T18:12:04Z".parse().unwrap();
+        assert_eq!(poll.end_time, Some(end));
+        assert!(!poll.is_open_at(end - Duration::hours(1)));
+
+        //make sure the poll survives a round trip through serialization
+        let ser = ::serde_json::to_string(&tweet).unwrap();
+        let tweet: Tweet = ::serde_json::from_str(&ser).unwrap();
+        assert_eq!(tweet.poll.as_ref(), Some(poll));
+    }
+
+    #[test]
+    fn timeline_requests_cards() {
+        let token = crate::auth::Token::Bearer("token".to_string());
+        let timeline = crate::tweet::home_timeline(&token);
+
+        let uri = timeline.request(None, None).uri().to_string();
+        assert!(uri.contains("include_cards=true"));
+        assert!(uri.contains("cards_platform=Web-12"));
+    }
+}
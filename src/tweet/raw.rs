@@ -1,15 +1,18 @@
 use crate::{place, user};
 use chrono;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 
 use crate::common::serde_datetime;
 
+use super::poll::RawCard;
 use super::{
-    deserialize_tweet_source, ExtendedTweetEntities, FilterLevel, Tweet, TweetEntities, TweetSource,
+    deserialize_tweet_source, ExtendedTweetEntities, FilterLevel, Tweet, TweetEntities, TweetId,
+    TweetSource,
 };
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct RawTweet {
+    pub card: Option<RawCard>,
     pub coordinates: Option<RawCoordinates>,
     #[serde(with = "serde_datetime")]
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -21,14 +24,16 @@ pub(crate) struct RawTweet {
     pub favorite_count: i32,
     pub favorited: Option<bool>,
     pub filter_level: Option<FilterLevel>,
-    pub id: u64,
-    pub in_reply_to_user_id: Option<u64>,
+    pub id: TweetId,
+    pub in_reply_to_user_id: Option<user::UserId>,
     pub in_reply_to_screen_name: Option<String>,
-    pub in_reply_to_status_id: Option<u64>,
+    pub in_reply_to_status_id: Option<TweetId>,
+    #[serde(default)]
+    pub is_quote_status: bool,
     pub lang: Option<String>,
     pub place: Option<place::Place>,
     pub possibly_sensitive: Option<bool>,
-    pub quoted_status_id: Option<u64>,
+    pub quoted_status_id: Option<TweetId>,
     pub quoted_status: Option<Box<Tweet>>,
     pub retweet_count: i32,
     pub retweeted: Option<bool>,
@@ -38,6 +43,7 @@ pub(crate) struct RawTweet {
     pub text: Option<String>,
     pub full_text: Option<String>,
     pub truncated: bool,
+    #[serde(default, deserialize_with = "deserialize_author")]
     pub user: Option<Box<user::TwitterUser>>,
     #[serde(default)]
     pub withheld_copyright: bool,
@@ -45,6 +51,22 @@ pub(crate) struct RawTweet {
     pub withheld_scope: Option<String>,
 }
 
+/// Loads the author of a tweet, treating the stub user object that Twitter sends when `trim_user`
+/// is set (which only contains the user's ID) as absent.
+fn deserialize_author<'de, D>(deser: D) -> Result<Option<Box<user::TwitterUser>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    match Option::<serde_json::Value>::deserialize(deser)? {
+        Some(user) if user.get("screen_name").is_some() => serde_json::from_value(user)
+            .map(Some)
+            .map_err(D::Error::custom),
+        _ => Ok(None),
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct RawExtendedTweet {
     pub full_text: String,
@@ -54,6 +76,7 @@ pub(crate) struct RawExtendedTweet {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
 pub(crate) struct RawCoordinates {
     #[serde(rename = "type")]
     pub kind: String,
@@ -62,5 +85,5 @@ pub(crate) struct RawCoordinates {
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct CurrentUserRetweet {
-    pub id: u64,
+    pub id: TweetId,
 }
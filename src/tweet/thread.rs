@@ -0,0 +1,254 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::borrow::Cow;
+
+use crate::auth;
+use crate::common::*;
+use crate::error::Result;
+
+use super::{DraftTweet, Tweet, TweetId};
+
+/// The maximum weighted length of a single tweet.
+pub const MAX_TWEET_LENGTH: usize = 280;
+
+/// The length that Twitter counts every URL as, since it shortens them all with t.co.
+const URL_LENGTH: usize = 23;
+
+fn char_weight(c: char) -> usize {
+    match c as u32 {
+        0..=4351 | 8192..=8205 | 8208..=8223 | 8242..=8247 => 1,
+        _ => 2,
+    }
+}
+
+fn is_url(word: &str) -> bool {
+    word.starts_with("http://") || word.starts_with("https://")
+}
+
+fn word_weight(word: &str) -> usize {
+    if is_url(word) {
+        URL_LENGTH
+    } else {
+        word.chars().map(char_weight).sum()
+    }
+}
+
+/// Returns the length of the given text as Twitter counts it against the 280-character limit.
+///
+/// Twitter doesn't simply count characters: most CJK characters and emoji count as two
+/// characters, and every URL counts as 23 characters regardless of its actual length, since it
+/// will be shortened with t.co. This function follows those rules, so that a text whose weighted
+/// length is at most `MAX_TWEET_LENGTH` will fit in a single tweet.
+///
+/// ```rust
+/// use egg_mode::tweet::weighted_length;
+///
+/// assert_eq!(weighted_length("hello!"), 6);
+/// assert_eq!(weighted_length("こんにちは"), 10);
+/// assert_eq!(weighted_length("see https://github.com/egg-mode-rs/egg-mode"), 27);
+/// ```
+pub fn weighted_length(text: &str) -> usize {
+    tokens(text)
+        .map(|token| {
+            let word = token.trim_end_matches(char::is_whitespace);
+            word_weight(word) + token[word.len()..].chars().map(char_weight).sum::<usize>()
+        })
+        .sum()
+}
+
+/// Splits the given text after each whitespace character, so that each piece is a word followed
+/// by the whitespace after it. (This is `str::split_inclusive`, which is newer than our MSRV.)
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| c.is_whitespace())
+            .map_or(rest.len(), |(idx, c)| idx + c.len_utf8());
+        let (token, tail) = rest.split_at(end);
+        rest = tail;
+        Some(token)
+    })
+}
+
+/// Represents a long text that should be posted as a thread of tweets.
+///
+/// A `ThreadDraft` takes text of any length and splits it into pieces that each fit within the
+/// 280-character limit (see `weighted_length` for how this is counted). Text is only split between
+/// words, so URLs and words stay intact; the only exception is a single word too long to fit in a
+/// tweet on its own, which is split wherever it needs to be.
+///
+/// When sent, each piece is posted as a reply to the previous one, forming a thread. To attach the
+/// whole thread to an existing conversation, use `in_reply_to` to give the tweet the first piece
+/// should reply to.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet::ThreadDraft;
+///
+/// let essay = std::fs::read_to_string("essay.txt").unwrap();
+/// let thread = ThreadDraft::new(essay).send(&token).await.unwrap();
+/// println!("posted {} tweets", thread.len());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThreadDraft {
+    /// The full text of the thread.
+    pub text: Cow<'static, str>,
+    /// If present, the ID of the tweet the first tweet of the thread is replying to.
+    pub in_reply_to: Option<TweetId>,
+}
+
+impl ThreadDraft {
+    /// Creates a new `ThreadDraft` with the given text.
+    pub fn new<S: Into<Cow<'static, str>>>(text: S) -> Self {
+        ThreadDraft {
+            text: text.into(),
+            in_reply_to: None,
+        }
+    }
+
+    /// Marks the first tweet of this thread as replying to the given status ID.
+    pub fn in_reply_to(self, in_reply_to: impl Into<TweetId>) -> Self {
+        ThreadDraft {
+            in_reply_to: Some(in_reply_to.into()),
+            ..self
+        }
+    }
+
+    /// Splits the text of the thread into the pieces that will be posted as individual tweets.
+    ///
+    /// Whitespace between words is kept as-is within a piece, but trimmed where the text is split.
+    pub fn split(&self) -> Vec<String> {
+        let mut pieces = Vec::new();
+        let mut current = String::new();
+        let mut weight = 0;
+
+        fn flush(pieces: &mut Vec<String>, current: &mut String, weight: &mut usize) {
+            let piece = current.trim_end();
+            if !piece.is_empty() {
+                pieces.push(piece.to_string());
+            }
+            current.clear();
+            *weight = 0;
+        }
+
+        for token in tokens(&self.text) {
+            let word = token.trim_end_matches(char::is_whitespace);
+            let space = &token[word.len()..];
+
+            if !word.is_empty() {
+                let word_weight = word_weight(word);
+                if weight + word_weight > MAX_TWEET_LENGTH {
+                    flush(&mut pieces, &mut current, &mut weight);
+                }
+
+                if word_weight > MAX_TWEET_LENGTH {
+                    for c in word.chars() {
+                        if weight + char_weight(c) > MAX_TWEET_LENGTH {
+                            flush(&mut pieces, &mut current, &mut weight);
+                        }
+                        current.push(c);
+                        weight += char_weight(c);
+                    }
+                } else {
+                    current.push_str(word);
+                    weight += word_weight;
+                }
+            }
+
+            if !current.is_empty() {
+                current.push_str(space);
+                weight += space.chars().map(char_weight).sum::<usize>();
+            }
+        }
+
+        flush(&mut pieces, &mut current, &mut weight);
+        pieces
+    }
+
+    /// Posts the thread as the authenticated user, returning the tweets that were posted in order.
+    ///
+    /// If posting one of the tweets fails, the error is returned and the rest of the thread is not
+    /// posted. The tweets posted before the failure are left in place.
+    pub async fn send(&self, token: &auth::Token) -> Result<Response<Vec<Tweet>>> {
+        let mut thread: Option<Response<Vec<Tweet>>> = None;
+        let mut in_reply_to = self.in_reply_to;
+
+        for piece in self.split() {
+            let mut draft = DraftTweet::new(piece);
+            draft.in_reply_to = in_reply_to;
+
+            let tweet = draft.send(token).await?;
+            in_reply_to = Some(tweet.id);
+
+            let tweet = Response::map(tweet, |t| vec![t]);
+            match thread {
+                Some(ref mut thread) => thread.extend(tweet),
+                None => thread = Some(tweet),
+            }
+        }
+
+        match thread {
+            Some(thread) => Ok(thread),
+            None => Err(crate::error::Error::InvalidParam(
+                "a thread must contain some text",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_thread() {
+        let sentence = "The quick brown fox jumps over the lazy dog. ";
+        let text = format!(
+            "{}https://github.com/egg-mode-rs/egg-mode/blob/master/src/tweet/mod.rs\n\n{}",
+            sentence.repeat(6),
+            sentence.repeat(10)
+        );
+        let thread = ThreadDraft::new(text.clone());
+        let pieces = thread.split();
+
+        assert!(pieces.len() > 1);
+        for piece in &pieces {
+            assert!(weighted_length(piece) <= MAX_TWEET_LENGTH);
+            assert_eq!(piece.trim(), piece);
+        }
+        assert!(pieces.iter().any(|p| p.contains("src/tweet/mod.rs\n\nThe")));
+        assert_eq!(
+            pieces.join(" ").split_whitespace().collect::<Vec<_>>(),
+            text.split_whitespace().collect::<Vec<_>>()
+        );
+
+        let long_word = "a".repeat(MAX_TWEET_LENGTH + 20);
+        let pieces = ThreadDraft::new(long_word).split();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].len(), MAX_TWEET_LENGTH);
+
+        assert!(ThreadDraft::new("  \n ").split().is_empty());
+    }
+
+    #[test]
+    fn split_tokens() {
+        assert_eq!(
+            tokens("one two\n\nthree").collect::<Vec<_>>(),
+            vec!["one ", "two\n", "\n", "three"]
+        );
+        assert_eq!(tokens("ends　").collect::<Vec<_>>(), vec!["ends　"]);
+        assert_eq!(tokens("").count(), 0);
+    }
+}
@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::tweet::Tweet;
+
+use super::{TwitterUser, UserId};
+
+/// A cache that deduplicates the user objects embedded in tweets.
+///
+/// Every tweet Twitter returns carries a full copy of its author's profile, so a large crawl of
+/// timelines, searches, or streams can end up holding thousands of copies of the same handful of
+/// `TwitterUser`s. A `UserCache` keeps one shared copy of each user, keyed by their ID, and hands
+/// out `Arc<TwitterUser>` references to it.
+///
+/// To use it, pass each tweet to `take_author` as you receive it. This removes the embedded user
+/// from the tweet (dropping the duplicate copy) and returns the shared copy from the cache, which
+/// you can keep alongside the tweet. Retweets and quoted tweets contain their own author, so if you
+/// keep those around as well, call `take_author` on them too - or use `take_all_authors`, which
+/// clears out every user in the tweet and returns the author of the outer tweet.
+///
+/// The first copy of a user that the cache sees is the one it keeps. If you'd rather keep the most
+/// recent profile information, call `update` with the newer copy; references that were handed out
+/// before the update will continue to point to the older copy.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::tweet;
+/// use egg_mode::user::UserCache;
+///
+/// let mut cache = UserCache::new();
+/// let (_timeline, feed) = tweet::home_timeline(&token).start().await.unwrap();
+///
+/// let feed = feed
+///     .response
+///     .into_iter()
+///     .map(|mut tweet| {
+///         let author = cache.take_all_authors(&mut tweet);
+///         (tweet, author)
+///     })
+///     .collect::<Vec<_>>();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UserCache {
+    users: HashMap<UserId, Arc<TwitterUser>>,
+}
+
+impl UserCache {
+    /// Creates a new, empty `UserCache`.
+    pub fn new() -> UserCache {
+        UserCache::default()
+    }
+
+    /// Returns the number of distinct users held by the cache.
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// Returns the cached copy of the given user, if present.
+    pub fn get(&self, id: impl Into<UserId>) -> Option<Arc<TwitterUser>> {
+        self.users.get(&id.into()).cloned()
+    }
+
+    /// Adds the given user to the cache, returning the shared copy. If the cache already holds a
+    /// copy of this user, the given one is dropped and the existing copy is returned.
+    pub fn insert(&mut self, user: TwitterUser) -> Arc<TwitterUser> {
+        self.users
+            .entry(user.id)
+            .or_insert_with(|| Arc::new(user))
+            .clone()
+    }
+
+    /// Replaces the cached copy of the given user with the given one, returning the new shared
+    /// copy.
+    pub fn update(&mut self, user: TwitterUser) -> Arc<TwitterUser> {
+        let user = Arc::new(user);
+        self.users.insert(user.id, user.clone());
+        user
+    }
+
+    /// Removes the given user from the cache, returning its cached copy if present.
+    pub fn remove(&mut self, id: impl Into<UserId>) -> Option<Arc<TwitterUser>> {
+        self.users.remove(&id.into())
+    }
+
+    /// Removes every user from the cache.
+    pub fn clear(&mut self) {
+        self.users.clear();
+    }
+
+    /// Removes the author embedded in the given tweet, returning the shared copy from the cache.
+    ///
+    /// Any retweeted or quoted status within the tweet is left untouched.
+    pub fn take_author(&mut self, tweet: &mut Tweet) -> Option<Arc<TwitterUser>> {
+        tweet.user.take().map(|user| self.insert(*user))
+    }
+
+    /// Removes every user embedded in the given tweet, including the authors of any retweeted or
+    /// quoted status, and returns the shared copy of the outer tweet's author.
+    ///
+    /// The inner authors are still added to the cache, but since they can no longer be found from
+    /// the tweet afterward, this is most useful when you only need the outer author.
+    pub fn take_all_authors(&mut self, tweet: &mut Tweet) -> Option<Arc<TwitterUser>> {
+        if let Some(ref mut rt) = tweet.retweeted_status {
+            self.take_all_authors(rt);
+        }
+        if let Some(ref mut quote) = tweet.quoted_status {
+            self.take_all_authors(quote);
+        }
+        self.take_author(tweet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserCache;
+    use crate::common::tests::load_file;
+    use crate::tweet::Tweet;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn dedup_tweet_authors() {
+        let content = load_file("sample_payloads/sample-retweet.json");
+        let mut first: Tweet = ::serde_json::from_str(&content).unwrap();
+        let mut second = first.clone();
+
+        let mut cache = UserCache::new();
+        let a = cache.take_author(&mut first).unwrap();
+        let b = cache.take_author(&mut second).unwrap();
+
+        assert!(first.user.is_none());
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+
+        cache.take_all_authors(&mut second);
+        assert!(second.retweeted_status.unwrap().user.is_none());
+        assert_eq!(cache.len(), 2);
+    }
+}
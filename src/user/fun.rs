@@ -73,12 +73,29 @@ use super::*;
 /// let users = egg_mode::user::lookup(list, &token).await.unwrap();
 /// # }
 /// ```
+///
+/// Twitter only accepts 100 accounts per call to this endpoint. If more than 100 accounts are
+/// given, egg-mode will split them into batches of 100 and make one call for each, returning the
+/// users from all of them in a single `Response`. The rate-limit information in that response is
+/// from the last call made. If any of the calls fail, the whole lookup returns that error.
 pub async fn lookup<T, I>(accts: I, token: &auth::Token) -> Result<Response<Vec<TwitterUser>>>
 where
     T: Into<UserID>,
     I: IntoIterator<Item = T>,
 {
-    let (id_param, name_param) = multiple_names_param(accts);
+    let accts = accts.into_iter().map(Into::into).collect::<Vec<UserID>>();
+    let mut chunks = accts.chunks(LOOKUP_CHUNK_SIZE);
+
+    let mut resp = lookup_chunk(chunks.next().unwrap_or(&[]), token).await?;
+    for chunk in chunks {
+        resp.extend(lookup_chunk(chunk, token).await?);
+    }
+
+    Ok(resp)
+}
+
+async fn lookup_chunk(accts: &[UserID], token: &auth::Token) -> Result<Response<Vec<TwitterUser>>> {
+    let (id_param, name_param) = multiple_names_param(accts.iter().cloned());
 
     let params = ParamList::new()
         .extended_tweets()
@@ -104,7 +121,7 @@ pub async fn show<T: Into<UserID>>(acct: T, token: &auth::Token) -> Result<Respo
 /// Lookup the user IDs that the authenticating user has disabled retweets from.
 ///
 /// Use `update_follow` to enable/disable viewing retweets from a specific user.
-pub async fn friends_no_retweets(token: &auth::Token) -> Result<Response<Vec<u64>>> {
+pub async fn friends_no_retweets(token: &auth::Token) -> Result<Response<Vec<UserId>>> {
     let req = get(links::users::FRIENDS_NO_RETWEETS, token, None);
 
     request_with_json_response(req).await
@@ -181,7 +198,9 @@ pub fn friends_of<T: Into<UserID>>(
     acct: T,
     token: &auth::Token,
 ) -> cursor::CursorIter<cursor::UserCursor> {
-    let params = ParamList::new().add_user_param(acct.into());
+    let params = ParamList::new()
+        .extended_tweets()
+        .add_user_param(acct.into());
     cursor::CursorIter::new(links::users::FRIENDS_LIST, token, Some(params), Some(20))
 }
 
@@ -193,7 +212,9 @@ pub fn friends_of<T: Into<UserID>>(
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To make the most of the rate limit,
+/// call `with_page_size(5000)` on the returned stream; `with_stringified_ids` asks Twitter to send
+/// the IDs as strings, though they're returned as `UserId`s either way.
 pub fn friends_ids<T: Into<UserID>>(
     acct: T,
     token: &auth::Token,
@@ -223,7 +244,9 @@ pub fn followers_of<T: Into<UserID>>(
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To make the most of the rate limit,
+/// call `with_page_size(5000)` on the returned stream; `with_stringified_ids` asks Twitter to send
+/// the IDs as strings, though they're returned as `UserId`s either way.
 pub fn followers_ids<T: Into<UserID>>(
     acct: T,
     token: &auth::Token,
@@ -239,7 +262,8 @@ pub fn followers_ids<T: Into<UserID>>(
 /// change the page size used by the network call. Setting `page_size` manually may result in an
 /// error from Twitter.
 pub fn blocks(token: &auth::Token) -> cursor::CursorIter<cursor::UserCursor> {
-    cursor::CursorIter::new(links::users::BLOCKS_LIST, token, None, None)
+    let params = ParamList::new().extended_tweets();
+    cursor::CursorIter::new(links::users::BLOCKS_LIST, token, Some(params), None)
 }
 
 /// Lookup the users that have been blocked by the authenticated user, but only return their user
@@ -264,7 +288,8 @@ pub fn blocks_ids(token: &auth::Token) -> cursor::CursorIter<cursor::IDCursor> {
 /// change the page size used by the network call. Setting `page_size` manually may result in an
 /// error from Twitter.
 pub fn mutes(token: &auth::Token) -> cursor::CursorIter<cursor::UserCursor> {
-    cursor::CursorIter::new(links::users::MUTES_LIST, token, None, None)
+    let params = ParamList::new().extended_tweets();
+    cursor::CursorIter::new(links::users::MUTES_LIST, token, Some(params), None)
 }
 
 /// Lookup the users that have been muted by the authenticated user, but only return their user IDs.
@@ -20,6 +20,8 @@
 //!   these types (`TwitterUser` contains the other two) describe the content of a user's profile,
 //!   and a handful of settings relating to how their profile is displayed.
 //! - `UserSearch`: returned by `search`, this is a stream of search results.
+//! - `UserCache`: a cache that deduplicates the user objects embedded in tweets, handing out shared
+//!   references to them instead.
 //!
 //! ## Functions
 //!
@@ -67,11 +69,30 @@ use serde::{Deserialize, Serialize};
 use crate::common::*;
 use crate::{auth, entities, error, links, tweet};
 
+mod cache;
 mod fun;
 mod raw;
 
+pub use self::cache::UserCache;
 pub use self::fun::*;
 
+id_newtype! {
+    /// The numeric ID of a Twitter account.
+    ///
+    /// This is a thin wrapper around the `u64` that Twitter uses, so that user IDs can't be
+    /// accidentally passed to functions that expect a tweet or list ID. It converts freely to and
+    /// from `u64`, and can be used anywhere a `UserID` is expected.
+    ///
+    /// ```rust
+    /// use egg_mode::user::UserId;
+    ///
+    /// let id = UserId::from(165262228);
+    /// assert_eq!(id, 165262228);
+    /// assert_eq!(id.to_string(), "165262228");
+    /// ```
+    UserId
+}
+
 /// Convenience enum to generalize between referring to an account by numeric ID or by screen name.
 ///
 /// Many API calls ask for a user either by either screen name (e.g. `rustlang`) or by a numeric ID
@@ -79,6 +100,7 @@ pub use self::fun::*;
 /// enum, and can take any type that converts into it. This enum has `From` implementations for the
 /// following types:
 ///
+/// * `UserId`
 /// * `u64`
 /// * `&u64` (convenient when used with iterators)
 /// * `&str`
@@ -92,12 +114,24 @@ pub use self::fun::*;
 #[derive(Debug, Clone, derive_more::From)]
 pub enum UserID {
     /// Referring via the account's numeric ID.
-    ID(u64),
+    ID(UserId),
     /// Referring via the account's screen name.
     ScreenName(CowStr),
 }
 
-impl<'a> From<&'static str> for UserID {
+impl From<u64> for UserID {
+    fn from(id: u64) -> UserID {
+        UserID::ID(id.into())
+    }
+}
+
+impl From<&u64> for UserID {
+    fn from(id: &u64) -> UserID {
+        UserID::ID(id.into())
+    }
+}
+
+impl From<&'static str> for UserID {
     fn from(name: &'static str) -> UserID {
         UserID::ScreenName(name.into())
     }
@@ -199,7 +233,7 @@ round_trip! { raw::RawTwitterUser,
         /// posting a new Tweet.
         pub geo_enabled: bool,
         /// Unique identifier for this user.
-        pub id: u64,
+        pub id: UserId,
         /// Indicates whether the user participates in Twitter's translator community.
         pub is_translator: bool,
         /// Language code for the user's self-declared interface language.
@@ -353,6 +387,7 @@ impl From<raw::RawTwitterUser> for TwitterUser {
             verified: raw.verified,
             withheld_in_countries: raw.withheld_in_countries,
             withheld_scope: raw.withheld_scope,
+            raw: None,
         }
     }
 }
@@ -381,6 +416,53 @@ pub struct UserEntityDetail {
     pub urls: Vec<entities::UrlEntity>,
 }
 
+impl UserEntityDetail {
+    /// Returns a copy of the given text with each of the t.co links described by this entity
+    /// replaced by the URL it points to.
+    ///
+    /// The given text should be the profile field these entities were parsed from. If an entity
+    /// is missing its `expanded_url`, its t.co link is left in place.
+    pub fn expand_urls(&self, text: &str) -> String {
+        let mut urls = self.urls.iter().collect::<Vec<_>>();
+        urls.sort_by_key(|u| u.range.0);
+
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for url in urls {
+            let (start, end) = url.range;
+            if start < last || end > text.len() {
+                continue;
+            }
+            if let (Some(before), Some(expanded)) = (text.get(last..start), &url.expanded_url) {
+                out.push_str(before);
+                out.push_str(expanded);
+                last = end;
+            }
+        }
+        out.push_str(&text[last..]);
+
+        out
+    }
+}
+
+impl TwitterUser {
+    /// Returns the user's `description`, with its t.co links replaced by the URLs they point to.
+    pub fn expanded_description(&self) -> Option<String> {
+        self.description
+            .as_ref()
+            .map(|desc| self.entities.description.expand_urls(desc))
+    }
+
+    /// Returns the user's `url`, with its t.co link replaced by the URL it points to.
+    pub fn expanded_url(&self) -> Option<String> {
+        match (&self.url, &self.entities.url) {
+            (Some(url), Some(entities)) => Some(entities.expand_urls(url)),
+            (Some(url), None) => Some(url.clone()),
+            _ => None,
+        }
+    }
+}
+
 /// Represents an active user search.
 ///
 /// This struct is returned by [`search`][] and is meant to be used as a `Stream`. That means all
@@ -514,6 +596,7 @@ impl UserSearch {
     /// change `page_num` between calls.
     pub fn call(&self) -> impl Future<Output = error::Result<Response<Vec<TwitterUser>>>> {
         let params = ParamList::new()
+            .extended_tweets()
             .add_param("q", self.query.clone())
             .add_param("page", self.page_num.to_string())
             .add_param("count", self.page_size.to_string());
@@ -588,7 +671,7 @@ pub struct Relationship {
 #[derive(Debug, Deserialize)]
 pub struct RelationTarget {
     /// Numeric ID for this account.
-    pub id: u64,
+    pub id: UserId,
     /// Screen name for this account.
     pub screen_name: String,
     /// Indicates whether the source account follows this target account.
@@ -607,7 +690,7 @@ pub struct RelationTarget {
 #[derive(Debug, Deserialize)]
 pub struct RelationSource {
     /// Numeric ID for this account.
-    pub id: u64,
+    pub id: UserId,
     /// Screen name for this account.
     pub screen_name: String,
     /// Indicates whether this source account follows the target account.
@@ -630,6 +713,7 @@ pub struct RelationSource {
     ///
     /// Note that there is no mechanism with which to toggle this setting, at least none that this
     /// author could find, either through the API or through the official site.
+    #[allow(dead_code)]
     all_replies: Option<bool>,
     /// Indicates whether this source account has decided to show retweets from the target account.
     /// If the source account is not the authenticated user, holds `None` instead.
@@ -650,7 +734,7 @@ pub struct RelationLookup {
     /// The screen name of the target account.
     pub screen_name: String,
     /// The numeric ID of the target account.
-    pub id: u64,
+    pub id: UserId,
     /// The ways the target account is connected to the authenticated user.
     ///
     /// If the target account has no relation to the authenticated user, this will not be empty; its
@@ -686,9 +770,35 @@ pub enum Connection {
 
 #[cfg(test)]
 mod tests {
-    use super::TwitterUser;
+    use super::{TwitterUser, UserEntityDetail};
     use crate::common::tests::load_file;
 
+    #[test]
+    fn expand_profile_urls() {
+        let sample = load_file("sample_payloads/user_array.json");
+        let users: Vec<TwitterUser> = serde_json::from_str(&sample).unwrap();
+        let rustlang = users.iter().find(|u| u.screen_name == "rustlang").unwrap();
+        assert_eq!(
+            rustlang.expanded_url().as_deref(),
+            Some("http://www.rust-lang.org")
+        );
+
+        let desc = "Café owner. Menu at https://t.co/abc and https://t.co/xyz!";
+        let detail: UserEntityDetail = serde_json::from_value(serde_json::json!({
+            "urls": [
+                { "url": "https://t.co/xyz", "expanded_url": "https://example.com/hours",
+                  "display_url": "example.com/hours", "indices": [42, 58] },
+                { "url": "https://t.co/abc", "expanded_url": "https://example.com/menu",
+                  "display_url": "example.com/menu", "indices": [21, 37] },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            detail.expand_urls(desc),
+            "Café owner. Menu at https://example.com/menu and https://example.com/hours!"
+        );
+    }
+
     #[test]
     fn roundtrip_deser() {
         let sample = load_file("sample_payloads/user_array.json");
@@ -8,7 +8,7 @@ use crate::tweet;
 use chrono;
 use serde::Deserialize;
 
-use super::UserEntities;
+use super::{UserEntities, UserId};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RawTwitterUser {
@@ -50,7 +50,7 @@ pub struct RawTwitterUser {
     /// posting a new Tweet.
     pub geo_enabled: bool,
     /// Unique identifier for this user.
-    pub id: u64,
+    pub id: UserId,
     /// Indicates whether the user participates in Twitter's translator community.
     pub is_translator: bool,
     /// Language code for the user's self-declared interface language.
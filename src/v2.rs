@@ -0,0 +1,635 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Types shared by the functions that call Twitter's v2 API.
+//!
+//! Twitter's v2 API returns different objects than the v1.1 API that most of egg-mode uses. Each
+//! object only contains a handful of fields by default; anything else needs to be asked for by
+//! name, using the `tweet.fields`, `user.fields`, and `list.fields` parameters. Related objects,
+//! like the author of a tweet or the owner of a list, aren't embedded in the object either.
+//! Instead, you can ask for them with the `expansions` parameter, and they're returned separately
+//! in an `includes` section of the response.
+//!
+//! Every function in egg-mode that calls a v2 endpoint takes a [`Fields`] to set these parameters,
+//! and returns its results wrapped in a [`Single`] or a [`Page`], which hold the `includes` and
+//! any partial errors alongside the requested data. The objects themselves are [`Tweet`],
+//! [`User`], and [`List`]; every field that isn't returned by default is an `Option` or an empty
+//! `Vec`, since it's only filled in if the matching field was requested.
+//!
+//! [`Fields`]: struct.Fields.html
+//! [`Single`]: struct.Single.html
+//! [`Page`]: struct.Page.html
+//! [`Tweet`]: struct.Tweet.html
+//! [`User`]: struct.User.html
+//! [`List`]: struct.List.html
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream};
+use hyper::{Body, Request};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::auth;
+use crate::common::*;
+use crate::error::{Error, Result};
+use crate::list::ListId;
+use crate::tweet::TweetId;
+use crate::user::UserId;
+
+/// A tweet, as returned by the v2 API.
+///
+/// Only `id` and `text` are returned by default. The other fields are only filled in if the
+/// matching `TweetField` was requested.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tweet {
+    /// The ID of this tweet.
+    #[serde(with = "serde_via_string")]
+    pub id: TweetId,
+    /// The text of this tweet.
+    pub text: String,
+    /// The ID of the user who posted this tweet.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub author_id: Option<UserId>,
+    /// UTC timestamp from when this tweet was posted.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The ID of the tweet that started the conversation this tweet belongs to.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub conversation_id: Option<TweetId>,
+    /// If this tweet is a reply, the ID of the user it replies to.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub in_reply_to_user_id: Option<UserId>,
+    /// The language Twitter detected this tweet to be written in, as a BCP 47 language tag.
+    pub lang: Option<String>,
+    /// Whether this tweet links to content that may be sensitive.
+    pub possibly_sensitive: Option<bool>,
+    /// The name of the app this tweet was posted from.
+    pub source: Option<String>,
+    /// Engagement counts for this tweet.
+    pub public_metrics: Option<TweetMetrics>,
+    /// The tweets this tweet retweets, quotes, or replies to.
+    #[serde(default)]
+    pub referenced_tweets: Vec<ReferencedTweet>,
+}
+
+/// Public engagement counts for a v2 `Tweet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct TweetMetrics {
+    /// The number of times this tweet has been retweeted.
+    pub retweet_count: u64,
+    /// The number of replies to this tweet.
+    pub reply_count: u64,
+    /// The number of times this tweet has been liked.
+    pub like_count: u64,
+    /// The number of times this tweet has been quoted.
+    pub quote_count: u64,
+}
+
+/// A tweet referenced by a v2 `Tweet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ReferencedTweet {
+    /// How the tweet is referenced.
+    #[serde(rename = "type")]
+    pub kind: ReferenceKind,
+    /// The ID of the referenced tweet.
+    #[serde(with = "serde_via_string")]
+    pub id: TweetId,
+}
+
+/// The ways a tweet can reference another tweet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// The tweet is a retweet of the referenced tweet.
+    Retweeted,
+    /// The tweet quotes the referenced tweet.
+    Quoted,
+    /// The tweet is a reply to the referenced tweet.
+    RepliedTo,
+}
+
+/// A user, as returned by the v2 API.
+///
+/// Only `id`, `name`, and `username` are returned by default. The other fields are only filled in
+/// if the matching `UserField` was requested.
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    /// The ID of this user.
+    #[serde(with = "serde_via_string")]
+    pub id: UserId,
+    /// The display name of this user.
+    pub name: String,
+    /// The screen name of this user, without the leading `@`.
+    pub username: String,
+    /// UTC timestamp from when this account was created.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The biography of this user.
+    pub description: Option<String>,
+    /// The location this user has given in their profile.
+    pub location: Option<String>,
+    /// The ID of the tweet this user has pinned to their profile.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub pinned_tweet_id: Option<TweetId>,
+    /// The URL of this user's profile image.
+    pub profile_image_url: Option<String>,
+    /// Whether this user has protected their tweets.
+    pub protected: Option<bool>,
+    /// The URL this user has given in their profile.
+    pub url: Option<String>,
+    /// Whether this user is verified.
+    pub verified: Option<bool>,
+    /// Follower and tweet counts for this user.
+    pub public_metrics: Option<UserMetrics>,
+}
+
+/// Public counts for a v2 `User`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct UserMetrics {
+    /// The number of accounts following this user.
+    pub followers_count: u64,
+    /// The number of accounts this user follows.
+    pub following_count: u64,
+    /// The number of tweets this user has posted, including retweets.
+    pub tweet_count: u64,
+    /// The number of lists this user is a member of.
+    pub listed_count: u64,
+}
+
+/// A list, as returned by the v2 API.
+///
+/// Only `id` and `name` are returned by default. The other fields are only filled in if the
+/// matching `ListField` was requested.
+#[derive(Debug, Clone, Deserialize)]
+pub struct List {
+    /// The ID of this list.
+    #[serde(with = "serde_via_string")]
+    pub id: ListId,
+    /// The name of this list.
+    pub name: String,
+    /// UTC timestamp from when this list was created.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The description of this list.
+    pub description: Option<String>,
+    /// The number of users who follow this list.
+    pub follower_count: Option<u64>,
+    /// The number of members of this list.
+    pub member_count: Option<u64>,
+    /// Whether this list is private.
+    pub private: Option<bool>,
+    /// The ID of the user who owns this list.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub owner_id: Option<UserId>,
+}
+
+/// The related objects returned alongside the main data of a v2 response, as requested with
+/// `Fields::expand`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Includes {
+    /// The users referenced by the returned data.
+    #[serde(default)]
+    pub users: Vec<User>,
+    /// The tweets referenced by the returned data.
+    #[serde(default)]
+    pub tweets: Vec<Tweet>,
+}
+
+impl Includes {
+    /// Returns the included user with the given ID, if it was returned.
+    pub fn user(&self, id: UserId) -> Option<&User> {
+        self.users.iter().find(|u| u.id == id)
+    }
+
+    /// Returns the included tweet with the given ID, if it was returned.
+    pub fn tweet(&self, id: TweetId) -> Option<&Tweet> {
+        self.tweets.iter().find(|t| t.id == id)
+    }
+}
+
+/// An error returned by the v2 API.
+///
+/// The v2 API reports problems with part of a request, like an expansion that refers to a deleted
+/// or protected tweet, without failing the whole call. These errors describe what couldn't be
+/// loaded. When nothing could be loaded at all, like when the requested object doesn't exist, the
+/// errors are returned as `Error::TwitterErrorV2` instead.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PartialError {
+    /// A short description of the kind of error.
+    pub title: String,
+    /// A longer description of the error.
+    pub detail: Option<String>,
+    /// The kind of object that couldn't be loaded, like `"tweet"` or `"user"`.
+    pub resource_type: Option<String>,
+    /// The ID of the object that couldn't be loaded.
+    pub resource_id: Option<String>,
+    /// The request parameter that referred to the object.
+    pub parameter: Option<String>,
+}
+
+impl fmt::Display for PartialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.detail {
+            Some(ref detail) => write!(f, "{}: {}", self.title, detail),
+            None => write!(f, "{}", self.title),
+        }
+    }
+}
+
+/// The response from a v2 endpoint that returns a single object.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Single<T> {
+    /// The requested object.
+    pub data: T,
+    /// The related objects requested with `Fields::expand`.
+    #[serde(default)]
+    pub includes: Includes,
+    /// Any problems loading parts of the response.
+    #[serde(default)]
+    pub errors: Vec<PartialError>,
+}
+
+/// A `Single` as Twitter sends it, which has no `data` if the object couldn't be loaded.
+#[derive(Deserialize)]
+struct RawSingle<T> {
+    data: Option<T>,
+    #[serde(default)]
+    includes: Includes,
+    #[serde(default)]
+    errors: Vec<PartialError>,
+}
+
+impl<T> RawSingle<T> {
+    fn into_single(self) -> Result<Single<T>> {
+        match self.data {
+            Some(data) => Ok(Single {
+                data,
+                includes: self.includes,
+                errors: self.errors,
+            }),
+            None if !self.errors.is_empty() => Err(Error::TwitterErrorV2(self.errors)),
+            None => Err(Error::MissingValue("data")),
+        }
+    }
+}
+
+/// A page of results from a v2 endpoint that returns a collection of objects.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page<T> {
+    /// The objects on this page.
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    /// The related objects requested with `Fields::expand`.
+    #[serde(default)]
+    pub includes: Includes,
+    /// Information about this page and how to load the next one.
+    #[serde(default)]
+    pub meta: PageMeta,
+    /// Any problems loading parts of the response.
+    #[serde(default)]
+    pub errors: Vec<PartialError>,
+}
+
+/// Paging information for a v2 `Page`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageMeta {
+    /// The number of objects on this page.
+    #[serde(default)]
+    pub result_count: u32,
+    /// The token to give to load the next page, if more results exist.
+    pub next_token: Option<String>,
+    /// The token to give to load the previous page, if there is one.
+    pub previous_token: Option<String>,
+    /// The ID of the newest tweet on this page, if this is a page of tweets.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub newest_id: Option<TweetId>,
+    /// The ID of the oldest tweet on this page, if this is a page of tweets.
+    #[serde(default, deserialize_with = "serde_opt_via_string::deserialize")]
+    pub oldest_id: Option<TweetId>,
+}
+
+/// Optional tweet fields that can be requested from the v2 API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TweetField {
+    /// The ID of the user who posted the tweet.
+    AuthorId,
+    /// The ID of the tweet that started the conversation.
+    ConversationId,
+    /// The time the tweet was posted.
+    CreatedAt,
+    /// The ID of the user the tweet replies to.
+    InReplyToUserId,
+    /// The language of the tweet.
+    Lang,
+    /// Whether the tweet links to sensitive content.
+    PossiblySensitive,
+    /// Engagement counts for the tweet.
+    PublicMetrics,
+    /// The tweets the tweet retweets, quotes, or replies to.
+    ReferencedTweets,
+    /// The app the tweet was posted from.
+    Source,
+}
+
+impl TweetField {
+    fn as_str(self) -> &'static str {
+        match self {
+            TweetField::AuthorId => "author_id",
+            TweetField::ConversationId => "conversation_id",
+            TweetField::CreatedAt => "created_at",
+            TweetField::InReplyToUserId => "in_reply_to_user_id",
+            TweetField::Lang => "lang",
+            TweetField::PossiblySensitive => "possibly_sensitive",
+            TweetField::PublicMetrics => "public_metrics",
+            TweetField::ReferencedTweets => "referenced_tweets",
+            TweetField::Source => "source",
+        }
+    }
+}
+
+/// Optional user fields that can be requested from the v2 API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UserField {
+    /// The time the account was created.
+    CreatedAt,
+    /// The user's biography.
+    Description,
+    /// The location given in the user's profile.
+    Location,
+    /// The ID of the user's pinned tweet.
+    PinnedTweetId,
+    /// The URL of the user's profile image.
+    ProfileImageUrl,
+    /// Whether the user's tweets are protected.
+    Protected,
+    /// Follower and tweet counts for the user.
+    PublicMetrics,
+    /// The URL given in the user's profile.
+    Url,
+    /// Whether the user is verified.
+    Verified,
+}
+
+impl UserField {
+    fn as_str(self) -> &'static str {
+        match self {
+            UserField::CreatedAt => "created_at",
+            UserField::Description => "description",
+            UserField::Location => "location",
+            UserField::PinnedTweetId => "pinned_tweet_id",
+            UserField::ProfileImageUrl => "profile_image_url",
+            UserField::Protected => "protected",
+            UserField::PublicMetrics => "public_metrics",
+            UserField::Url => "url",
+            UserField::Verified => "verified",
+        }
+    }
+}
+
+/// Optional list fields that can be requested from the v2 API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListField {
+    /// The time the list was created.
+    CreatedAt,
+    /// The list's description.
+    Description,
+    /// The number of users following the list.
+    FollowerCount,
+    /// The number of members of the list.
+    MemberCount,
+    /// The ID of the list's owner.
+    OwnerId,
+    /// Whether the list is private.
+    Private,
+}
+
+impl ListField {
+    fn as_str(self) -> &'static str {
+        match self {
+            ListField::CreatedAt => "created_at",
+            ListField::Description => "description",
+            ListField::FollowerCount => "follower_count",
+            ListField::MemberCount => "member_count",
+            ListField::OwnerId => "owner_id",
+            ListField::Private => "private",
+        }
+    }
+}
+
+/// Related objects that can be loaded into the `includes` of a v2 response.
+///
+/// Not every expansion applies to every endpoint: the ones referring to tweets can be used where
+/// tweets are returned, `PinnedTweetId` where users are returned, and `OwnerId` where lists are
+/// returned. Twitter rejects requests that ask for an expansion that doesn't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expansion {
+    /// The author of each tweet.
+    AuthorId,
+    /// The user each tweet replies to.
+    InReplyToUserId,
+    /// The tweets each tweet retweets, quotes, or replies to.
+    ReferencedTweets,
+    /// The authors of the tweets each tweet retweets, quotes, or replies to.
+    ReferencedTweetsAuthors,
+    /// The users mentioned in each tweet.
+    Mentions,
+    /// The pinned tweet of each user.
+    PinnedTweetId,
+    /// The owner of each list.
+    OwnerId,
+}
+
+impl Expansion {
+    fn as_str(self) -> &'static str {
+        match self {
+            Expansion::AuthorId => "author_id",
+            Expansion::InReplyToUserId => "in_reply_to_user_id",
+            Expansion::ReferencedTweets => "referenced_tweets.id",
+            Expansion::ReferencedTweetsAuthors => "referenced_tweets.id.author_id",
+            Expansion::Mentions => "entities.mentions.username",
+            Expansion::PinnedTweetId => "pinned_tweet_id",
+            Expansion::OwnerId => "owner_id",
+        }
+    }
+}
+
+/// The optional fields and expansions to request from a v2 endpoint.
+///
+/// By default, no optional fields or expansions are requested, so `Fields::default()` loads the
+/// smallest version of each object.
+///
+/// # Example
+///
+/// ```rust
+/// use egg_mode::v2::{Expansion, Fields, ListField, UserField};
+///
+/// let fields = Fields::new()
+///     .list(&[ListField::Description, ListField::MemberCount, ListField::OwnerId])
+///     .user(&[UserField::Verified])
+///     .expand(&[Expansion::OwnerId]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Fields {
+    tweet: Vec<TweetField>,
+    user: Vec<UserField>,
+    list: Vec<ListField>,
+    expansions: Vec<Expansion>,
+}
+
+impl Fields {
+    /// Creates a new `Fields` that doesn't request any optional fields or expansions.
+    pub fn new() -> Fields {
+        Fields::default()
+    }
+
+    /// Requests the given fields on every tweet returned, including tweets in `includes`.
+    pub fn tweet(mut self, fields: &[TweetField]) -> Self {
+        extend_unique(&mut self.tweet, fields);
+        self
+    }
+
+    /// Requests the given fields on every user returned, including users in `includes`.
+    pub fn user(mut self, fields: &[UserField]) -> Self {
+        extend_unique(&mut self.user, fields);
+        self
+    }
+
+    /// Requests the given fields on every list returned.
+    pub fn list(mut self, fields: &[ListField]) -> Self {
+        extend_unique(&mut self.list, fields);
+        self
+    }
+
+    /// Requests the given related objects, to be returned in the `includes` of the response.
+    pub fn expand(mut self, expansions: &[Expansion]) -> Self {
+        extend_unique(&mut self.expansions, expansions);
+        self
+    }
+
+    /// Adds the requested fields and expansions to the given `ParamList`.
+    pub(crate) fn add_params(&self, params: ParamList) -> ParamList {
+        fn join<T: Copy>(items: &[T], as_str: fn(T) -> &'static str) -> Option<String> {
+            if items.is_empty() {
+                None
+            } else {
+                Some(
+                    items
+                        .iter()
+                        .map(|&item| as_str(item))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )
+            }
+        }
+
+        params
+            .add_opt_param("tweet.fields", join(&self.tweet, TweetField::as_str))
+            .add_opt_param("user.fields", join(&self.user, UserField::as_str))
+            .add_opt_param("list.fields", join(&self.list, ListField::as_str))
+            .add_opt_param("expansions", join(&self.expansions, Expansion::as_str))
+    }
+}
+
+/// Loads the given request to a v2 endpoint that returns a single object, returning
+/// `Error::TwitterErrorV2` if Twitter sent errors instead of the object.
+pub(crate) async fn request_single<T: DeserializeOwned>(
+    req: Request<Body>,
+) -> Result<Response<Single<T>>> {
+    let resp = request_with_json_response::<RawSingle<T>>(req).await?;
+    Response::try_map(resp, RawSingle::into_single)
+}
+
+/// Returns a `Stream` of every page of results from the given v2 endpoint, following the
+/// `next_token` of each page until the last one.
+///
+/// `token_param` is the name of the parameter the endpoint takes the token in, since some endpoints
+/// call it `next_token` and others `pagination_token`.
+pub(crate) fn page_stream<T: DeserializeOwned>(
+    url: String,
+    params: ParamList,
+    token_param: &'static str,
+    token: &auth::Token,
+) -> impl Stream<Item = Result<Response<Page<T>>>> {
+    let token = token.clone();
+    stream::try_unfold(Some(params), move |params| {
+        let url = url.clone();
+        let token = token.clone();
+        async move {
+            let params = match params {
+                Some(params) => params,
+                None => return Ok(None),
+            };
+            let req = get(&url, &token, Some(&params));
+            let page = request_with_json_response::<Page<T>>(req).await?;
+            let next = page.meta.next_token.clone().map(|next| {
+                let mut params = params.clone();
+                params.add_param_ref(token_param, next);
+                params
+            });
+            Ok(Some((page, next)))
+        }
+    })
+}
+
+fn extend_unique<T: Copy + PartialEq>(dest: &mut Vec<T>, items: &[T]) {
+    for &item in items {
+        if !dest.contains(&item) {
+            dest.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_params() {
+        let fields = Fields::new()
+            .tweet(&[TweetField::CreatedAt, TweetField::PublicMetrics])
+            .tweet(&[TweetField::CreatedAt])
+            .expand(&[Expansion::AuthorId, Expansion::ReferencedTweets]);
+        let params = fields.add_params(ParamList::new());
+
+        assert_eq!(
+            params.get("tweet.fields").map(|v| &**v),
+            Some("created_at,public_metrics")
+        );
+        assert_eq!(
+            params.get("expansions").map(|v| &**v),
+            Some("author_id,referenced_tweets.id")
+        );
+        assert!(params.get("user.fields").is_none());
+        assert!(params.get("list.fields").is_none());
+    }
+
+    #[test]
+    fn errors_only_single() {
+        let json = r#"{"data":{"id":"1253713667363123200","name":"Rust"},
+            "errors":[{"title":"Not Found Error","resource_type":"user"}]}"#;
+        let single = serde_json::from_str::<RawSingle<List>>(json)
+            .unwrap()
+            .into_single()
+            .unwrap();
+        assert_eq!(single.data.name, "Rust");
+        assert_eq!(single.errors.len(), 1);
+
+        let json = r#"{"errors":[{"title":"Not Found Error",
+            "detail":"Could not find list with id: [1].","resource_type":"list",
+            "resource_id":"1","parameter":"id"}]}"#;
+        let raw = serde_json::from_str::<RawSingle<List>>(json).unwrap();
+        match raw.into_single() {
+            Err(Error::TwitterErrorV2(errors)) => assert_eq!(
+                errors[0].to_string(),
+                "Not Found Error: Could not find list with id: [1]."
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let raw = serde_json::from_str::<RawSingle<List>>("{}").unwrap();
+        assert!(matches!(
+            raw.into_single(),
+            Err(Error::MissingValue("data"))
+        ));
+    }
+}